@@ -0,0 +1,52 @@
+use crate::CodecError;
+
+/// Extracts the `charset` parameter from a `content-type` value, lowercased
+/// and with surrounding quotes removed.
+pub fn charset_from_content_type(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("charset") {
+            return None;
+        }
+        let value = value.trim().trim_matches('"').trim();
+        (!value.is_empty()).then(|| value.to_ascii_lowercase())
+    })
+}
+
+/// Decodes `input` from the named charset into a `String`.
+pub fn decode_charset(input: &[u8], charset: &str) -> Result<String, CodecError> {
+    match charset.trim().to_ascii_lowercase().as_str() {
+        "utf-8" | "utf8" => {
+            String::from_utf8(input.to_vec()).map_err(|err| CodecError::Utf8(err.to_string()))
+        }
+        "iso-8859-1" | "iso8859-1" | "latin1" | "latin-1" | "l1" | "us-ascii" | "ascii" => {
+            Ok(input.iter().map(|&byte| char::from(byte)).collect())
+        }
+        "utf-16le" => decode_utf16(input, u16::from_le_bytes),
+        "utf-16be" => decode_utf16(input, u16::from_be_bytes),
+        "utf-16" => match input {
+            [0xff, 0xfe, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+            [0xfe, 0xff, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+            _ => decode_utf16(input, u16::from_be_bytes),
+        },
+        other => Err(CodecError::UnsupportedCharset(other.to_string())),
+    }
+}
+
+fn decode_utf16(input: &[u8], to_unit: fn([u8; 2]) -> u16) -> Result<String, CodecError> {
+    if !input.len().is_multiple_of(2) {
+        return Err(CodecError::Charset(
+            "odd number of utf-16 bytes".to_string(),
+        ));
+    }
+    let units = input
+        .chunks_exact(2)
+        .map(|pair| to_unit([pair[0], pair[1]]));
+    let text = char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|err| CodecError::Charset(err.to_string()))?;
+    Ok(text
+        .strip_prefix('\u{feff}')
+        .map(str::to_string)
+        .unwrap_or(text))
+}
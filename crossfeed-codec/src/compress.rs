@@ -1,7 +1,7 @@
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
 
 use flate2::Compression;
-use flate2::read::{DeflateDecoder, GzDecoder};
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
 use flate2::write::{DeflateEncoder, GzEncoder};
 
 use crate::CodecError;
@@ -17,7 +17,7 @@ pub fn gzip_compress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
 }
 
 pub fn gzip_decompress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
-    let mut decoder = GzDecoder::new(input);
+    let mut decoder = MultiGzDecoder::new(input);
     let mut output = Vec::new();
     decoder
         .read_to_end(&mut output)
@@ -25,6 +25,38 @@ pub fn gzip_decompress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
     Ok(output)
 }
 
+/// Streams a gzip body into `writer` in fixed-size chunks instead of
+/// buffering the whole decoded payload.
+pub fn gzip_decompress_to_writer(input: &[u8], writer: &mut impl Write) -> Result<(), CodecError> {
+    let mut decoder = MultiGzDecoder::new(input);
+    io::copy(&mut decoder, writer).map_err(|err| CodecError::Compression(err.to_string()))?;
+    Ok(())
+}
+
+/// Returns the gzip-compressed length of `input` without keeping the output.
+pub fn estimate_compressed_size(input: &[u8]) -> usize {
+    let mut encoder = GzEncoder::new(ByteCounter(0), Compression::default());
+    // Writing into a counter cannot fail.
+    let _ = encoder.write_all(input);
+    encoder
+        .finish()
+        .map(|counter| counter.0)
+        .unwrap_or_default()
+}
+
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 pub fn deflate_compress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
     let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
     encoder
@@ -35,11 +67,86 @@ pub fn deflate_compress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
         .map_err(|err| CodecError::Compression(err.to_string()))
 }
 
+/// Decodes `content-encoding: deflate` bodies, which servers send either
+/// zlib-wrapped (RFC 1950) or as raw deflate (RFC 1951).
 pub fn deflate_decompress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    let mut output = Vec::new();
+    if ZlibDecoder::new(input).read_to_end(&mut output).is_ok() {
+        return Ok(output);
+    }
+    output.clear();
     let mut decoder = DeflateDecoder::new(input);
+    decoder
+        .read_to_end(&mut output)
+        .map_err(|err| CodecError::Compression(err.to_string()))?;
+    Ok(output)
+}
+
+/// Streaming counterpart of [`deflate_decompress`]. The zlib wrapper is
+/// detected from the stream header since partial output cannot be retracted
+/// from `writer` once written.
+pub fn deflate_decompress_to_writer(
+    input: &[u8],
+    writer: &mut impl Write,
+) -> Result<(), CodecError> {
+    let result = if has_zlib_header(input) {
+        io::copy(&mut ZlibDecoder::new(input), writer)
+    } else {
+        io::copy(&mut DeflateDecoder::new(input), writer)
+    };
+    result.map_err(|err| CodecError::Compression(err.to_string()))?;
+    Ok(())
+}
+
+fn has_zlib_header(input: &[u8]) -> bool {
+    match input {
+        [cmf, flg, ..] => cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0,
+        _ => false,
+    }
+}
+
+pub fn zstd_compress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    zstd::encode_all(input, 0).map_err(|err| CodecError::Compression(err.to_string()))
+}
+
+pub fn zstd_decompress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    zstd::decode_all(input).map_err(|err| CodecError::Compression(err.to_string()))
+}
+
+pub fn brotli_compress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+    encoder
+        .write_all(input)
+        .map_err(|err| CodecError::Compression(err.to_string()))?;
+    encoder
+        .flush()
+        .map_err(|err| CodecError::Compression(err.to_string()))?;
+    Ok(encoder.into_inner())
+}
+
+pub fn brotli_decompress(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    let mut decoder = brotli::Decompressor::new(input, 4096);
     let mut output = Vec::new();
     decoder
         .read_to_end(&mut output)
         .map_err(|err| CodecError::Compression(err.to_string()))?;
     Ok(output)
 }
+
+/// Undoes a `content-encoding` chain. Codings are listed in the order they
+/// were applied, so they are removed from last to first.
+pub fn decode_encoding_chain(body: &[u8], encodings: &[&str]) -> Result<Vec<u8>, CodecError> {
+    let mut output = body.to_vec();
+    for encoding in encodings.iter().rev() {
+        let encoding = encoding.trim().to_ascii_lowercase();
+        output = match encoding.as_str() {
+            "" | "identity" => output,
+            "gzip" | "x-gzip" => gzip_decompress(&output)?,
+            "deflate" => deflate_decompress(&output)?,
+            "br" => brotli_decompress(&output)?,
+            "zstd" => zstd_decompress(&output)?,
+            _ => return Err(CodecError::UnsupportedEncoding(encoding)),
+        };
+    }
+    Ok(output)
+}
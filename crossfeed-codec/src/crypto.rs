@@ -0,0 +1,77 @@
+use aes::cipher::block_padding::Pkcs7;
+use aes::cipher::{BlockCipher, BlockDecryptMut, KeyIvInit};
+use aes_gcm::aead::consts::U12;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{AesGcm, Nonce};
+
+use crate::error::CodecError;
+
+/// Decrypts AES-GCM (128/192/256-bit key, 96-bit nonce). `ciphertext` must
+/// carry the 16-byte tag at the end, as most libraries emit it.
+pub fn aes_gcm_decrypt(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, CodecError> {
+    if nonce.len() != 12 {
+        return Err(CodecError::Crypto(format!(
+            "expected 12-byte nonce, got {}",
+            nonce.len()
+        )));
+    }
+    let nonce = Nonce::<U12>::from_slice(nonce);
+    let payload = Payload {
+        msg: ciphertext,
+        aad,
+    };
+    let result = match key.len() {
+        16 => gcm_cipher::<aes::Aes128>(key)?.decrypt(nonce, payload),
+        24 => gcm_cipher::<aes::Aes192>(key)?.decrypt(nonce, payload),
+        32 => gcm_cipher::<aes::Aes256>(key)?.decrypt(nonce, payload),
+        other => return Err(invalid_key_len(other)),
+    };
+    result.map_err(|_| CodecError::AuthenticationFailed)
+}
+
+/// Decrypts AES-CBC (128/192/256-bit key) and strips PKCS#7 padding.
+pub fn aes_cbc_decrypt(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, CodecError> {
+    if iv.len() != 16 {
+        return Err(CodecError::Crypto(format!(
+            "expected 16-byte iv, got {}",
+            iv.len()
+        )));
+    }
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(16) {
+        return Err(CodecError::Crypto(format!(
+            "ciphertext length {} is not a multiple of 16",
+            ciphertext.len()
+        )));
+    }
+    let result = match key.len() {
+        16 => cbc_decryptor::<aes::Aes128>(key, iv)?.decrypt_padded_vec_mut::<Pkcs7>(ciphertext),
+        24 => cbc_decryptor::<aes::Aes192>(key, iv)?.decrypt_padded_vec_mut::<Pkcs7>(ciphertext),
+        32 => cbc_decryptor::<aes::Aes256>(key, iv)?.decrypt_padded_vec_mut::<Pkcs7>(ciphertext),
+        other => return Err(invalid_key_len(other)),
+    };
+    result.map_err(|_| CodecError::Crypto("invalid PKCS#7 padding".to_string()))
+}
+
+fn gcm_cipher<C>(key: &[u8]) -> Result<AesGcm<C, U12>, CodecError>
+where
+    AesGcm<C, U12>: KeyInit,
+{
+    AesGcm::<C, U12>::new_from_slice(key).map_err(|_| invalid_key_len(key.len()))
+}
+
+fn cbc_decryptor<C>(key: &[u8], iv: &[u8]) -> Result<cbc::Decryptor<C>, CodecError>
+where
+    C: BlockCipher + BlockDecryptMut,
+    cbc::Decryptor<C>: KeyIvInit,
+{
+    cbc::Decryptor::<C>::new_from_slices(key, iv).map_err(|_| invalid_key_len(key.len()))
+}
+
+fn invalid_key_len(len: usize) -> CodecError {
+    CodecError::Crypto(format!("expected 16, 24 or 32-byte key, got {len}"))
+}
@@ -1,12 +1,53 @@
 use std::borrow::Cow;
 
 use base64::Engine;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
 use percent_encoding::percent_decode;
 
 use crate::CodecError;
 
+/// Bytes left unescaped by [`url_encode_bytes_with_set`] for common URL
+/// contexts, in addition to ASCII alphanumerics.
+///
+/// `PATH`, `QUERY` and `ALL` output round-trips through [`url_decode_bytes`],
+/// which keeps `+` literal. `FORM` output must be read back with
+/// [`url_decode_form_bytes`], which turns `+` into a space.
+pub mod url_set {
+    /// A single path segment: RFC 3986 `pchar`, so `/` is escaped and `+`
+    /// stays literal.
+    pub const PATH: &[u8] = b"-._~!$&'()*+,;=:@";
+    /// A query parameter name or value: `&`, `=`, `+` and `#` are escaped.
+    pub const QUERY: &[u8] = b"-._~!$'()*,;:@/?";
+    /// `application/x-www-form-urlencoded`: spaces become `+` and a literal
+    /// `+` is escaped as `%2B`.
+    pub const FORM: &[u8] = b"*-._ ";
+    /// Escape every byte that is not an ASCII alphanumeric.
+    pub const ALL: &[u8] = b"";
+}
+
 pub fn url_encode_bytes(input: &[u8]) -> String {
-    percent_encoding::percent_encode(input, percent_encoding::NON_ALPHANUMERIC).to_string()
+    url_encode_bytes_with_set(input, url_set::ALL)
+}
+
+/// Percent-encodes every byte except ASCII alphanumerics and `unreserved`.
+/// A space can never appear literally in a URL, so listing it as unreserved
+/// writes it as `+` (form encoding).
+pub fn url_encode_bytes_with_set(input: &[u8], unreserved: &[u8]) -> String {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    let mut output = String::with_capacity(input.len());
+    for &byte in input {
+        if byte.is_ascii_alphanumeric() {
+            output.push(char::from(byte));
+        } else if unreserved.contains(&byte) {
+            output.push(if byte == b' ' { '+' } else { char::from(byte) });
+        } else {
+            output.push('%');
+            output.push(char::from(HEX[usize::from(byte >> 4)]));
+            output.push(char::from(HEX[usize::from(byte & 0x0f)]));
+        }
+    }
+    output
 }
 
 pub fn url_encode_str(input: &str) -> String {
@@ -25,6 +66,23 @@ pub fn url_decode_str(input: &str) -> Result<String, CodecError> {
     Ok(decoded.into_owned())
 }
 
+/// Decodes `application/x-www-form-urlencoded` text: `+` is a space, while
+/// an escaped `%2B` stays a literal `+`.
+pub fn url_decode_form_bytes(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    url_decode_bytes(&plus_to_space(input))
+}
+
+pub fn url_decode_form_str(input: &str) -> Result<String, CodecError> {
+    url_decode_str(&String::from_utf8_lossy(&plus_to_space(input.as_bytes())))
+}
+
+fn plus_to_space(input: &[u8]) -> Vec<u8> {
+    input
+        .iter()
+        .map(|&byte| if byte == b'+' { b' ' } else { byte })
+        .collect()
+}
+
 pub fn base64_encode_bytes(input: &[u8]) -> String {
     base64::engine::general_purpose::STANDARD.encode(input)
 }
@@ -43,6 +101,27 @@ pub fn base64_decode_str(input: &str) -> Result<Vec<u8>, CodecError> {
     base64_decode_bytes(input.as_bytes())
 }
 
+/// Like [`base64_decode_bytes`] but ignores ASCII whitespace (line-wrapped
+/// PEM-style blobs, folded headers) and accepts missing `=` padding.
+pub fn base64_decode_bytes_lenient(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    const LENIENT: GeneralPurpose = GeneralPurpose::new(
+        &alphabet::STANDARD,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+    let compact: Vec<u8> = input
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    LENIENT
+        .decode(compact)
+        .map_err(|err| CodecError::Base64(err.to_string()))
+}
+
+pub fn base64_decode_str_lenient(input: &str) -> Result<Vec<u8>, CodecError> {
+    base64_decode_bytes_lenient(input.as_bytes())
+}
+
 pub fn base64url_encode_bytes(input: &[u8]) -> String {
     base64::engine::general_purpose::URL_SAFE.encode(input)
 }
@@ -118,6 +197,14 @@ pub fn html_escape_str(input: &str) -> String {
     html_escape::encode_safe(input).to_string()
 }
 
+/// Escapes text for a quoted attribute value: `&`, `<`, `>` and both quote
+/// characters, so the result is safe inside `"..."` or `'...'`.
+pub fn html_escape_attr_str(input: &str) -> String {
+    html_escape::encode_quoted_attribute(input).to_string()
+}
+
+/// Decodes named entities as well as decimal (`&#39;`) and hex (`&#x27;`)
+/// character references.
 pub fn html_unescape_str(input: &str) -> String {
     html_escape::decode_html_entities(input).to_string()
 }
@@ -16,6 +16,20 @@ pub enum CodecError {
     Url(String),
     #[error("invalid utf-8: {0}")]
     Utf8(String),
+    #[error("invalid json: {0}")]
+    Json(String),
     #[error("compression error: {0}")]
     Compression(String),
+    #[error("invalid text encoding: {0}")]
+    Charset(String),
+    #[error("unsupported charset: {0}")]
+    UnsupportedCharset(String),
+    #[error("unsupported content-encoding: {0}")]
+    UnsupportedEncoding(String),
+    #[error("decryption error: {0}")]
+    Crypto(String),
+    #[error("key derivation error: {0}")]
+    KeyDerivation(String),
+    #[error("authentication tag mismatch")]
+    AuthenticationFailed,
 }
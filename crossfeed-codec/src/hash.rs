@@ -1,5 +1,9 @@
+use blake2::digest::consts::U32;
 use sha2::Digest;
 
+use crate::encode::{base64_decode_str, base64url_decode_str};
+use crate::error::CodecError;
+
 pub fn md5_hex(input: &[u8]) -> String {
     format!("{:x}", md5::compute(input))
 }
@@ -33,3 +37,81 @@ pub fn sha512_hex(input: &[u8]) -> String {
     hasher.update(input);
     format!("{:x}", hasher.finalize())
 }
+
+pub fn sha3_256_hex(input: &[u8]) -> String {
+    let mut hasher = sha3::Sha3_256::new();
+    hasher.update(input);
+    format!("{:x}", hasher.finalize())
+}
+
+pub fn sha3_512_hex(input: &[u8]) -> String {
+    let mut hasher = sha3::Sha3_512::new();
+    hasher.update(input);
+    format!("{:x}", hasher.finalize())
+}
+
+pub fn blake2b_256_hex(input: &[u8]) -> String {
+    let mut hasher = blake2::Blake2b::<U32>::new();
+    hasher.update(input);
+    format!("{:x}", hasher.finalize())
+}
+
+pub fn pbkdf2_hmac_sha256(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out_len: usize,
+) -> Vec<u8> {
+    let mut out = vec![0u8; out_len];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password, salt, iterations, &mut out);
+    out
+}
+
+/// HKDF extract-and-expand with SHA-256. `out_len` may not exceed the
+/// RFC 5869 maximum of 255 * 32 bytes.
+pub fn hkdf_sha256(
+    ikm: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    out_len: usize,
+) -> Result<Vec<u8>, CodecError> {
+    let mut out = vec![0u8; out_len];
+    hkdf::Hkdf::<sha2::Sha256>::new(Some(salt), ikm)
+        .expand(info, &mut out)
+        .map_err(|_| {
+            CodecError::KeyDerivation(format!(
+                "HKDF-SHA256 output is limited to {} bytes, requested {out_len}",
+                255 * 32
+            ))
+        })?;
+    Ok(out)
+}
+
+/// Guesses which digest algorithms could have produced `input`, based on the
+/// decoded length of a hex or base64/base64url string. Hex is preferred when
+/// the input is valid as both. Returns an empty list when nothing matches.
+pub fn identify_hash(input: &str) -> Vec<&'static str> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Vec::new();
+    }
+    if input.len().is_multiple_of(2) && input.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return hash_candidates_for_len(input.len() / 2);
+    }
+    base64_decode_str(input)
+        .or_else(|_| base64url_decode_str(input))
+        .map(|bytes| hash_candidates_for_len(bytes.len()))
+        .unwrap_or_default()
+}
+
+fn hash_candidates_for_len(len: usize) -> Vec<&'static str> {
+    match len {
+        16 => vec!["MD5"],
+        20 => vec!["SHA-1"],
+        28 => vec!["SHA-224"],
+        32 => vec!["SHA-256", "SHA3-256", "BLAKE2b-256"],
+        48 => vec!["SHA-384"],
+        64 => vec!["SHA-512", "SHA3-512"],
+        _ => Vec::new(),
+    }
+}
@@ -1,17 +1,32 @@
+mod charset;
 mod compress;
+mod crypto;
 mod encode;
 mod error;
 mod hash;
+mod minify;
 
-pub use compress::{deflate_compress, deflate_decompress, gzip_compress, gzip_decompress};
+pub use charset::{charset_from_content_type, decode_charset};
+pub use compress::{
+    brotli_compress, brotli_decompress, decode_encoding_chain, deflate_compress,
+    deflate_decompress, deflate_decompress_to_writer, estimate_compressed_size, gzip_compress,
+    gzip_decompress, gzip_decompress_to_writer, zstd_compress, zstd_decompress,
+};
+pub use crypto::{aes_cbc_decrypt, aes_gcm_decrypt};
 pub use encode::{
     base32_decode_bytes, base32_decode_str, base32_encode_bytes, base32_encode_str,
     base58_decode_bytes, base58_decode_str, base58_encode_bytes, base58_encode_str,
-    base64_decode_bytes, base64_decode_str, base64_encode_bytes, base64_encode_str,
-    base64url_decode_bytes, base64url_decode_str, base64url_encode_bytes, base64url_encode_str,
-    bytes_to_string_lossy, hex_decode_bytes, hex_decode_str, hex_encode_bytes, hex_encode_str,
-    html_escape_str, html_unescape_str, rot13_str, string_to_bytes, url_decode_bytes,
-    url_decode_str, url_encode_bytes, url_encode_str,
+    base64_decode_bytes, base64_decode_bytes_lenient, base64_decode_str, base64_decode_str_lenient,
+    base64_encode_bytes, base64_encode_str, base64url_decode_bytes, base64url_decode_str,
+    base64url_encode_bytes, base64url_encode_str, bytes_to_string_lossy, hex_decode_bytes,
+    hex_decode_str, hex_encode_bytes, hex_encode_str, html_escape_attr_str, html_escape_str,
+    html_unescape_str, rot13_str, string_to_bytes, url_decode_bytes, url_decode_form_bytes,
+    url_decode_form_str, url_decode_str, url_encode_bytes, url_encode_bytes_with_set,
+    url_encode_str, url_set,
 };
 pub use error::CodecError;
-pub use hash::{md5_hex, sha1_hex, sha224_hex, sha256_hex, sha384_hex, sha512_hex};
+pub use hash::{
+    blake2b_256_hex, hkdf_sha256, identify_hash, md5_hex, pbkdf2_hmac_sha256, sha1_hex,
+    sha3_256_hex, sha3_512_hex, sha224_hex, sha256_hex, sha384_hex, sha512_hex,
+};
+pub use minify::{canonical_json, format_json, minify_json};
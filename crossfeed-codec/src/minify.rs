@@ -0,0 +1,309 @@
+use serde::de::IgnoredAny;
+
+use crate::CodecError;
+
+/// Strips insignificant whitespace from a JSON document. Keys, key order and
+/// number formatting are preserved exactly as captured.
+pub fn minify_json(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    serde_json::from_slice::<IgnoredAny>(input).map_err(|err| CodecError::Json(err.to_string()))?;
+    let mut output = Vec::with_capacity(input.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for &byte in input {
+        if in_string {
+            output.push(byte);
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match byte {
+            b' ' | b'\t' | b'\n' | b'\r' => {}
+            b'"' => {
+                in_string = true;
+                output.push(byte);
+            }
+            _ => output.push(byte),
+        }
+    }
+    Ok(output)
+}
+
+/// Pretty-prints a JSON document with `indent` spaces per level. Key order
+/// is kept as captured unless `sort_keys` is set, in which case object keys
+/// are ordered so two documents diff cleanly. Strings and number formatting
+/// are always kept as captured.
+pub fn format_json(input: &[u8], indent: usize, sort_keys: bool) -> Result<String, CodecError> {
+    let compact = if sort_keys {
+        let mut output = Vec::with_capacity(input.len());
+        write_sorted(&RawParser::parse(input)?, false, &mut output)?;
+        output
+    } else {
+        minify_json(input)?
+    };
+    let compact = String::from_utf8(compact).map_err(|err| CodecError::Json(err.to_string()))?;
+    let mut output = String::with_capacity(compact.len() * 2);
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = compact.chars().peekable();
+    let newline = |output: &mut String, depth: usize| {
+        output.push('\n');
+        output.extend(std::iter::repeat_n(' ', depth * indent));
+    };
+    while let Some(ch) = chars.next() {
+        if in_string {
+            output.push(ch);
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match ch {
+            '"' => {
+                in_string = true;
+                output.push(ch);
+            }
+            '{' | '[' => {
+                output.push(ch);
+                if matches!(chars.peek(), Some('}' | ']')) {
+                    output.extend(chars.next());
+                } else {
+                    depth += 1;
+                    newline(&mut output, depth);
+                }
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                newline(&mut output, depth);
+                output.push(ch);
+            }
+            ',' => {
+                output.push(ch);
+                newline(&mut output, depth);
+            }
+            ':' => output.push_str(": "),
+            _ => output.push(ch),
+        }
+    }
+    Ok(output)
+}
+
+/// Serializes a JSON document deterministically so semantically equal
+/// documents compare byte-for-byte: keys sorted, no whitespace, strings
+/// re-escaped and numbers rewritten exactly (`1.0`, `1e0` and `1` all become
+/// `1`). Documents that repeat a key in one object are rejected, since their
+/// meaning depends on the reader.
+pub fn canonical_json(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    let mut output = Vec::with_capacity(input.len());
+    write_sorted(&RawParser::parse(input)?, true, &mut output)?;
+    Ok(output)
+}
+
+/// A JSON value split into tokens without interpreting them, so numbers and
+/// strings keep their captured text.
+enum RawJson<'a> {
+    Literal(&'a [u8]),
+    String(&'a [u8]),
+    Number(&'a [u8]),
+    Array(Vec<RawJson<'a>>),
+    Object(Vec<(&'a [u8], RawJson<'a>)>),
+}
+
+struct RawParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RawParser<'a> {
+    fn parse(input: &'a [u8]) -> Result<RawJson<'a>, CodecError> {
+        serde_json::from_slice::<IgnoredAny>(input)
+            .map_err(|err| CodecError::Json(err.to_string()))?;
+        Self { input, pos: 0 }.value()
+    }
+
+    fn peek(&mut self) -> Result<u8, CodecError> {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.pos) {
+            self.pos += 1;
+        }
+        self.input
+            .get(self.pos)
+            .copied()
+            .ok_or_else(|| CodecError::Json("unexpected end of JSON".to_string()))
+    }
+
+    fn value(&mut self) -> Result<RawJson<'a>, CodecError> {
+        match self.peek()? {
+            b'{' => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                loop {
+                    match self.peek()? {
+                        b'}' => break,
+                        b',' => self.pos += 1,
+                        _ => {
+                            let key = self.string()?;
+                            self.peek()?;
+                            self.pos += 1;
+                            members.push((key, self.value()?));
+                        }
+                    }
+                }
+                self.pos += 1;
+                Ok(RawJson::Object(members))
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    match self.peek()? {
+                        b']' => break,
+                        b',' => self.pos += 1,
+                        _ => items.push(self.value()?),
+                    }
+                }
+                self.pos += 1;
+                Ok(RawJson::Array(items))
+            }
+            b'"' => self.string().map(RawJson::String),
+            b'-' | b'0'..=b'9' => Ok(RawJson::Number(self.take_while(|byte| {
+                matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+            }))),
+            _ => Ok(RawJson::Literal(
+                self.take_while(|byte| byte.is_ascii_alphabetic()),
+            )),
+        }
+    }
+
+    fn string(&mut self) -> Result<&'a [u8], CodecError> {
+        let start = self.pos;
+        let mut escaped = false;
+        for (offset, &byte) in self.input[start + 1..].iter().enumerate() {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                self.pos = start + offset + 2;
+                return Ok(&self.input[start..self.pos]);
+            }
+        }
+        Err(CodecError::Json("unterminated JSON string".to_string()))
+    }
+
+    fn take_while(&mut self, accept: impl Fn(u8) -> bool) -> &'a [u8] {
+        let start = self.pos;
+        while self.input.get(self.pos).is_some_and(|&byte| accept(byte)) {
+            self.pos += 1;
+        }
+        &self.input[start..self.pos]
+    }
+}
+
+/// Writes `value` compactly with object keys sorted. `canonical` also
+/// rewrites strings and numbers into one spelling and rejects repeated keys.
+fn write_sorted(value: &RawJson, canonical: bool, output: &mut Vec<u8>) -> Result<(), CodecError> {
+    match value {
+        RawJson::Literal(raw) => output.extend_from_slice(raw),
+        RawJson::String(raw) if canonical => output.extend(canonical_string(raw)?),
+        RawJson::String(raw) => output.extend_from_slice(raw),
+        RawJson::Number(raw) if canonical => output.extend(canonical_number(raw)?.into_bytes()),
+        RawJson::Number(raw) => output.extend_from_slice(raw),
+        RawJson::Array(items) => {
+            output.push(b'[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push(b',');
+                }
+                write_sorted(item, canonical, output)?;
+            }
+            output.push(b']');
+        }
+        RawJson::Object(members) => {
+            let mut members = members
+                .iter()
+                .map(|(key, value)| Ok((decode_string(key)?, *key, value)))
+                .collect::<Result<Vec<_>, CodecError>>()?;
+            members.sort_by(|left, right| left.0.cmp(&right.0));
+            output.push(b'{');
+            for (index, (key, raw_key, value)) in members.iter().enumerate() {
+                if index > 0 {
+                    if canonical && members[index - 1].0 == *key {
+                        return Err(CodecError::Json(format!("duplicate object key {key:?}")));
+                    }
+                    output.push(b',');
+                }
+                if canonical {
+                    output.extend(canonical_string(raw_key)?);
+                } else {
+                    output.extend_from_slice(raw_key);
+                }
+                output.push(b':');
+                write_sorted(value, canonical, output)?;
+            }
+            output.push(b'}');
+        }
+    }
+    Ok(())
+}
+
+fn decode_string(raw: &[u8]) -> Result<String, CodecError> {
+    serde_json::from_slice(raw).map_err(|err| CodecError::Json(err.to_string()))
+}
+
+fn canonical_string(raw: &[u8]) -> Result<Vec<u8>, CodecError> {
+    serde_json::to_vec(&decode_string(raw)?).map_err(|err| CodecError::Json(err.to_string()))
+}
+
+/// Rewrites a JSON number as `digits × 10^exponent` with no redundant zeros,
+/// then prints that exactly. No floating-point conversion is involved, so
+/// large integers and long fractions keep every digit.
+fn canonical_number(raw: &[u8]) -> Result<String, CodecError> {
+    let out_of_range = || CodecError::Json("JSON number exponent out of range".to_string());
+    let text = String::from_utf8_lossy(raw);
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.as_ref()),
+    };
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (
+            mantissa,
+            exponent.parse::<i64>().map_err(|_| out_of_range())?,
+        ),
+        None => (text, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let mut exponent = exponent
+        .checked_sub(fraction.len() as i64)
+        .ok_or_else(out_of_range)?;
+    let digits = format!("{integer}{fraction}");
+    let digits = digits.trim_start_matches('0');
+    let trimmed = digits.trim_end_matches('0');
+    if trimmed.is_empty() {
+        return Ok("0".to_string());
+    }
+    exponent = exponent
+        .checked_add((digits.len() - trimmed.len()) as i64)
+        .ok_or_else(out_of_range)?;
+    let digits = trimmed;
+    let sign = if negative { "-" } else { "" };
+    let len = digits.len() as i64;
+    Ok(if (0..=21).contains(&exponent) {
+        format!("{sign}{digits}{}", "0".repeat(exponent as usize))
+    } else if exponent < 0 && -exponent < len {
+        let (integer, fraction) = digits.split_at((len + exponent) as usize);
+        format!("{sign}{integer}.{fraction}")
+    } else if exponent < 0 && -exponent - len <= 6 {
+        format!("{sign}0.{}{digits}", "0".repeat((-exponent - len) as usize))
+    } else {
+        format!("{sign}{digits}e{exponent}")
+    })
+}
@@ -0,0 +1,49 @@
+use assert_matches::assert_matches;
+use crossfeed_codec::*;
+
+#[test]
+fn charset_is_parsed_from_content_type() {
+    assert_eq!(
+        charset_from_content_type("text/html; charset=ISO-8859-1").as_deref(),
+        Some("iso-8859-1")
+    );
+    assert_eq!(
+        charset_from_content_type("text/plain;format=flowed; Charset=\"utf-16\"").as_deref(),
+        Some("utf-16")
+    );
+    assert_eq!(charset_from_content_type("application/json"), None);
+}
+
+#[test]
+fn latin1_body_decodes_to_text() {
+    let body = b"caf\xe9 na\xefve \xa9";
+    assert_eq!(decode_charset(body, "iso-8859-1").unwrap(), "café naïve ©");
+}
+
+#[test]
+fn utf16le_body_decodes_to_text() {
+    let body = "héllo ✓"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    assert_eq!(decode_charset(&body, "utf-16le").unwrap(), "héllo ✓");
+}
+
+#[test]
+fn utf16_honors_byte_order_mark() {
+    let mut body = vec![0xff, 0xfe];
+    body.extend("bom".encode_utf16().flat_map(u16::to_le_bytes));
+    assert_eq!(decode_charset(&body, "utf-16").unwrap(), "bom");
+}
+
+#[test]
+fn odd_length_utf16_errors() {
+    let err = decode_charset(b"abc", "utf-16le").unwrap_err();
+    assert_matches!(err, CodecError::Charset(_));
+}
+
+#[test]
+fn unknown_charset_errors() {
+    let err = decode_charset(b"abc", "koi8-r").unwrap_err();
+    assert_matches!(err, CodecError::UnsupportedCharset(name) if name == "koi8-r");
+}
@@ -9,6 +9,21 @@ fn gzip_roundtrip() {
     assert_eq!(decompressed, input);
 }
 
+#[test]
+fn gzip_decompress_joins_concatenated_members() {
+    let mut joined = gzip_compress(b"first member, ").unwrap();
+    joined.extend(gzip_compress(b"second member").unwrap());
+
+    assert_eq!(
+        gzip_decompress(&joined).unwrap(),
+        b"first member, second member"
+    );
+    assert_eq!(
+        gzip_decompress(&gzip_compress(b"single").unwrap()).unwrap(),
+        b"single"
+    );
+}
+
 #[test]
 fn deflate_roundtrip() {
     let input = b"hello deflate";
@@ -17,6 +32,110 @@ fn deflate_roundtrip() {
     assert_eq!(decompressed, input);
 }
 
+#[test]
+fn deflate_decompress_accepts_zlib_and_raw_streams() {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::{DeflateEncoder, ZlibEncoder};
+
+    let input = b"same payload, two deflate flavors";
+    let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+    zlib.write_all(input).unwrap();
+    let zlib = zlib.finish().unwrap();
+    let mut raw = DeflateEncoder::new(Vec::new(), Compression::default());
+    raw.write_all(input).unwrap();
+    let raw = raw.finish().unwrap();
+
+    assert_ne!(zlib, raw);
+    assert_eq!(deflate_decompress(&zlib).unwrap(), input);
+    assert_eq!(deflate_decompress(&raw).unwrap(), input);
+}
+
+#[test]
+fn gzip_decompress_to_writer_matches_buffered_decode() {
+    let compressed = gzip_compress(b"hello streaming gzip").unwrap();
+    let mut output = Vec::new();
+    gzip_decompress_to_writer(&compressed, &mut output).unwrap();
+    assert_eq!(output, gzip_decompress(&compressed).unwrap());
+}
+
+#[test]
+fn deflate_decompress_to_writer_accepts_zlib_and_raw_streams() {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::{DeflateEncoder, ZlibEncoder};
+
+    let input = b"hello streaming deflate";
+    let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+    zlib.write_all(input).unwrap();
+    let zlib = zlib.finish().unwrap();
+    let mut raw = DeflateEncoder::new(Vec::new(), Compression::default());
+    raw.write_all(input).unwrap();
+    let raw = raw.finish().unwrap();
+
+    for encoded in [zlib, raw] {
+        let mut output = Vec::new();
+        deflate_decompress_to_writer(&encoded, &mut output).unwrap();
+        assert_eq!(output, input);
+    }
+}
+
+#[test]
+fn estimate_compressed_size_matches_gzip_output() {
+    let input = b"repetitive body ".repeat(256);
+    let estimate = estimate_compressed_size(&input);
+
+    assert_eq!(estimate, gzip_compress(&input).unwrap().len());
+    assert!(estimate < input.len());
+}
+
+#[test]
+fn zstd_roundtrip() {
+    let input = b"hello zstd";
+    let compressed = zstd_compress(input).unwrap();
+    let decompressed = zstd_decompress(&compressed).unwrap();
+    assert_eq!(decompressed, input);
+}
+
+#[test]
+fn brotli_roundtrip() {
+    let input = b"hello brotli";
+    let compressed = brotli_compress(input).unwrap();
+    let decompressed = brotli_decompress(&compressed).unwrap();
+    assert_eq!(decompressed, input);
+}
+
+#[test]
+fn decode_encoding_chain_removes_layers_in_reverse_order() {
+    let input = b"double encoded body";
+    let encoded = brotli_compress(&gzip_compress(input).unwrap()).unwrap();
+
+    assert_eq!(decode_encoding_chain(&encoded, &["gzip", "br"]).unwrap(), input);
+    assert_matches!(
+        decode_encoding_chain(&encoded, &["br", "gzip"]),
+        Err(CodecError::Compression(_))
+    );
+}
+
+#[test]
+fn decode_encoding_chain_passes_identity_through() {
+    let encoded = gzip_compress(b"plain").unwrap();
+    assert_eq!(
+        decode_encoding_chain(&encoded, &["identity", "gzip"]).unwrap(),
+        b"plain"
+    );
+    assert_eq!(decode_encoding_chain(b"plain", &[]).unwrap(), b"plain");
+}
+
+#[test]
+fn decode_encoding_chain_rejects_unknown_token() {
+    let encoded = gzip_compress(b"plain").unwrap();
+    let err = decode_encoding_chain(&encoded, &["gzip", "compress"]).unwrap_err();
+    assert_matches!(err, CodecError::UnsupportedEncoding(token) if token == "compress");
+}
+
 #[test]
 fn gzip_invalid_errors() {
     let err = gzip_decompress(b"not gzip").unwrap_err();
@@ -28,3 +147,15 @@ fn deflate_invalid_errors() {
     let err = deflate_decompress(b"not deflate").unwrap_err();
     assert_matches!(err, CodecError::Compression(_));
 }
+
+#[test]
+fn gzip_decompress_to_writer_invalid_errors() {
+    let err = gzip_decompress_to_writer(b"not gzip", &mut Vec::new()).unwrap_err();
+    assert_matches!(err, CodecError::Compression(_));
+}
+
+#[test]
+fn zstd_invalid_errors() {
+    let err = zstd_decompress(b"not zstd").unwrap_err();
+    assert_matches!(err, CodecError::Compression(_));
+}
@@ -0,0 +1,103 @@
+use assert_matches::assert_matches;
+use crossfeed_codec::*;
+
+fn hex(input: &str) -> Vec<u8> {
+    hex_decode_str(input).unwrap()
+}
+
+#[test]
+fn aes_gcm_decrypts_empty_aad_vector() {
+    // GCM spec test case 2.
+    let plaintext = aes_gcm_decrypt(
+        &[0u8; 16],
+        &[0u8; 12],
+        b"",
+        &hex("0388dace60b6a392f328c2b971b2fe78ab6e47d42cec13bdf53a67b21257bddf"),
+    )
+    .unwrap();
+    assert_eq!(plaintext, vec![0u8; 16]);
+}
+
+#[test]
+fn aes_gcm_decrypts_with_aad() {
+    // GCM spec test case 4.
+    let key = hex("feffe9928665731c6d6a8f9467308308");
+    let nonce = hex("cafebabefacedbaddecaf888");
+    let aad = hex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+    let ciphertext = hex(
+        "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091\
+5bc94fbc3221a5db94fae95ae7121a47",
+    );
+    let plaintext = aes_gcm_decrypt(&key, &nonce, &aad, &ciphertext).unwrap();
+    assert_eq!(
+        hex_encode_bytes(&plaintext),
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39"
+    );
+
+    let mut tampered = ciphertext.clone();
+    tampered[0] ^= 1;
+    assert_matches!(
+        aes_gcm_decrypt(&key, &nonce, &aad, &tampered),
+        Err(CodecError::AuthenticationFailed)
+    );
+    assert_matches!(
+        aes_gcm_decrypt(&key, &nonce, b"other aad", &ciphertext),
+        Err(CodecError::AuthenticationFailed)
+    );
+}
+
+#[test]
+fn aes_gcm_rejects_bad_parameters() {
+    assert_matches!(
+        aes_gcm_decrypt(&[0u8; 15], &[0u8; 12], b"", &[0u8; 16]),
+        Err(CodecError::Crypto(_))
+    );
+    assert_matches!(
+        aes_gcm_decrypt(&[0u8; 16], &[0u8; 8], b"", &[0u8; 16]),
+        Err(CodecError::Crypto(_))
+    );
+}
+
+#[test]
+fn aes_cbc_decrypts_padded_vectors() {
+    // SP 800-38A F.2.1 first block, followed by a full PKCS#7 padding block.
+    let plaintext = aes_cbc_decrypt(
+        &hex("2b7e151628aed2a6abf7158809cf4f3c"),
+        &hex("000102030405060708090a0b0c0d0e0f"),
+        &hex("7649abac8119b246cee98e9b12e9197d8964e0b149c10b7b682e6e39aaeb731c"),
+    )
+    .unwrap();
+    assert_eq!(
+        hex_encode_bytes(&plaintext),
+        "6bc1bee22e409f96e93d7e117393172a"
+    );
+
+    let plaintext = aes_cbc_decrypt(
+        &hex("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"),
+        &hex("000102030405060708090a0b0c0d0e0f"),
+        &hex("652abe2a0a61a6290727827fdfdfb4cd"),
+    )
+    .unwrap();
+    assert_eq!(plaintext, b"crossfeed");
+}
+
+#[test]
+fn aes_cbc_rejects_bad_input() {
+    let key = hex("2b7e151628aed2a6abf7158809cf4f3c");
+    let iv = hex("000102030405060708090a0b0c0d0e0f");
+    // The unpadded SP 800-38A block decrypts to bytes that are not valid padding.
+    assert_matches!(
+        aes_cbc_decrypt(&key, &iv, &hex("7649abac8119b246cee98e9b12e9197d")),
+        Err(CodecError::Crypto(_))
+    );
+    assert_matches!(
+        aes_cbc_decrypt(&key, &iv, &[0u8; 15]),
+        Err(CodecError::Crypto(_))
+    );
+    assert_matches!(
+        aes_cbc_decrypt(&key, &iv[..8], &[0u8; 16]),
+        Err(CodecError::Crypto(_))
+    );
+}
@@ -16,6 +16,70 @@ fn url_decode_str_is_lossy() {
     assert_eq!(decoded, "&")
 }
 
+#[test]
+fn url_encode_default_matches_all_preset() {
+    let input = b"a b/c?d=e&f+g~";
+    assert_eq!(url_encode_bytes(input), "a%20b%2Fc%3Fd%3De%26f%2Bg%7E");
+    assert_eq!(
+        url_encode_bytes_with_set(input, url_set::ALL),
+        url_encode_bytes(input)
+    );
+}
+
+#[test]
+fn url_encode_space_depends_on_preset() {
+    assert_eq!(url_encode_bytes_with_set(b"a b", url_set::PATH), "a%20b");
+    assert_eq!(url_encode_bytes_with_set(b"a b", url_set::QUERY), "a%20b");
+    assert_eq!(url_encode_bytes_with_set(b"a b", url_set::FORM), "a+b");
+}
+
+#[test]
+fn url_encode_presets_escape_context_delimiters() {
+    let input = b"a/b?c=d&e+f";
+    assert_eq!(
+        url_encode_bytes_with_set(input, url_set::PATH),
+        "a%2Fb%3Fc=d&e+f"
+    );
+    assert_eq!(
+        url_encode_bytes_with_set(input, url_set::QUERY),
+        "a/b?c%3Dd%26e%2Bf"
+    );
+    assert_eq!(
+        url_encode_bytes_with_set(input, url_set::FORM),
+        "a%2Fb%3Fc%3Dd%26e%2Bf"
+    );
+}
+
+#[test]
+fn url_encode_custom_set_preserves_listed_bytes() {
+    assert_eq!(url_encode_bytes_with_set(b"{id}-1", b"{}"), "{id}%2D1");
+}
+
+#[test]
+fn url_path_mode_roundtrips_plus_literally() {
+    for input in ["a b", "a+b", "a%2Bb"] {
+        let encoded = url_encode_bytes_with_set(input.as_bytes(), url_set::PATH);
+        assert_eq!(
+            url_decode_bytes(encoded.as_bytes()).unwrap(),
+            input.as_bytes()
+        );
+    }
+    assert_eq!(url_decode_bytes(b"a+b").unwrap(), b"a+b");
+}
+
+#[test]
+fn url_form_mode_roundtrips_plus_as_space() {
+    for input in ["a b", "a+b", "a%2Bb"] {
+        let encoded = url_encode_bytes_with_set(input.as_bytes(), url_set::FORM);
+        assert_eq!(
+            url_decode_form_bytes(encoded.as_bytes()).unwrap(),
+            input.as_bytes()
+        );
+    }
+    assert_eq!(url_decode_form_bytes(b"a+b").unwrap(), b"a b");
+    assert_eq!(url_decode_form_str("a%2Bb+c").unwrap(), "a+b c");
+}
+
 #[test]
 fn base64_roundtrip() {
     let input = b"hello";
@@ -24,6 +88,31 @@ fn base64_roundtrip() {
     assert_eq!(decoded, input);
 }
 
+#[test]
+fn base64_lenient_ignores_embedded_newlines() {
+    let wrapped = "aGVsbG8g\r\nd3JhcHBl\nZCB3b3Js\tZA==";
+    assert_eq!(
+        base64_decode_str_lenient(wrapped).unwrap(),
+        b"hello wrapped world"
+    );
+    assert_matches!(base64_decode_str(wrapped), Err(CodecError::Base64(_)));
+}
+
+#[test]
+fn base64_lenient_accepts_missing_padding() {
+    assert_eq!(base64_decode_str_lenient("aGk").unwrap(), b"hi");
+    assert_eq!(base64_decode_str_lenient("aGk=").unwrap(), b"hi");
+    assert_matches!(base64_decode_str("aGk"), Err(CodecError::Base64(_)));
+}
+
+#[test]
+fn base64_lenient_still_rejects_invalid_alphabet() {
+    assert_matches!(
+        base64_decode_str_lenient("a*b="),
+        Err(CodecError::Base64(_))
+    );
+}
+
 #[test]
 fn base64url_roundtrip() {
     let input = b"hello?";
@@ -88,6 +177,24 @@ fn html_escape_unescape_roundtrip() {
     assert_eq!(unescaped, input);
 }
 
+#[test]
+fn html_escape_attr_escapes_both_quotes() {
+    let escaped = html_escape_attr_str(r#"say "hi" & 'bye' <b>"#);
+    assert_eq!(
+        escaped,
+        "say &quot;hi&quot; &amp; &#x27;bye&#x27; &lt;b&gt;"
+    );
+    assert_eq!(html_unescape_str(&escaped), r#"say "hi" & 'bye' <b>"#);
+}
+
+#[test]
+fn html_unescape_decodes_numeric_references() {
+    assert_eq!(html_unescape_str("&#39;"), "'");
+    assert_eq!(html_unescape_str("&#x27;"), "'");
+    assert_eq!(html_unescape_str("&amp;"), "&");
+    assert_eq!(html_unescape_str("a&#X41;&#65;b"), "aAAb");
+}
+
 #[test]
 fn rot13_roundtrip() {
     let input = "Hello World";
@@ -43,3 +43,122 @@ fn sha512_vector() {
 2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
     );
 }
+
+#[test]
+fn sha3_256_vector() {
+    assert_eq!(
+        sha3_256_hex(b"abc"),
+        "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+    );
+}
+
+#[test]
+fn sha3_512_vector() {
+    assert_eq!(
+        sha3_512_hex(b"abc"),
+        "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+    );
+}
+
+#[test]
+fn blake2b_256_vector() {
+    assert_eq!(
+        blake2b_256_hex(b"abc"),
+        "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+    );
+}
+
+#[test]
+fn identifies_hex_digests_by_length() {
+    assert_eq!(identify_hash(&md5_hex(b"")), vec!["MD5"]);
+    assert_eq!(identify_hash(&sha1_hex(b"")), vec!["SHA-1"]);
+    assert_eq!(
+        identify_hash(&sha256_hex(b"abc").to_uppercase()),
+        vec!["SHA-256", "SHA3-256", "BLAKE2b-256"]
+    );
+}
+
+#[test]
+fn identifies_base64_digests() {
+    let digest = hex_decode_str(&sha256_hex(b"abc")).unwrap();
+    let encoded = base64_encode_bytes(&digest);
+    assert_eq!(encoded.len(), 44);
+    assert_eq!(
+        identify_hash(&encoded),
+        vec!["SHA-256", "SHA3-256", "BLAKE2b-256"]
+    );
+    assert_eq!(
+        identify_hash(&base64url_encode_bytes(&digest)),
+        vec!["SHA-256", "SHA3-256", "BLAKE2b-256"]
+    );
+}
+
+#[test]
+fn identify_hash_rejects_unknown_inputs() {
+    assert!(identify_hash("").is_empty());
+    assert!(identify_hash("abc123").is_empty());
+    assert!(identify_hash("not a hash at all").is_empty());
+}
+
+#[test]
+fn pbkdf2_hmac_sha256_vectors() {
+    // RFC 6070 inputs, SHA-256 outputs.
+    let cases = [
+        (
+            1,
+            "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b",
+        ),
+        (
+            2,
+            "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43",
+        ),
+        (
+            4096,
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a",
+        ),
+    ];
+    for (iterations, expected) in cases {
+        let derived = pbkdf2_hmac_sha256(b"password", b"salt", iterations, 32);
+        assert_eq!(hex_encode_bytes(&derived), expected);
+    }
+
+    // RFC 7914 section 11.
+    assert_eq!(
+        hex_encode_bytes(&pbkdf2_hmac_sha256(b"passwd", b"salt", 1, 64)),
+        "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+    );
+}
+
+#[test]
+fn hkdf_sha256_vectors() {
+    // RFC 5869 test case 1.
+    let ikm = [0x0b; 22];
+    let salt = hex_decode_str("000102030405060708090a0b0c").unwrap();
+    let info = hex_decode_str("f0f1f2f3f4f5f6f7f8f9").unwrap();
+    assert_eq!(
+        hex_encode_bytes(&hkdf_sha256(&ikm, &salt, &info, 42).unwrap()),
+        "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf\
+34007208d5b887185865"
+    );
+
+    // RFC 5869 test case 3: empty salt and info.
+    assert_eq!(
+        hex_encode_bytes(&hkdf_sha256(&ikm, b"", b"", 42).unwrap()),
+        "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
+9d201395faa4b61a96c8"
+    );
+}
+
+#[test]
+fn hkdf_sha256_rejects_overlong_output() {
+    assert_eq!(
+        hkdf_sha256(b"key", b"", b"", 255 * 32).unwrap().len(),
+        255 * 32
+    );
+    assert!(matches!(
+        hkdf_sha256(b"key", b"", b"", 255 * 32 + 1),
+        Err(CodecError::KeyDerivation(_))
+    ));
+}
@@ -0,0 +1,124 @@
+use assert_matches::assert_matches;
+use crossfeed_codec::*;
+
+#[test]
+fn minified_json_is_smaller_and_equivalent() {
+    let input = br#"{
+  "name": "crossfeed",
+  "tags": [ "proxy", "spaced out \" string" ],
+  "nested": { "count": 1.50, "ok": true }
+}"#;
+    let minified = minify_json(input).unwrap();
+
+    assert!(minified.len() < input.len());
+    assert_eq!(
+        minified,
+        br#"{"name":"crossfeed","tags":["proxy","spaced out \" string"],"nested":{"count":1.50,"ok":true}}"#
+    );
+    let original: serde_json::Value = serde_json::from_slice(input).unwrap();
+    let reparsed: serde_json::Value = serde_json::from_slice(&minified).unwrap();
+    assert_eq!(original, reparsed);
+}
+
+#[test]
+fn minify_invalid_json_errors() {
+    let err = minify_json(b"{\"open\": ").unwrap_err();
+    assert_matches!(err, CodecError::Json(_));
+}
+
+#[test]
+fn format_json_uses_requested_indent() {
+    let input = br#"{"b":[1,{}],"a":"x, y: z"}"#;
+    assert_eq!(
+        format_json(input, 2, false).unwrap(),
+        "{\n  \"b\": [\n    1,\n    {}\n  ],\n  \"a\": \"x, y: z\"\n}"
+    );
+    assert_eq!(
+        format_json(input, 4, false).unwrap(),
+        "{\n    \"b\": [\n        1,\n        {}\n    ],\n    \"a\": \"x, y: z\"\n}"
+    );
+}
+
+#[test]
+fn format_json_sorts_keys_deterministically() {
+    let first = br#"{"z":1,"a":{"y":true,"b":null},"m":[]}"#;
+    let second = br#"{ "m": [], "a": { "b": null, "y": true }, "z": 1 }"#;
+    let expected =
+        "{\n  \"a\": {\n    \"b\": null,\n    \"y\": true\n  },\n  \"m\": [],\n  \"z\": 1\n}";
+    assert_eq!(format_json(first, 2, true).unwrap(), expected);
+    assert_eq!(format_json(second, 2, true).unwrap(), expected);
+    assert_ne!(format_json(first, 2, false).unwrap(), expected);
+}
+
+#[test]
+fn format_invalid_json_errors() {
+    assert_matches!(format_json(b"[1,", 2, true), Err(CodecError::Json(_)));
+    assert_matches!(format_json(b"[1,", 2, false), Err(CodecError::Json(_)));
+}
+
+#[test]
+fn canonical_json_ignores_formatting_and_key_order() {
+    let first = br#"{"b": [1.0, 2.50, {"y": null, "x": true}], "a": "text"}"#;
+    let second = b"{\n  \"a\": \"text\",\n  \"b\": [1, 2.5e0, {\"x\": true, \"y\": null}]\n}";
+    let canonical = canonical_json(first).unwrap();
+    assert_eq!(canonical, canonical_json(second).unwrap());
+    assert_eq!(
+        canonical,
+        br#"{"a":"text","b":[1,2.5,{"x":true,"y":null}]}"#
+    );
+}
+
+#[test]
+fn canonical_json_keeps_distinct_values_distinct() {
+    assert_ne!(
+        canonical_json(br#"{"a":1}"#).unwrap(),
+        canonical_json(br#"{"a":"1"}"#).unwrap()
+    );
+    assert_ne!(
+        canonical_json(br#"[1,2]"#).unwrap(),
+        canonical_json(br#"[2,1]"#).unwrap()
+    );
+    assert_matches!(canonical_json(b"{\"a\":"), Err(CodecError::Json(_)));
+}
+
+#[test]
+fn format_json_sorting_keeps_captured_numbers_and_strings() {
+    let input = br#"{"b":1.0,"a":["\u0041",12345678901234567890123,1e2]}"#;
+    assert_eq!(
+        format_json(input, 0, true).unwrap(),
+        "{\n\"a\": [\n\"\\u0041\",\n12345678901234567890123,\n1e2\n],\n\"b\": 1.0\n}"
+    );
+}
+
+#[test]
+fn canonical_json_keeps_numbers_exact() {
+    assert_ne!(
+        canonical_json(b"9007199254740993").unwrap(),
+        canonical_json(b"9007199254740992").unwrap()
+    );
+    assert_ne!(
+        canonical_json(b"0.10000000000000000001").unwrap(),
+        canonical_json(b"0.1").unwrap()
+    );
+    assert_eq!(
+        canonical_json(b"[100, 1e2, 1.00E+2]").unwrap(),
+        b"[100,100,100]"
+    );
+    assert_eq!(
+        canonical_json(b"[-0.0, 0.0050, 12.50]").unwrap(),
+        b"[0,0.005,12.5]"
+    );
+    assert_eq!(canonical_json(b"\"\\u0041\"").unwrap(), b"\"A\"");
+}
+
+#[test]
+fn canonical_json_rejects_duplicate_keys() {
+    assert_matches!(
+        canonical_json(br#"{"a":1,"b":2,"a":3}"#),
+        Err(CodecError::Json(_))
+    );
+    assert_matches!(
+        canonical_json(br#"{"a":1,"\u0061":1}"#),
+        Err(CodecError::Json(_))
+    );
+}
@@ -0,0 +1,89 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossfeed_codec::*;
+
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+/// Discards output while checking it matches the expected pattern.
+struct PatternSink {
+    written: usize,
+    largest_write: usize,
+}
+
+impl Write for PatternSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for (offset, byte) in buf.iter().enumerate() {
+            assert_eq!(*byte, pattern_byte(self.written + offset));
+        }
+        self.written += buf.len();
+        self.largest_write = self.largest_write.max(buf.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn pattern_byte(index: usize) -> u8 {
+    b"crossfeed streaming payload "[index % 28]
+}
+
+type StreamDecoder = fn(&[u8], &mut PatternSink) -> Result<(), CodecError>;
+
+const PAYLOAD_LEN: usize = 8 * 1024 * 1024;
+const CHUNK_BOUND: usize = 256 * 1024;
+
+// Allocation tracking is process-wide, so this binary holds a single test to
+// keep other tests from allocating concurrently.
+#[test]
+fn streaming_decompress_keeps_peak_allocation_bounded() {
+    let payload = (0..PAYLOAD_LEN).map(pattern_byte).collect::<Vec<_>>();
+    let gzip = gzip_compress(&payload).unwrap();
+    let deflate = deflate_compress(&payload).unwrap();
+    drop(payload);
+
+    let decoders: [(&[u8], StreamDecoder); 2] = [
+        (&gzip, gzip_decompress_to_writer),
+        (&deflate, deflate_decompress_to_writer),
+    ];
+    for (input, decode) in decoders {
+        let mut sink = PatternSink {
+            written: 0,
+            largest_write: 0,
+        };
+        let baseline = CURRENT.load(Ordering::SeqCst);
+        PEAK.store(baseline, Ordering::SeqCst);
+
+        decode(input, &mut sink).unwrap();
+
+        assert_eq!(sink.written, PAYLOAD_LEN);
+        assert!(sink.largest_write <= CHUNK_BOUND);
+        let peak = PEAK.load(Ordering::SeqCst) - baseline;
+        assert!(peak < CHUNK_BOUND, "peak allocation {peak} bytes");
+    }
+}
@@ -0,0 +1,32 @@
+pub fn format_bytes(bytes: usize, truncated: bool) -> String {
+    let base = if bytes > 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes > 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{bytes} B")
+    };
+    if truncated {
+        format!("{base} (truncated)")
+    } else {
+        base
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_bytes;
+
+    #[test]
+    fn formats_byte_units() {
+        assert_eq!(format_bytes(0, false), "0 B");
+        assert_eq!(format_bytes(1024, false), "1024 B");
+        assert_eq!(format_bytes(1536, false), "1.5 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024 + 1, false), "5.0 MB");
+    }
+
+    #[test]
+    fn marks_truncated_sizes() {
+        assert_eq!(format_bytes(2048 + 1, true), "2.0 KB (truncated)");
+    }
+}
@@ -0,0 +1,147 @@
+pub const REDACTED_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-auth-token",
+    "x-csrf-token",
+];
+
+pub const REDACTED_VALUE: &str = "[redacted]";
+
+pub fn is_redacted_header(name: &str) -> bool {
+    REDACTED_HEADERS
+        .iter()
+        .any(|redacted| redacted.eq_ignore_ascii_case(name.trim()))
+}
+
+pub fn redact_header_value<'a>(name: &str, value: &'a str) -> &'a str {
+    if is_redacted_header(name) {
+        REDACTED_VALUE
+    } else {
+        value
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedHeaders {
+    entries: Vec<(String, String)>,
+}
+
+impl ParsedHeaders {
+    pub fn parse(raw: &[u8]) -> Self {
+        let end = raw
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .unwrap_or(raw.len());
+        let text = String::from_utf8_lossy(&raw[..end]);
+        let entries = text
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                if name.is_empty() || name.chars().any(|ch| ch.is_whitespace()) {
+                    return None;
+                }
+                Some((name.to_string(), value.trim().to_string()))
+            })
+            .collect();
+        Self { entries }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl FromIterator<(String, String)> for ParsedHeaders {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParsedHeaders, REDACTED_VALUE, is_redacted_header, redact_header_value};
+
+    const RESPONSE: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nSet-Cookie: a=1\r\nset-cookie: b=2\r\n\r\nContent-Type: body";
+
+    #[test]
+    fn parsed_headers_lookup_is_case_insensitive() {
+        let headers = ParsedHeaders::parse(RESPONSE);
+        assert_eq!(headers.get("content-type"), Some("text/html"));
+        assert_eq!(headers.get("CONTENT-TYPE"), Some("text/html"));
+        assert_eq!(headers.len(), 3);
+    }
+
+    #[test]
+    fn parsed_headers_collects_multi_value_headers() {
+        let headers = ParsedHeaders::parse(RESPONSE);
+        assert_eq!(headers.get_all("Set-Cookie"), vec!["a=1", "b=2"]);
+        assert_eq!(headers.get("set-cookie"), Some("a=1"));
+    }
+
+    #[test]
+    fn parsed_headers_handles_absent_headers() {
+        let headers = ParsedHeaders::parse(RESPONSE);
+        assert_eq!(headers.get("content-encoding"), None);
+        assert!(headers.get_all("content-encoding").is_empty());
+        assert!(ParsedHeaders::parse(b"").is_empty());
+    }
+
+    #[test]
+    fn parsed_headers_skips_request_line() {
+        let headers = ParsedHeaders::parse(b"GET /a:b HTTP/1.1\r\nHost: example.com\r\n\r\n");
+        let collected: Vec<_> = headers.iter().collect();
+        assert_eq!(collected, vec![("Host", "example.com")]);
+    }
+
+    #[test]
+    fn parsed_headers_collects_from_pairs() {
+        let headers: ParsedHeaders = [("Origin".to_string(), "https://a.example".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(headers.get("origin"), Some("https://a.example"));
+        assert_eq!(headers.len(), 1);
+    }
+
+    #[test]
+    fn matches_redacted_headers_case_insensitively() {
+        assert!(is_redacted_header("Authorization"));
+        assert!(is_redacted_header("SET-COOKIE"));
+        assert!(is_redacted_header(" cookie "));
+        assert!(!is_redacted_header("content-type"));
+    }
+
+    #[test]
+    fn redacts_only_sensitive_values() {
+        assert_eq!(redact_header_value("Cookie", "sid=abc"), REDACTED_VALUE);
+        assert_eq!(redact_header_value("Accept", "*/*"), "*/*");
+    }
+}
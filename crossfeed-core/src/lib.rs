@@ -1,19 +1,13 @@
-#[derive(Debug, Default)]
-pub struct CorePlaceholder;
+mod bytes;
+mod headers;
+mod status;
+mod timestamp;
+mod version;
 
-impl CorePlaceholder {
-    pub fn name(&self) -> &'static str {
-        "crossfeed-core"
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::CorePlaceholder;
-
-    #[test]
-    fn reports_name() {
-        let placeholder = CorePlaceholder::default();
-        assert_eq!(placeholder.name(), "crossfeed-core");
-    }
-}
+pub use bytes::format_bytes;
+pub use headers::{
+    ParsedHeaders, REDACTED_HEADERS, REDACTED_VALUE, is_redacted_header, redact_header_value,
+};
+pub use status::{UNKNOWN_REASON, reason_phrase};
+pub use timestamp::{TimestampFormat, TimestampPrecision, TimestampZone};
+pub use version::HttpVersion;
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampPrecision {
+    #[default]
+    Auto,
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampZone {
+    #[default]
+    Utc,
+    Local,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimestampFormat {
+    #[serde(default)]
+    pub precision: TimestampPrecision,
+    #[serde(default)]
+    pub timezone: TimestampZone,
+}
+
+impl TimestampFormat {
+    pub fn now(&self) -> String {
+        self.format(Utc::now())
+    }
+
+    pub fn format(&self, value: DateTime<Utc>) -> String {
+        let precision = match self.precision {
+            TimestampPrecision::Auto => SecondsFormat::AutoSi,
+            TimestampPrecision::Seconds => SecondsFormat::Secs,
+            TimestampPrecision::Millis => SecondsFormat::Millis,
+            TimestampPrecision::Micros => SecondsFormat::Micros,
+            TimestampPrecision::Nanos => SecondsFormat::Nanos,
+        };
+        match self.timezone {
+            TimestampZone::Utc => value.to_rfc3339_opts(precision, false),
+            TimestampZone::Local => value
+                .with_timezone(&Local)
+                .to_rfc3339_opts(precision, false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::{TimestampFormat, TimestampPrecision, TimestampZone};
+
+    fn sample() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap()
+            + chrono::Duration::nanoseconds(123_456_789)
+    }
+
+    #[test]
+    fn default_matches_rfc3339() {
+        let value = sample();
+        assert_eq!(TimestampFormat::default().format(value), value.to_rfc3339());
+    }
+
+    #[test]
+    fn formats_requested_precision() {
+        let format = |precision| {
+            TimestampFormat {
+                precision,
+                timezone: TimestampZone::Utc,
+            }
+            .format(sample())
+        };
+        assert_eq!(
+            format(TimestampPrecision::Seconds),
+            "2024-01-02T03:04:05+00:00"
+        );
+        assert_eq!(
+            format(TimestampPrecision::Millis),
+            "2024-01-02T03:04:05.123+00:00"
+        );
+        assert_eq!(
+            format(TimestampPrecision::Micros),
+            "2024-01-02T03:04:05.123456+00:00"
+        );
+    }
+
+    #[test]
+    fn local_zone_preserves_instant() {
+        let format = TimestampFormat {
+            precision: TimestampPrecision::Nanos,
+            timezone: TimestampZone::Local,
+        };
+        let parsed = DateTime::parse_from_rfc3339(&format.format(sample())).unwrap();
+        assert_eq!(parsed.with_timezone(&Utc), sample());
+    }
+}
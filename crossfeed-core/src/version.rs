@@ -0,0 +1,54 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpVersion {
+    Http10,
+    Http11,
+    Other(String),
+}
+
+impl HttpVersion {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "HTTP/1.0" => HttpVersion::Http10,
+            "HTTP/1.1" => HttpVersion::Http11,
+            other => HttpVersion::Other(other.to_string()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            HttpVersion::Http10 => "HTTP/1.0",
+            HttpVersion::Http11 => "HTTP/1.1",
+            HttpVersion::Other(other) => other.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HttpVersion;
+
+    #[test]
+    fn displays_canonical_version() {
+        assert_eq!(HttpVersion::Http10.to_string(), "HTTP/1.0");
+        assert_eq!(HttpVersion::Http11.to_string(), "HTTP/1.1");
+        assert_eq!(
+            HttpVersion::Other("HTTP/2".to_string()).to_string(),
+            "HTTP/2"
+        );
+    }
+
+    #[test]
+    fn parses_round_trip() {
+        for raw in ["HTTP/1.0", "HTTP/1.1", "HTTP/0.9"] {
+            assert_eq!(HttpVersion::parse(raw).as_str(), raw);
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use regex::Regex;
 
-use crate::{AnalysisConfig, AnalysisResult, FuzzError};
+use crate::{
+    AnalysisConfig, AnalysisResult, FuzzError, FuzzResult, ResponseCluster, ScanResult,
+    SimilarityGroup, TimingAnomaly, TimingConfig,
+};
 
 pub fn analyze_response(body: &[u8], config: &AnalysisConfig) -> Result<AnalysisResult, FuzzError> {
     let text = String::from_utf8_lossy(body);
@@ -30,3 +33,127 @@ pub fn analyze_response(body: &[u8], config: &AnalysisConfig) -> Result<Analysis
         extracts,
     })
 }
+
+pub fn cluster_by_length(results: &[FuzzResult], granularity: usize) -> Vec<ResponseCluster> {
+    let mut clusters: Vec<ResponseCluster> = Vec::new();
+    for result in results {
+        let body_len = round_length(result.body_len, granularity);
+        match clusters.iter_mut().find(|cluster| {
+            cluster.status_code == result.status_code && cluster.body_len == body_len
+        }) {
+            Some(cluster) => cluster
+                .timeline_request_ids
+                .push(result.timeline_request_id),
+            None => clusters.push(ResponseCluster {
+                status_code: result.status_code,
+                body_len,
+                timeline_request_ids: vec![result.timeline_request_id],
+            }),
+        }
+    }
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.size()));
+    clusters
+}
+
+fn round_length(len: usize, granularity: usize) -> usize {
+    if granularity <= 1 {
+        return len;
+    }
+    (len + granularity / 2) / granularity * granularity
+}
+
+pub fn simhash(body: &[u8]) -> u64 {
+    let mut weights = [0i64; 64];
+    let text = String::from_utf8_lossy(body);
+    for token in text
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+    {
+        let hash = fnv1a64(token.as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0u64, |hash, (bit, _)| hash | (1 << bit))
+}
+
+pub fn group_by_similarity(results: &[FuzzResult], max_distance: u32) -> Vec<SimilarityGroup> {
+    let mut groups: Vec<SimilarityGroup> = Vec::new();
+    for result in results {
+        match groups
+            .iter_mut()
+            .find(|group| (group.simhash ^ result.body_simhash).count_ones() <= max_distance)
+        {
+            Some(group) => group.timeline_request_ids.push(result.timeline_request_id),
+            None => groups.push(SimilarityGroup {
+                simhash: result.body_simhash,
+                timeline_request_ids: vec![result.timeline_request_id],
+            }),
+        }
+    }
+    groups.sort_by_key(|group| std::cmp::Reverse(group.size()));
+    groups
+}
+
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+pub fn detect_timing_anomalies(
+    results: &[ScanResult],
+    config: &TimingConfig,
+) -> Vec<TimingAnomaly> {
+    let Some(baseline_ms) = median_duration(results) else {
+        return Vec::new();
+    };
+    let mut groups: Vec<(&ScanResult, Vec<i64>)> = Vec::new();
+    for result in results {
+        match groups.iter_mut().find(|(first, _)| {
+            first.parameter == result.parameter
+                && first.location == result.location
+                && first.payload == result.payload
+        }) {
+            Some((_, durations)) => durations.push(result.duration_ms),
+            None => groups.push((result, vec![result.duration_ms])),
+        }
+    }
+
+    let cutoff = baseline_ms.saturating_add(config.threshold_ms);
+    groups
+        .into_iter()
+        .filter(|(_, durations)| {
+            durations.len() >= config.min_repeats.max(1)
+                && durations.iter().all(|duration| *duration >= cutoff)
+        })
+        .map(|(first, durations)| TimingAnomaly {
+            parameter: first.parameter.clone(),
+            location: first.location,
+            payload: first.payload.clone(),
+            baseline_ms,
+            min_duration_ms: durations.iter().copied().min().unwrap_or_default(),
+            repeats: durations.len(),
+        })
+        .collect()
+}
+
+fn median_duration(results: &[ScanResult]) -> Option<i64> {
+    let mut durations: Vec<i64> = results.iter().map(|result| result.duration_ms).collect();
+    if durations.is_empty() {
+        return None;
+    }
+    durations.sort_unstable();
+    Some(durations[durations.len() / 2])
+}
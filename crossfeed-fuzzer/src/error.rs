@@ -4,10 +4,14 @@ use thiserror::Error;
 pub enum FuzzError {
     #[error("template error: {0}")]
     Template(String),
+    #[error("payload error: {0}")]
+    Payload(String),
     #[error("transform error: {0}")]
     Transform(String),
     #[error("analysis error: {0}")]
     Analysis(String),
+    #[error("request error: {0}")]
+    Request(String),
     #[error("storage error: {0}")]
     Storage(String),
 }
@@ -1,16 +1,28 @@
 mod analysis;
 mod error;
 mod model;
+mod passive;
 mod payload;
+mod positions;
 mod run;
+mod scan;
 mod template;
+mod waf;
 
-pub use analysis::analyze_response;
+pub use analysis::{
+    analyze_response, cluster_by_length, detect_timing_anomalies, group_by_similarity, simhash,
+};
 pub use error::FuzzError;
 pub use model::{
-    AnalysisConfig, AnalysisResult, FuzzResult, FuzzRunConfig, FuzzTemplate, Payload, Placeholder,
-    PlaceholderSpec, TransformStep,
+    AnalysisConfig, AnalysisResult, Finding, FindingKind, FindingSeverity, FuzzResult,
+    FuzzRunConfig, FuzzTemplate, ParameterLocation, Payload, Placeholder, PlaceholderSpec,
+    ResponseCluster, ScanResult, SimilarityGroup, TimingAnomaly, TimingConfig, TransformStep,
+    WafVendor,
 };
-pub use payload::{apply_transform_pipeline, payload_to_bytes};
-pub use run::{expand_fuzz_requests, run_fuzz};
+pub use passive::{check_cors, passive_findings};
+pub use payload::{apply_transform_pipeline, expand_payload, payload_to_bytes};
+pub use positions::auto_positions;
+pub use run::{expand_fuzz_requests, resume_fuzz_stream, run_fuzz, run_fuzz_stream};
+pub use scan::scan_parameters;
 pub use template::parse_template;
+pub use waf::fingerprint_waf;
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct FuzzTemplate {
@@ -18,6 +19,7 @@ pub struct Placeholder {
 pub enum Payload {
     Text(String),
     Bytes(Vec<u8>),
+    File { path: PathBuf, base64: bool },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -35,6 +37,10 @@ pub enum TransformStep {
     UrlEncodeStr,
     UrlDecodeBytes,
     UrlDecodeStr,
+    UrlEncodeLowerBytes,
+    DoubleUrlEncodeBytes,
+    UnicodeFullwidthStr,
+    AppendNullByte,
     Base64EncodeBytes,
     Base64EncodeStr,
     Base64DecodeBytes,
@@ -80,6 +86,9 @@ pub enum TransformStep {
 pub struct FuzzRunConfig {
     pub placeholder_prefix: String,
     pub concurrency: usize,
+    pub delay_ms: u64,
+    pub jitter_ms: u64,
+    pub checkpoint_interval: usize,
 }
 
 impl Default for FuzzRunConfig {
@@ -87,6 +96,9 @@ impl Default for FuzzRunConfig {
         Self {
             placeholder_prefix: "<<CFUZZ".to_string(),
             concurrency: 20,
+            delay_ms: 0,
+            jitter_ms: 0,
+            checkpoint_interval: 50,
         }
     }
 }
@@ -114,6 +126,112 @@ pub struct AnalysisResult {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct FuzzResult {
+    pub request_index: usize,
     pub timeline_request_id: i64,
+    pub status_code: u16,
+    pub body_len: usize,
+    pub body_simhash: u64,
+    pub duration_ms: Option<i64>,
     pub analysis: AnalysisResult,
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResponseCluster {
+    pub status_code: u16,
+    pub body_len: usize,
+    pub timeline_request_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SimilarityGroup {
+    pub simhash: u64,
+    pub timeline_request_ids: Vec<i64>,
+}
+
+impl SimilarityGroup {
+    pub fn size(&self) -> usize {
+        self.timeline_request_ids.len()
+    }
+}
+
+impl ResponseCluster {
+    pub fn size(&self) -> usize {
+        self.timeline_request_ids.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ParameterLocation {
+    Query,
+    Body,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScanResult {
+    pub parameter: String,
+    pub location: ParameterLocation,
+    pub payload: Payload,
+    pub status_code: u16,
+    pub duration_ms: i64,
+    pub analysis: AnalysisResult,
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimingConfig {
+    pub threshold_ms: i64,
+    pub min_repeats: usize,
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        Self {
+            threshold_ms: 2_000,
+            min_repeats: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimingAnomaly {
+    pub parameter: String,
+    pub location: ParameterLocation,
+    pub payload: Payload,
+    pub baseline_ms: i64,
+    pub min_duration_ms: i64,
+    pub repeats: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FindingSeverity {
+    Info,
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FindingKind {
+    CorsMisconfiguration,
+    WafDetected(WafVendor),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WafVendor {
+    Cloudflare,
+    Incapsula,
+    Akamai,
+    Sucuri,
+    AwsWaf,
+    F5BigIp,
+    ModSecurity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub severity: FindingSeverity,
+    pub detail: String,
 }
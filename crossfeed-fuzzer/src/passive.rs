@@ -0,0 +1,62 @@
+use crossfeed_core::ParsedHeaders;
+use http::HeaderMap;
+
+use crate::{Finding, FindingKind, FindingSeverity};
+
+pub fn passive_findings(
+    request_headers: &ParsedHeaders,
+    response_headers: &ParsedHeaders,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if let Some(finding) = check_cors(request_headers, response_headers) {
+        findings.push(finding);
+    }
+    findings
+}
+
+pub fn check_cors(
+    request_headers: &ParsedHeaders,
+    response_headers: &ParsedHeaders,
+) -> Option<Finding> {
+    let allow_origin = response_headers.get("access-control-allow-origin")?;
+    let allow_credentials = response_headers
+        .get("access-control-allow-credentials")
+        .is_some_and(|value| value.eq_ignore_ascii_case("true"));
+
+    if allow_origin == "*" {
+        if !allow_credentials {
+            return None;
+        }
+        return Some(Finding {
+            kind: FindingKind::CorsMisconfiguration,
+            severity: FindingSeverity::High,
+            detail: "Access-Control-Allow-Origin is * with Access-Control-Allow-Credentials: true"
+                .to_string(),
+        });
+    }
+
+    let origin = request_headers.get("origin")?;
+    if allow_origin != origin {
+        return None;
+    }
+    let (severity, suffix) = if allow_credentials {
+        (FindingSeverity::High, " with credentials allowed")
+    } else {
+        (FindingSeverity::Medium, "")
+    };
+    Some(Finding {
+        kind: FindingKind::CorsMisconfiguration,
+        severity,
+        detail: format!("request Origin {origin} reflected in Access-Control-Allow-Origin{suffix}"),
+    })
+}
+
+pub(crate) fn header_map_to_parsed(headers: &HeaderMap) -> ParsedHeaders {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).trim().to_string();
+            (name.as_str().to_string(), value)
+        })
+        .collect()
+}
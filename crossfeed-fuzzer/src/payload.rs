@@ -2,13 +2,46 @@ use crossfeed_codec as codec;
 
 use crate::{FuzzError, Payload, TransformStep};
 
-pub fn payload_to_bytes(payload: &Payload) -> Vec<u8> {
+/// A `Payload::File` yields its lines joined with `\n`; use
+/// [`expand_payload`] to get one payload per line.
+pub fn payload_to_bytes(payload: &Payload) -> Result<Vec<u8>, FuzzError> {
     match payload {
-        Payload::Text(text) => text.as_bytes().to_vec(),
-        Payload::Bytes(bytes) => bytes.clone(),
+        Payload::Text(text) => Ok(text.as_bytes().to_vec()),
+        Payload::Bytes(bytes) => Ok(bytes.clone()),
+        Payload::File { .. } => {
+            let lines = expand_payload(payload)?
+                .iter()
+                .map(payload_to_bytes)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(lines.join(&b'\n'))
+        }
     }
 }
 
+pub fn expand_payload(payload: &Payload) -> Result<Vec<Payload>, FuzzError> {
+    let Payload::File { path, base64 } = payload else {
+        return Ok(vec![payload.clone()]);
+    };
+    let contents = std::fs::read(path)
+        .map_err(|err| FuzzError::Payload(format!("failed to read {}: {err}", path.display())))?;
+    let mut payloads = Vec::new();
+    for (line_number, line) in contents.split(|byte| *byte == b'\n').enumerate() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        if *base64 {
+            let decoded = codec::base64_decode_bytes(line).map_err(|err| {
+                FuzzError::Payload(format!("{}:{}: {err}", path.display(), line_number + 1))
+            })?;
+            payloads.push(Payload::Bytes(decoded));
+        } else {
+            payloads.push(Payload::Bytes(line.to_vec()));
+        }
+    }
+    Ok(payloads)
+}
+
 pub fn apply_transform_pipeline(
     input: &[u8],
     steps: &[TransformStep],
@@ -30,6 +63,18 @@ fn apply_transform(input: &[u8], step: &TransformStep) -> Result<Vec<u8>, FuzzEr
         TransformStep::UrlDecodeStr => codec::url_decode_str(&codec::bytes_to_string_lossy(input))
             .map(|value| value.into_bytes())
             .map_err(map_err),
+        TransformStep::UrlEncodeLowerBytes => Ok(url_encode_lower(input).into_bytes()),
+        TransformStep::DoubleUrlEncodeBytes => {
+            Ok(codec::url_encode_bytes(url_encode_lower(input).as_bytes()).into_bytes())
+        }
+        TransformStep::UnicodeFullwidthStr => {
+            Ok(fullwidth(&codec::bytes_to_string_lossy(input)).into_bytes())
+        }
+        TransformStep::AppendNullByte => {
+            let mut output = input.to_vec();
+            output.push(0);
+            Ok(output)
+        }
         TransformStep::Base64EncodeBytes => Ok(codec::base64_encode_bytes(input).into_bytes()),
         TransformStep::Base64EncodeStr => {
             Ok(codec::base64_encode_str(&codec::bytes_to_string_lossy(input)).into_bytes())
@@ -102,6 +147,36 @@ fn apply_transform(input: &[u8], step: &TransformStep) -> Result<Vec<u8>, FuzzEr
     }
 }
 
+// Only the hex digits of `%XX` escapes are lowercased; unreserved characters
+// keep their original case.
+fn url_encode_lower(input: &[u8]) -> String {
+    let encoded = codec::url_encode_bytes(input);
+    let mut output = String::with_capacity(encoded.len());
+    let mut hex_digits = 0;
+    for ch in encoded.chars() {
+        if hex_digits > 0 {
+            output.push(ch.to_ascii_lowercase());
+            hex_digits -= 1;
+        } else {
+            if ch == '%' {
+                hex_digits = 2;
+            }
+            output.push(ch);
+        }
+    }
+    output
+}
+
+fn fullwidth(input: &str) -> String {
+    input
+        .chars()
+        .map(|ch| match ch {
+            '!'..='~' => char::from_u32(ch as u32 - 0x21 + 0xff01).unwrap_or(ch),
+            _ => ch,
+        })
+        .collect()
+}
+
 fn map_err(error: impl std::fmt::Display) -> FuzzError {
     FuzzError::Transform(error.to_string())
 }
@@ -0,0 +1,149 @@
+use std::ops::Range;
+
+use crate::{FuzzTemplate, Placeholder};
+
+pub fn auto_positions(raw_request: &[u8]) -> FuzzTemplate {
+    let mut ranges = Vec::new();
+    let (head_end, body_start) = match raw_request.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(index) => (index, index + 4),
+        None => (raw_request.len(), raw_request.len()),
+    };
+    let head = &raw_request[..head_end];
+    let line_end = head
+        .windows(2)
+        .position(|w| w == b"\r\n")
+        .unwrap_or(head.len());
+    ranges.extend(query_value_ranges(&head[..line_end]));
+
+    let body = &raw_request[body_start..];
+    match body_kind(&head[line_end..], body) {
+        BodyKind::Form => {
+            ranges.extend(shift(pair_value_ranges(body), body_start));
+        }
+        BodyKind::Json => {
+            ranges.extend(shift(json_string_value_ranges(body), body_start));
+        }
+        BodyKind::Other => {}
+    }
+
+    let placeholders = ranges
+        .into_iter()
+        .enumerate()
+        .map(|(index, range)| Placeholder {
+            index: index + 1,
+            token: String::from_utf8_lossy(&raw_request[range.clone()]).into_owned(),
+            ranges: vec![range],
+        })
+        .collect();
+
+    FuzzTemplate {
+        request_bytes: raw_request.to_vec(),
+        placeholders,
+    }
+}
+
+enum BodyKind {
+    Form,
+    Json,
+    Other,
+}
+
+fn body_kind(headers: &[u8], body: &[u8]) -> BodyKind {
+    if body.is_empty() {
+        return BodyKind::Other;
+    }
+    let headers = String::from_utf8_lossy(headers).to_ascii_lowercase();
+    let content_type = headers.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        (name.trim() == "content-type").then(|| value.trim().to_string())
+    });
+    match content_type {
+        Some(value) if value.starts_with("application/x-www-form-urlencoded") => BodyKind::Form,
+        Some(value) if value.contains("json") => BodyKind::Json,
+        Some(_) => BodyKind::Other,
+        None => match body.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'{') | Some(b'[') => BodyKind::Json,
+            _ if body.contains(&b'=') => BodyKind::Form,
+            _ => BodyKind::Other,
+        },
+    }
+}
+
+fn query_value_ranges(request_line: &[u8]) -> Vec<Range<usize>> {
+    let Some(target_start) = request_line.iter().position(|byte| *byte == b' ') else {
+        return Vec::new();
+    };
+    let target_start = target_start + 1;
+    let target_end = request_line[target_start..]
+        .iter()
+        .position(|byte| *byte == b' ')
+        .map(|index| target_start + index)
+        .unwrap_or(request_line.len());
+    let target = &request_line[target_start..target_end];
+    let Some(query_start) = target.iter().position(|byte| *byte == b'?') else {
+        return Vec::new();
+    };
+    let query_start = query_start + 1;
+    let query_end = target[query_start..]
+        .iter()
+        .position(|byte| *byte == b'#')
+        .map(|index| query_start + index)
+        .unwrap_or(target.len());
+    shift(
+        pair_value_ranges(&target[query_start..query_end]),
+        target_start + query_start,
+    )
+}
+
+fn pair_value_ranges(input: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start <= input.len() {
+        let end = input[start..]
+            .iter()
+            .position(|byte| *byte == b'&')
+            .map(|index| start + index)
+            .unwrap_or(input.len());
+        let pair = &input[start..end];
+        if let Some(equals) = pair.iter().position(|byte| *byte == b'=') {
+            ranges.push(start + equals + 1..end);
+        }
+        start = end + 1;
+    }
+    ranges
+}
+
+fn json_string_value_ranges(input: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut index = 0;
+    while index < input.len() {
+        if input[index] != b'"' {
+            index += 1;
+            continue;
+        }
+        let start = index + 1;
+        let mut end = start;
+        while end < input.len() && input[end] != b'"' {
+            if input[end] == b'\\' {
+                end += 1;
+            }
+            end += 1;
+        }
+        let end = end.min(input.len());
+        let next = input[(end + 1).min(input.len())..]
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace());
+        if next != Some(&b':') {
+            ranges.push(start..end);
+        }
+        index = end + 1;
+    }
+    ranges
+}
+
+fn shift(ranges: Vec<Range<usize>>, offset: usize) -> Vec<Range<usize>> {
+    ranges
+        .into_iter()
+        .map(|range| range.start + offset..range.end + offset)
+        .collect()
+}
@@ -1,25 +1,37 @@
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
 use async_stream::try_stream;
+use futures_util::StreamExt;
+use futures_util::stream::FuturesUnordered;
 
+use crate::passive::header_map_to_parsed;
 use crate::{
     AnalysisConfig, FuzzError, FuzzResult, FuzzRunConfig, FuzzTemplate, PlaceholderSpec,
-    analyze_response, apply_transform_pipeline, payload_to_bytes,
+    analyze_response, apply_transform_pipeline, expand_payload, passive_findings, payload_to_bytes,
+    simhash,
 };
+use crossfeed_core::ParsedHeaders;
 use crossfeed_storage::{TimelineRequest, TimelineResponse};
+use crossfeed_web::{Client, RateLimiter, Request, Response};
 
 pub fn expand_fuzz_requests(
     template: &FuzzTemplate,
     specs: &[PlaceholderSpec],
 ) -> Result<Vec<Vec<u8>>, FuzzError> {
-    let mut requests = Vec::new();
     let mut by_index = specs.to_vec();
     by_index.sort_by_key(|spec| spec.index);
 
-    let mut expanded = vec![template.request_bytes.clone()];
+    let mut expanded: Vec<Vec<(usize, Vec<u8>)>> = vec![Vec::new()];
     for spec in &by_index {
         let mut next = Vec::new();
-        for request in &expanded {
-            for payload in &spec.payloads {
-                let mut value = payload_to_bytes(payload);
+        let mut payloads = Vec::new();
+        for payload in &spec.payloads {
+            payloads.extend(expand_payload(payload)?);
+        }
+        for assignment in &expanded {
+            for payload in &payloads {
+                let mut value = payload_to_bytes(payload)?;
                 if let Some(prefix) = &spec.prefix {
                     let mut prefixed = prefix.clone();
                     prefixed.extend_from_slice(&value);
@@ -31,39 +43,42 @@ pub fn expand_fuzz_requests(
                     value = suffixed;
                 }
                 let value = apply_transform_pipeline(&value, &spec.transforms)?;
-                let replaced = replace_placeholder(request, &template, spec.index, &value)?;
-                next.push(replaced);
+                let mut filled = assignment.clone();
+                filled.push((spec.index, value));
+                next.push(filled);
             }
         }
         expanded = next;
     }
 
-    requests.extend(expanded);
-    Ok(requests)
+    expanded
+        .iter()
+        .map(|assignment| fill_placeholders(template, assignment))
+        .collect()
 }
 
-fn replace_placeholder(
-    request: &[u8],
+fn fill_placeholders(
     template: &FuzzTemplate,
-    index: usize,
-    value: &[u8],
+    assignment: &[(usize, Vec<u8>)],
 ) -> Result<Vec<u8>, FuzzError> {
-    let placeholder = template
-        .placeholders
-        .iter()
-        .find(|item| item.index == index)
-        .ok_or_else(|| FuzzError::Template(format!("missing placeholder {index}")))?;
+    let mut replacements = Vec::new();
+    for (index, value) in assignment {
+        let placeholder = template
+            .placeholders
+            .iter()
+            .find(|item| item.index == *index)
+            .ok_or_else(|| FuzzError::Template(format!("missing placeholder {index}")))?;
+        for range in &placeholder.ranges {
+            replacements.push((range.clone(), value.as_slice()));
+        }
+    }
 
-    let mut output = Vec::new();
-    let mut cursor = 0;
-    let mut ranges = placeholder.ranges.clone();
-    ranges.sort_by_key(|range| range.start);
-    for range in ranges {
-        output.extend_from_slice(&request[cursor..range.start]);
-        output.extend_from_slice(value);
-        cursor = range.end;
+    // Splice from the end of the template so earlier ranges keep their offsets.
+    replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut output = template.request_bytes.clone();
+    for (range, value) in replacements {
+        output.splice(range, value.iter().copied());
     }
-    output.extend_from_slice(&request[cursor..]);
     Ok(output)
 }
 
@@ -79,14 +94,123 @@ where
     I: IntoIterator<Item = (TimelineRequest, TimelineResponse)> + 'a,
 {
     try_stream! {
-        let _ = config;
         let _ = template;
         let _ = specs;
-        for (request, response) in responses {
+        let limiter = pacing_limiter(config);
+        for (request_index, (request, response)) in responses.into_iter().enumerate() {
+            if let Some(limiter) = &limiter {
+                limiter.acquire().await;
+            }
             let body = response.response_body.clone();
+            let status_code = response.status_code;
+            let body_len = response.response_body_size;
+            let duration_ms = request.duration_ms;
+            let body_simhash = simhash(&body);
             let analysis_result = analyze_response(&body, analysis)?;
+            let findings = passive_findings(
+                &ParsedHeaders::parse(&request.request_headers),
+                &ParsedHeaders::parse(&response.response_headers),
+            );
             let timeline_request_id = sender(request, response)?;
-            yield FuzzResult { timeline_request_id, analysis: analysis_result };
+            yield FuzzResult {
+                request_index,
+                timeline_request_id,
+                status_code,
+                body_len,
+                body_simhash,
+                duration_ms,
+                analysis: analysis_result,
+                findings,
+            };
         }
     }
 }
+
+pub fn run_fuzz_stream<'a>(
+    client: &'a Client,
+    requests: Vec<Request>,
+    analysis: &'a AnalysisConfig,
+    config: &'a FuzzRunConfig,
+    sender: impl FnMut(usize, &Request, &Response) -> Result<i64, FuzzError> + 'a,
+) -> impl futures_core::Stream<Item = Result<FuzzResult, FuzzError>> + 'a {
+    resume_fuzz_stream(client, requests, analysis, config, 0, sender, |_| Ok(()))
+}
+
+pub fn resume_fuzz_stream<'a>(
+    client: &'a Client,
+    requests: Vec<Request>,
+    analysis: &'a AnalysisConfig,
+    config: &'a FuzzRunConfig,
+    resume_from: usize,
+    mut sender: impl FnMut(usize, &Request, &Response) -> Result<i64, FuzzError> + 'a,
+    mut checkpoint: impl FnMut(usize) -> Result<(), FuzzError> + 'a,
+) -> impl futures_core::Stream<Item = Result<FuzzResult, FuzzError>> + 'a {
+    try_stream! {
+        let limiter = pacing_limiter(config);
+        let concurrency = config.concurrency.max(1);
+        let checkpoint_interval = config.checkpoint_interval.max(1);
+        let total = requests.len();
+        let mut pending = requests.into_iter().enumerate().skip(resume_from);
+        let mut in_flight = FuturesUnordered::new();
+        let mut completed = BTreeSet::new();
+        let mut watermark = resume_from.min(total);
+        let mut since_checkpoint = 0;
+        loop {
+            while in_flight.len() < concurrency {
+                let Some((request_index, request)) = pending.next() else {
+                    break;
+                };
+                if let Some(limiter) = &limiter {
+                    limiter.acquire().await;
+                }
+                let client = client.clone();
+                in_flight.push(tokio::spawn(async move {
+                    let started = Instant::now();
+                    let result = client.request(request.clone()).await;
+                    (request_index, request, result, started.elapsed())
+                }));
+            }
+            let Some(joined) = in_flight.next().await else {
+                break;
+            };
+            let (request_index, request, result, elapsed) =
+                joined.map_err(|err| FuzzError::Request(err.to_string()))?;
+            let response = result.map_err(FuzzError::Request)?;
+            let analysis_result = analyze_response(&response.body, analysis)?;
+            let findings = passive_findings(
+                &header_map_to_parsed(&request.headers),
+                &header_map_to_parsed(&response.headers),
+            );
+            let timeline_request_id = sender(request_index, &request, &response)?;
+            completed.insert(request_index);
+            while completed.remove(&watermark) {
+                watermark += 1;
+            }
+            since_checkpoint += 1;
+            if since_checkpoint >= checkpoint_interval || watermark == total {
+                checkpoint(watermark)?;
+                since_checkpoint = 0;
+            }
+            yield FuzzResult {
+                request_index,
+                timeline_request_id,
+                status_code: response.status,
+                body_len: response.body.len(),
+                body_simhash: simhash(&response.body),
+                duration_ms: Some(elapsed.as_millis() as i64),
+                analysis: analysis_result,
+                findings,
+            };
+        }
+    }
+}
+
+fn pacing_limiter(config: &FuzzRunConfig) -> Option<RateLimiter> {
+    if config.delay_ms == 0 && config.jitter_ms == 0 {
+        return None;
+    }
+    Some(RateLimiter::paced(
+        Duration::from_millis(config.delay_ms),
+        Duration::from_millis(config.jitter_ms),
+    ))
+}
@@ -0,0 +1,153 @@
+use std::time::Instant;
+
+use crossfeed_codec as codec;
+use crossfeed_web::{Client, Request};
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{HeaderValue, Uri};
+
+use crate::passive::header_map_to_parsed;
+use crate::waf::waf_finding;
+use crate::{
+    AnalysisConfig, FuzzError, ParameterLocation, Payload, ScanResult, analyze_response,
+    expand_payload, passive_findings, payload_to_bytes,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Parameter {
+    location: ParameterLocation,
+    position: usize,
+    name: String,
+}
+
+pub async fn scan_parameters(
+    client: &Client,
+    request: &Request,
+    payloads: &[Payload],
+    analysis: &AnalysisConfig,
+) -> Result<Vec<ScanResult>, FuzzError> {
+    let mut expanded = Vec::new();
+    for payload in payloads {
+        expanded.extend(expand_payload(payload)?);
+    }
+    let mut results = Vec::new();
+    for parameter in collect_parameters(request) {
+        for payload in &expanded {
+            let injected = inject_parameter(request, &parameter, payload)?;
+            let request_headers = header_map_to_parsed(&injected.headers);
+            let started = Instant::now();
+            let response = client.request(injected).await.map_err(FuzzError::Request)?;
+            let duration_ms = started.elapsed().as_millis() as i64;
+            let analysis_result = analyze_response(&response.body, analysis)?;
+            let mut findings: Vec<_> = waf_finding(&response).into_iter().collect();
+            findings.extend(passive_findings(
+                &request_headers,
+                &header_map_to_parsed(&response.headers),
+            ));
+            results.push(ScanResult {
+                parameter: parameter.name.clone(),
+                location: parameter.location,
+                payload: payload.clone(),
+                status_code: response.status,
+                duration_ms,
+                analysis: analysis_result,
+                findings,
+            });
+        }
+    }
+    Ok(results)
+}
+
+fn collect_parameters(request: &Request) -> Vec<Parameter> {
+    let mut parameters = Vec::new();
+    if let Some(query) = request.uri.query() {
+        for (position, pair) in split_pairs(query).iter().enumerate() {
+            parameters.push(Parameter {
+                location: ParameterLocation::Query,
+                position,
+                name: pair_name(pair).to_string(),
+            });
+        }
+    }
+    if is_form_body(request) {
+        let body = String::from_utf8_lossy(&request.body);
+        for (position, pair) in split_pairs(&body).iter().enumerate() {
+            parameters.push(Parameter {
+                location: ParameterLocation::Body,
+                position,
+                name: pair_name(pair).to_string(),
+            });
+        }
+    }
+    parameters
+}
+
+fn inject_parameter(
+    request: &Request,
+    parameter: &Parameter,
+    payload: &Payload,
+) -> Result<Request, FuzzError> {
+    let value = codec::url_encode_bytes(&payload_to_bytes(payload)?);
+    let mut injected = request.clone();
+    match parameter.location {
+        ParameterLocation::Query => {
+            let query = request.uri.query().unwrap_or_default();
+            let query = replace_pair_value(query, parameter.position, &value);
+            injected.uri = replace_query(&request.uri, &query)?;
+        }
+        ParameterLocation::Body => {
+            let body = String::from_utf8_lossy(&request.body);
+            injected.body = replace_pair_value(&body, parameter.position, &value).into_bytes();
+            injected
+                .headers
+                .insert(CONTENT_LENGTH, HeaderValue::from(injected.body.len()));
+        }
+    }
+    Ok(injected)
+}
+
+fn is_form_body(request: &Request) -> bool {
+    !request.body.is_empty()
+        && request
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                value
+                    .to_ascii_lowercase()
+                    .starts_with("application/x-www-form-urlencoded")
+            })
+}
+
+fn split_pairs(input: &str) -> Vec<&str> {
+    input.split('&').filter(|pair| !pair.is_empty()).collect()
+}
+
+fn pair_name(pair: &str) -> &str {
+    pair.split_once('=').map(|(name, _)| name).unwrap_or(pair)
+}
+
+fn replace_pair_value(input: &str, position: usize, value: &str) -> String {
+    split_pairs(input)
+        .iter()
+        .enumerate()
+        .map(|(index, pair)| {
+            if index == position {
+                format!("{}={}", pair_name(pair), value)
+            } else {
+                pair.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn replace_query(uri: &Uri, query: &str) -> Result<Uri, FuzzError> {
+    let mut parts = uri.clone().into_parts();
+    let path_and_query = format!("{}?{}", uri.path(), query);
+    parts.path_and_query = Some(
+        path_and_query
+            .parse()
+            .map_err(|err: http::uri::InvalidUri| FuzzError::Request(err.to_string()))?,
+    );
+    Uri::from_parts(parts).map_err(|err| FuzzError::Request(err.to_string()))
+}
@@ -0,0 +1,97 @@
+use crossfeed_web::Response;
+
+use crate::{Finding, FindingKind, FindingSeverity, WafVendor};
+
+const BLOCK_STATUSES: [u16; 2] = [403, 406];
+
+struct WafSignature {
+    vendor: WafVendor,
+    server: &'static [&'static str],
+    headers: &'static [&'static str],
+    body: &'static [&'static str],
+}
+
+const SIGNATURES: &[WafSignature] = &[
+    WafSignature {
+        vendor: WafVendor::Cloudflare,
+        server: &["cloudflare"],
+        headers: &["cf-ray", "cf-mitigated"],
+        body: &["cloudflare ray id", "attention required! | cloudflare"],
+    },
+    WafSignature {
+        vendor: WafVendor::Incapsula,
+        server: &[],
+        headers: &["x-iinfo"],
+        body: &["incapsula incident id", "_incapsula_resource"],
+    },
+    WafSignature {
+        vendor: WafVendor::Akamai,
+        server: &["akamaighost"],
+        headers: &["x-akamai-request-id"],
+        body: &["errors.edgesuite.net"],
+    },
+    WafSignature {
+        vendor: WafVendor::Sucuri,
+        server: &["sucuri/cloudproxy"],
+        headers: &["x-sucuri-id", "x-sucuri-block"],
+        body: &["sucuri website firewall"],
+    },
+    WafSignature {
+        vendor: WafVendor::AwsWaf,
+        server: &["awselb"],
+        headers: &["x-amzn-waf-action"],
+        body: &["request blocked by aws waf"],
+    },
+    WafSignature {
+        vendor: WafVendor::F5BigIp,
+        server: &["bigip"],
+        headers: &["x-wa-info"],
+        body: &["the requested url was rejected. please consult with your administrator"],
+    },
+    WafSignature {
+        vendor: WafVendor::ModSecurity,
+        server: &["mod_security"],
+        headers: &[],
+        body: &["mod_security", "modsecurity"],
+    },
+];
+
+pub fn fingerprint_waf(response: &Response) -> Option<WafVendor> {
+    if !BLOCK_STATUSES.contains(&response.status) {
+        return None;
+    }
+    let server = response
+        .headers
+        .get(http::header::SERVER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let body = String::from_utf8_lossy(&response.body).to_ascii_lowercase();
+
+    SIGNATURES
+        .iter()
+        .find(|signature| {
+            signature
+                .server
+                .iter()
+                .any(|marker| server.contains(marker))
+                || signature
+                    .headers
+                    .iter()
+                    .any(|name| response.headers.contains_key(*name))
+                || signature.body.iter().any(|marker| body.contains(marker))
+        })
+        .map(|signature| signature.vendor)
+}
+
+pub(crate) fn waf_finding(response: &Response) -> Option<Finding> {
+    let vendor = fingerprint_waf(response)?;
+    Some(Finding {
+        kind: FindingKind::WafDetected(vendor),
+        severity: FindingSeverity::Info,
+        detail: format!(
+            "status {} matches {:?} WAF signature",
+            response.status, vendor
+        ),
+    })
+}
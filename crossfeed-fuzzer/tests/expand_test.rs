@@ -1,6 +1,9 @@
+use std::io::Write;
+
+use assert_matches::assert_matches;
 use crossfeed_fuzzer::{
-    AnalysisConfig, FuzzRunConfig, Payload, PlaceholderSpec, TransformStep, expand_fuzz_requests,
-    parse_template,
+    AnalysisConfig, FuzzError, FuzzRunConfig, Payload, PlaceholderSpec, TransformStep,
+    expand_fuzz_requests, parse_template,
 };
 
 #[test]
@@ -59,3 +62,102 @@ fn analysis_defaults_empty() {
     assert!(config.grep.is_empty());
     assert!(config.extract.is_empty());
 }
+
+#[test]
+fn expands_payloads_from_wordlist_file() {
+    let mut wordlist = tempfile::NamedTempFile::new().unwrap();
+    write!(wordlist, "admin\r\nroot\n\nguest\n").unwrap();
+    let bytes = b"GET /?user=<<CFUZZ:1>> HTTP/1.1\r\n\r\n";
+    let template = parse_template(bytes, &FuzzRunConfig::default().placeholder_prefix).unwrap();
+    let specs = vec![PlaceholderSpec {
+        index: 1,
+        payloads: vec![
+            Payload::File {
+                path: wordlist.path().to_path_buf(),
+                base64: false,
+            },
+            Payload::Text("extra".to_string()),
+        ],
+        transforms: Vec::new(),
+        prefix: None,
+        suffix: None,
+    }];
+    let requests = expand_fuzz_requests(&template, &specs).unwrap();
+    assert_eq!(requests.len(), 4);
+    assert_eq!(requests[0], b"GET /?user=admin HTTP/1.1\r\n\r\n".to_vec());
+    assert_eq!(requests[2], b"GET /?user=guest HTTP/1.1\r\n\r\n".to_vec());
+}
+
+#[test]
+fn expands_base64_wordlist_lines() {
+    let mut wordlist = tempfile::NamedTempFile::new().unwrap();
+    writeln!(wordlist, "Li4vZXRjL3Bhc3N3ZA==").unwrap();
+    let bytes = b"GET /<<CFUZZ:1>> HTTP/1.1\r\n\r\n";
+    let template = parse_template(bytes, &FuzzRunConfig::default().placeholder_prefix).unwrap();
+    let specs = vec![PlaceholderSpec {
+        index: 1,
+        payloads: vec![Payload::File {
+            path: wordlist.path().to_path_buf(),
+            base64: true,
+        }],
+        transforms: Vec::new(),
+        prefix: None,
+        suffix: None,
+    }];
+    let requests = expand_fuzz_requests(&template, &specs).unwrap();
+    assert_eq!(
+        requests,
+        vec![b"GET /../etc/passwd HTTP/1.1\r\n\r\n".to_vec()]
+    );
+}
+
+#[test]
+fn missing_wordlist_file_is_a_payload_error() {
+    let bytes = b"GET /?q=<<CFUZZ:1>> HTTP/1.1\r\n\r\n";
+    let template = parse_template(bytes, &FuzzRunConfig::default().placeholder_prefix).unwrap();
+    let specs = vec![PlaceholderSpec {
+        index: 1,
+        payloads: vec![Payload::File {
+            path: "/nonexistent/crossfeed-wordlist.txt".into(),
+            base64: false,
+        }],
+        transforms: Vec::new(),
+        prefix: None,
+        suffix: None,
+    }];
+    let result = expand_fuzz_requests(&template, &specs);
+    assert_matches!(result, Err(FuzzError::Payload(message)) if message.contains("crossfeed-wordlist.txt"));
+}
+
+#[test]
+fn fills_multiple_positions_against_the_original_template() {
+    let bytes = b"GET /<<CFUZZ:1>>?a=<<CFUZZ:2>>&b=<<CFUZZ:1>> HTTP/1.1\r\n\r\n";
+    let template = parse_template(bytes, &FuzzRunConfig::default().placeholder_prefix).unwrap();
+    let specs = vec![
+        PlaceholderSpec {
+            index: 1,
+            payloads: vec![Payload::Text("longer-value".to_string())],
+            transforms: Vec::new(),
+            prefix: None,
+            suffix: None,
+        },
+        PlaceholderSpec {
+            index: 2,
+            payloads: vec![
+                Payload::Text("x".to_string()),
+                Payload::Text("yz".to_string()),
+            ],
+            transforms: Vec::new(),
+            prefix: None,
+            suffix: None,
+        },
+    ];
+    let requests = expand_fuzz_requests(&template, &specs).unwrap();
+    assert_eq!(
+        requests,
+        vec![
+            b"GET /longer-value?a=x&b=longer-value HTTP/1.1\r\n\r\n".to_vec(),
+            b"GET /longer-value?a=yz&b=longer-value HTTP/1.1\r\n\r\n".to_vec(),
+        ]
+    );
+}
@@ -0,0 +1,42 @@
+use crossfeed_core::ParsedHeaders;
+use crossfeed_fuzzer::{FindingKind, FindingSeverity, check_cors, passive_findings};
+
+#[test]
+fn cors_wildcard_with_credentials_is_flagged() {
+    let request = ParsedHeaders::parse(
+        b"GET /account HTTP/1.1\r\nHost: api.example.com\r\nOrigin: https://evil.example\r\n\r\n",
+    );
+    let response = ParsedHeaders::parse(
+        b"HTTP/1.1 200 OK\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Credentials: true\r\n\r\n",
+    );
+    let finding = check_cors(&request, &response).unwrap();
+    assert_eq!(finding.kind, FindingKind::CorsMisconfiguration);
+    assert_eq!(finding.severity, FindingSeverity::High);
+}
+
+#[test]
+fn cors_reflected_origin_is_flagged() {
+    let request = ParsedHeaders::parse(
+        b"GET /account HTTP/1.1\r\nHost: api.example.com\r\nOrigin: https://evil.example\r\n\r\n",
+    );
+    let response = ParsedHeaders::parse(
+        b"HTTP/1.1 200 OK\r\naccess-control-allow-origin: https://evil.example\r\naccess-control-allow-credentials: true\r\n\r\n",
+    );
+    let findings = passive_findings(&request, &response);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].kind, FindingKind::CorsMisconfiguration);
+    assert_eq!(findings[0].severity, FindingSeverity::High);
+    assert!(findings[0].detail.contains("https://evil.example"));
+}
+
+#[test]
+fn cors_fixed_origin_is_not_flagged() {
+    let request = ParsedHeaders::parse(
+        b"GET /account HTTP/1.1\r\nHost: api.example.com\r\nOrigin: https://evil.example\r\n\r\n",
+    );
+    let response = ParsedHeaders::parse(
+        b"HTTP/1.1 200 OK\r\nAccess-Control-Allow-Origin: https://app.example.com\r\nAccess-Control-Allow-Credentials: true\r\n\r\n",
+    );
+    assert!(check_cors(&request, &response).is_none());
+    assert!(passive_findings(&request, &response).is_empty());
+}
@@ -1,5 +1,11 @@
-use crossfeed_fuzzer::{AnalysisConfig, FuzzResult, FuzzRunConfig, analyze_response, run_fuzz};
+use std::time::{Duration, Instant};
+
+use crossfeed_fuzzer::{
+    AnalysisConfig, AnalysisResult, FindingKind, FuzzResult, FuzzRunConfig, analyze_response,
+    cluster_by_length, group_by_similarity, run_fuzz, simhash,
+};
 use crossfeed_storage::{TimelineRequest, TimelineResponse};
+use futures_util::StreamExt;
 
 fn sample_request() -> TimelineRequest {
     TimelineRequest {
@@ -24,6 +30,8 @@ fn sample_request() -> TimelineRequest {
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
     }
 }
 
@@ -38,6 +46,7 @@ fn sample_response(body: &[u8]) -> TimelineResponse {
         response_body_truncated: false,
         http_version: "HTTP/1.1".to_string(),
         received_at: "now".to_string(),
+        ttfb_ms: None,
     }
 }
 
@@ -86,3 +95,160 @@ fn run_fuzz_streams_results() {
     let collected: Vec<Result<FuzzResult, _>> = futures_executor::block_on_stream(stream).collect();
     assert_eq!(collected.len(), 2);
 }
+
+#[test]
+fn run_fuzz_reports_passive_findings() {
+    let analysis = AnalysisConfig::default();
+    let config = FuzzRunConfig::default();
+    let template = crossfeed_fuzzer::FuzzTemplate {
+        request_bytes: Vec::new(),
+        placeholders: Vec::new(),
+    };
+    let specs = Vec::new();
+
+    let mut request = sample_request();
+    request.request_headers = b"Host: example.com\r\nOrigin: https://evil.example\r\n".to_vec();
+    let mut response = sample_response(b"ok");
+    response.response_headers =
+        b"Access-Control-Allow-Origin: https://evil.example\r\nContent-Length: 2\r\n".to_vec();
+    let responses = vec![
+        (request, response),
+        (sample_request(), sample_response(b"ok")),
+    ];
+
+    let stream = run_fuzz(
+        &template,
+        &specs,
+        &analysis,
+        &config,
+        |_: TimelineRequest, _: TimelineResponse| Ok(1),
+        responses,
+    );
+    let stream = std::pin::pin!(stream);
+    let results: Vec<FuzzResult> = futures_executor::block_on_stream(stream)
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(results[0].findings.len(), 1);
+    assert_eq!(
+        results[0].findings[0].kind,
+        FindingKind::CorsMisconfiguration
+    );
+    assert!(results[1].findings.is_empty());
+}
+
+#[tokio::test]
+async fn run_fuzz_honors_delay() {
+    let analysis = AnalysisConfig::default();
+    let config = FuzzRunConfig {
+        delay_ms: 100,
+        ..FuzzRunConfig::default()
+    };
+    let template = crossfeed_fuzzer::FuzzTemplate {
+        request_bytes: Vec::new(),
+        placeholders: Vec::new(),
+    };
+    let specs = Vec::new();
+    let responses = (0..3).map(|_| (sample_request(), sample_response(b"ok")));
+
+    let started = Instant::now();
+    let stream = run_fuzz(
+        &template,
+        &specs,
+        &analysis,
+        &config,
+        |_: TimelineRequest, _: TimelineResponse| Ok(1),
+        responses,
+    );
+    let results: Vec<_> = stream.collect().await;
+
+    assert_eq!(results.len(), 3);
+    assert!(started.elapsed() >= Duration::from_millis(200));
+}
+
+fn fuzz_result(timeline_request_id: i64, status_code: u16, body_len: usize) -> FuzzResult {
+    FuzzResult {
+        request_index: timeline_request_id as usize,
+        timeline_request_id,
+        status_code,
+        body_len,
+        body_simhash: 0,
+        duration_ms: None,
+        analysis: AnalysisResult {
+            grep_matches: Vec::new(),
+            extracts: Vec::new(),
+        },
+        findings: Vec::new(),
+    }
+}
+
+#[test]
+fn cluster_by_length_isolates_outlier() {
+    let mut results: Vec<FuzzResult> = (1..=9).map(|id| fuzz_result(id, 200, 1024)).collect();
+    results.push(fuzz_result(10, 200, 2048));
+
+    let clusters = cluster_by_length(&results, 10);
+    assert_eq!(clusters.len(), 2);
+    assert_eq!(clusters[0].size(), 9);
+    assert_eq!(clusters[1].size(), 1);
+    assert_eq!(clusters[1].timeline_request_ids, vec![10]);
+}
+
+#[test]
+fn cluster_by_length_rounds_and_splits_on_status() {
+    let results = vec![
+        fuzz_result(1, 200, 1001),
+        fuzz_result(2, 200, 1003),
+        fuzz_result(3, 403, 1001),
+    ];
+
+    let clusters = cluster_by_length(&results, 10);
+    assert_eq!(clusters.len(), 2);
+    assert_eq!(clusters[0].status_code, 200);
+    assert_eq!(clusters[0].body_len, 1000);
+    assert_eq!(clusters[0].size(), 2);
+    assert_eq!(clusters[1].status_code, 403);
+}
+
+fn article_page(timestamp: &str, topic: &str) -> String {
+    let mut page =
+        format!("<html><head><title>{topic}</title></head><body><p>Generated {timestamp}</p>");
+    for index in 0..60 {
+        page.push_str(&format!("<li>{topic} entry {index} with details</li>"));
+    }
+    page.push_str("</body></html>");
+    page
+}
+
+#[test]
+fn simhash_tolerates_small_differences() {
+    let first = simhash(article_page("2024-01-01 10:00:01", "products").as_bytes());
+    let second = simhash(article_page("2024-01-01 10:00:02", "products").as_bytes());
+    let different = simhash(b"<html><body>Access denied: invalid session token</body></html>");
+
+    assert!((first ^ second).count_ones() <= 3);
+    assert!((first ^ different).count_ones() > 3);
+}
+
+#[test]
+fn group_by_similarity_collapses_near_duplicates() {
+    let pages = [
+        article_page("2024-01-01 10:00:01", "products"),
+        article_page("2024-01-01 10:00:02", "products"),
+        "<html><body>Access denied: invalid session token</body></html>".to_string(),
+    ];
+    let results: Vec<FuzzResult> = pages
+        .iter()
+        .enumerate()
+        .map(|(index, page)| {
+            let mut result = fuzz_result(index as i64 + 1, 200, page.len());
+            result.body_simhash = simhash(page.as_bytes());
+            result
+        })
+        .collect();
+
+    let groups = group_by_similarity(&results, 3);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].timeline_request_ids, vec![1, 2]);
+    assert_eq!(groups[1].timeline_request_ids, vec![3]);
+}
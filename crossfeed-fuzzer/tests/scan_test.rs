@@ -0,0 +1,131 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crossfeed_fuzzer::{
+    AnalysisConfig, ParameterLocation, Payload, TimingConfig, detect_timing_anomalies,
+    scan_parameters,
+};
+use crossfeed_web::{Client, ClientConfig, Request};
+use http::Uri;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+async fn start_echo_server(expected: usize) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        for _ in 0..expected {
+            if let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request_line = String::from_utf8_lossy(&buf[..n])
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                    request_line.len(),
+                    request_line
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        }
+    });
+
+    addr
+}
+
+async fn start_delay_server(expected: usize, marker: &'static str, delay: Duration) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        for _ in 0..expected {
+            if let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                if String::from_utf8_lossy(&buf[..n]).contains(marker) {
+                    tokio::time::sleep(delay).await;
+                }
+                let response = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK";
+                let _ = stream.write_all(response).await;
+            }
+        }
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn scan_parameters_injects_each_payload_per_parameter() {
+    let addr = start_echo_server(6).await;
+    let client = Client::new(ClientConfig::default());
+    let uri: Uri = format!("http://{}/search?a=1&b=2", addr).parse().unwrap();
+    let request = Request::builder(uri).build();
+    let payloads = vec![
+        Payload::Text("x".to_string()),
+        Payload::Text("y".to_string()),
+        Payload::Text("z".to_string()),
+    ];
+    let analysis = AnalysisConfig {
+        grep: Vec::new(),
+        extract: vec![r"a=([^& ]*)&b=([^& ]*)".to_string()],
+    };
+
+    let results = scan_parameters(&client, &request, &payloads, &analysis)
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 6);
+    for result in &results {
+        assert_eq!(result.location, ParameterLocation::Query);
+        assert_eq!(result.status_code, 200);
+        let Payload::Text(value) = &result.payload else {
+            panic!("expected text payload");
+        };
+        let expected = match result.parameter.as_str() {
+            "a" => vec![value.clone(), "2".to_string()],
+            "b" => vec!["1".to_string(), value.clone()],
+            other => panic!("unexpected parameter {other}"),
+        };
+        assert_eq!(result.analysis.extracts[0], expected);
+    }
+    assert_eq!(results.iter().filter(|r| r.parameter == "a").count(), 3);
+    assert_eq!(results.iter().filter(|r| r.parameter == "b").count(), 3);
+}
+
+#[tokio::test]
+async fn timing_detector_flags_consistently_delayed_payload() {
+    let addr = start_delay_server(6, "SLEEP", Duration::from_millis(400)).await;
+    let client = Client::new(ClientConfig::default());
+    let uri: Uri = format!("http://{}/item?id=1", addr).parse().unwrap();
+    let request = Request::builder(uri).build();
+    let payloads = vec![
+        Payload::Text("fast".to_string()),
+        Payload::Text("SLEEP".to_string()),
+        Payload::Text("quick".to_string()),
+    ];
+    let analysis = AnalysisConfig::default();
+
+    let mut results = Vec::new();
+    for _ in 0..2 {
+        results.extend(
+            scan_parameters(&client, &request, &payloads, &analysis)
+                .await
+                .unwrap(),
+        );
+    }
+
+    let config = TimingConfig {
+        threshold_ms: 250,
+        min_repeats: 2,
+    };
+    let anomalies = detect_timing_anomalies(&results, &config);
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].parameter, "id");
+    assert_eq!(anomalies[0].payload, Payload::Text("SLEEP".to_string()));
+    assert_eq!(anomalies[0].repeats, 2);
+    assert!(anomalies[0].min_duration_ms >= 350);
+}
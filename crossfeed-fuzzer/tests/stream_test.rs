@@ -0,0 +1,161 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crossfeed_fuzzer::{AnalysisConfig, FuzzRunConfig, resume_fuzz_stream, run_fuzz_stream};
+use crossfeed_storage::SqliteStore;
+use crossfeed_web::{Client, ClientConfig, Request, Response};
+use futures_util::StreamExt;
+use http::Uri;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+async fn start_tracking_server(hold: Duration) -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let max_seen = max_in_flight.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let in_flight = in_flight.clone();
+            let max_in_flight = max_in_flight.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(hold).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                let response = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK";
+                let _ = stream.write_all(response).await;
+            });
+        }
+    });
+
+    (addr, max_seen)
+}
+
+fn requests(addr: SocketAddr, count: usize) -> Vec<Request> {
+    (0..count)
+        .map(|index| {
+            let uri: Uri = format!("http://{}/?n={}", addr, index).parse().unwrap();
+            Request::builder(uri).build()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn run_fuzz_stream_honors_delay() {
+    let (addr, _) = start_tracking_server(Duration::ZERO).await;
+    let client = Client::new(ClientConfig::default());
+    let analysis = AnalysisConfig::default();
+    let config = FuzzRunConfig {
+        concurrency: 1,
+        delay_ms: 100,
+        ..FuzzRunConfig::default()
+    };
+
+    let started = Instant::now();
+    let stream = run_fuzz_stream(
+        &client,
+        requests(addr, 4),
+        &analysis,
+        &config,
+        |index, _: &Request, _: &Response| Ok(index as i64 + 1),
+    );
+    let results: Vec<_> = stream.collect().await;
+
+    assert_eq!(results.len(), 4);
+    assert!(results.iter().all(|result| result.is_ok()));
+    assert!(started.elapsed() >= Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn run_fuzz_stream_bounds_in_flight_requests() {
+    let (addr, max_in_flight) = start_tracking_server(Duration::from_millis(100)).await;
+    let client = Client::new(ClientConfig::default());
+    let analysis = AnalysisConfig::default();
+    let config = FuzzRunConfig {
+        concurrency: 2,
+        ..FuzzRunConfig::default()
+    };
+
+    let stream = run_fuzz_stream(
+        &client,
+        requests(addr, 6),
+        &analysis,
+        &config,
+        |index, _: &Request, _: &Response| Ok(index as i64 + 1),
+    );
+    let results: Vec<_> = stream.collect().await;
+
+    assert_eq!(results.len(), 6);
+    let mut indexes: Vec<usize> = results
+        .into_iter()
+        .map(|result| result.unwrap().request_index)
+        .collect();
+    indexes.sort_unstable();
+    assert_eq!(indexes, vec![0, 1, 2, 3, 4, 5]);
+    assert!(max_in_flight.load(Ordering::SeqCst) <= 2);
+}
+
+#[tokio::test]
+async fn resume_fuzz_stream_skips_checkpointed_requests() {
+    let (addr, _) = start_tracking_server(Duration::ZERO).await;
+    let client = Client::new(ClientConfig::default());
+    let analysis = AnalysisConfig::default();
+    let config = FuzzRunConfig {
+        concurrency: 1,
+        checkpoint_interval: 1,
+        ..FuzzRunConfig::default()
+    };
+    let store = SqliteStore::open_in_memory().unwrap();
+    let run_id = store
+        .create_fuzz_run("wordlist", b"GET /?n=<<CFUZZ:1>>", 5, "start")
+        .unwrap();
+
+    let interrupted = resume_fuzz_stream(
+        &client,
+        requests(addr, 5),
+        &analysis,
+        &config,
+        0,
+        |index, _: &Request, _: &Response| Ok(index as i64 + 1),
+        |completed| {
+            store
+                .update_fuzz_run_checkpoint(run_id, completed, "checkpoint")
+                .map_err(crossfeed_fuzzer::FuzzError::Storage)
+        },
+    );
+    let first: Vec<_> = interrupted.take(3).collect().await;
+    assert_eq!(first.len(), 3);
+
+    let run = store.get_fuzz_run(run_id).unwrap().unwrap();
+    assert_eq!(run.completed_index, 3);
+
+    let resumed = resume_fuzz_stream(
+        &client,
+        requests(addr, 5),
+        &analysis,
+        &config,
+        run.completed_index,
+        |index, _: &Request, _: &Response| Ok(index as i64 + 1),
+        |completed| {
+            store
+                .update_fuzz_run_checkpoint(run_id, completed, "checkpoint")
+                .map_err(crossfeed_fuzzer::FuzzError::Storage)
+        },
+    );
+    let remaining: Vec<usize> = resumed
+        .map(|result| result.unwrap().request_index)
+        .collect()
+        .await;
+    assert_eq!(remaining, vec![3, 4]);
+    assert_eq!(
+        store.get_fuzz_run(run_id).unwrap().unwrap().completed_index,
+        5
+    );
+}
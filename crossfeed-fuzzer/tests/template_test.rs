@@ -1,4 +1,6 @@
-use crossfeed_fuzzer::{FuzzRunConfig, parse_template};
+use crossfeed_fuzzer::{
+    FuzzRunConfig, Payload, PlaceholderSpec, auto_positions, expand_fuzz_requests, parse_template,
+};
 
 #[test]
 fn parses_placeholders_with_default_prefix() {
@@ -24,3 +26,48 @@ fn parses_custom_prefix() {
     assert_eq!(template.placeholders.len(), 1);
     assert_eq!(template.placeholders[0].index, 2);
 }
+
+#[test]
+fn auto_positions_marks_query_and_form_values() {
+    let bytes = b"POST /login?next=home HTTP/1.1\r\nHost: example.com\r\nContent-Type: application/x-www-form-urlencoded\r\n\r\nuser=alice&pass=secret";
+    let template = auto_positions(bytes);
+    let values: Vec<&[u8]> = template
+        .placeholders
+        .iter()
+        .map(|placeholder| &bytes[placeholder.ranges[0].clone()])
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            b"home".as_slice(),
+            b"alice".as_slice(),
+            b"secret".as_slice()
+        ]
+    );
+    let indexes: Vec<usize> = template.placeholders.iter().map(|p| p.index).collect();
+    assert_eq!(indexes, vec![1, 2, 3]);
+}
+
+#[test]
+fn auto_positions_marks_json_string_values() {
+    let bytes = b"POST /api HTTP/1.1\r\nContent-Type: application/json\r\n\r\n{\"user\": \"alice\", \"tags\": [\"a\", \"b\"], \"age\": 3}";
+    let template = auto_positions(bytes);
+    assert_eq!(template.placeholders.len(), 3);
+    let body_start = bytes.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+    let first = template.placeholders[0].ranges[0].clone();
+    assert_eq!(first, body_start + 10..body_start + 15);
+    assert_eq!(&bytes[first], b"alice");
+
+    let specs = vec![PlaceholderSpec {
+        index: 1,
+        payloads: vec![Payload::Text("bob".to_string())],
+        transforms: Vec::new(),
+        prefix: None,
+        suffix: None,
+    }];
+    let requests = expand_fuzz_requests(&template, &specs).unwrap();
+    assert!(
+        String::from_utf8_lossy(&requests[0])
+            .contains("{\"user\": \"bob\", \"tags\": [\"a\", \"b\"]")
+    );
+}
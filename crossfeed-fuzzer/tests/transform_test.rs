@@ -1,9 +1,11 @@
+use std::io::Write;
+
 use crossfeed_fuzzer::{Payload, TransformStep, apply_transform_pipeline, payload_to_bytes};
 
 #[test]
 fn applies_prefix_suffix_and_transforms() {
     let payload = Payload::Text("hello".to_string());
-    let mut value = payload_to_bytes(&payload);
+    let mut value = payload_to_bytes(&payload).unwrap();
     let mut prefixed = b"pre-".to_vec();
     prefixed.extend_from_slice(&value);
     prefixed.extend_from_slice(b"-post");
@@ -19,12 +21,60 @@ fn applies_prefix_suffix_and_transforms() {
     assert!(!output_text.is_empty());
 }
 
+#[test]
+fn file_payload_bytes_are_its_lines() {
+    let mut wordlist = tempfile::NamedTempFile::new().unwrap();
+    write!(wordlist, "admin\r\nroot\n\nguest\n").unwrap();
+    let payload = Payload::File {
+        path: wordlist.path().to_path_buf(),
+        base64: false,
+    };
+    assert_eq!(
+        payload_to_bytes(&payload).unwrap(),
+        b"admin\nroot\nguest".to_vec()
+    );
+}
+
 #[test]
 fn chain_handles_string_transforms() {
     let payload = Payload::Text("<tag>".to_string());
-    let bytes = payload_to_bytes(&payload);
+    let bytes = payload_to_bytes(&payload).unwrap();
     let steps = vec![TransformStep::HtmlEscapeStr, TransformStep::Rot13Str];
     let output = apply_transform_pipeline(&bytes, &steps).unwrap();
     let text = String::from_utf8(output).unwrap();
     assert!(!text.contains('<'));
 }
+
+#[test]
+fn encoding_variations_for_traversal() {
+    let input = b"../";
+    let single = apply_transform_pipeline(input, &[TransformStep::UrlEncodeLowerBytes]).unwrap();
+    assert_eq!(single, b"%2e%2e%2f".to_vec());
+
+    let double = apply_transform_pipeline(input, &[TransformStep::DoubleUrlEncodeBytes]).unwrap();
+    assert_eq!(double, b"%252e%252e%252f".to_vec());
+
+    let fullwidth = apply_transform_pipeline(input, &[TransformStep::UnicodeFullwidthStr]).unwrap();
+    assert_eq!(
+        String::from_utf8(fullwidth).unwrap(),
+        "\u{ff0e}\u{ff0e}\u{ff0f}"
+    );
+}
+
+#[test]
+fn null_byte_chains_with_url_encoding() {
+    let steps = vec![
+        TransformStep::AppendNullByte,
+        TransformStep::UrlEncodeLowerBytes,
+    ];
+    let output = apply_transform_pipeline(b"a.php", &steps).unwrap();
+    assert_eq!(output, b"a%2ephp%00".to_vec());
+}
+
+#[test]
+fn lower_url_encoding_keeps_literal_case() {
+    let output =
+        apply_transform_pipeline(b"Admin/Index.PHP", &[TransformStep::UrlEncodeLowerBytes])
+            .unwrap();
+    assert_eq!(output, b"Admin%2fIndex%2ePHP".to_vec());
+}
@@ -0,0 +1,45 @@
+use crossfeed_fuzzer::{WafVendor, fingerprint_waf};
+use crossfeed_web::Response;
+use http::{HeaderMap, HeaderValue};
+
+fn response(status: u16, headers: &[(&'static str, &'static str)], body: &[u8]) -> Response {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.append(*name, HeaderValue::from_static(value));
+    }
+    Response {
+        status,
+        headers: map,
+        body: body.to_vec(),
+        raw: None,
+    }
+}
+
+#[test]
+fn fingerprints_cloudflare_by_server_header() {
+    let response = response(
+        403,
+        &[("server", "cloudflare"), ("cf-ray", "8a1b2c3d4e5f-AMS")],
+        b"<html>blocked</html>",
+    );
+    assert_eq!(fingerprint_waf(&response), Some(WafVendor::Cloudflare));
+}
+
+#[test]
+fn fingerprints_incapsula_by_body_marker() {
+    let response = response(
+        406,
+        &[("server", "nginx")],
+        b"<html><body>Request unsuccessful. Incapsula incident ID: 1234-5678</body></html>",
+    );
+    assert_eq!(fingerprint_waf(&response), Some(WafVendor::Incapsula));
+}
+
+#[test]
+fn ignores_unblocked_or_unknown_responses() {
+    let allowed = response(200, &[("server", "cloudflare")], b"ok");
+    assert_eq!(fingerprint_waf(&allowed), None);
+
+    let forbidden = response(403, &[("server", "nginx")], b"<h1>Forbidden</h1>");
+    assert_eq!(fingerprint_waf(&forbidden), None);
+}
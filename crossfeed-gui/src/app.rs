@@ -39,10 +39,12 @@ use crate::theme::{
     menu_item_button_style, menu_panel_style, pane_border_style, tab_button_style, text_danger,
     text_input_style, text_muted, text_primary, theme_config_path,
 };
-use crate::timeline::{PaneLayout, TimelineState};
+use crate::timeline::{
+    PaneLayout, TimelineState, endpoint_query, host_query, unfiltered_query,
+};
 use crate::ui::panes::{
-    PaneModuleKind, response_preview_from_bytes, response_preview_placeholder,
-    timeline_request_details_view, timeline_request_list_view,
+    JsonPreviewFormat, PaneModuleKind, RequestListOptions, response_preview_from_bytes,
+    response_preview_placeholder, timeline_request_details_view, timeline_request_list_view,
 };
 use crate::timeline::default_pane_layout;
 
@@ -78,9 +80,15 @@ pub enum Message {
     PaneDragged(pane_grid::DragEvent),
     PaneResized(pane_grid::ResizeEvent),
     TimelineSelected(usize),
+    TimelineInScopeOnlyToggled(bool),
+    TimelineDetailRawToggled(bool),
+    FilterByHost(String),
+    FilterByEndpoint { host: String, path: String },
+    ClearTimelineFilter,
     KeyPressed(keyboard::Key, Modifiers),
     ShowProjectSettings,
     SaveProjectSettings,
+    ConfirmExposedProxyBind,
     CloseProjectSettings,
     UpdateProxyHost(String),
     UpdateProxyPort(String),
@@ -515,7 +523,41 @@ impl AppState {
             }
             Message::TimelineSelected(index) => {
                 if let Screen::Timeline(state) = &mut self.screen {
-                    state.selected = Some(index);
+                    state.select(Some(index));
+                }
+                Task::none()
+            }
+            Message::TimelineInScopeOnlyToggled(enabled) => {
+                if let Screen::Timeline(state) = &mut self.screen {
+                    let result = state.set_in_scope_only(enabled);
+                    state.query_error = result.err();
+                }
+                Task::none()
+            }
+            Message::TimelineDetailRawToggled(raw) => {
+                if let Screen::Timeline(state) = &mut self.screen {
+                    state.set_detail_raw(raw);
+                }
+                Task::none()
+            }
+            Message::FilterByHost(host) => {
+                if let Screen::Timeline(state) = &mut self.screen {
+                    let result = state.apply_query(host_query(&state.query, &host));
+                    state.query_error = result.err();
+                }
+                Task::none()
+            }
+            Message::FilterByEndpoint { host, path } => {
+                if let Screen::Timeline(state) = &mut self.screen {
+                    let result = state.apply_query(endpoint_query(&state.query, &host, &path));
+                    state.query_error = result.err();
+                }
+                Task::none()
+            }
+            Message::ClearTimelineFilter => {
+                if let Screen::Timeline(state) = &mut self.screen {
+                    let result = state.apply_query(unfiltered_query(&state.query));
+                    state.query_error = result.err();
                 }
                 Task::none()
             }
@@ -535,10 +577,18 @@ impl AppState {
                 }
                 Task::none()
             }
-            Message::SaveProjectSettings => self.save_project_settings(),
+            Message::SaveProjectSettings => match &mut self.screen {
+                Screen::ProjectSettings(settings) if settings.needs_bind_confirmation() => {
+                    settings.confirm_exposed_bind = true;
+                    Task::none()
+                }
+                _ => self.save_project_settings(),
+            },
+            Message::ConfirmExposedProxyBind => self.save_project_settings(),
             Message::UpdateProxyHost(value) => {
                 if let Screen::ProjectSettings(settings) = &mut self.screen {
                     settings.proxy_host = value;
+                    settings.confirm_exposed_bind = false;
                 }
                 Task::none()
             }
@@ -1497,8 +1547,8 @@ impl AppState {
                             state.view(
                                 self.focus,
                                 &self.theme,
-                                Some(Message::TimelineContextMenuOpen),
-                                Some(Message::TimelineListCursor),
+                                self.config.request_list_options(),
+                                self.config.json_preview_format(),
                             )
                         }
                     }
@@ -1519,6 +1569,7 @@ impl AppState {
                                 &self.replay_scheme,
                                 &self.replay_host,
                                 &self.replay_port,
+                                self.config.json_preview_format(),
                             )
                         }
                     }
@@ -2119,9 +2170,8 @@ impl AppState {
                             &state.tags,
                             &state.responses,
                             state.selected,
+                            self.config.request_list_options(),
                             theme,
-                            Some(Message::TimelineContextMenuOpen),
-                            Some(Message::TimelineListCursor),
                         )
                     } else {
                         self.pane_placeholder("No timeline data", theme)
@@ -2135,7 +2185,17 @@ impl AppState {
                     if let Screen::Timeline(state) = &self.screen {
                         let selected = state.selected.and_then(|idx| state.timeline.get(idx));
                         let response = selected.and_then(|item| state.responses.get(&item.id));
-                        timeline_request_details_view(selected, response, theme)
+                        let raw_request = state.selected_raw_request();
+                        let http2_connection = state.selected_http2_connection();
+                        timeline_request_details_view(
+                            selected,
+                            response,
+                            raw_request,
+                            http2_connection,
+                            state.detail_raw,
+                            state.is_filtered(),
+                            theme,
+                        )
                     } else {
                         self.pane_placeholder("No timeline data", theme)
                     }
@@ -2174,6 +2234,7 @@ impl AppState {
                                     response_headers,
                                     body,
                                     truncated,
+                                    self.config.json_preview_format(),
                                     theme,
                                 );
                             }
@@ -2977,7 +3038,7 @@ impl AppState {
             return Task::none();
         }
         Task::perform(
-            create_replay_from_timeline(path, request_id),
+            create_replay_from_timeline(path, request_id, None),
             Message::ReplayCreatedFromTimeline,
         )
     }
@@ -3056,6 +3117,38 @@ pub struct GuiConfig {
     pub pane_layout: Option<PaneLayout>,
     pub tabs: Vec<TabConfig>,
     pub active_tab_id: Option<String>,
+    /// Timeline rows slower than this are highlighted; 0 disables it.
+    #[serde(default = "default_slow_response_ms")]
+    pub slow_response_ms: u64,
+    #[serde(default = "default_json_indent")]
+    pub json_indent: usize,
+    #[serde(default)]
+    pub json_sort_keys: bool,
+}
+
+fn default_slow_response_ms() -> u64 {
+    1000
+}
+
+fn default_json_indent() -> usize {
+    2
+}
+
+impl GuiConfig {
+    pub fn request_list_options(&self) -> RequestListOptions {
+        RequestListOptions {
+            slow_response_ms: self.slow_response_ms,
+            on_context: Some(Message::TimelineContextMenuOpen),
+            on_move: Some(Message::TimelineListCursor),
+        }
+    }
+
+    pub fn json_preview_format(&self) -> JsonPreviewFormat {
+        JsonPreviewFormat {
+            indent: self.json_indent,
+            sort_keys: self.json_sort_keys,
+        }
+    }
 }
 
 impl Default for GuiConfig {
@@ -3067,6 +3160,9 @@ impl Default for GuiConfig {
             pane_layout: None,
             tabs: Vec::new(),
             active_tab_id: None,
+            slow_response_ms: default_slow_response_ms(),
+            json_indent: default_json_indent(),
+            json_sort_keys: false,
         }
     }
 }
@@ -3472,3 +3568,44 @@ async fn tail_query_gui(
 ) -> Result<TailUpdate, String> {
     tail_query(store_path, cursor, existing_ids, 200).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::GuiConfig;
+
+    #[test]
+    fn gui_config_defaults_slow_response_threshold() {
+        let config: GuiConfig = toml::from_str(
+            "window_width = 1200.0\nwindow_height = 800.0\ntabs = []\n",
+        )
+        .unwrap();
+        assert_eq!(config.slow_response_ms, 1000);
+
+        let config: GuiConfig = toml::from_str(
+            "window_width = 1200.0\nwindow_height = 800.0\ntabs = []\nslow_response_ms = 250\n",
+        )
+        .unwrap();
+        assert_eq!(config.slow_response_ms, 250);
+
+        let raw = toml::to_string_pretty(&config).unwrap();
+        let reloaded: GuiConfig = toml::from_str(&raw).unwrap();
+        assert_eq!(reloaded.slow_response_ms, 250);
+    }
+
+    #[test]
+    fn gui_config_defaults_json_preview_format() {
+        let config: GuiConfig = toml::from_str(
+            "window_width = 1200.0\nwindow_height = 800.0\ntabs = []\n",
+        )
+        .unwrap();
+        assert_eq!(config.json_indent, 2);
+        assert!(!config.json_sort_keys);
+
+        let config: GuiConfig = toml::from_str(
+            "window_width = 1200.0\nwindow_height = 800.0\ntabs = []\njson_indent = 4\njson_sort_keys = true\n",
+        )
+        .unwrap();
+        assert_eq!(config.json_indent, 4);
+        assert!(config.json_sort_keys);
+    }
+}
@@ -1,9 +1,12 @@
+use std::net::IpAddr;
+
 use iced::widget::{column, container, row, text_input};
 use iced::{Alignment, Element, Length};
 
 use crate::app::Message;
 use crate::theme::{
-    ThemePalette, action_button, background_style, text_input_style, text_muted, text_primary,
+    ThemePalette, action_button, background_style, text_danger, text_input_style, text_muted,
+    text_primary,
 };
 use crate::timeline::TimelineState;
 use crossfeed_storage::{ProjectConfig, ProjectPaths};
@@ -15,6 +18,7 @@ pub struct ProjectSettingsState {
     pub project_config: ProjectConfig,
     pub proxy_host: String,
     pub proxy_port: String,
+    pub confirm_exposed_bind: bool,
 }
 
 impl ProjectSettingsState {
@@ -25,9 +29,14 @@ impl ProjectSettingsState {
             project_config: state.project_config.clone(),
             proxy_host: state.project_config.proxy.listen_host.clone(),
             proxy_port: state.project_config.proxy.listen_port.to_string(),
+            confirm_exposed_bind: false,
         }
     }
 
+    pub fn needs_bind_confirmation(&self) -> bool {
+        !is_loopback_host(&self.proxy_host)
+    }
+
     pub fn view(&self, theme: &ThemePalette) -> Element<'_, Message> {
         let content = column![
             text_primary("Project Settings", 28, *theme),
@@ -47,14 +56,30 @@ impl ProjectSettingsState {
                     let theme = *theme;
                     move |_theme, status| text_input_style(theme, status)
                 }),
+        ]
+        .spacing(16)
+        .align_x(Alignment::Start);
+        let content = if self.needs_bind_confirmation() {
+            content.push(text_danger(
+                "This host is not a loopback address. The proxy will be reachable from other machines on the network.",
+                14,
+                *theme,
+            ))
+        } else {
+            content
+        };
+        let actions = if self.confirm_exposed_bind {
+            row![
+                action_button("Bind anyway", Message::ConfirmExposedProxyBind, *theme),
+                action_button("Close", Message::CloseProjectSettings, *theme),
+            ]
+        } else {
             row![
                 action_button("Save", Message::SaveProjectSettings, *theme),
                 action_button("Close", Message::CloseProjectSettings, *theme),
             ]
-            .spacing(12),
-        ]
-        .spacing(16)
-        .align_x(Alignment::Start);
+        };
+        let content = content.push(actions.spacing(12));
 
         container(content)
             .width(Length::Fill)
@@ -67,3 +92,39 @@ impl ProjectSettingsState {
             .into()
     }
 }
+
+pub fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim();
+    if host.eq_ignore_ascii_case("localhost") {
+        return true;
+    }
+    let host = host
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .unwrap_or(host);
+    host.parse::<IpAddr>().is_ok_and(|addr| addr.is_loopback())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_loopback_host;
+
+    #[test]
+    fn loopback_hosts_are_detected() {
+        assert!(is_loopback_host("127.0.0.1"));
+        assert!(is_loopback_host("127.0.0.2"));
+        assert!(is_loopback_host("::1"));
+        assert!(is_loopback_host("[::1]"));
+        assert!(is_loopback_host("localhost"));
+        assert!(is_loopback_host(" LOCALHOST "));
+    }
+
+    #[test]
+    fn network_hosts_are_not_loopback() {
+        assert!(!is_loopback_host("0.0.0.0"));
+        assert!(!is_loopback_host("::"));
+        assert!(!is_loopback_host("192.168.1.20"));
+        assert!(!is_loopback_host("proxy.internal"));
+        assert!(!is_loopback_host(""));
+    }
+}
@@ -18,7 +18,8 @@ use crate::theme::{
     text_editor_style, text_input_style, text_muted, text_primary,
 };
 use crate::ui::panes::{
-    pane_scroll, pane_text_editor, response_preview_from_bytes, response_preview_placeholder,
+    JsonPreviewFormat, pane_scroll, pane_text_editor, response_preview_from_bytes,
+    response_preview_placeholder,
 };
 
 #[derive(Debug)]
@@ -67,6 +68,7 @@ impl ReplayState {
         scheme: &str,
         host: &str,
         port: &str,
+        json_format: JsonPreviewFormat,
     ) -> Element<'_, Message> {
         let grid = PaneGrid::new(&self.panes, |_, state, _| {
             let pane_content: Element<'_, Message> = match state {
@@ -74,7 +76,7 @@ impl ReplayState {
                 ReplayPaneKind::Editor => {
                     self.request_editor_view(*theme, send_inflight, send_blocked, scheme, host, port)
                 }
-                ReplayPaneKind::Response => self.response_view(*theme),
+                ReplayPaneKind::Response => self.response_view(*theme, json_format),
             };
             let content = container(pane_content)
                 .width(Length::Fill)
@@ -300,7 +302,11 @@ impl ReplayState {
             .into()
     }
 
-    fn response_view(&self, theme: ThemePalette) -> Element<'_, Message> {
+    fn response_view(
+        &self,
+        theme: ThemePalette,
+        json_format: JsonPreviewFormat,
+    ) -> Element<'_, Message> {
         let content = if let Some((request_id, error)) = &self.send_error {
             if self.selected_request_id == Some(*request_id) {
                 response_preview_placeholder(&format!("Replay send failed: {error}"), theme)
@@ -315,6 +321,7 @@ impl ReplayState {
                     &response.response_headers,
                     &response.response_body,
                     response.response_body_truncated,
+                    json_format,
                     theme,
                 )
             } else {
@@ -331,6 +338,7 @@ impl ReplayState {
                 &response.response_headers,
                 &response.response_body,
                 response.response_body_truncated,
+                json_format,
                 theme,
             )
         } else {
@@ -3,17 +3,18 @@ use std::path::PathBuf;
 
 use crossfeed_ingest::{TailCursor, TailUpdate, TimelineItem};
 use crossfeed_storage::{
-    ProjectConfig, ProjectPaths, ResponseSummary, SqliteStore, TimelineQuery, TimelineSort,
+    Http2ConnectionRecord, ProjectConfig, ProjectPaths, ResponseSummary, SqliteStore,
+    TimelineQuery, TimelineRequestSummary, TimelineSort,
 };
-use iced::widget::{PaneGrid, container, pane_grid, text};
+use iced::widget::{PaneGrid, checkbox, column, container, pane_grid, text};
 use iced::{Element, Length, Theme};
 use serde::{Deserialize, Serialize};
 
 use crate::app::Message;
-use crate::theme::{ThemePalette, pane_border_style};
+use crate::theme::{ThemePalette, pane_border_style, text_danger};
 use crate::ui::panes::{
-    response_preview_from_bytes, response_preview_placeholder, timeline_request_details_view,
-    timeline_request_list_view,
+    JsonPreviewFormat, RequestListOptions, response_preview_from_bytes,
+    response_preview_placeholder, timeline_request_details_view, timeline_request_list_view,
 };
 
 #[derive(Debug, Clone)]
@@ -28,18 +29,18 @@ pub struct TimelineState {
     pub tags: HashMap<i64, Vec<String>>,
     pub responses: HashMap<i64, ResponseSummary>,
     pub tail_cursor: TailCursor,
+    pub query: TimelineQuery,
+    pub detail_raw: bool,
+    /// Set when the last filter change failed to load; the previous rows stay.
+    pub query_error: Option<String>,
+    selected_raw: Option<TimelineRequestSummary>,
+    selected_http2: Option<(i64, Http2ConnectionRecord)>,
 }
 
 impl TimelineState {
     pub fn new(project_paths: ProjectPaths, project_config: ProjectConfig) -> Result<Self, String> {
         let store_path = project_paths.database.clone();
-        let store = SqliteStore::open(&store_path)?;
-        let requests = store
-            .query_request_summaries(&TimelineQuery::default(), TimelineSort::StartedAtDesc)?;
-        let ids: Vec<i64> = requests.iter().map(|item| item.id).collect();
-        let tags = store.get_request_tags(&ids)?;
-        let responses = store.get_response_summaries(&ids)?;
-        let timeline: Vec<TimelineItem> = requests.into_iter().map(TimelineItem::from).collect();
+        let (timeline, tags, responses) = load_timeline(&store_path, &TimelineQuery::default())?;
 
         let (mut panes, root) = pane_grid::State::new(PaneKind::Timeline);
         let (right, _) = panes
@@ -62,21 +63,106 @@ impl TimelineState {
             tags,
             responses,
             tail_cursor,
+            query: TimelineQuery::default(),
+            detail_raw: false,
+            query_error: None,
+            selected_raw: None,
+            selected_http2: None,
         })
     }
 
+    pub fn in_scope_only(&self) -> bool {
+        self.query.scope_status.as_deref() == Some(IN_SCOPE)
+    }
+
+    pub fn is_filtered(&self) -> bool {
+        self.query.host.is_some() || self.query.path_exact.is_some()
+    }
+
+    pub fn selected_raw_request(&self) -> Option<&TimelineRequestSummary> {
+        if !self.detail_raw {
+            return None;
+        }
+        let selected = self.selected.and_then(|idx| self.timeline.get(idx))?;
+        self.selected_raw
+            .as_ref()
+            .filter(|request| request.id == selected.id)
+    }
+
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index;
+        self.refresh_selected_details();
+    }
+
+    pub fn set_detail_raw(&mut self, raw: bool) {
+        self.detail_raw = raw;
+        self.refresh_selected_details();
+    }
+
+    // Detail panes render every frame, so the stored request and HTTP/2
+    // connection are loaded here on selection change instead of from `view`.
+    fn refresh_selected_details(&mut self) {
+        self.selected_raw = None;
+        self.selected_http2 = None;
+        let Some(selected) = self.selected.and_then(|idx| self.timeline.get(idx)) else {
+            return;
+        };
+        let id = selected.id;
+        if self.detail_raw {
+            self.selected_raw = SqliteStore::open(&self.store_path)
+                .ok()
+                .and_then(|store| store.get_request_summary(id).ok())
+                .flatten();
+        } else if selected.http_version.starts_with("HTTP/2") {
+            self.selected_http2 = SqliteStore::open(&self.store_path)
+                .ok()
+                .and_then(|store| store.get_http2_connection_for_request(id).ok())
+                .flatten()
+                .map(|connection| (id, connection));
+        }
+    }
+
+    pub fn selected_http2_connection(&self) -> Option<&Http2ConnectionRecord> {
+        if self.detail_raw {
+            return None;
+        }
+        let selected = self.selected.and_then(|idx| self.timeline.get(idx))?;
+        self.selected_http2
+            .as_ref()
+            .filter(|(request_id, _)| *request_id == selected.id)
+            .map(|(_, connection)| connection)
+    }
+
+    pub fn set_in_scope_only(&mut self, enabled: bool) -> Result<(), String> {
+        let query = TimelineQuery {
+            scope_status: enabled.then(|| IN_SCOPE.to_string()),
+            ..self.query.clone()
+        };
+        self.apply_query(query)
+    }
+
+    pub fn apply_query(&mut self, query: TimelineQuery) -> Result<(), String> {
+        let (timeline, tags, responses) = load_timeline(&self.store_path, &query)?;
+        self.query = query;
+        self.timeline = timeline;
+        self.tags = tags;
+        self.responses = responses;
+        self.select(None);
+        Ok(())
+    }
+
     pub fn view(
         &self,
         focus: crate::app::FocusArea,
         theme: &ThemePalette,
-        on_context: Option<fn(i64) -> Message>,
-        on_move: Option<fn(iced::Point) -> Message>,
+        list_options: RequestListOptions,
+        json_format: JsonPreviewFormat,
     ) -> Element<'_, Message> {
         let grid = PaneGrid::new(&self.panes, |_, state, _| {
             let pane_content: Element<'_, Message> = match state {
-                PaneKind::Timeline => self.timeline_view(focus, *theme, on_context, on_move),
+                PaneKind::Timeline => self.timeline_view(focus, *theme, list_options),
                 PaneKind::Detail => self.detail_view(focus, *theme),
-                PaneKind::Response => self.response_view(focus, *theme),
+                PaneKind::Response => self.response_view(focus, *theme, json_format),
             };
             let content = container(pane_content)
                 .width(Length::Fill)
@@ -114,27 +200,55 @@ impl TimelineState {
         &self,
         _focus: crate::app::FocusArea,
         theme: ThemePalette,
-        on_context: Option<fn(i64) -> Message>,
-        on_move: Option<fn(iced::Point) -> Message>,
+        list_options: RequestListOptions,
     ) -> Element<'_, Message> {
-        timeline_request_list_view(
+        let toggle = checkbox("In-scope only", self.in_scope_only())
+            .on_toggle(Message::TimelineInScopeOnlyToggled)
+            .size(14)
+            .text_size(13)
+            .style(
+                move |theme_ref: &Theme, status| iced::widget::checkbox::Style {
+                    text_color: Some(theme.text),
+                    ..iced::widget::checkbox::primary(theme_ref, status)
+                },
+            );
+        let list = timeline_request_list_view(
             &self.timeline,
             &self.tags,
             &self.responses,
             self.selected,
+            list_options,
             theme,
-            on_context,
-            on_move,
-        )
+        );
+        let mut content = column![container(toggle).padding([6, 8])];
+        if let Some(error) = &self.query_error {
+            content = content.push(container(text_danger(error, 13, theme)).padding([0, 8]));
+        }
+        content.push(list).into()
     }
 
     fn detail_view(&self, _focus: crate::app::FocusArea, theme: ThemePalette) -> Element<'_, Message> {
         let selected = self.selected.and_then(|idx| self.timeline.get(idx));
         let response = selected.and_then(|item| self.responses.get(&item.id));
-        timeline_request_details_view(selected, response, theme)
+        let raw_request = self.selected_raw_request();
+        let http2_connection = self.selected_http2_connection();
+        timeline_request_details_view(
+            selected,
+            response,
+            raw_request,
+            http2_connection,
+            self.detail_raw,
+            self.is_filtered(),
+            theme,
+        )
     }
 
-    fn response_view(&self, _focus: crate::app::FocusArea, theme: ThemePalette) -> Element<'_, Message> {
+    fn response_view(
+        &self,
+        _focus: crate::app::FocusArea,
+        theme: ThemePalette,
+        json_format: JsonPreviewFormat,
+    ) -> Element<'_, Message> {
         if let Some(selected) = self.selected.and_then(|idx| self.timeline.get(idx)) {
             let response = self.responses.get(&selected.id);
 
@@ -165,6 +279,7 @@ impl TimelineState {
                     response_headers,
                     body,
                     truncated,
+                    json_format,
                     theme,
                 )
             } else {
@@ -179,10 +294,16 @@ impl TimelineState {
         let Ok(update) = update else {
             return;
         };
-        if update.new_items.is_empty() {
+        self.tail_cursor = update.cursor;
+        let new_items: Vec<&TimelineItem> = update
+            .new_items
+            .iter()
+            .filter(|item| matches_query(&self.query, item))
+            .collect();
+        if new_items.is_empty() {
             return;
         }
-        for item in update.new_items.iter().rev() {
+        for item in new_items.into_iter().rev() {
             self.timeline.insert(0, item.clone());
         }
         for (id, tags) in update.tags {
@@ -191,31 +312,30 @@ impl TimelineState {
         for (id, response) in update.responses {
             self.responses.insert(id, response);
         }
-        self.tail_cursor = update.cursor;
     }
 
     pub fn select_next(&mut self) {
         if self.timeline.is_empty() {
-            self.selected = None;
+            self.select(None);
             return;
         }
         let next = match self.selected {
             Some(index) => (index + 1).min(self.timeline.len() - 1),
             None => 0,
         };
-        self.selected = Some(next);
+        self.select(Some(next));
     }
 
     pub fn select_prev(&mut self) {
         if self.timeline.is_empty() {
-            self.selected = None;
+            self.select(None);
             return;
         }
         let prev = match self.selected {
             Some(index) => index.saturating_sub(1),
             None => 0,
         };
-        self.selected = Some(prev);
+        self.select(Some(prev));
     }
 
     pub fn snapshot_layout(&self) -> PaneLayout {
@@ -243,6 +363,60 @@ impl TimelineState {
     }
 }
 
+const IN_SCOPE: &str = "in_scope";
+
+pub fn host_query(base: &TimelineQuery, host: &str) -> TimelineQuery {
+    TimelineQuery {
+        host: Some(host.to_string()),
+        path_exact: None,
+        ..base.clone()
+    }
+}
+
+pub fn endpoint_query(base: &TimelineQuery, host: &str, path: &str) -> TimelineQuery {
+    TimelineQuery {
+        host: Some(host.to_string()),
+        path_exact: Some(path.to_string()),
+        ..base.clone()
+    }
+}
+
+pub fn unfiltered_query(base: &TimelineQuery) -> TimelineQuery {
+    TimelineQuery {
+        host: None,
+        path_exact: None,
+        ..base.clone()
+    }
+}
+
+fn matches_query(query: &TimelineQuery, item: &TimelineItem) -> bool {
+    query.host.as_ref().is_none_or(|host| *host == item.host)
+        && query
+            .path_exact
+            .as_ref()
+            .is_none_or(|path| *path == item.path)
+        && query
+            .scope_status
+            .as_ref()
+            .is_none_or(|status| *status == item.scope_status_at_capture)
+}
+
+type LoadedTimeline = (
+    Vec<TimelineItem>,
+    HashMap<i64, Vec<String>>,
+    HashMap<i64, ResponseSummary>,
+);
+
+fn load_timeline(store_path: &PathBuf, query: &TimelineQuery) -> Result<LoadedTimeline, String> {
+    let store = SqliteStore::open(store_path)?;
+    let requests = store.query_request_summaries(query, TimelineSort::StartedAtDesc)?;
+    let ids: Vec<i64> = requests.iter().map(|item| item.id).collect();
+    let tags = store.get_request_tags(&ids)?;
+    let responses = store.get_response_summaries(&ids)?;
+    let timeline = requests.into_iter().map(TimelineItem::from).collect();
+    Ok((timeline, tags, responses))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PaneKind {
     Timeline,
@@ -351,3 +525,64 @@ pub fn default_pane_layout() -> PaneLayout {
         .expect("Default timeline split failed");
     PaneLayout::from(&panes)
 }
+
+#[cfg(test)]
+mod tests {
+    use crossfeed_ingest::TimelineItem;
+    use crossfeed_storage::TimelineQuery;
+
+    use super::{endpoint_query, host_query, matches_query, unfiltered_query};
+
+    fn item(host: &str, path: &str) -> TimelineItem {
+        TimelineItem {
+            id: 7,
+            source: "proxy".to_string(),
+            method: "GET".to_string(),
+            host: host.to_string(),
+            path: path.to_string(),
+            url: format!("https://{host}{path}"),
+            started_at: "2024-01-01T00:00:00Z".to_string(),
+            duration_ms: None,
+            request_body_size: 0,
+            request_body_truncated: false,
+            completed_at: None,
+            http_version: "HTTP/1.1".to_string(),
+            scope_status_at_capture: "in_scope".to_string(),
+            scope_status_current: None,
+            client_alpn: None,
+            upstream_alpn: None,
+        }
+    }
+
+    #[test]
+    fn host_and_endpoint_queries_follow_selected_item() {
+        let selected = item("api.example.com", "/users");
+        let base = TimelineQuery {
+            scope_status: Some("in_scope".to_string()),
+            path_exact: Some("/old".to_string()),
+            ..TimelineQuery::default()
+        };
+
+        let by_host = host_query(&base, &selected.host);
+        assert_eq!(by_host.host.as_deref(), Some("api.example.com"));
+        assert_eq!(by_host.path_exact, None);
+        assert_eq!(by_host.scope_status.as_deref(), Some("in_scope"));
+
+        let by_endpoint = endpoint_query(&base, &selected.host, &selected.path);
+        assert_eq!(by_endpoint.host.as_deref(), Some("api.example.com"));
+        assert_eq!(by_endpoint.path_exact.as_deref(), Some("/users"));
+
+        let cleared = unfiltered_query(&by_endpoint);
+        assert_eq!(cleared.host, None);
+        assert_eq!(cleared.path_exact, None);
+        assert_eq!(cleared.scope_status.as_deref(), Some("in_scope"));
+    }
+
+    #[test]
+    fn tail_items_are_matched_against_active_filter() {
+        let query = endpoint_query(&TimelineQuery::default(), "api.example.com", "/users");
+        assert!(matches_query(&query, &item("api.example.com", "/users")));
+        assert!(!matches_query(&query, &item("api.example.com", "/orders")));
+        assert!(!matches_query(&query, &item("cdn.example.com", "/users")));
+    }
+}
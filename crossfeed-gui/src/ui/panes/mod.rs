@@ -3,9 +3,9 @@ pub mod request_list;
 pub mod response_preview;
 
 pub use request_details::timeline_request_details_view;
-pub use request_list::timeline_request_list_view;
+pub use request_list::{RequestListOptions, timeline_request_list_view};
 pub use response_preview::{
-    response_preview_from_bytes, response_preview_placeholder,
+    JsonPreviewFormat, response_preview_from_bytes, response_preview_placeholder,
 };
 
 use serde::{Deserialize, Serialize};
@@ -53,18 +53,3 @@ pub fn pane_text_editor<'a, Message: 'a>(
 ) -> Element<'a, Message> {
     pane_root(container(editor).padding(12).width(Length::Fill).height(Length::Fill).into())
 }
-
-pub fn format_bytes(bytes: usize, truncated: bool) -> String {
-    let base = if bytes > 1024 * 1024 {
-        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
-    } else if bytes > 1024 {
-        format!("{:.1} KB", bytes as f64 / 1024.0)
-    } else {
-        format!("{bytes} B")
-    };
-    if truncated {
-        format!("{base} (truncated)")
-    } else {
-        base
-    }
-}
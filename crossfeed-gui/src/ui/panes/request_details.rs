@@ -1,18 +1,35 @@
+use crossfeed_core::format_bytes;
 use crossfeed_ingest::TimelineItem;
-use crossfeed_storage::ResponseSummary;
-use iced::widget::{column, container, row};
+use crossfeed_storage::{
+    Http2ConnectionRecord, Http2SettingsRecord, ResponseSummary, TimelineRequestSummary,
+};
+use iced::widget::{button, column, container, row};
 use iced::Element;
 
 use crate::app::Message;
-use crate::theme::{ThemePalette, text_muted, text_primary};
-use crate::ui::panes::{format_bytes, pane_scroll};
+use crate::theme::{ThemePalette, action_button, tab_button_style, text_muted, text_primary};
+use crate::ui::panes::pane_scroll;
+use crate::ui::panes::response_preview::hex_dump;
 
 pub fn timeline_request_details_view(
     selected: Option<&TimelineItem>,
     response: Option<&ResponseSummary>,
+    raw_request: Option<&TimelineRequestSummary>,
+    http2_connection: Option<&Http2ConnectionRecord>,
+    show_raw: bool,
+    filtered: bool,
     theme: ThemePalette,
 ) -> Element<'static, Message> {
-    let content = if let Some(selected) = selected {
+    let content = if selected.is_some() && show_raw {
+        let raw = raw_request
+            .map(render_raw_request)
+            .unwrap_or_else(|| "(request unavailable)".to_string());
+        column![
+            detail_mode_tabs(show_raw, theme),
+            container(text_primary(raw, 12, theme)).padding(10),
+        ]
+        .spacing(8)
+    } else if let Some(selected) = selected {
         let status_text = response
             .map(|resp| resp.status_code.to_string())
             .unwrap_or_else(|| "Pending".to_string());
@@ -35,12 +52,43 @@ pub fn timeline_request_details_view(
             .to_string();
         let request_size =
             format_bytes(selected.request_body_size, selected.request_body_truncated);
+        let alpn = alpn_text(
+            selected.client_alpn.as_deref(),
+            selected.upstream_alpn.as_deref(),
+        );
 
-        column![
+        let mut actions = row![
+            action_button(
+                "All requests to host",
+                Message::FilterByHost(selected.host.clone()),
+                theme,
+            ),
+            action_button(
+                "Same endpoint",
+                Message::FilterByEndpoint {
+                    host: selected.host.clone(),
+                    path: selected.path.clone(),
+                },
+                theme,
+            ),
+        ]
+        .spacing(6);
+        if filtered {
+            actions = actions.push(action_button(
+                "Clear filter",
+                Message::ClearTimelineFilter,
+                theme,
+            ));
+        }
+
+        let mut details = column![
+            detail_mode_tabs(show_raw, theme),
+            actions,
             detail_line("URL", selected.url.clone(), theme),
             detail_line("Method", selected.method.clone(), theme),
             detail_line("Status", status_text, theme),
             detail_line("HTTP", selected.http_version.clone(), theme),
+            detail_line("ALPN", alpn, theme),
             detail_line("Started", selected.started_at.clone(), theme),
             detail_line("Completed", completed, theme),
             detail_line("Duration", duration_text, theme),
@@ -49,7 +97,29 @@ pub fn timeline_request_details_view(
             detail_line("Scope current", scope_current, theme),
             detail_line("Request size", request_size, theme),
             detail_line("Response size", response_size, theme),
-        ]
+        ];
+        if let Some(connection) = http2_connection {
+            details = details
+                .push(detail_line(
+                    "H2 client settings",
+                    http2_settings_text(&connection.client_settings),
+                    theme,
+                ))
+                .push(detail_line(
+                    "H2 upstream settings",
+                    http2_settings_text(&connection.upstream_settings),
+                    theme,
+                ))
+                .push(detail_line(
+                    "H2 send windows",
+                    format!(
+                        "client {} / upstream {}",
+                        connection.client_send_window, connection.upstream_send_window
+                    ),
+                    theme,
+                ));
+        }
+        details
     } else {
         column![text_muted("Select a request to view details", 16, theme)]
     };
@@ -57,9 +127,163 @@ pub fn timeline_request_details_view(
     pane_scroll(container(content).padding(12).into())
 }
 
+fn detail_mode_tabs(show_raw: bool, theme: ThemePalette) -> Element<'static, Message> {
+    let tab = |label: &'static str, raw: bool| {
+        button(text_primary(label, 12, theme))
+            .padding([4, 10])
+            .on_press(Message::TimelineDetailRawToggled(raw))
+            .style(move |_theme, status| tab_button_style(theme, status, show_raw == raw))
+    };
+    row![tab("Details", false), tab("Raw", true)]
+        .spacing(6)
+        .into()
+}
+
+pub fn render_raw_request(request: &TimelineRequestSummary) -> String {
+    let raw = request.request_headers.as_slice();
+    let head = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|index| &raw[..index])
+        .unwrap_or(raw);
+    let head = String::from_utf8_lossy(head).replace("\r\n", "\n");
+    let has_request_line = head.lines().next().is_some_and(|line| {
+        line.rsplit(' ')
+            .next()
+            .is_some_and(|part| part.starts_with("HTTP/"))
+    });
+
+    let mut output = String::new();
+    if !has_request_line {
+        let target = match &request.query {
+            Some(query) => format!("{}?{}", request.path, query),
+            None => request.path.clone(),
+        };
+        output.push_str(&format!(
+            "{} {} {}\n",
+            request.method, target, request.http_version
+        ));
+    }
+    output.push_str(head.trim_end_matches('\n'));
+    output.push_str("\n\n");
+    if !request.request_body.is_empty() {
+        match std::str::from_utf8(&request.request_body) {
+            Ok(text) => output.push_str(text),
+            Err(_) => output.push_str(&hex_dump(&request.request_body)),
+        }
+    }
+    if request.request_body_truncated {
+        output.push_str("\n(body truncated)");
+    }
+    output
+}
+
+fn alpn_text(client: Option<&str>, upstream: Option<&str>) -> String {
+    if client.is_none() && upstream.is_none() {
+        return "-".to_string();
+    }
+    format!(
+        "client {} / upstream {}",
+        client.unwrap_or("-"),
+        upstream.unwrap_or("-")
+    )
+}
+
+fn http2_settings_text(settings: &Http2SettingsRecord) -> String {
+    format!(
+        "window {} / frame {} / hpack {} / header list {} / push {}",
+        settings.initial_window_size,
+        settings.max_frame_size,
+        settings.header_table_size,
+        settings.max_header_list_size,
+        if settings.enable_push { "on" } else { "off" }
+    )
+}
+
 fn detail_line(label: &'static str, value: impl Into<String>, theme: ThemePalette) -> Element<'static, Message> {
     let value = value.into();
     row![text_muted(label, 12, theme), text_primary(value, 14, theme)]
         .spacing(8)
         .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use crossfeed_storage::TimelineRequestSummary;
+
+    use super::{alpn_text, http2_settings_text, render_raw_request};
+
+    fn summary(headers: &[u8], body: &[u8]) -> TimelineRequestSummary {
+        TimelineRequestSummary {
+            id: 1,
+            source: "proxy".to_string(),
+            method: "POST".to_string(),
+            scheme: "http".to_string(),
+            host: "example.com".to_string(),
+            port: 80,
+            path: "/login".to_string(),
+            query: Some("next=home".to_string()),
+            url: "http://example.com/login?next=home".to_string(),
+            http_version: "HTTP/1.1".to_string(),
+            request_headers: headers.to_vec(),
+            request_body: body.to_vec(),
+            request_body_size: body.len(),
+            request_body_truncated: false,
+            started_at: "2024-01-01T00:00:00Z".to_string(),
+            completed_at: None,
+            duration_ms: None,
+            scope_status_at_capture: "in_scope".to_string(),
+            scope_status_current: None,
+            scope_rules_version: 1,
+            capture_filtered: false,
+            timeline_filtered: false,
+            client_alpn: None,
+            upstream_alpn: None,
+        }
+    }
+
+    #[test]
+    fn raw_request_uses_forwarded_head_and_body() {
+        let request = summary(
+            b"POST /login HTTP/1.1\r\nHost: example.com\r\nContent-Length: 7\r\n\r\nuser=me",
+            b"user=me",
+        );
+        assert_eq!(
+            render_raw_request(&request),
+            "POST /login HTTP/1.1\nHost: example.com\nContent-Length: 7\n\nuser=me"
+        );
+    }
+
+    #[test]
+    fn raw_request_adds_request_line_and_hex_dumps_binary_body() {
+        let request = summary(b"Host: example.com\r\n", &[0x00, 0xff, 0x10]);
+        assert_eq!(
+            render_raw_request(&request),
+            "POST /login?next=home HTTP/1.1\nHost: example.com\n\n00 ff 10"
+        );
+    }
+
+    #[test]
+    fn alpn_text_shows_both_legs() {
+        assert_eq!(alpn_text(None, None), "-");
+        assert_eq!(
+            alpn_text(Some("h2"), Some("http/1.1")),
+            "client h2 / upstream http/1.1"
+        );
+    }
+
+    #[test]
+    fn http2_settings_text_lists_negotiated_values() {
+        let settings = crossfeed_storage::Http2SettingsRecord {
+            header_table_size: 4096,
+            enable_push: false,
+            initial_window_size: 1_048_576,
+            max_frame_size: 32_768,
+            max_header_list_size: 262_144,
+        };
+        assert_eq!(
+            http2_settings_text(&settings),
+            "window 1048576 / frame 32768 / hpack 4096 / header list 262144 / push off"
+        );
+    }
+}
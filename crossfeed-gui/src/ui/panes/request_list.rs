@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use crossfeed_core::format_bytes;
 use crossfeed_ingest::TimelineItem;
 use crossfeed_storage::ResponseSummary;
 use iced::mouse;
@@ -7,18 +8,34 @@ use iced::widget::{button, column, container, mouse_area, row};
 use iced::{Element, Length, Point};
 
 use crate::app::Message;
-use crate::theme::{ThemePalette, badge_style, text_muted, text_primary, timeline_row_style};
-use crate::ui::panes::{format_bytes, pane_scroll};
+use crate::theme::{
+    ThemePalette, badge_style, text_danger, text_muted, text_primary, timeline_row_style,
+};
+use crate::ui::panes::pane_scroll;
+
+/// Display settings and row callbacks for the timeline request list.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestListOptions {
+    /// Rows slower than this many milliseconds are highlighted; 0 turns the
+    /// highlight off.
+    pub slow_response_ms: u64,
+    pub on_context: Option<fn(i64) -> Message>,
+    pub on_move: Option<fn(Point) -> Message>,
+}
 
 pub fn timeline_request_list_view<'a>(
     items: &'a [TimelineItem],
     tags: &'a HashMap<i64, Vec<String>>,
     responses: &'a HashMap<i64, ResponseSummary>,
     selected: Option<usize>,
+    options: RequestListOptions,
     theme: ThemePalette,
-    on_context: Option<fn(i64) -> Message>,
-    on_move: Option<fn(Point) -> Message>,
 ) -> Element<'a, Message> {
+    let RequestListOptions {
+        slow_response_ms,
+        on_context,
+        on_move,
+    } = options;
     let mut content = column![].spacing(12);
 
     for (index, item) in items.iter().enumerate() {
@@ -26,7 +43,8 @@ pub fn timeline_request_list_view<'a>(
         let tags = tags.get(&item.id).cloned().unwrap_or_default();
         let response = responses.get(&item.id);
         let status = response.map(|resp| resp.status_code);
-        let row = timeline_row(item, status, &tags, is_selected, theme)
+        let slow = is_slow(item.duration_ms, slow_response_ms);
+        let row = timeline_row(item, status, &tags, is_selected, slow, theme)
             .on_press(Message::TimelineSelected(index));
         let element: Element<'a, Message> = if let Some(on_context) = on_context {
             mouse_area(row)
@@ -55,6 +73,7 @@ fn timeline_row(
     status: Option<u16>,
     tags: &[String],
     selected: bool,
+    slow: bool,
     theme: ThemePalette,
 ) -> iced::widget::Button<'static, Message> {
     let status_text = status
@@ -90,7 +109,12 @@ fn timeline_row(
         ]
         .spacing(8),
         row![
-            text_muted(format!("{} • {}", duration, body_size), 12, theme),
+            if slow {
+                text_danger(duration, 12, theme)
+            } else {
+                text_muted(duration, 12, theme)
+            },
+            text_muted(format!("• {body_size}"), 12, theme),
             text_muted(tag_label, 12, theme),
         ]
         .spacing(8),
@@ -109,3 +133,24 @@ fn badge(label: String, theme: ThemePalette) -> Element<'static, Message> {
         .style(move |_| badge_style(theme))
         .into()
 }
+
+fn is_slow(duration_ms: Option<i64>, threshold_ms: u64) -> bool {
+    threshold_ms > 0
+        && duration_ms
+            .and_then(|duration| u64::try_from(duration).ok())
+            .is_some_and(|duration| duration > threshold_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_slow;
+
+    #[test]
+    fn is_slow_compares_duration_against_threshold() {
+        assert!(is_slow(Some(1500), 1000));
+        assert!(!is_slow(Some(1000), 1000));
+        assert!(!is_slow(Some(20), 1000));
+        assert!(!is_slow(None, 1000));
+        assert!(!is_slow(Some(1500), 0));
+    }
+}
@@ -1,4 +1,7 @@
-use crossfeed_codec::{deflate_decompress, gzip_decompress};
+use crossfeed_codec::{
+    charset_from_content_type, decode_charset, decode_encoding_chain, format_json,
+};
+use crossfeed_core::ParsedHeaders;
 use iced::widget::{column, container};
 use iced::Element;
 
@@ -6,15 +9,36 @@ use crate::app::Message;
 use crate::theme::{ThemePalette, text_muted, text_primary};
 use crate::ui::panes::pane_scroll;
 
+/// Layout applied to JSON bodies shown in the preview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonPreviewFormat {
+    pub indent: usize,
+    pub sort_keys: bool,
+}
+
+impl Default for JsonPreviewFormat {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            sort_keys: false,
+        }
+    }
+}
+
 pub fn response_preview_from_bytes(
     status_line: String,
     response_headers: &[u8],
     response_body: &[u8],
     body_truncated: bool,
+    json_format: JsonPreviewFormat,
     theme: ThemePalette,
 ) -> Element<'static, Message> {
     let headers = render_response_headers(response_headers);
-    let body_text = render_response_body(response_body, &headers);
+    let body_text = render_response_body(
+        response_body,
+        &ParsedHeaders::parse(response_headers),
+        json_format,
+    );
     let body_label = if body_truncated {
         "Body (truncated)"
     } else {
@@ -64,45 +88,58 @@ fn render_response_headers(raw: &[u8]) -> String {
     }
 }
 
-fn render_response_body(body: &[u8], headers: &str) -> String {
+fn render_response_body(
+    body: &[u8],
+    headers: &ParsedHeaders,
+    json_format: JsonPreviewFormat,
+) -> String {
     if body.is_empty() {
         return "(empty body)".to_string();
     }
     let decoded = decode_response_body(body, headers);
+    let content_type = headers.get("content-type").unwrap_or_default();
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    // SVG and other XML-based images are text and preview as such.
+    if mime.starts_with("image/") && !mime.ends_with("+xml") {
+        return format!("(binary image, {} bytes)", decoded.len());
+    }
+    if mime == "application/octet-stream" {
+        return format!("(binary data, {} bytes)", decoded.len());
+    }
+    if (mime == "application/json" || mime.ends_with("+json"))
+        && let Ok(pretty) = format_json(&decoded, json_format.indent, json_format.sort_keys)
+    {
+        return pretty;
+    }
+    let charset = charset_from_content_type(content_type);
+    if let Some(text) = charset.and_then(|charset| decode_charset(&decoded, &charset).ok()) {
+        return text;
+    }
     match std::str::from_utf8(&decoded) {
         Ok(text) => text.to_string(),
         Err(_) => hex_dump(&decoded),
     }
 }
 
-fn decode_response_body(body: &[u8], headers: &str) -> Vec<u8> {
-    let encoding = find_header_value(headers, "content-encoding")
-        .unwrap_or_default()
-        .to_ascii_lowercase();
-    let encoding = encoding
-        .split(',')
-        .next()
-        .map(|value| value.trim())
-        .unwrap_or("");
-    match encoding {
-        "gzip" | "x-gzip" => gzip_decompress(body).unwrap_or_else(|_| body.to_vec()),
-        "deflate" => deflate_decompress(body).unwrap_or_else(|_| body.to_vec()),
-        _ => body.to_vec(),
-    }
-}
-
-fn find_header_value(headers: &str, name: &str) -> Option<String> {
-    headers.lines().skip(1).find_map(|line| {
-        let (key, value) = line.split_once(':')?;
-        if key.trim().eq_ignore_ascii_case(name) {
-            Some(value.trim().to_string())
-        } else {
-            None
-        }
-    })
+fn decode_response_body(body: &[u8], headers: &ParsedHeaders) -> Vec<u8> {
+    let encodings = headers
+        .get_all("content-encoding")
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .collect::<Vec<_>>();
+    // Unknown codings and captures cut off at the body limit fail to decode;
+    // show the raw bytes instead.
+    decode_encoding_chain(body, &encodings).unwrap_or_else(|_| body.to_vec())
 }
 
-fn hex_dump(bytes: &[u8]) -> String {
+pub(crate) fn hex_dump(bytes: &[u8]) -> String {
     if bytes.is_empty() {
         return "(empty body)".to_string();
     }
@@ -120,3 +157,152 @@ fn hex_dump(bytes: &[u8]) -> String {
     }
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use crossfeed_codec::{brotli_compress, gzip_compress, zstd_compress};
+
+    use super::*;
+
+    fn zstd_headers() -> ParsedHeaders {
+        ParsedHeaders::parse(b"HTTP/1.1 200 OK\r\nContent-Encoding: zstd\r\n\r\n")
+    }
+
+    #[test]
+    fn zstd_body_is_decoded_for_preview() {
+        let body = zstd_compress(b"{\"status\":\"ok\"}").unwrap();
+        assert_eq!(
+            decode_response_body(&body, &zstd_headers()),
+            b"{\"status\":\"ok\"}"
+        );
+    }
+
+    #[test]
+    fn truncated_zstd_body_falls_back_to_raw_bytes() {
+        let body = zstd_compress(&[b'a'; 4096]).unwrap();
+        let truncated = &body[..body.len() / 2];
+        assert_eq!(decode_response_body(truncated, &zstd_headers()), truncated);
+    }
+
+    #[test]
+    fn chained_encodings_are_decoded_in_reverse_order() {
+        let headers =
+            ParsedHeaders::parse(b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip, br\r\n\r\n");
+        let body = brotli_compress(&gzip_compress(b"layered").unwrap()).unwrap();
+        assert_eq!(decode_response_body(&body, &headers), b"layered");
+    }
+
+    #[test]
+    fn unknown_encoding_in_chain_falls_back_to_raw_bytes() {
+        let headers =
+            ParsedHeaders::parse(b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip, compress\r\n\r\n");
+        let body = gzip_compress(b"layered").unwrap();
+        assert_eq!(decode_response_body(&body, &headers), body);
+    }
+
+    #[test]
+    fn declared_charset_is_used_for_text_bodies() {
+        let latin1 = ParsedHeaders::parse(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=iso-8859-1\r\n\r\n",
+        );
+        assert_eq!(
+            render_response_body(b"caf\xe9", &latin1, JsonPreviewFormat::default()),
+            "café"
+        );
+
+        let utf16 = ParsedHeaders::parse(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-16le\r\n\r\n",
+        );
+        let body = "hi".encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<_>>();
+        assert_eq!(
+            render_response_body(&body, &utf16, JsonPreviewFormat::default()),
+            "hi"
+        );
+    }
+
+    fn headers_with_content_type(content_type: &str) -> ParsedHeaders {
+        ParsedHeaders::parse(
+            format!("HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\n\r\n").as_bytes(),
+        )
+    }
+
+    #[test]
+    fn json_body_is_pretty_printed() {
+        let headers = headers_with_content_type("application/json; charset=utf-8");
+        assert_eq!(
+            render_response_body(b"{\"ok\":true}", &headers, JsonPreviewFormat::default()),
+            "{\n  \"ok\": true\n}"
+        );
+    }
+
+    #[test]
+    fn json_body_follows_indent_and_key_order_settings() {
+        let headers = headers_with_content_type("application/json");
+        let body = b"{\"b\":1,\"a\":2}";
+        assert_eq!(
+            render_response_body(body, &headers, JsonPreviewFormat::default()),
+            "{\n  \"b\": 1,\n  \"a\": 2\n}"
+        );
+        assert_eq!(
+            render_response_body(
+                body,
+                &headers,
+                JsonPreviewFormat {
+                    indent: 4,
+                    sort_keys: true,
+                }
+            ),
+            "{\n    \"a\": 2,\n    \"b\": 1\n}"
+        );
+    }
+
+    #[test]
+    fn invalid_json_body_is_shown_as_text() {
+        let headers = headers_with_content_type("application/json");
+        assert_eq!(
+            render_response_body(b"{not json", &headers, JsonPreviewFormat::default()),
+            "{not json"
+        );
+    }
+
+    #[test]
+    fn image_body_shows_placeholder() {
+        let headers = headers_with_content_type("image/png");
+        assert_eq!(
+            render_response_body(
+                &[0x89, b'P', b'N', b'G'],
+                &headers,
+                JsonPreviewFormat::default()
+            ),
+            "(binary image, 4 bytes)"
+        );
+    }
+
+    #[test]
+    fn svg_body_is_shown_as_text() {
+        let headers = headers_with_content_type("image/svg+xml");
+        assert_eq!(
+            render_response_body(b"<svg/>", &headers, JsonPreviewFormat::default()),
+            "<svg/>"
+        );
+    }
+
+    #[test]
+    fn octet_stream_body_shows_placeholder() {
+        let headers = headers_with_content_type("application/octet-stream");
+        assert_eq!(
+            render_response_body(&[0, 1, 2], &headers, JsonPreviewFormat::default()),
+            "(binary data, 3 bytes)"
+        );
+    }
+
+    #[test]
+    fn unknown_binary_body_is_hex_dumped() {
+        let headers = headers_with_content_type("application/x-custom");
+        let body = [0xff, 0x00, 0xfe];
+        assert_eq!(
+            render_response_body(&body, &headers, JsonPreviewFormat::default()),
+            hex_dump(&body)
+        );
+    }
+}
@@ -4,7 +4,7 @@ mod replay_runtime;
 mod scope;
 mod timeline_tail;
 
-use crossfeed_proxy::{ProxyEvent, ProxyEventKind};
+use crossfeed_proxy::{ProxyConfig, ProxyEvent, ProxyEventKind, parse_tap_pair};
 use crossfeed_storage::{
     BodyLimits, TimelineEvent, TimelineStore, TimelineWorkerConfig, TimelineWorkerHandle,
     spawn_timeline_worker,
@@ -18,7 +18,7 @@ pub use proxy_runtime::{ProxyRuntimeConfig, start_proxy};
 pub use replay_runtime::{
     activate_latest_replay_child, apply_replay_edit, apply_replay_raw_edit,
     create_collection_and_add_request, create_replay_collection, create_replay_from_timeline,
-    duplicate_replay_request,
+    diff_replay_versions, duplicate_replay_request,
     get_latest_replay_execution, get_latest_replay_response, get_replay_active_version,
     get_replay_request, list_replay_collections, list_replay_requests_in_collection,
     list_replay_requests_unassigned, move_replay_request_to_collection,
@@ -27,8 +27,12 @@ pub use replay_runtime::{
     update_replay_request_sort,
 };
 pub use crossfeed_web::CancelToken;
-pub use crossfeed_replay::ReplayEdit;
-pub use scope::{ScopeEvaluation, evaluate_scope};
+pub use crossfeed_replay::{ReplayDiff, ReplayEdit, ReplayTarget};
+pub use crossfeed_storage::{TimelineInsertResult, TimelineWorkerHealth};
+pub use scope::{
+    ScopeEvaluation, ScopeExplanation, ScopeRuleMatch, evaluate_scope, explain_scope,
+    recompute_scope,
+};
 pub use timeline_tail::{TailCursor, TailUpdate, TimelineItem, tail_query};
 
 #[cfg(feature = "sync-runtime")]
@@ -69,37 +73,85 @@ impl IngestHandle {
         }
     }
 
+    pub fn subscribe_inserts(&self) -> tokio::sync::broadcast::Receiver<TimelineInsertResult> {
+        self.worker.subscribe_inserts()
+    }
+
+    pub fn worker_health(&self) -> TimelineWorkerHealth {
+        self.worker.health()
+    }
+
     pub async fn ingest_stream(&self, mut events: impl futures::Stream<Item = ProxyEvent> + Unpin) {
         while let Some(event) = events.next().await {
+            if let Some(frame) = event.websocket_frame {
+                // Frames are not worth stalling the proxy for: a full queue
+                // drops them and the drop shows up in `worker_health`.
+                let _ = self.worker.send_websocket_frame(frame);
+                continue;
+            }
             if let Some(mut timeline) = map_proxy_event(event) {
-                if !self.store_path.as_os_str().is_empty() {
-                    if let Ok(scope) = evaluate_scope(
-                        &self.store_path,
-                        &timeline.request.host,
-                        &timeline.request.path,
-                    ) {
-                        timeline.request.scope_status_at_capture = scope.scope_status_at_capture;
-                        timeline.request.scope_rules_version = scope.scope_rules_version;
-                        timeline.request.capture_filtered = scope.capture_filtered;
-                        timeline.request.timeline_filtered = scope.timeline_filtered;
-                    }
+                self.apply_scope(&mut timeline);
+                if self.worker.send(timeline).is_err() {
+                    break;
                 }
-                let _ = self.worker.send(timeline);
             }
         }
     }
+
+    pub fn ingest_pair(
+        &self,
+        request_bytes: &[u8],
+        response_bytes: &[u8],
+        config: &ProxyConfig,
+    ) -> Result<(), String> {
+        let (request, response) =
+            parse_tap_pair(request_bytes, response_bytes, config).map_err(|err| err.to_string())?;
+        let mut timeline = TimelineEvent {
+            request,
+            response: Some(response),
+            http2_connection: None,
+            websocket_connection_id: None,
+        };
+        self.apply_scope(&mut timeline);
+        self.worker.send(timeline)
+    }
+
+    fn apply_scope(&self, timeline: &mut TimelineEvent) {
+        if self.store_path.as_os_str().is_empty() {
+            return;
+        }
+        if let Ok(scope) = evaluate_scope(
+            &self.store_path,
+            &timeline.request.scheme,
+            &timeline.request.host,
+            timeline.request.port,
+            &timeline.request.path,
+        ) {
+            timeline.request.scope_status_at_capture = scope.scope_status_at_capture;
+            timeline.request.scope_rules_version = scope.scope_rules_version;
+            timeline.request.capture_filtered = scope.capture_filtered;
+            timeline.request.timeline_filtered = scope.timeline_filtered;
+        }
+    }
 }
 
 fn map_proxy_event(event: ProxyEvent) -> Option<TimelineEvent> {
-    match event.kind {
-        ProxyEventKind::ResponseForwarded => {
-            let request = event.request?;
-            let response = event.response?;
-            Some(TimelineEvent {
-                request: request.timeline,
-                response: Some(response.timeline),
-            })
-        }
-        _ => None,
+    let websocket_connection_id = match event.kind {
+        ProxyEventKind::ResponseForwarded => None,
+        ProxyEventKind::WebSocketEstablished => Some(event.request_id.to_string()),
+        _ => return None,
+    };
+    let request = event.request?;
+    let mut response = event.response?;
+    // An unreadable spill file still leaves the parsed head; keep the
+    // exchange with its body marked truncated instead of dropping it.
+    if response.load_spilled().is_err() {
+        response.timeline.response_body_truncated = true;
     }
+    Some(TimelineEvent {
+        request: request.timeline,
+        response: Some(response.timeline),
+        http2_connection: request.http2_connection,
+        websocket_connection_id,
+    })
 }
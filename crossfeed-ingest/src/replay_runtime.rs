@@ -2,7 +2,8 @@ use chrono::Utc;
 use std::path::PathBuf;
 
 use crossfeed_replay::{
-    ReplayEdit, ReplaySendScope, ReplayService, send_replay_request as replay_send_request,
+    ReplayDiff, ReplayEdit, ReplaySendScope, ReplayService, ReplayTarget,
+    send_replay_request as replay_send_request,
 };
 use crossfeed_storage::{
     ReplayCollection, ReplayExecution, ReplayRequest, ReplayVersion, SqliteStore, TimelineResponse,
@@ -154,6 +155,7 @@ pub async fn get_latest_replay_response(
 pub async fn create_replay_from_timeline(
     store_path: PathBuf,
     timeline_request_id: i64,
+    target: Option<ReplayTarget>,
 ) -> Result<i64, String> {
     let store = SqliteStore::open(store_path)?;
     let summary = store
@@ -166,6 +168,11 @@ pub async fn create_replay_from_timeline(
     let (request, _version) = service
         .import_from_timeline(&timeline_request, name, Some(timeline_request_id))
         .map_err(|err| err.to_string())?;
+    if let Some(target) = target {
+        service
+            .retarget(&request, target)
+            .map_err(|err| err.to_string())?;
+    }
     let now = Utc::now().to_rfc3339();
     service
         .store()
@@ -214,6 +221,19 @@ pub async fn set_replay_active_version(
         .map_err(|err| err.to_string())
 }
 
+pub async fn diff_replay_versions(
+    store_path: PathBuf,
+    request_id: i64,
+    from_version: i64,
+    to_version: i64,
+) -> Result<ReplayDiff, String> {
+    let store = SqliteStore::open(store_path)?;
+    let service = ReplayService::new(store);
+    service
+        .diff_versions(request_id, from_version, to_version)
+        .map_err(|err| err.to_string())
+}
+
 pub async fn activate_latest_replay_child(
     store_path: PathBuf,
     request_id: i64,
@@ -243,12 +263,19 @@ pub async fn send_replay_request(
     let version = store
         .get_replay_active_version(request_id)?
         .ok_or_else(|| "Missing active replay version".to_string())?;
-    let scope = evaluate_scope(&store_path, &version.host, &version.path)?;
+    let scope = evaluate_scope(
+        &store_path,
+        &version.scheme,
+        &version.host,
+        version.port,
+        &version.path,
+    )?;
     let send_scope = ReplaySendScope {
         scope_status_at_capture: scope.scope_status_at_capture,
         scope_rules_version: scope.scope_rules_version,
         capture_filtered: scope.capture_filtered,
         timeline_filtered: scope.timeline_filtered,
+        via_proxy: None,
     };
     match replay_send_request(&store_path, request_id, send_scope, cancel).await {
         Ok(result) => Ok(Some(result.timeline_request_id)),
@@ -1,6 +1,8 @@
 use std::path::Path;
 
-use crossfeed_proxy::{ScopePatternType, ScopeRule, ScopeRuleType, ScopeTarget, is_in_scope};
+use crossfeed_proxy::{
+    ScopePatternType, ScopeRule, ScopeRuleType, ScopeTarget, decisive_scope_rule, is_in_scope,
+};
 use crossfeed_storage::{ScopeRuleRow, SqliteStore};
 
 #[derive(Debug, Clone)]
@@ -11,24 +13,99 @@ pub struct ScopeEvaluation {
     pub timeline_filtered: bool,
 }
 
-pub fn evaluate_scope(store_path: &Path, host: &str, path: &str) -> Result<ScopeEvaluation, String> {
+pub fn evaluate_scope(
+    store_path: &Path,
+    scheme: &str,
+    host: &str,
+    port: u16,
+    path: &str,
+) -> Result<ScopeEvaluation, String> {
     let store = SqliteStore::open(store_path)?;
-    let rules = store.list_scope_rules()?;
-    let scope_rules: Vec<ScopeRule> = rules
+    let scope_rules = load_scope_rules(&store)?;
+    Ok(ScopeEvaluation {
+        scope_status_at_capture: scope_status(&scope_rules, scheme, host, port, path).to_string(),
+        scope_rules_version: scope_rules.len() as i64,
+        capture_filtered: true,
+        timeline_filtered: true,
+    })
+}
+
+pub fn recompute_scope(store_path: &Path) -> Result<usize, String> {
+    let store = SqliteStore::open(store_path)?;
+    let scope_rules = load_scope_rules(&store)?;
+    store.recompute_scope_status(scope_rules.len() as i64, |scheme, host, port, path| {
+        scope_status(&scope_rules, scheme, host, port, path).to_string()
+    })
+}
+
+fn load_scope_rules(store: &SqliteStore) -> Result<Vec<ScopeRule>, String> {
+    Ok(store
+        .list_scope_rules()?
         .into_iter()
         .filter_map(map_scope_rule)
-        .collect();
-    let in_scope = is_in_scope(&scope_rules, host, path);
-    let scope_status_at_capture = if in_scope {
+        .collect())
+}
+
+fn scope_status(
+    rules: &[ScopeRule],
+    scheme: &str,
+    host: &str,
+    port: u16,
+    path: &str,
+) -> &'static str {
+    if is_in_scope(rules, scheme, host, port, path) {
         "in_scope"
     } else {
         "out_of_scope"
-    };
-    Ok(ScopeEvaluation {
-        scope_status_at_capture: scope_status_at_capture.to_string(),
-        scope_rules_version: scope_rules.len() as i64,
-        capture_filtered: true,
-        timeline_filtered: true,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeExplanation {
+    pub in_scope: bool,
+    pub decisive_rule: Option<ScopeRuleMatch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeRuleMatch {
+    pub index: usize,
+    pub rule_id: i64,
+    pub rule_type: ScopeRuleType,
+    pub target: ScopeTarget,
+    pub pattern: String,
+}
+
+pub fn explain_scope(
+    store_path: &Path,
+    scheme: &str,
+    host: &str,
+    port: u16,
+    path: &str,
+) -> Result<ScopeExplanation, String> {
+    let store = SqliteStore::open(store_path)?;
+    let rows = store.list_scope_rules()?;
+    let (positions, scope_rules): (Vec<usize>, Vec<ScopeRule>) = rows
+        .iter()
+        .enumerate()
+        .filter_map(|(index, row)| map_scope_rule(row.clone()).map(|rule| (index, rule)))
+        .unzip();
+    let decisive_rule =
+        decisive_scope_rule(&scope_rules, scheme, host, port, path).map(|matched| {
+            let rule = &scope_rules[matched];
+            let index = positions[matched];
+            ScopeRuleMatch {
+                index,
+                rule_id: rows[index].id,
+                rule_type: rule.rule_type.clone(),
+                target: rule.target.clone(),
+                pattern: rule.pattern.clone(),
+            }
+        });
+    Ok(ScopeExplanation {
+        in_scope: decisive_rule
+            .as_ref()
+            .is_some_and(|rule| rule.rule_type == ScopeRuleType::Include),
+        decisive_rule,
     })
 }
 
@@ -53,6 +130,8 @@ fn map_scope_rule(row: ScopeRuleRow) -> Option<ScopeRule> {
         pattern_type,
         target,
         pattern: row.pattern,
+        scheme: row.scheme,
+        port: row.port,
         enabled: row.enabled,
     })
 }
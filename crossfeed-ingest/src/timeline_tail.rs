@@ -58,6 +58,8 @@ pub struct TimelineItem {
     pub http_version: String,
     pub scope_status_at_capture: String,
     pub scope_status_current: Option<String>,
+    pub client_alpn: Option<String>,
+    pub upstream_alpn: Option<String>,
 }
 
 impl From<TimelineRequestSummary> for TimelineItem {
@@ -77,6 +79,8 @@ impl From<TimelineRequestSummary> for TimelineItem {
             http_version: value.http_version,
             scope_status_at_capture: value.scope_status_at_capture,
             scope_status_current: value.scope_status_current,
+            client_alpn: value.client_alpn,
+            upstream_alpn: value.upstream_alpn,
         }
     }
 }
@@ -0,0 +1,74 @@
+use std::time::{Duration, Instant};
+
+use crossfeed_ingest::IngestHandle;
+use crossfeed_proxy::ProxyConfig;
+use crossfeed_storage::{
+    BodyLimits, SqliteStore, TimelineQuery, TimelineRequestSummary, TimelineSort,
+};
+
+fn wait_for_request(store: &SqliteStore) -> TimelineRequestSummary {
+    let started = Instant::now();
+    loop {
+        let summaries = store
+            .query_request_summaries(&TimelineQuery::default(), TimelineSort::StartedAtDesc)
+            .unwrap();
+        if let Some(summary) = summaries.into_iter().next() {
+            return summary;
+        }
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "timeline row never appeared"
+        );
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn ingest_pair_records_timeline_row() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let handle = IngestHandle::new(
+        Box::new(SqliteStore::open(file.path()).unwrap()),
+        BodyLimits::default(),
+    );
+
+    handle
+        .ingest_pair(
+            b"POST /submit HTTP/1.1\r\nHost: example.com:8080\r\nContent-Length: 5\r\n\r\nhello",
+            b"HTTP/1.1 201 Created\r\nContent-Length: 2\r\n\r\nok",
+            &ProxyConfig::default(),
+        )
+        .unwrap();
+
+    let store = SqliteStore::open(file.path()).unwrap();
+    let request = wait_for_request(&store);
+    assert_eq!(request.source, "tap");
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.host, "example.com");
+    assert_eq!(request.port, 8080);
+    assert_eq!(request.path, "/submit");
+    assert_eq!(request.request_body, b"hello");
+
+    let response = store
+        .get_response_by_request_id(request.id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(response.status_code, 201);
+    assert_eq!(response.response_body, b"ok");
+}
+
+#[test]
+fn ingest_pair_rejects_unparseable_request() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let handle = IngestHandle::new(
+        Box::new(SqliteStore::open(file.path()).unwrap()),
+        BodyLimits::default(),
+    );
+
+    let result = handle.ingest_pair(
+        b"GET /missing-host HTTP/1.1\r\n\r\n",
+        b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n",
+        &ProxyConfig::default(),
+    );
+
+    assert!(result.is_err());
+}
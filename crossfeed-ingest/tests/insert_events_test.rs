@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+
+use crossfeed_ingest::IngestHandle;
+use crossfeed_proxy::ProxyConfig;
+use crossfeed_storage::{BodyLimits, SqliteStore, TimelineQuery, TimelineSort};
+use tokio::sync::broadcast::error::TryRecvError;
+
+#[test]
+fn inserted_events_publish_row_ids() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let handle = IngestHandle::new(
+        Box::new(SqliteStore::open(file.path()).unwrap()),
+        BodyLimits::default(),
+    );
+    let mut inserts = handle.subscribe_inserts();
+
+    handle
+        .ingest_pair(
+            b"GET /feed HTTP/1.1\r\nHost: example.com\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n",
+            &ProxyConfig::default(),
+        )
+        .unwrap();
+
+    let started = Instant::now();
+    let inserted = loop {
+        match inserts.try_recv() {
+            Ok(inserted) => break inserted,
+            Err(TryRecvError::Empty) => {
+                assert!(
+                    started.elapsed() < Duration::from_secs(5),
+                    "insert result never published"
+                );
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            Err(err) => panic!("insert subscription failed: {err}"),
+        }
+    };
+
+    let store = SqliteStore::open(file.path()).unwrap();
+    let summaries = store
+        .query_request_summaries(&TimelineQuery::default(), TimelineSort::StartedAtDesc)
+        .unwrap();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(inserted.request_id, summaries[0].id);
+}
@@ -0,0 +1,98 @@
+use crossfeed_ingest::{ScopeRuleMatch, explain_scope};
+use crossfeed_proxy::{ScopeRuleType, ScopeTarget};
+use crossfeed_storage::{ScopeRuleRow, SqliteStore};
+use tempfile::NamedTempFile;
+
+fn store_with_rules(rules: &[(&str, &str, &str)]) -> NamedTempFile {
+    let rules: Vec<_> = rules
+        .iter()
+        .map(|(rule_type, target, pattern)| (*rule_type, *target, *pattern, None))
+        .collect();
+    store_with_port_rules(&rules)
+}
+
+fn store_with_port_rules(rules: &[(&str, &str, &str, Option<u16>)]) -> NamedTempFile {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    for (rule_type, target, pattern, port) in rules {
+        store
+            .insert_scope_rule(&ScopeRuleRow {
+                id: 0,
+                rule_type: rule_type.to_string(),
+                pattern_type: "wildcard".to_string(),
+                target: target.to_string(),
+                pattern: pattern.to_string(),
+                scheme: None,
+                port: *port,
+                enabled: true,
+                created_at: "now".to_string(),
+            })
+            .unwrap();
+    }
+    file
+}
+
+#[test]
+fn explain_scope_reports_matching_include() {
+    let file = store_with_rules(&[
+        ("include", "host", "*.example.org"),
+        ("include", "host", "*.example.com"),
+    ]);
+
+    let explanation = explain_scope(file.path(), "https", "api.example.com", 443, "/").unwrap();
+
+    assert!(explanation.in_scope);
+    let rule = explanation.decisive_rule.unwrap();
+    assert_eq!(rule.index, 1);
+    assert_eq!(rule.rule_type, ScopeRuleType::Include);
+    assert_eq!(rule.target, ScopeTarget::Host);
+    assert_eq!(rule.pattern, "*.example.com");
+}
+
+#[test]
+fn explain_scope_reports_exclude_overriding_include() {
+    let file = store_with_rules(&[
+        ("include", "host", "*.example.com"),
+        ("exclude", "path", "/logout*"),
+    ]);
+
+    let explanation =
+        explain_scope(file.path(), "https", "api.example.com", 443, "/logout").unwrap();
+
+    assert!(!explanation.in_scope);
+    assert_eq!(
+        explanation.decisive_rule,
+        Some(ScopeRuleMatch {
+            index: 1,
+            rule_id: 2,
+            rule_type: ScopeRuleType::Exclude,
+            target: ScopeTarget::Path,
+            pattern: "/logout*".to_string(),
+        })
+    );
+}
+
+#[test]
+fn explain_scope_falls_back_to_default_without_match() {
+    let file = store_with_rules(&[("include", "host", "*.example.com")]);
+
+    let explanation = explain_scope(file.path(), "https", "example.org", 443, "/").unwrap();
+
+    assert!(!explanation.in_scope);
+    assert_eq!(explanation.decisive_rule, None);
+}
+
+#[test]
+fn explain_scope_skips_rules_for_other_ports() {
+    let file = store_with_port_rules(&[
+        ("include", "host", "example.com", None),
+        ("exclude", "host", "example.com", Some(8443)),
+    ]);
+
+    let default_port = explain_scope(file.path(), "https", "example.com", 443, "/").unwrap();
+    let admin_port = explain_scope(file.path(), "https", "example.com", 8443, "/").unwrap();
+
+    assert!(default_port.in_scope);
+    assert!(!admin_port.in_scope);
+    assert_eq!(admin_port.decisive_rule.unwrap().index, 1);
+}
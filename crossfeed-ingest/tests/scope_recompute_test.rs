@@ -0,0 +1,82 @@
+use crossfeed_ingest::recompute_scope;
+use crossfeed_storage::{ScopeRuleRow, SqliteStore, TimelineRequest, TimelineStore};
+use tempfile::NamedTempFile;
+
+fn scope_rule(rule_type: &str, target: &str, pattern: &str) -> ScopeRuleRow {
+    ScopeRuleRow {
+        id: 0,
+        rule_type: rule_type.to_string(),
+        pattern_type: "wildcard".to_string(),
+        target: target.to_string(),
+        pattern: pattern.to_string(),
+        scheme: None,
+        port: None,
+        enabled: true,
+        created_at: "now".to_string(),
+    }
+}
+
+fn captured_request(host: &str, path: &str) -> TimelineRequest {
+    TimelineRequest {
+        source: "proxy".to_string(),
+        method: "GET".to_string(),
+        scheme: "https".to_string(),
+        host: host.to_string(),
+        port: 443,
+        path: path.to_string(),
+        query: None,
+        url: format!("https://{host}{path}"),
+        http_version: "HTTP/1.1".to_string(),
+        request_headers: Vec::new(),
+        request_body: Vec::new(),
+        request_body_size: 0,
+        request_body_truncated: false,
+        started_at: "2024-01-01T00:00:00Z".to_string(),
+        completed_at: None,
+        duration_ms: None,
+        scope_status_at_capture: "in_scope".to_string(),
+        scope_status_current: None,
+        scope_rules_version: 1,
+        capture_filtered: false,
+        timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
+    }
+}
+
+#[test]
+fn recompute_scope_tracks_rule_changes() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    store
+        .insert_scope_rule(&scope_rule("include", "host", "*.example.com"))
+        .unwrap();
+    let admin = store
+        .insert_request(captured_request("api.example.com", "/admin"))
+        .unwrap()
+        .request_id;
+    let users = store
+        .insert_request(captured_request("api.example.com", "/users"))
+        .unwrap()
+        .request_id;
+
+    assert_eq!(recompute_scope(file.path()).unwrap(), 2);
+    let summary = store.get_request_summary(admin).unwrap().unwrap();
+    assert_eq!(summary.scope_status_current.as_deref(), Some("in_scope"));
+    assert_eq!(summary.scope_rules_version, 1);
+
+    store
+        .insert_scope_rule(&scope_rule("exclude", "path", "/admin*"))
+        .unwrap();
+    recompute_scope(file.path()).unwrap();
+
+    let summary = store.get_request_summary(admin).unwrap().unwrap();
+    assert_eq!(
+        summary.scope_status_current.as_deref(),
+        Some("out_of_scope")
+    );
+    assert_eq!(summary.scope_status_at_capture, "in_scope");
+    assert_eq!(summary.scope_rules_version, 2);
+    let summary = store.get_request_summary(users).unwrap().unwrap();
+    assert_eq!(summary.scope_status_current.as_deref(), Some("in_scope"));
+}
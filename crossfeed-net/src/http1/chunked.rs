@@ -0,0 +1,109 @@
+const CRLF: &[u8] = b"\r\n";
+const LAST_CHUNK: &[u8] = b"0\r\n\r\n";
+
+/// Frames body bytes as HTTP/1.1 chunks as they become available.
+#[derive(Debug)]
+pub struct ChunkedEncoder {
+    chunk_size: usize,
+    finished: bool,
+}
+
+impl ChunkedEncoder {
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            finished: false,
+        }
+    }
+
+    /// Encodes `bytes` as chunks of at most `chunk_size`. Empty input yields
+    /// nothing, since a zero-length chunk would end the body.
+    pub fn encode(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(bytes.len() + 16);
+        if self.finished {
+            return out;
+        }
+        for chunk in bytes.chunks(self.chunk_size) {
+            out.extend_from_slice(format!("{:x}", chunk.len()).as_bytes());
+            out.extend_from_slice(CRLF);
+            out.extend_from_slice(chunk);
+            out.extend_from_slice(CRLF);
+        }
+        out
+    }
+
+    /// Returns the terminal chunk the first time it is called.
+    pub fn finish(&mut self) -> Vec<u8> {
+        if self.finished {
+            return Vec::new();
+        }
+        self.finished = true;
+        LAST_CHUNK.to_vec()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+pub fn encode_chunked(body: &[u8], chunk_size: usize) -> Vec<u8> {
+    let mut encoder = ChunkedEncoder::new(chunk_size);
+    let mut out = encoder.encode(body);
+    out.extend_from_slice(&encoder.finish());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkedEncoder, encode_chunked};
+    use crate::http1::{ParseStatus, ResponseParser};
+
+    fn decode(encoded: &[u8]) -> Vec<u8> {
+        let mut message = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+        message.extend_from_slice(encoded);
+        match ResponseParser::new().push(&message) {
+            ParseStatus::Complete { message, .. } => message.body,
+            other => panic!("unexpected status {other:?}"),
+        }
+    }
+
+    #[test]
+    fn encodes_body_in_fixed_size_chunks() {
+        let encoded = encode_chunked(b"hello world", 4);
+        assert_eq!(
+            encoded,
+            b"4\r\nhell\r\n4\r\no wo\r\n3\r\nrld\r\n0\r\n\r\n".to_vec()
+        );
+        assert_eq!(decode(&encoded), b"hello world");
+    }
+
+    #[test]
+    fn encodes_empty_body_as_terminal_chunk() {
+        let encoded = encode_chunked(b"", 8);
+        assert_eq!(encoded, b"0\r\n\r\n".to_vec());
+        assert!(decode(&encoded).is_empty());
+    }
+
+    #[test]
+    fn uses_hex_chunk_sizes() {
+        let body = vec![b'a'; 300];
+        let encoded = encode_chunked(&body, 256);
+        assert!(encoded.starts_with(b"100\r\n"));
+        assert_eq!(decode(&encoded), body);
+    }
+
+    #[test]
+    fn streaming_encoder_round_trips_through_parser() {
+        let mut encoder = ChunkedEncoder::new(3);
+        let mut encoded = Vec::new();
+        for part in [&b"stream"[..], b"", b"ed body"] {
+            encoded.extend_from_slice(&encoder.encode(part));
+        }
+        encoded.extend_from_slice(&encoder.finish());
+        assert!(encoder.is_finished());
+        assert!(encoder.finish().is_empty());
+        assert!(encoder.encode(b"late").is_empty());
+        assert!(encoded.ends_with(b"0\r\n\r\n"));
+        assert_eq!(decode(&encoded), b"streamed body");
+    }
+}
@@ -1,7 +1,9 @@
+mod chunked;
 mod parser;
 mod types;
 pub mod stream;
 
+pub use chunked::{ChunkedEncoder, encode_chunked};
 pub use parser::{ParseStatus, RequestParser, ResponseParser};
 pub use stream::{
     RequestFrameInfo, RequestStreamEvent, RequestStreamParser, ResponseFrameInfo,
@@ -131,7 +131,12 @@ fn parse_request_from_buffer(
     let line = parse_request_line(&buffer[cursor..line_end], cursor, warnings)?;
     cursor = line_end + CRLF.len();
 
-    let headers = parse_headers(&buffer[cursor..headers_end], cursor, warnings)?;
+    let headers = if cursor <= headers_end {
+        parse_headers(&buffer[cursor..headers_end], cursor, warnings)?
+    } else {
+        Vec::new()
+    };
+    check_message_framing(&headers, cursor, warnings);
     cursor = headers_end + HEADER_TERMINATOR.len();
 
     let (body, body_consumed) = parse_body(buffer, cursor, limits, warnings)?;
@@ -165,7 +170,12 @@ fn parse_response_from_buffer(
     let line = parse_status_line(&buffer[cursor..line_end], cursor, warnings)?;
     cursor = line_end + CRLF.len();
 
-    let headers = parse_headers(&buffer[cursor..headers_end], cursor, warnings)?;
+    let headers = if cursor <= headers_end {
+        parse_headers(&buffer[cursor..headers_end], cursor, warnings)?
+    } else {
+        Vec::new()
+    };
+    check_message_framing(&headers, cursor, warnings);
     cursor = headers_end + HEADER_TERMINATOR.len();
 
     let (body, body_consumed) = parse_body(buffer, cursor, limits, warnings)?;
@@ -388,6 +398,36 @@ fn parse_headers(
     Ok(headers)
 }
 
+pub(super) fn check_message_framing(
+    headers: &[Header],
+    offset: usize,
+    warnings: &mut Vec<ParseWarning>,
+) {
+    let mut content_lengths = headers
+        .iter()
+        .filter(|header| header.name.eq_ignore_ascii_case("content-length"))
+        .flat_map(|header| header.value.split(','))
+        .map(str::trim);
+    let has_transfer_encoding = headers
+        .iter()
+        .any(|header| header.name.eq_ignore_ascii_case("transfer-encoding"));
+
+    if let Some(first) = content_lengths.next() {
+        if has_transfer_encoding {
+            warnings.push(ParseWarning {
+                kind: ParseWarningKind::ConflictingFraming,
+                offset,
+            });
+        }
+        if content_lengths.any(|value| value != first) {
+            warnings.push(ParseWarning {
+                kind: ParseWarningKind::DuplicateContentLength,
+                offset,
+            });
+        }
+    }
+}
+
 fn parse_body(
     buffer: &[u8],
     body_start: usize,
@@ -541,6 +581,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_messages_without_headers() {
+        let mut parser = RequestParser::new();
+        match parser.push(b"GET / HTTP/1.1\r\n\r\n") {
+            ParseStatus::Complete { message, .. } => assert!(message.headers.is_empty()),
+            other => panic!("unexpected status {other:?}"),
+        }
+
+        let mut parser = ResponseParser::new();
+        match parser.push(b"HTTP/1.0 200 OK\r\n\r\n") {
+            ParseStatus::Complete { message, .. } => assert!(message.headers.is_empty()),
+            other => panic!("unexpected status {other:?}"),
+        }
+    }
+
     #[test]
     fn parses_request_across_buffers() {
         let mut parser = RequestParser::new();
@@ -632,6 +687,66 @@ mod tests {
         assert!(matches!(status, ParseStatus::Error { .. }));
     }
 
+    fn has_warning(warnings: &[super::ParseWarning], kind: ParseWarningKind) -> bool {
+        warnings.iter().any(|warning| warning.kind == kind)
+    }
+
+    #[test]
+    fn warns_on_content_length_with_transfer_encoding() {
+        let mut parser = RequestParser::new();
+        let input =
+            b"POST / HTTP/1.1\r\nContent-Length: 5\r\nTransfer-Encoding: chunked\r\n\r\nhello";
+        match parser.push(input) {
+            ParseStatus::Complete { warnings, .. } => {
+                assert!(has_warning(&warnings, ParseWarningKind::ConflictingFraming));
+                assert!(!has_warning(
+                    &warnings,
+                    ParseWarningKind::DuplicateContentLength
+                ));
+            }
+            other => panic!("unexpected status {other:?}"),
+        }
+    }
+
+    #[test]
+    fn warns_on_conflicting_content_lengths() {
+        let mut parser = RequestParser::new();
+        let input = b"POST / HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 6\r\n\r\nhello!";
+        match parser.push(input) {
+            ParseStatus::Complete { warnings, .. } => {
+                assert!(has_warning(
+                    &warnings,
+                    ParseWarningKind::DuplicateContentLength
+                ));
+                assert!(!has_warning(
+                    &warnings,
+                    ParseWarningKind::ConflictingFraming
+                ));
+            }
+            other => panic!("unexpected status {other:?}"),
+        }
+
+        let mut parser = ResponseParser::new();
+        let input = b"HTTP/1.1 200 OK\r\nContent-Length: 5, 7\r\n\r\nhello";
+        match parser.push(input) {
+            ParseStatus::Complete { warnings, .. } => assert!(has_warning(
+                &warnings,
+                ParseWarningKind::DuplicateContentLength
+            )),
+            other => panic!("unexpected status {other:?}"),
+        }
+    }
+
+    #[test]
+    fn allows_repeated_identical_content_length() {
+        let mut parser = RequestParser::new();
+        let input = b"POST / HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 5\r\n\r\nhello";
+        match parser.push(input) {
+            ParseStatus::Complete { warnings, .. } => assert!(warnings.is_empty()),
+            other => panic!("unexpected status {other:?}"),
+        }
+    }
+
     #[test]
     fn warns_on_unknown_version() {
         let mut parser = ResponseParser::new();
@@ -1,4 +1,5 @@
-use super::{Header, HttpVersion, Limits, ParseError, ParseErrorKind};
+use super::parser::check_message_framing;
+use super::{Header, HttpVersion, Limits, ParseError, ParseErrorKind, ParseWarning};
 
 const CRLF: &[u8] = b"\r\n";
 const HEADER_TERMINATOR: &[u8] = b"\r\n\r\n";
@@ -12,6 +13,8 @@ pub struct ResponseFrameInfo {
     pub chunked: bool,
     pub close_delimited: bool,
     pub connection_close: bool,
+    /// Framing warnings (conflicting or duplicate lengths) found in the head.
+    pub warnings: Vec<ParseWarning>,
 }
 
 #[derive(Debug, Clone)]
@@ -24,6 +27,8 @@ pub struct RequestFrameInfo {
     pub chunked: bool,
     pub close_delimited: bool,
     pub connection_close: bool,
+    /// Framing warnings (conflicting or duplicate lengths) found in the head.
+    pub warnings: Vec<ParseWarning>,
 }
 
 #[derive(Debug, Clone)]
@@ -511,6 +516,8 @@ fn parse_response_headers(bytes: &[u8]) -> Result<(ResponseFrameInfo, BodyMode),
     let status_line = parts.next().unwrap_or("");
     let (version, status_code) = parse_status_line(status_line)?;
     let headers = parse_headers(parts.collect::<Vec<_>>())?;
+    let mut warnings = Vec::new();
+    check_message_framing(&headers, status_line.len() + CRLF.len(), &mut warnings);
     let content_length = parse_content_length(&headers);
     let chunked = header_has_token(&headers, "transfer-encoding", "chunked");
     let close_delimited = !chunked && content_length.is_none() && !status_has_no_body(status_code);
@@ -524,6 +531,7 @@ fn parse_response_headers(bytes: &[u8]) -> Result<(ResponseFrameInfo, BodyMode),
         chunked,
         close_delimited,
         connection_close,
+        warnings,
     };
 
     let body_mode = if status_has_no_body(status_code) || content_length == Some(0) {
@@ -552,6 +560,8 @@ fn parse_request_headers(
     let request_line = parts.next().unwrap_or("");
     let (method, target, version) = parse_request_line(request_line)?;
     let headers = parse_headers(parts.collect::<Vec<_>>())?;
+    let mut warnings = Vec::new();
+    check_message_framing(&headers, request_line.len() + CRLF.len(), &mut warnings);
     let content_length = parse_content_length(&headers);
     let chunked = header_has_token(&headers, "transfer-encoding", "chunked");
     let connection_close = request_should_close(&version, &headers);
@@ -566,6 +576,7 @@ fn parse_request_headers(
         chunked,
         close_delimited: false,
         connection_close,
+        warnings,
     };
 
     let body_mode = if content_length == Some(0) {
@@ -618,11 +629,7 @@ fn parse_status_line(line: &str) -> Result<(HttpVersion, u16), ParseError> {
 }
 
 fn parse_http_version(version_raw: &str) -> HttpVersion {
-    match version_raw {
-        "HTTP/1.0" => HttpVersion::Http10,
-        "HTTP/1.1" => HttpVersion::Http11,
-        other => HttpVersion::Other(other.to_string()),
-    }
+    HttpVersion::parse(version_raw)
 }
 
 fn parse_headers(lines: Vec<&str>) -> Result<Vec<Header>, ParseError> {
@@ -717,3 +724,64 @@ fn response_should_close(version: &HttpVersion, headers: &[Header]) -> bool {
         HttpVersion::Other(_) => header_has_token(headers, "connection", "close"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http1::ParseWarningKind;
+
+    fn has_warning(warnings: &[ParseWarning], kind: ParseWarningKind) -> bool {
+        warnings.iter().any(|warning| warning.kind == kind)
+    }
+
+    #[test]
+    fn request_stream_warns_on_content_length_with_transfer_encoding() {
+        let mut parser = RequestStreamParser::new();
+        let events = parser
+            .push(b"POST / HTTP/1.1\r\nContent-Length: 5\r\nTransfer-Encoding: chunked\r\n\r\n")
+            .unwrap();
+        match &events[0] {
+            RequestStreamEvent::Headers(info) => {
+                assert!(has_warning(
+                    &info.warnings,
+                    ParseWarningKind::ConflictingFraming
+                ));
+                assert_eq!(info.warnings[0].offset, b"POST / HTTP/1.1\r\n".len());
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    #[test]
+    fn response_stream_warns_on_conflicting_content_lengths() {
+        let mut parser = ResponseStreamParser::new();
+        let events = parser
+            .push(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nContent-Length: 6\r\n\r\nhello")
+            .unwrap();
+        match &events[0] {
+            ResponseStreamEvent::Headers(info) => {
+                assert!(has_warning(
+                    &info.warnings,
+                    ParseWarningKind::DuplicateContentLength
+                ));
+                assert!(!has_warning(
+                    &info.warnings,
+                    ParseWarningKind::ConflictingFraming
+                ));
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    #[test]
+    fn stream_heads_without_framing_conflicts_have_no_warnings() {
+        let mut parser = RequestStreamParser::new();
+        let events = parser
+            .push(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello")
+            .unwrap();
+        match &events[0] {
+            RequestStreamEvent::Headers(info) => assert!(info.warnings.is_empty()),
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+pub use crossfeed_core::HttpVersion;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Header {
     pub name: String,
@@ -19,13 +21,6 @@ pub struct StatusLine {
     pub reason: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum HttpVersion {
-    Http10,
-    Http11,
-    Other(String),
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Request {
     pub line: RequestLine,
@@ -67,6 +62,10 @@ pub enum ParseWarningKind {
     ObsFoldDetected,
     InvalidHeaderName,
     InvalidHeaderValue,
+    /// Both `Content-Length` and `Transfer-Encoding` are present.
+    ConflictingFraming,
+    /// Multiple `Content-Length` values that disagree with each other.
+    DuplicateContentLength,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
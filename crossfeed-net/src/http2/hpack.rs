@@ -1,11 +1,17 @@
 use std::sync::OnceLock;
 
 use crate::http2::types::{HeaderField, Http2Error, Http2ErrorKind};
+use hpack::encoder::encode_integer_into;
 use hpack::{Decoder, Encoder};
 
+/// RFC 7541 default dynamic table size, and the fixed size of the table the
+/// `hpack` encoder keeps.
+const DEFAULT_DYNAMIC_TABLE_SIZE: usize = 4096;
+
 pub struct HpackDecoder {
     inner: Decoder<'static>,
     max_table_size: u32,
+    dynamic_table_size: usize,
 }
 
 static HPACK_SELF_TEST: OnceLock<()> = OnceLock::new();
@@ -16,18 +22,31 @@ impl HpackDecoder {
         Self {
             inner: Decoder::new(),
             max_table_size: 0,
+            dynamic_table_size: DEFAULT_DYNAMIC_TABLE_SIZE,
         }
     }
 
     pub fn set_max_table_size(&mut self, size: u32) {
-        self.inner.set_max_table_size(size as usize);
-        self.max_table_size = size;
+        self.set_max_dynamic_table_size(size as usize);
     }
 
     pub fn max_table_size(&self) -> u32 {
         self.max_table_size
     }
 
+    /// Caps the dynamic table at `size` octets, evicting entries that no
+    /// longer fit.
+    pub fn set_max_dynamic_table_size(&mut self, size: usize) {
+        self.inner.set_max_table_size(size);
+        self.max_table_size = size.min(u32::MAX as usize) as u32;
+        self.dynamic_table_size = size;
+    }
+
+    /// Size limit of the dynamic table in octets.
+    pub fn dynamic_table_size(&self) -> usize {
+        self.dynamic_table_size
+    }
+
     pub fn decode(&mut self, block: &[u8]) -> Result<Vec<HeaderField>, Http2Error> {
         self.inner
             .decode(block)
@@ -53,20 +72,130 @@ fn run_hpack_self_test() {
 
 pub struct HpackEncoder {
     inner: Encoder<'static>,
+    dynamic_table_size: usize,
+    pending_size_update: bool,
 }
 
 impl HpackEncoder {
     pub fn new() -> Self {
         Self {
             inner: Encoder::new(),
+            dynamic_table_size: DEFAULT_DYNAMIC_TABLE_SIZE,
+            pending_size_update: false,
+        }
+    }
+
+    /// Caps the dynamic table at `size` octets, as a peer's
+    /// SETTINGS_HEADER_TABLE_SIZE does. The `hpack` encoder's table cannot
+    /// shrink, so a cap below 4096 octets turns indexing off instead. The
+    /// change is announced at the start of the next header block.
+    pub fn set_max_dynamic_table_size(&mut self, size: usize) {
+        let dynamic_table_size = if size >= DEFAULT_DYNAMIC_TABLE_SIZE {
+            DEFAULT_DYNAMIC_TABLE_SIZE
+        } else {
+            0
+        };
+        if dynamic_table_size != self.dynamic_table_size {
+            self.inner = Encoder::new();
+            self.dynamic_table_size = dynamic_table_size;
+            self.pending_size_update = true;
         }
     }
 
+    /// Size limit of the dynamic table in octets.
+    pub fn dynamic_table_size(&self) -> usize {
+        self.dynamic_table_size
+    }
+
     pub fn encode(&mut self, headers: &[HeaderField]) -> Vec<u8> {
-        self.inner.encode(
+        let mut block = Vec::new();
+        if std::mem::take(&mut self.pending_size_update) {
+            // Empty the peer's table first so it matches the fresh encoder.
+            let _ = encode_integer_into(0, 5, 0x20, &mut block);
+            if self.dynamic_table_size > 0 {
+                let _ = encode_integer_into(self.dynamic_table_size, 5, 0x20, &mut block);
+            }
+        }
+        if self.dynamic_table_size == 0 {
+            for header in headers {
+                block.push(0x00);
+                write_string(&mut block, &header.name);
+                write_string(&mut block, &header.value);
+            }
+            return block;
+        }
+        let _ = self.inner.encode_into(
             headers
                 .iter()
                 .map(|header| (header.name.as_slice(), header.value.as_slice())),
-        )
+            &mut block,
+        );
+        block
+    }
+}
+
+fn write_string(block: &mut Vec<u8>, value: &[u8]) {
+    let _ = encode_integer_into(value.len(), 7, 0x00, block);
+    block.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, value: &str) -> HeaderField {
+        HeaderField {
+            name: name.as_bytes().to_vec(),
+            value: value.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn table_size_downgrade_evicts_peer_entries() {
+        let mut encoder = HpackEncoder::new();
+        let mut decoder = HpackDecoder::new();
+        let headers = vec![field("x-trace-id", "abc123")];
+        decoder.decode(&encoder.encode(&headers)).unwrap();
+        assert_eq!(decoder.decode(&[0xbe]).unwrap(), headers);
+
+        encoder.set_max_dynamic_table_size(1024);
+        assert_eq!(encoder.dynamic_table_size(), 0);
+        let block = encoder.encode(&headers);
+        assert_eq!(block[0], 0x20);
+        assert_eq!(decoder.decode(&block).unwrap(), headers);
+        assert!(decoder.decode(&[0xbe]).is_err());
+        assert_eq!(decoder.decode(&encoder.encode(&headers)).unwrap(), headers);
+    }
+
+    #[test]
+    fn raised_table_size_restores_indexing() {
+        let mut encoder = HpackEncoder::new();
+        let mut decoder = HpackDecoder::new();
+        encoder.set_max_dynamic_table_size(0);
+        encoder.set_max_dynamic_table_size(8192);
+        assert_eq!(encoder.dynamic_table_size(), DEFAULT_DYNAMIC_TABLE_SIZE);
+
+        let headers = vec![field("x-tenant", "acme")];
+        let block = encoder.encode(&headers);
+        assert!(block.starts_with(&[0x20, 0x3f, 0xe1, 0x1f]));
+        assert_eq!(decoder.decode(&block).unwrap(), headers);
+        let repeat = encoder.encode(&headers);
+        assert_eq!(repeat, vec![0xbe]);
+        assert_eq!(decoder.decode(&repeat).unwrap(), headers);
+    }
+
+    #[test]
+    fn decoder_cap_evicts_entries() {
+        let mut encoder = HpackEncoder::new();
+        let mut decoder = HpackDecoder::new();
+        decoder
+            .decode(&encoder.encode(&[field("x-a", "1")]))
+            .unwrap();
+        assert_eq!(decoder.dynamic_table_size(), DEFAULT_DYNAMIC_TABLE_SIZE);
+
+        decoder.set_max_dynamic_table_size(0);
+        assert_eq!(decoder.dynamic_table_size(), 0);
+        assert_eq!(decoder.max_table_size(), 0);
+        assert!(decoder.decode(&[0xbe]).is_err());
     }
 }
@@ -2,12 +2,13 @@ mod http1;
 mod http2;
 mod socks;
 mod tls;
+mod websocket;
 
 pub use http1::{
-    Header, HttpVersion, Limits, ParseError, ParseErrorKind, ParseStatus, ParseWarning,
-    ParseWarningKind, Request, RequestFrameInfo, RequestLine, RequestParser, RequestStreamEvent,
-    RequestStreamParser, Response, ResponseFrameInfo, ResponseParser, ResponseStreamEvent,
-    ResponseStreamParser, StatusLine,
+    ChunkedEncoder, Header, HttpVersion, Limits, ParseError, ParseErrorKind, ParseStatus,
+    ParseWarning, ParseWarningKind, Request, RequestFrameInfo, RequestLine, RequestParser,
+    RequestStreamEvent, RequestStreamParser, Response, ResponseFrameInfo, ResponseParser,
+    ResponseStreamEvent, ResponseStreamParser, StatusLine, encode_chunked,
 };
 
 pub use http2::{
@@ -20,14 +21,21 @@ pub use http2::{
 };
 
 pub use tls::{
-    CaCertificate, CaMaterial, CaMaterialPaths, CertCache, LeafCertificate, TlsConfig, TlsError,
-    TlsErrorKind, build_acceptor, generate_ca, generate_leaf_cert, load_or_generate_ca,
-    write_ca_to_dir,
+    CA_CERT_FILE_NAME, CA_KEY_FILE_NAME, CaCertificate, CaMaterial, CaMaterialPaths, CertCache,
+    LeafCertificate, TlsConfig, TlsError, TlsErrorKind, TrustPlatform, build_acceptor,
+    ca_fingerprint_sha256, ca_trust_instructions, ca_trust_instructions_for,
+    cert_der_fingerprint_sha256, cert_pem_to_der, generate_ca, generate_leaf_cert,
+    load_or_generate_ca, write_ca_to_dir,
 };
 
 pub use socks::{
     SocksAddress, SocksAuth, SocksCommand, SocksError, SocksErrorKind, SocksParseStatus,
     SocksReply, SocksRequest, SocksResponse, SocksResponseParser, SocksVersion,
-    build_handshake_request, build_socks4_connect, build_socks5_connect, parse_handshake_response,
-    parse_socks_response,
+    build_handshake_request, build_socks4_connect, build_socks5_connect, build_userpass_auth,
+    parse_handshake_response, parse_socks_response, parse_userpass_auth_response,
+};
+
+pub use websocket::{
+    WsError, WsErrorKind, WsFrame, WsFrameParser, WsOpcode, WsParseStatus, encode_ws_frame,
+    parse_ws_frame, websocket_accept_key,
 };
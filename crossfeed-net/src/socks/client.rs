@@ -41,6 +41,41 @@ pub fn parse_handshake_response(bytes: &[u8]) -> Result<u8, SocksError> {
     Ok(bytes[1])
 }
 
+/// RFC 1929 limits the username and password to 1..=255 bytes each.
+pub fn build_userpass_auth(username: &str, password: &str) -> Result<Vec<u8>, SocksError> {
+    for credential in [username, password] {
+        if credential.is_empty() || credential.len() > 255 {
+            return Err(SocksError {
+                kind: SocksErrorKind::InvalidCredentials,
+                offset: 0,
+            });
+        }
+    }
+    let mut buf = Vec::with_capacity(3 + username.len() + password.len());
+    buf.push(0x01);
+    buf.push(username.len() as u8);
+    buf.extend_from_slice(username.as_bytes());
+    buf.push(password.len() as u8);
+    buf.extend_from_slice(password.as_bytes());
+    Ok(buf)
+}
+
+pub fn parse_userpass_auth_response(bytes: &[u8]) -> Result<bool, SocksError> {
+    if bytes.len() < 2 {
+        return Err(SocksError {
+            kind: SocksErrorKind::UnexpectedEof,
+            offset: bytes.len(),
+        });
+    }
+    if bytes[0] != 0x01 {
+        return Err(SocksError {
+            kind: SocksErrorKind::InvalidVersion,
+            offset: 0,
+        });
+    }
+    Ok(bytes[1] == 0x00)
+}
+
 pub fn build_socks5_connect(address: SocksAddress, port: u16) -> Vec<u8> {
     let mut buf = Vec::new();
     buf.push(0x05);
@@ -275,6 +310,48 @@ mod tests {
         assert_eq!(method, 0x00);
     }
 
+    #[test]
+    fn builds_userpass_auth_request() {
+        let bytes = build_userpass_auth("user", "pw").unwrap();
+        assert_eq!(
+            bytes,
+            vec![0x01, 0x04, b'u', b's', b'e', b'r', 0x02, b'p', b'w']
+        );
+    }
+
+    #[test]
+    fn rejects_empty_or_overlong_userpass_credentials() {
+        let long = "a".repeat(256);
+        for (username, password) in [
+            ("", "pw"),
+            ("user", ""),
+            (long.as_str(), "pw"),
+            ("user", long.as_str()),
+        ] {
+            assert_eq!(
+                build_userpass_auth(username, password).unwrap_err().kind,
+                SocksErrorKind::InvalidCredentials
+            );
+        }
+        assert!(build_userpass_auth(&long[..255], &long[..255]).is_ok());
+    }
+
+    #[test]
+    fn parses_userpass_auth_response() {
+        assert_eq!(parse_userpass_auth_response(&[0x01, 0x00]), Ok(true));
+        assert_eq!(parse_userpass_auth_response(&[0x01, 0x01]), Ok(false));
+        assert_eq!(
+            parse_userpass_auth_response(&[0x05, 0x00])
+                .unwrap_err()
+                .kind,
+            SocksErrorKind::InvalidVersion
+        );
+        assert_eq!(
+            parse_userpass_auth_response(&[0x01]).unwrap_err().kind,
+            SocksErrorKind::UnexpectedEof
+        );
+    }
+
     #[test]
     fn builds_socks5_connect_ipv4() {
         let bytes = build_socks5_connect(SocksAddress::IpV4([127, 0, 0, 1]), 8080);
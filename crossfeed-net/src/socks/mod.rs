@@ -4,7 +4,8 @@ mod types;
 
 pub use client::{
     SocksAuth, build_handshake_request, build_socks4_connect, build_socks5_connect,
-    parse_handshake_response, parse_socks_response,
+    build_userpass_auth, parse_handshake_response, parse_socks_response,
+    parse_userpass_auth_response,
 };
 pub use parser::{SocksParseStatus, SocksResponseParser};
 pub use types::{
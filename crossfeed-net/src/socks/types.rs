@@ -58,4 +58,5 @@ pub enum SocksErrorKind {
     InvalidResponse,
     UnsupportedAddressType,
     UnexpectedEof,
+    InvalidCredentials,
 }
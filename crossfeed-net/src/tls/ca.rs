@@ -9,6 +9,9 @@ use super::types::{CaCertificate, CaMaterial, CaMaterialPaths, TlsError, TlsErro
 
 const DEFAULT_CA_VALIDITY_DAYS: u64 = 180;
 
+pub const CA_CERT_FILE_NAME: &str = "crossfeed-ca.pem";
+pub const CA_KEY_FILE_NAME: &str = "crossfeed-ca-key.pem";
+
 pub fn generate_ca(common_name: &str) -> Result<CaCertificate, TlsError> {
     let mut params = CertificateParams::new(Vec::new());
     params.is_ca = IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
@@ -38,9 +41,7 @@ pub fn generate_ca(common_name: &str) -> Result<CaCertificate, TlsError> {
         .serialize_pem()
         .map_err(|err| TlsError::new(TlsErrorKind::Rcgen, err.to_string()))?
         .into_bytes();
-    let cert_der = cert
-        .serialize_der()
-        .map_err(|err| TlsError::new(TlsErrorKind::Rcgen, err.to_string()))?;
+    let cert_der = cert_pem_to_der(&cert_pem)?;
 
     let key_pem = cert.serialize_private_key_pem().into_bytes();
     let key_der = cert.serialize_private_key_der();
@@ -56,7 +57,22 @@ pub fn generate_ca(common_name: &str) -> Result<CaCertificate, TlsError> {
     })
 }
 
-#[allow(dead_code)]
+/// Colon-separated uppercase hex SHA-256 of the CA's DER certificate, in the
+/// same form browsers and OS trust stores display.
+pub fn ca_fingerprint_sha256(ca: &CaCertificate) -> String {
+    cert_der_fingerprint_sha256(&ca.material.cert_der)
+}
+
+/// Same as [`ca_fingerprint_sha256`], for a certificate that is only
+/// available as DER (no private key loaded).
+pub fn cert_der_fingerprint_sha256(der: &[u8]) -> String {
+    ::openssl::sha::sha256(der)
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
 pub fn write_ca_to_dir(
     dir: impl AsRef<Path>,
     material: &CaMaterial,
@@ -64,8 +80,8 @@ pub fn write_ca_to_dir(
     let dir = dir.as_ref();
     fs::create_dir_all(dir).map_err(|err| TlsError::new(TlsErrorKind::Io, err.to_string()))?;
 
-    let cert_path = dir.join("crossfeed-ca.pem");
-    let key_path = dir.join("crossfeed-ca-key.pem");
+    let cert_path = dir.join(CA_CERT_FILE_NAME);
+    let key_path = dir.join(CA_KEY_FILE_NAME);
 
     fs::write(&cert_path, &material.cert_pem)
         .map_err(|err| TlsError::new(TlsErrorKind::Io, err.to_string()))?;
@@ -83,17 +99,15 @@ pub fn load_or_generate_ca(
     common_name: &str,
 ) -> Result<(CaCertificate, CaMaterialPaths), TlsError> {
     let dir = dir.as_ref();
-    let cert_path = dir.join("crossfeed-ca.pem");
-    let key_path = dir.join("crossfeed-ca-key.pem");
+    let cert_path = dir.join(CA_CERT_FILE_NAME);
+    let key_path = dir.join(CA_KEY_FILE_NAME);
     if cert_path.exists() && key_path.exists() {
         let cert_pem =
             fs::read(&cert_path).map_err(|err| TlsError::new(TlsErrorKind::Io, err.to_string()))?;
         let key_pem =
             fs::read(&key_path).map_err(|err| TlsError::new(TlsErrorKind::Io, err.to_string()))?;
         let cert = load_ca_certificate(&cert_pem, &key_pem)?;
-        let cert_der = cert
-            .serialize_der()
-            .map_err(|err| TlsError::new(TlsErrorKind::Rcgen, err.to_string()))?;
+        let cert_der = cert_pem_to_der(&cert_pem)?;
         let key_der = cert.serialize_private_key_der();
         let material = CaMaterial {
             cert_pem,
@@ -115,6 +129,14 @@ pub fn load_or_generate_ca(
     Ok((ca, paths))
 }
 
+// Every rcgen serialization re-signs the certificate, so the DER is always
+// derived from the PEM to keep both byte-identical to the CA users trust.
+pub fn cert_pem_to_der(cert_pem: &[u8]) -> Result<Vec<u8>, TlsError> {
+    ::openssl::x509::X509::from_pem(cert_pem)
+        .and_then(|cert| cert.to_der())
+        .map_err(|err| TlsError::new(TlsErrorKind::OpenSsl, err.to_string()))
+}
+
 fn load_ca_certificate(cert_pem: &[u8], key_pem: &[u8]) -> Result<Certificate, TlsError> {
     let cert_str = std::str::from_utf8(cert_pem)
         .map_err(|err| TlsError::new(TlsErrorKind::Rcgen, err.to_string()))?;
@@ -127,3 +149,27 @@ fn load_ca_certificate(cert_pem: &[u8], key_pem: &[u8]) -> Result<Certificate, T
     Certificate::from_params(params)
         .map_err(|err| TlsError::new(TlsErrorKind::Rcgen, err.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_formats_known_digest() {
+        assert_eq!(
+            cert_der_fingerprint_sha256(b"abc"),
+            "BA:78:16:BF:8F:01:CF:EA:41:41:40:DE:5D:AE:22:23:\
+             B0:03:61:A3:96:17:7A:9C:B4:10:FF:61:F2:00:15:AD"
+        );
+    }
+
+    #[test]
+    fn fingerprint_matches_fixture_certificate() {
+        let der = cert_pem_to_der(include_bytes!("../../testdata/crossfeed-ca.pem")).unwrap();
+        assert_eq!(
+            cert_der_fingerprint_sha256(&der),
+            "83:F3:E7:B6:3C:D8:46:3F:29:39:5C:CF:A6:44:6B:E4:\
+             7F:D7:97:A9:4C:09:A1:80:38:16:D0:28:FB:D6:34:92"
+        );
+    }
+}
@@ -2,12 +2,17 @@ mod ca;
 mod cache;
 mod cert;
 mod openssl;
+mod trust;
 mod types;
 
-pub use ca::{generate_ca, load_or_generate_ca, write_ca_to_dir};
+pub use ca::{
+    CA_CERT_FILE_NAME, CA_KEY_FILE_NAME, ca_fingerprint_sha256, cert_der_fingerprint_sha256,
+    cert_pem_to_der, generate_ca, load_or_generate_ca, write_ca_to_dir,
+};
 pub use cache::CertCache;
 pub use cert::generate_leaf_cert;
 pub use openssl::{TlsConfig, build_acceptor};
+pub use trust::{TrustPlatform, ca_trust_instructions, ca_trust_instructions_for};
 pub use types::{
     CaCertificate, CaMaterial, CaMaterialPaths, LeafCertificate, TlsError, TlsErrorKind,
 };
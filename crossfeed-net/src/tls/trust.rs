@@ -0,0 +1,120 @@
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustPlatform {
+    MacOs,
+    Windows,
+    Debian,
+    Fedora,
+    Linux,
+    Other,
+}
+
+impl TrustPlatform {
+    pub fn detect() -> Self {
+        match std::env::consts::OS {
+            "macos" => Self::MacOs,
+            "windows" => Self::Windows,
+            "linux" => {
+                if Path::new("/usr/sbin/update-ca-certificates").exists()
+                    || Path::new("/etc/debian_version").exists()
+                {
+                    Self::Debian
+                } else if Path::new("/usr/bin/update-ca-trust").exists() {
+                    Self::Fedora
+                } else {
+                    Self::Linux
+                }
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+pub fn ca_trust_instructions(cert_path: &Path, fingerprint: &str) -> String {
+    ca_trust_instructions_for(TrustPlatform::detect(), cert_path, fingerprint)
+}
+
+pub fn ca_trust_instructions_for(
+    platform: TrustPlatform,
+    cert_path: &Path,
+    fingerprint: &str,
+) -> String {
+    let cert = cert_path.display();
+    let steps = match platform {
+        TrustPlatform::MacOs => vec![
+            format!(
+                "sudo security add-trusted-cert -d -r trustRoot -k /Library/Keychains/System.keychain \"{cert}\""
+            ),
+            "Or import it in Keychain Access and mark it \"Always Trust\".".to_string(),
+        ],
+        TrustPlatform::Windows => vec![
+            format!("certutil -addstore -f Root \"{cert}\" (from an elevated prompt)"),
+            "Or import it with certmgr.msc under Trusted Root Certification Authorities."
+                .to_string(),
+        ],
+        TrustPlatform::Debian => vec![
+            format!("sudo cp \"{cert}\" /usr/local/share/ca-certificates/crossfeed-ca.crt"),
+            "sudo update-ca-certificates".to_string(),
+        ],
+        TrustPlatform::Fedora => vec![
+            format!("sudo cp \"{cert}\" /etc/pki/ca-trust/source/anchors/crossfeed-ca.pem"),
+            "sudo update-ca-trust".to_string(),
+        ],
+        TrustPlatform::Linux | TrustPlatform::Other => vec![format!(
+            "Import \"{cert}\" into your system or browser trust store as a certificate authority."
+        )],
+    };
+
+    let mut text =
+        format!("CA certificate: {cert}\nSHA-256 fingerprint: {fingerprint}\n\nTo trust it:\n");
+    for (index, step) in steps.iter().enumerate() {
+        text.push_str(&format!("  {}. {}\n", index + 1, step));
+    }
+    text.push_str(
+        "\nFirefox keeps its own store: Settings > Privacy & Security > Certificates > Import.\n",
+    );
+    text.push_str("Verify the fingerprint shown by your OS or browser matches the one above.\n");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: &str = "AB:CD:EF";
+
+    #[test]
+    fn instructions_include_fingerprint_and_platform_tool() {
+        let cert = Path::new("/tmp/certs/crossfeed-ca.pem");
+        for (platform, keyword) in [
+            (TrustPlatform::MacOs, "security add-trusted-cert"),
+            (TrustPlatform::Windows, "certutil -addstore"),
+            (TrustPlatform::Debian, "update-ca-certificates"),
+            (TrustPlatform::Fedora, "update-ca-trust"),
+            (TrustPlatform::Linux, "trust store"),
+        ] {
+            let text = ca_trust_instructions_for(platform, cert, FINGERPRINT);
+            assert!(
+                text.contains("SHA-256 fingerprint: AB:CD:EF"),
+                "{platform:?}"
+            );
+            assert!(text.contains("/tmp/certs/crossfeed-ca.pem"), "{platform:?}");
+            assert!(text.contains(keyword), "{platform:?}");
+        }
+    }
+
+    #[test]
+    fn detected_platform_matches_target_os() {
+        let platform = TrustPlatform::detect();
+        match std::env::consts::OS {
+            "macos" => assert_eq!(platform, TrustPlatform::MacOs),
+            "windows" => assert_eq!(platform, TrustPlatform::Windows),
+            "linux" => assert!(matches!(
+                platform,
+                TrustPlatform::Debian | TrustPlatform::Fedora | TrustPlatform::Linux
+            )),
+            _ => assert_eq!(platform, TrustPlatform::Other),
+        }
+    }
+}
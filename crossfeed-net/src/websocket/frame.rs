@@ -0,0 +1,163 @@
+use openssl::base64::encode_block;
+use openssl::sha::sha1;
+
+use super::types::{WsError, WsErrorKind, WsFrame, WsOpcode};
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const MAX_CONTROL_PAYLOAD: usize = 125;
+
+pub fn websocket_accept_key(key: &str) -> String {
+    let digest = sha1(format!("{}{}", key.trim(), WEBSOCKET_GUID).as_bytes());
+    encode_block(&digest)
+}
+
+pub fn encode_ws_frame(frame: &WsFrame, mask: Option<[u8; 4]>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(frame.payload.len() + 14);
+    let fin = if frame.fin { 0x80 } else { 0x00 };
+    bytes.push(fin | frame.opcode.as_u8());
+    let mask_bit = if mask.is_some() { 0x80 } else { 0x00 };
+    let len = frame.payload.len();
+    if len < 126 {
+        bytes.push(mask_bit | len as u8);
+    } else if len <= u16::MAX as usize {
+        bytes.push(mask_bit | 126);
+        bytes.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        bytes.push(mask_bit | 127);
+        bytes.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+    match mask {
+        Some(key) => {
+            bytes.extend_from_slice(&key);
+            bytes.extend(
+                frame
+                    .payload
+                    .iter()
+                    .enumerate()
+                    .map(|(index, byte)| byte ^ key[index % 4]),
+            );
+        }
+        None => bytes.extend_from_slice(&frame.payload),
+    }
+    bytes
+}
+
+pub fn parse_ws_frame(input: &[u8], max_payload: usize) -> Result<(WsFrame, usize), WsError> {
+    if input.len() < 2 {
+        return Err(error(WsErrorKind::UnexpectedEof, input.len()));
+    }
+    if input[0] & 0x70 != 0 {
+        return Err(error(WsErrorKind::ReservedBitsSet, 0));
+    }
+    let fin = input[0] & 0x80 != 0;
+    let opcode = WsOpcode::from_u8(input[0] & 0x0F);
+    let masked = input[1] & 0x80 != 0;
+    let (len, mut offset) = match input[1] & 0x7F {
+        126 => {
+            let bytes = input
+                .get(2..4)
+                .ok_or_else(|| error(WsErrorKind::UnexpectedEof, input.len()))?;
+            (u16::from_be_bytes([bytes[0], bytes[1]]) as u64, 4)
+        }
+        127 => {
+            let bytes = input
+                .get(2..10)
+                .ok_or_else(|| error(WsErrorKind::UnexpectedEof, input.len()))?;
+            let mut len = [0u8; 8];
+            len.copy_from_slice(bytes);
+            (u64::from_be_bytes(len), 10)
+        }
+        len => (len as u64, 2),
+    };
+    if opcode.is_control() && (!fin || len > MAX_CONTROL_PAYLOAD as u64) {
+        return Err(error(WsErrorKind::InvalidControlFrame, 1));
+    }
+    if len > max_payload as u64 {
+        return Err(error(WsErrorKind::FrameTooLarge, 1));
+    }
+    let len = len as usize;
+    let mask = if masked {
+        let key = input
+            .get(offset..offset + 4)
+            .ok_or_else(|| error(WsErrorKind::UnexpectedEof, input.len()))?;
+        offset += 4;
+        Some([key[0], key[1], key[2], key[3]])
+    } else {
+        None
+    };
+    let payload = input
+        .get(offset..offset + len)
+        .ok_or_else(|| error(WsErrorKind::UnexpectedEof, input.len()))?;
+    let payload = match mask {
+        Some(key) => payload
+            .iter()
+            .enumerate()
+            .map(|(index, byte)| byte ^ key[index % 4])
+            .collect(),
+        None => payload.to_vec(),
+    };
+    Ok((
+        WsFrame {
+            fin,
+            opcode,
+            payload,
+        },
+        offset + len,
+    ))
+}
+
+fn error(kind: WsErrorKind, offset: usize) -> WsError {
+    WsError { kind, offset }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_rfc6455_accept_key() {
+        assert_eq!(
+            websocket_accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn round_trips_masked_text_frame() {
+        let frame = WsFrame::text("Hello");
+        let bytes = encode_ws_frame(&frame, Some([0x37, 0xfa, 0x21, 0x3d]));
+        assert_eq!(
+            bytes,
+            vec![
+                0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58
+            ]
+        );
+        let (parsed, consumed) = parse_ws_frame(&bytes, usize::MAX).unwrap();
+        assert_eq!(parsed, frame);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn round_trips_extended_lengths() {
+        for len in [126, 65_535, 65_536] {
+            let frame = WsFrame::binary(vec![0xAB; len]);
+            let bytes = encode_ws_frame(&frame, None);
+            let (parsed, consumed) = parse_ws_frame(&bytes, usize::MAX).unwrap();
+            assert_eq!(parsed, frame);
+            assert_eq!(consumed, bytes.len());
+        }
+    }
+
+    #[test]
+    fn rejects_fragmented_control_frame() {
+        let error = parse_ws_frame(&[0x09, 0x00], usize::MAX).unwrap_err();
+        assert_eq!(error.kind, WsErrorKind::InvalidControlFrame);
+    }
+
+    #[test]
+    fn rejects_oversized_payload() {
+        let bytes = encode_ws_frame(&WsFrame::binary(vec![0; 32]), None);
+        let error = parse_ws_frame(&bytes, 16).unwrap_err();
+        assert_eq!(error.kind, WsErrorKind::FrameTooLarge);
+    }
+}
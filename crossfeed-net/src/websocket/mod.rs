@@ -0,0 +1,7 @@
+mod frame;
+mod parser;
+mod types;
+
+pub use frame::{encode_ws_frame, parse_ws_frame, websocket_accept_key};
+pub use parser::{WsFrameParser, WsParseStatus};
+pub use types::{WsError, WsErrorKind, WsFrame, WsOpcode};
@@ -0,0 +1,74 @@
+use super::frame::parse_ws_frame;
+use super::types::{WsError, WsErrorKind, WsFrame};
+
+const DEFAULT_MAX_PAYLOAD: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsParseStatus {
+    NeedMore,
+    Complete { frame: WsFrame },
+    Error { error: WsError },
+}
+
+#[derive(Debug)]
+pub struct WsFrameParser {
+    buffer: Vec<u8>,
+    max_payload: usize,
+}
+
+impl Default for WsFrameParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WsFrameParser {
+    pub fn new() -> Self {
+        Self::with_max_payload(DEFAULT_MAX_PAYLOAD)
+    }
+
+    pub fn with_max_payload(max_payload: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_payload,
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) -> WsParseStatus {
+        self.buffer.extend_from_slice(bytes);
+        match parse_ws_frame(&self.buffer, self.max_payload) {
+            Ok((frame, consumed)) => {
+                self.buffer.drain(..consumed);
+                WsParseStatus::Complete { frame }
+            }
+            Err(error) => match error.kind {
+                WsErrorKind::UnexpectedEof => WsParseStatus::NeedMore,
+                _ => WsParseStatus::Error { error },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WsFrameParser, WsParseStatus};
+    use crate::websocket::{WsFrame, encode_ws_frame};
+
+    #[test]
+    fn parses_frames_across_buffers() {
+        let mut bytes = encode_ws_frame(&WsFrame::text("first"), Some([1, 2, 3, 4]));
+        bytes.extend(encode_ws_frame(&WsFrame::text("second"), None));
+        let mut parser = WsFrameParser::new();
+
+        assert!(matches!(parser.push(&bytes[..4]), WsParseStatus::NeedMore));
+        match parser.push(&bytes[4..]) {
+            WsParseStatus::Complete { frame } => assert_eq!(frame, WsFrame::text("first")),
+            other => panic!("unexpected status {other:?}"),
+        }
+        match parser.push(&[]) {
+            WsParseStatus::Complete { frame } => assert_eq!(frame, WsFrame::text("second")),
+            other => panic!("unexpected status {other:?}"),
+        }
+        assert!(matches!(parser.push(&[]), WsParseStatus::NeedMore));
+    }
+}
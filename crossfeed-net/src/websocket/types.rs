@@ -0,0 +1,87 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsOpcode {
+    Continuation,
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+    Other(u8),
+}
+
+impl WsOpcode {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0x0 => WsOpcode::Continuation,
+            0x1 => WsOpcode::Text,
+            0x2 => WsOpcode::Binary,
+            0x8 => WsOpcode::Close,
+            0x9 => WsOpcode::Ping,
+            0xA => WsOpcode::Pong,
+            other => WsOpcode::Other(other),
+        }
+    }
+
+    pub fn as_u8(self) -> u8 {
+        match self {
+            WsOpcode::Continuation => 0x0,
+            WsOpcode::Text => 0x1,
+            WsOpcode::Binary => 0x2,
+            WsOpcode::Close => 0x8,
+            WsOpcode::Ping => 0x9,
+            WsOpcode::Pong => 0xA,
+            WsOpcode::Other(value) => value & 0x0F,
+        }
+    }
+
+    pub fn is_control(self) -> bool {
+        self.as_u8() & 0x8 != 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WsFrame {
+    pub fin: bool,
+    pub opcode: WsOpcode,
+    pub payload: Vec<u8>,
+}
+
+impl WsFrame {
+    pub fn text(text: &str) -> Self {
+        Self {
+            fin: true,
+            opcode: WsOpcode::Text,
+            payload: text.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn binary(payload: Vec<u8>) -> Self {
+        Self {
+            fin: true,
+            opcode: WsOpcode::Binary,
+            payload,
+        }
+    }
+
+    pub fn close() -> Self {
+        Self {
+            fin: true,
+            opcode: WsOpcode::Close,
+            payload: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WsError {
+    pub kind: WsErrorKind,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsErrorKind {
+    UnexpectedEof,
+    ReservedBitsSet,
+    InvalidControlFrame,
+    FrameTooLarge,
+}
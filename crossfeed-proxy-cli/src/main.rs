@@ -1,26 +1,119 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::{Path, PathBuf};
 
 use crossfeed_ingest::IngestHandle;
+use crossfeed_net::{
+    CA_CERT_FILE_NAME, CA_KEY_FILE_NAME, ca_trust_instructions, cert_der_fingerprint_sha256,
+    cert_pem_to_der, generate_ca, write_ca_to_dir,
+};
 use crossfeed_proxy::{Proxy, ProxyConfig};
 use crossfeed_storage::{ProjectConfig, ProjectLayout, ProjectPaths, SqliteStore};
 
 #[derive(Debug, Parser)]
-#[command(name = "crossfeed-proxy-cli")]
+#[command(name = "crossfeed-proxy-cli", subcommand_negates_reqs = true)]
 struct Cli {
-    #[arg(long = "proxy-dir")]
-    proxy_dir: PathBuf,
+    #[arg(long = "proxy-dir", required = true)]
+    proxy_dir: Option<PathBuf>,
     #[arg(long = "request-body-limit-mb", default_value_t = 40)]
     request_body_limit_mb: usize,
     #[arg(long = "response-body-limit-mb", default_value_t = 40)]
     response_body_limit_mb: usize,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand, PartialEq, Eq)]
+enum Command {
+    /// Generate a new proxy CA certificate and key in a directory.
+    GenerateCa {
+        #[arg(long)]
+        dir: PathBuf,
+    },
+    /// Export the proxy CA certificate for installing into a trust store.
+    ExportCa {
+        #[arg(long)]
+        dir: PathBuf,
+        #[arg(long, value_enum, default_value_t = CaFormat::Pem)]
+        format: CaFormat,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Show the proxy CA location, fingerprint and how to trust it on this OS.
+    CaInfo {
+        #[arg(long)]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CaFormat {
+    Pem,
+    Der,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
     let cli = Cli::parse();
+    match cli.command {
+        Some(Command::GenerateCa { dir }) => {
+            let cert_path = generate_ca_command(&dir)?;
+            println!("generated CA certificate at {}", cert_path.display());
+            Ok(())
+        }
+        Some(Command::ExportCa { dir, format, out }) => {
+            export_ca_command(&dir, format, &out)?;
+            println!("exported CA certificate to {}", out.display());
+            Ok(())
+        }
+        Some(Command::CaInfo { dir }) => {
+            print!("{}", ca_info_command(&dir)?);
+            Ok(())
+        }
+        None => run_proxy(cli).await,
+    }
+}
+
+fn generate_ca_command(dir: &Path) -> Result<PathBuf, String> {
+    if dir.join(CA_CERT_FILE_NAME).exists() || dir.join(CA_KEY_FILE_NAME).exists() {
+        return Err(format!("a CA already exists in {}", dir.display()));
+    }
+    let ca = generate_ca(&ProxyConfig::default().tls.ca_common_name).map_err(|err| err.message)?;
+    let paths = write_ca_to_dir(dir, &ca.material).map_err(|err| err.message)?;
+    Ok(paths.cert_path)
+}
+
+fn export_ca_command(dir: &Path, format: CaFormat, out: &Path) -> Result<(), String> {
+    let cert_path = dir.join(CA_CERT_FILE_NAME);
+    if !cert_path.exists() {
+        return Err(format!("no CA found in {}", dir.display()));
+    }
+    let cert_pem = std::fs::read(&cert_path).map_err(|err| err.to_string())?;
+    let bytes = match format {
+        CaFormat::Pem => cert_pem,
+        CaFormat::Der => cert_pem_to_der(&cert_pem).map_err(|err| err.message)?,
+    };
+    std::fs::write(out, bytes).map_err(|err| err.to_string())
+}
+
+fn ca_info_command(dir: &Path) -> Result<String, String> {
+    let cert_path = dir.join(CA_CERT_FILE_NAME);
+    if !cert_path.exists() {
+        return Err(format!("no CA found in {}", dir.display()));
+    }
+    let cert_pem = std::fs::read(&cert_path).map_err(|err| err.to_string())?;
+    let cert_der = cert_pem_to_der(&cert_pem).map_err(|err| err.message)?;
+    Ok(ca_trust_instructions(
+        &cert_path,
+        &cert_der_fingerprint_sha256(&cert_der),
+    ))
+}
+
+async fn run_proxy(cli: Cli) -> Result<(), String> {
+    let proxy_dir = cli
+        .proxy_dir
+        .ok_or_else(|| "--proxy-dir is required".to_string())?;
     let layout = ProjectLayout::default();
-    let paths = ProjectPaths::new(&cli.proxy_dir, &layout);
+    let paths = ProjectPaths::new(&proxy_dir, &layout);
 
     ensure_dir(&paths.root)?;
     ensure_dir(&paths.exports_dir)?;
@@ -61,3 +154,116 @@ async fn main() -> Result<(), String> {
 fn ensure_dir(path: &Path) -> Result<(), String> {
     std::fs::create_dir_all(path).map_err(|err| err.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CA_CERT_FILE_NAME, CA_KEY_FILE_NAME, CaFormat, Cli, Command, ca_info_command,
+        export_ca_command, generate_ca_command,
+    };
+    use clap::Parser;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parses_ca_subcommands_without_proxy_dir() {
+        let cli =
+            Cli::try_parse_from(["crossfeed-proxy-cli", "generate-ca", "--dir", "certs"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::GenerateCa {
+                dir: PathBuf::from("certs")
+            })
+        );
+
+        let cli = Cli::try_parse_from([
+            "crossfeed-proxy-cli",
+            "export-ca",
+            "--dir",
+            "certs",
+            "--format",
+            "der",
+            "--out",
+            "ca.der",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::ExportCa {
+                dir: PathBuf::from("certs"),
+                format: CaFormat::Der,
+                out: PathBuf::from("ca.der"),
+            })
+        );
+
+        assert!(Cli::try_parse_from(["crossfeed-proxy-cli"]).is_err());
+        assert!(Cli::try_parse_from(["crossfeed-proxy-cli", "--proxy-dir", "proj"]).is_ok());
+    }
+
+    #[test]
+    fn exports_generated_ca_as_pem_and_der() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("certs");
+        generate_ca_command(&dir).unwrap();
+        assert!(generate_ca_command(&dir).is_err());
+
+        let pem_path = temp.path().join("ca.pem");
+        export_ca_command(&dir, CaFormat::Pem, &pem_path).unwrap();
+        let pem = std::fs::read_to_string(&pem_path).unwrap();
+        assert_eq!(
+            pem,
+            std::fs::read_to_string(dir.join(CA_CERT_FILE_NAME)).unwrap()
+        );
+        assert!(pem.starts_with("-----BEGIN CERTIFICATE-----"));
+
+        let der_path = temp.path().join("ca.der");
+        export_ca_command(&dir, CaFormat::Der, &der_path).unwrap();
+        let body: String = pem
+            .lines()
+            .filter(|line| !line.starts_with("-----"))
+            .collect();
+        let expected = crossfeed_codec::base64_decode_str(&body).unwrap();
+        assert_eq!(std::fs::read(&der_path).unwrap(), expected);
+
+        let missing = temp.path().join("missing");
+        assert!(export_ca_command(&missing, CaFormat::Pem, &pem_path).is_err());
+    }
+
+    #[test]
+    fn generate_ca_refuses_to_overwrite_existing_key() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join(CA_KEY_FILE_NAME), b"existing key").unwrap();
+
+        assert!(generate_ca_command(dir).is_err());
+        assert_eq!(
+            std::fs::read(dir.join(CA_KEY_FILE_NAME)).unwrap(),
+            b"existing key"
+        );
+        assert!(!dir.join(CA_CERT_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn ca_info_reports_path_fingerprint_and_steps() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("certs");
+        assert!(ca_info_command(&dir).is_err());
+        assert!(!dir.exists());
+        generate_ca_command(&dir).unwrap();
+
+        let cli =
+            Cli::try_parse_from(["crossfeed-proxy-cli", "ca-info", "--dir", "certs"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::CaInfo {
+                dir: PathBuf::from("certs")
+            })
+        );
+
+        let info = ca_info_command(&dir).unwrap();
+        let (ca, _) = crossfeed_net::load_or_generate_ca(&dir, "unused").unwrap();
+        let fingerprint = crossfeed_net::ca_fingerprint_sha256(&ca);
+        assert!(info.contains(&dir.join(CA_CERT_FILE_NAME).display().to_string()));
+        assert!(info.contains(&format!("SHA-256 fingerprint: {fingerprint}")));
+        assert!(info.contains("To trust it:"));
+    }
+}
@@ -0,0 +1,107 @@
+use tempfile::TempPath;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::ProxyError;
+
+pub(crate) struct CaptureBuffer {
+    limit: usize,
+    spill_threshold: Option<usize>,
+    memory: Vec<u8>,
+    spill: Option<(File, TempPath)>,
+    len: usize,
+}
+
+pub(crate) enum CapturedResponse {
+    Memory(Vec<u8>),
+    Spilled(TempPath),
+}
+
+impl CaptureBuffer {
+    pub(crate) fn new(limit: usize, spill_threshold: Option<usize>) -> Self {
+        Self {
+            limit,
+            spill_threshold,
+            memory: Vec::new(),
+            spill: None,
+            len: 0,
+        }
+    }
+
+    pub(crate) async fn extend(&mut self, bytes: &[u8]) -> Result<(), ProxyError> {
+        if self.len >= self.limit {
+            return Ok(());
+        }
+        let bytes = &bytes[..bytes.len().min(self.limit - self.len)];
+        self.len += bytes.len();
+        if self.spill.is_none()
+            && self
+                .spill_threshold
+                .is_some_and(|threshold| self.len > threshold)
+        {
+            self.spill = Some(spill_file().await?);
+        }
+        match self.spill.as_mut() {
+            Some((file, _)) => {
+                if !self.memory.is_empty() {
+                    file.write_all(&std::mem::take(&mut self.memory)).await?;
+                }
+                file.write_all(bytes).await?;
+            }
+            None => self.memory.extend_from_slice(bytes),
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn finish(self) -> Result<CapturedResponse, ProxyError> {
+        match self.spill {
+            Some((mut file, path)) => {
+                file.flush().await?;
+                Ok(CapturedResponse::Spilled(path))
+            }
+            None => Ok(CapturedResponse::Memory(self.memory)),
+        }
+    }
+}
+
+async fn spill_file() -> Result<(File, TempPath), ProxyError> {
+    let (file, path) = tempfile::Builder::new()
+        .prefix("crossfeed-capture-")
+        .tempfile()?
+        .into_parts();
+    Ok((File::from_std(file), path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CaptureBuffer, CapturedResponse};
+
+    #[tokio::test]
+    async fn keeps_small_captures_in_memory() {
+        let mut buffer = CaptureBuffer::new(1024, Some(16));
+        buffer.extend(b"0123456789").await.unwrap();
+
+        match buffer.finish().await.unwrap() {
+            CapturedResponse::Memory(bytes) => assert_eq!(bytes, b"0123456789"),
+            CapturedResponse::Spilled(_) => panic!("expected in-memory capture"),
+        }
+    }
+
+    #[tokio::test]
+    async fn spills_past_threshold_and_honors_limit() {
+        let mut buffer = CaptureBuffer::new(24, Some(8));
+        buffer.extend(b"0123456789").await.unwrap();
+        buffer.extend(b"abcdefghijklmnop").await.unwrap();
+
+        let CapturedResponse::Spilled(path) = buffer.finish().await.unwrap() else {
+            panic!("expected spilled capture");
+        };
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            b"0123456789abcdefghijklmn".to_vec()
+        );
+        let kept = path.to_path_buf();
+        drop(path);
+        assert!(!kept.exists());
+    }
+}
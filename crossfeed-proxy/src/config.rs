@@ -1,3 +1,4 @@
+use crossfeed_core::TimestampFormat;
 use crossfeed_storage::BodyLimits;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +11,52 @@ pub struct ProxyConfig {
     pub body_limits: BodyLimits,
     pub protocol_mode: ProxyProtocolMode,
     pub http1_max_header_bytes: usize,
+    #[serde(default = "default_http2_max_buffered_bytes")]
+    pub http2_max_buffered_bytes: usize,
+    /// Closes keep-alive client connections idle for this long; 0 keeps
+    /// them open indefinitely.
+    #[serde(default = "default_client_idle_timeout_secs")]
+    pub client_idle_timeout_secs: u64,
+    #[serde(default)]
+    pub capture_spill_threshold: Option<usize>,
+    #[serde(default)]
+    pub timestamps: TimestampFormat,
+    #[serde(default)]
+    pub auth: ProxyAuthConfig,
+    #[serde(default)]
+    pub http2_keepalive: Http2KeepaliveConfig,
+}
+
+fn default_http2_max_buffered_bytes() -> usize {
+    128 * 1024 * 1024
+}
+
+fn default_client_idle_timeout_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ProxyAuthConfig {
+    pub enabled: bool,
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Http2KeepaliveConfig {
+    pub enabled: bool,
+    pub interval_ms: u64,
+    pub timeout_ms: u64,
+}
+
+impl Default for Http2KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 30_000,
+            timeout_ms: 10_000,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -39,6 +86,12 @@ pub struct TlsMitmConfig {
 pub struct UpstreamConfig {
     pub mode: UpstreamMode,
     pub socks: Option<SocksConfig>,
+    #[serde(default = "default_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+}
+
+fn default_connect_timeout_ms() -> u64 {
+    10_000
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -79,6 +132,10 @@ pub struct ScopeRule {
     pub pattern_type: ScopePatternType,
     pub target: ScopeTarget,
     pub pattern: String,
+    #[serde(default)]
+    pub scheme: Option<String>,
+    #[serde(default)]
+    pub port: Option<u16>,
     pub enabled: bool,
 }
 
@@ -117,11 +174,18 @@ impl Default for ProxyConfig {
             upstream: UpstreamConfig {
                 mode: UpstreamMode::Direct,
                 socks: None,
+                connect_timeout_ms: default_connect_timeout_ms(),
             },
             scope: ScopeConfig { rules: Vec::new() },
             body_limits: BodyLimits::default(),
             protocol_mode: ProxyProtocolMode::Auto,
             http1_max_header_bytes: 256 * 1024,
+            http2_max_buffered_bytes: default_http2_max_buffered_bytes(),
+            client_idle_timeout_secs: default_client_idle_timeout_secs(),
+            capture_spill_threshold: None,
+            timestamps: TimestampFormat::default(),
+            auth: ProxyAuthConfig::default(),
+            http2_keepalive: Http2KeepaliveConfig::default(),
         }
     }
 }
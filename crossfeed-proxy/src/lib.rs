@@ -1,23 +1,27 @@
 #![recursion_limit = "512"]
 
+mod capture;
 mod config;
 mod error;
 mod events;
 mod intercept;
 mod proxy;
 mod scope;
+mod tap;
 mod timeline_event;
 
 pub use config::{
-    ListenConfig, ProxyConfig, ProxyProtocolMode, ScopeConfig, ScopePatternType, ScopeRule,
+    Http2KeepaliveConfig, ListenConfig, ProxyAuthConfig, ProxyConfig, ProxyProtocolMode,
+    ScopeConfig, ScopePatternType, ScopeRule,
     ScopeRuleType, ScopeTarget, SocksAuthConfig, SocksConfig, SocksVersion, TlsMitmConfig,
     UpstreamConfig, UpstreamMode,
 };
 pub use error::ProxyError;
 pub use events::{ProxyCommand, ProxyControl, ProxyEvents, control_channel, event_channel};
 pub use intercept::{InterceptDecision, InterceptManager, InterceptResult};
-pub use proxy::Proxy;
-pub use scope::is_in_scope;
+pub use proxy::{Proxy, h2_headers_to_http1};
+pub use scope::{decisive_scope_rule, is_in_scope};
+pub use tap::parse_tap_pair;
 pub use timeline_event::{ProxyEvent, ProxyEventKind};
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
@@ -11,25 +12,35 @@ use crossfeed_net::{
     CertCache, HpackEncoder, Http2ParseStatus, Http2Parser, RequestParser,
     RequestStreamEvent, RequestStreamParser, ResponseParser, ResponseStreamEvent,
     ResponseStreamParser, SocksAddress, SocksAuth, SocksResponseParser, SocksVersion,
-    TlsConfig, build_acceptor, encode_data_frames, encode_headers_from_fields,
+    TlsConfig, WsFrameParser, WsParseStatus, build_acceptor, encode_data_frames, encode_headers_from_fields,
     encode_raw_frame, generate_leaf_cert, load_or_generate_ca,
 };
-use crossfeed_storage::{TimelineRequest, TimelineResponse};
+use crossfeed_core::{UNKNOWN_REASON, reason_phrase};
+use crossfeed_storage::{
+    Http2ConnectionRecord, Http2SettingsRecord, TimelineRequest, TimelineResponse,
+    TimelineWebSocketFrame, WEBSOCKET_CLIENT_TO_SERVER, WEBSOCKET_SERVER_TO_CLIENT,
+};
 
+use crate::capture::{CaptureBuffer, CapturedResponse};
 use crate::config::{
-    ProxyConfig, ProxyProtocolMode, SocksAuthConfig, SocksConfig,
+    ProxyAuthConfig, ProxyConfig, ProxyProtocolMode, SocksAuthConfig, SocksConfig,
     SocksVersion as ProxySocksVersion, UpstreamMode,
 };
 use crate::error::ProxyError;
 use crate::events::{ProxyCommand, ProxyControl, ProxyEvents, control_channel, event_channel};
 use crate::intercept::{InterceptDecision, InterceptManager, InterceptResult};
 use crate::scope::is_in_scope;
-use crate::timeline_event::{ProxyEvent, ProxyEventKind, ProxyRequest, ProxyResponse};
+use crate::timeline_event::{ProxyEvent, ProxyEventKind, ProxyRequest, ProxyResponse, SpillFile};
 
+const HTTP2_NO_ERROR: u32 = 0x0;
+const HTTP2_ENHANCE_YOUR_CALM: u32 = 0xb;
 const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const PROXY_AUTH_REQUIRED: &[u8] = b"HTTP/1.1 407 Proxy Authentication Required\r\nProxy-Authenticate: Basic realm=\"crossfeed\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
 
 pub struct Proxy {
     state: Arc<ProxyState>,
+    listener: std::net::TcpListener,
+    local_addr: SocketAddr,
 }
 
 struct ProxyState {
@@ -51,6 +62,11 @@ impl Proxy {
         let cache = Mutex::new(CertCache::with_disk_path(1024, &config.tls.leaf_cert_dir));
         let (sender, events) = event_channel();
         let (control, control_rx) = control_channel();
+        let listener =
+            std::net::TcpListener::bind((config.listen.host.as_str(), config.listen.port))
+                .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+                .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+        let local_addr = listener.local_addr()?;
         Ok((
             Self {
                 state: Arc::new(ProxyState {
@@ -63,19 +79,25 @@ impl Proxy {
                     _ca_paths: ca_paths,
                     alpn_cache: Mutex::new(HashMap::new()),
                 }),
+                listener,
+                local_addr,
             },
             events,
             control,
         ))
     }
 
+    /// The address the proxy listener is bound to, including the concrete
+    /// port when the configured port is 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
     pub async fn run(&self) -> Result<(), ProxyError> {
-        let addr = format!(
-            "{}:{}",
-            self.state.config.listen.host, self.state.config.listen.port
-        );
-        let listener = TcpListener::bind(&addr)
-            .await
+        let listener = self
+            .listener
+            .try_clone()
+            .and_then(TcpListener::from_std)
             .map_err(|err| ProxyError::Runtime(err.to_string()))?;
 
         let control_state = Arc::clone(&self.state);
@@ -124,21 +146,134 @@ async fn handle_connection(
 
 async fn handle_http2(
     state: Arc<ProxyState>,
-    client: TcpStream,
-    buffer: Vec<u8>,
+    mut client: TcpStream,
+    mut buffer: Vec<u8>,
 ) -> Result<(), ProxyError> {
-    let _ = state;
-    let _ = client;
-    let _ = buffer;
-    Err(ProxyError::Runtime(
-        "http2 cleartext not supported".to_string(),
-    ))
+    let mut temp = vec![0u8; 8192];
+    let preview = loop {
+        if let Some(preview) = peek_h2c_request(&buffer)? {
+            break preview;
+        }
+        if buffer.len() > state.config.http2_max_buffered_bytes {
+            return Err(ProxyError::Runtime(
+                "h2c request headers too large".to_string(),
+            ));
+        }
+        let n = client
+            .read(&mut temp)
+            .await
+            .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+        if n == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&temp[..n]);
+    };
+
+    if !is_h2c_proxy_authorized(&state.config.auth, &preview.headers) {
+        return reject_h2c_unauthorized(&mut client, preview.stream_id).await;
+    }
+
+    let meta = preview.meta;
+    let upstream = connect_upstream(&state.config, meta.host.clone(), meta.port).await?;
+    handle_http2_stream(state, client, upstream, buffer, meta.host, meta.port, None).await
+}
+
+struct H2cRequestPreview {
+    meta: Http2RequestMeta,
+    stream_id: u32,
+    headers: Vec<crossfeed_net::HeaderField>,
+}
+
+fn is_h2c_proxy_authorized(auth: &ProxyAuthConfig, headers: &[crossfeed_net::HeaderField]) -> bool {
+    let headers: Vec<crossfeed_net::Header> = headers
+        .iter()
+        .map(|field| crossfeed_net::Header {
+            name: String::from_utf8_lossy(&field.name).to_string(),
+            raw_name: String::from_utf8_lossy(&field.name).to_string(),
+            value: String::from_utf8_lossy(&field.value).to_string(),
+        })
+        .collect();
+    is_proxy_authorized(auth, &headers)
+}
+
+/// Answers the first h2c stream with a 407 and closes the connection, the
+/// HTTP/2 counterpart of [`PROXY_AUTH_REQUIRED`].
+async fn reject_h2c_unauthorized(client: &mut TcpStream, stream_id: u32) -> Result<(), ProxyError> {
+    let mut frames = vec![encode_raw_frame(
+        crossfeed_net::FrameType::Settings,
+        0,
+        0,
+        &[],
+    )];
+    let mut encoder = HpackEncoder::new();
+    frames.extend(encode_headers_from_fields(
+        stream_id,
+        true,
+        &[
+            crossfeed_net::HeaderField {
+                name: b":status".to_vec(),
+                value: b"407".to_vec(),
+            },
+            crossfeed_net::HeaderField {
+                name: b"proxy-authenticate".to_vec(),
+                value: b"Basic realm=\"crossfeed\"".to_vec(),
+            },
+        ],
+        &mut encoder,
+        crossfeed_net::DEFAULT_MAX_FRAME_SIZE,
+    ));
+    let mut goaway = Vec::with_capacity(8);
+    goaway.extend_from_slice(&stream_id.to_be_bytes());
+    goaway.extend_from_slice(&0u32.to_be_bytes());
+    frames.push(encode_raw_frame(
+        crossfeed_net::FrameType::GoAway,
+        0,
+        0,
+        &goaway,
+    ));
+    write_frames(client, &frames).await?;
+    let _ = client.shutdown().await;
+    Ok(())
+}
+
+/// Parses the buffered prior-knowledge bytes far enough to find the first
+/// request's target, which decides the cleartext upstream to dial, and its
+/// headers, which carry any proxy credentials.
+fn peek_h2c_request(bytes: &[u8]) -> Result<Option<H2cRequestPreview>, ProxyError> {
+    let mut parser = Http2Parser::new();
+    let mut status = parser.push(bytes);
+    loop {
+        match status {
+            Http2ParseStatus::NeedMore { .. } => return Ok(None),
+            Http2ParseStatus::Error { error, .. } => {
+                return Err(ProxyError::Runtime(format!("http2 parse error {error:?}")));
+            }
+            Http2ParseStatus::Complete { frame, .. } => match frame.payload {
+                crossfeed_net::FramePayload::Settings(_) => parser.set_settings_received(true),
+                crossfeed_net::FramePayload::Headers(headers) => {
+                    if !has_http2_authority(&headers.headers) {
+                        return Err(ProxyError::Runtime("missing :authority".to_string()));
+                    }
+                    let meta = parse_http2_request_meta(&headers.headers, "", 80)?;
+                    return Ok(Some(H2cRequestPreview {
+                        meta,
+                        stream_id: frame.header.stream_id,
+                        headers: headers.headers,
+                    }));
+                }
+                _ => {}
+            },
+        }
+        status = parser.push(&[]);
+    }
 }
 
 #[derive(Debug)]
 struct Http2StreamState {
     request_headers: Vec<crossfeed_net::HeaderField>,
     request_body: Vec<u8>,
+    request_body_size: usize,
+    request_body_truncated: bool,
     request_complete: bool,
     request_sent: bool,
     pending_request_data: Vec<u8>,
@@ -146,6 +281,8 @@ struct Http2StreamState {
     request_end_stream_sent: bool,
     response_headers: Vec<crossfeed_net::HeaderField>,
     response_body: Vec<u8>,
+    response_body_size: usize,
+    response_body_truncated: bool,
     response_complete: bool,
     response_sent: bool,
     pending_response_data: Vec<u8>,
@@ -169,6 +306,8 @@ impl Http2StreamState {
         Self {
             request_headers: Vec::new(),
             request_body: Vec::new(),
+            request_body_size: 0,
+            request_body_truncated: false,
             request_complete: false,
             request_sent: false,
             pending_request_data: Vec::new(),
@@ -176,6 +315,8 @@ impl Http2StreamState {
             request_end_stream_sent: false,
             response_headers: Vec::new(),
             response_body: Vec::new(),
+            response_body_size: 0,
+            response_body_truncated: false,
             response_complete: false,
             response_sent: false,
             pending_response_data: Vec::new(),
@@ -215,6 +356,7 @@ async fn handle_http2_stream<C, U>(
     mut buffer: Vec<u8>,
     default_host: String,
     default_port: u16,
+    alpn: Option<&'static str>,
 ) -> Result<(), ProxyError>
 where
     C: AsyncRead + AsyncWrite + Unpin,
@@ -224,6 +366,9 @@ where
     let mut upstream_parser = Http2Parser::new_without_preface();
     let mut client_session = Http2Session::new();
     let mut upstream_session = Http2Session::new();
+    upstream_session.connection_id = client_session.connection_id;
+    client_session.alpn = alpn;
+    upstream_session.alpn = alpn;
     let (mut client_read, mut client_write) = tokio::io::split(client);
     let (mut upstream_read, mut upstream_write) = tokio::io::split(upstream);
     let (decision_tx, mut decision_rx) = mpsc::channel(128);
@@ -253,8 +398,18 @@ where
 
     let mut temp = vec![0u8; 8192];
     let mut upstream_temp = vec![0u8; 8192];
+    let keepalive = &state.config.http2_keepalive;
+    let ping_interval = std::time::Duration::from_millis(keepalive.interval_ms);
+    let ping_timeout = std::time::Duration::from_millis(keepalive.timeout_ms);
+    let mut next_ping_at = tokio::time::Instant::now() + ping_interval;
+    let mut ping_counter = 0u64;
 
     loop {
+        let keepalive_at = match upstream_session.pending_ping {
+            Some((_, sent_at)) => sent_at + ping_timeout,
+            None => next_ping_at,
+        };
+
         if let Ok(decision) = decision_rx.try_recv() {
             handle_http2_decision(
                 &state,
@@ -291,6 +446,11 @@ where
                     default_port,
                 )
                 .await?;
+                if http2_buffered_bytes(&streams) > state.config.http2_max_buffered_bytes {
+                    send_goaway(&mut client_write, &streams, HTTP2_ENHANCE_YOUR_CALM).await?;
+                    send_goaway(&mut upstream_write, &streams, HTTP2_ENHANCE_YOUR_CALM).await?;
+                    return Ok(());
+                }
             }
             upstream_read_result = upstream_read.read(&mut upstream_temp) => {
                 let n = upstream_read_result.map_err(|err| ProxyError::Runtime(err.to_string()))?;
@@ -312,6 +472,11 @@ where
                     default_port,
                 )
                 .await?;
+                if http2_buffered_bytes(&streams) > state.config.http2_max_buffered_bytes {
+                    send_goaway(&mut client_write, &streams, HTTP2_ENHANCE_YOUR_CALM).await?;
+                    send_goaway(&mut upstream_write, &streams, HTTP2_ENHANCE_YOUR_CALM).await?;
+                    return Ok(());
+                }
             }
             decision = decision_rx.recv() => {
                 if let Some(decision) = decision {
@@ -329,6 +494,27 @@ where
                     .await?;
                 }
             }
+            _ = tokio::time::sleep_until(keepalive_at), if keepalive.enabled => {
+                if upstream_session.pending_ping.is_some() {
+                    send_goaway(&mut client_write, &streams, HTTP2_NO_ERROR).await?;
+                    send_goaway(&mut upstream_write, &streams, HTTP2_NO_ERROR).await?;
+                    return Ok(());
+                }
+                ping_counter += 1;
+                let opaque_data = ping_counter.to_be_bytes();
+                let ping = encode_raw_frame(crossfeed_net::FrameType::Ping, 0, 0, &opaque_data);
+                upstream_write
+                    .write_all(&ping)
+                    .await
+                    .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+                upstream_write
+                    .flush()
+                    .await
+                    .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+                let sent_at = tokio::time::Instant::now();
+                upstream_session.pending_ping = Some((opaque_data, sent_at));
+                next_ping_at = sent_at + ping_interval;
+            }
         }
     }
 }
@@ -350,6 +536,18 @@ struct Http2Settings {
     max_header_list_size: u32,
 }
 
+impl Http2Settings {
+    fn to_record(&self) -> Http2SettingsRecord {
+        Http2SettingsRecord {
+            header_table_size: self.header_table_size,
+            enable_push: self.enable_push,
+            initial_window_size: self.initial_window_size,
+            max_frame_size: self.max_frame_size,
+            max_header_list_size: self.max_header_list_size,
+        }
+    }
+}
+
 impl Default for Http2Settings {
     fn default() -> Self {
         Self {
@@ -363,6 +561,7 @@ impl Default for Http2Settings {
 }
 
 struct Http2Session {
+    connection_id: Uuid,
     hpack_encoder: HpackEncoder,
     local_settings: Http2Settings,
     peer_settings: Http2Settings,
@@ -371,6 +570,11 @@ struct Http2Session {
     recv_conn_window: i32,
     recv_stream_windows: HashMap<u32, i32>,
     peer_settings_received: bool,
+    pending_ping: Option<([u8; 8], tokio::time::Instant)>,
+    /// Round trip of the most recently acknowledged keepalive PING.
+    ping_rtt: Option<std::time::Duration>,
+    /// ALPN both TLS legs negotiated; `None` for cleartext h2c.
+    alpn: Option<&'static str>,
 }
 
 impl Http2Session {
@@ -380,6 +584,7 @@ impl Http2Session {
         let send_conn_window = peer_settings.initial_window_size as i32;
         let recv_conn_window = local_settings.initial_window_size as i32;
         Self {
+            connection_id: Uuid::new_v4(),
             hpack_encoder: HpackEncoder::new(),
             local_settings,
             peer_settings,
@@ -388,6 +593,9 @@ impl Http2Session {
             recv_conn_window,
             recv_stream_windows: HashMap::new(),
             peer_settings_received: false,
+            pending_ping: None,
+            ping_rtt: None,
+            alpn: None,
         }
     }
 
@@ -412,6 +620,8 @@ impl Http2Session {
             match *id {
                 0x1 => {
                     self.peer_settings.header_table_size = *value;
+                    self.hpack_encoder
+                        .set_max_dynamic_table_size(*value as usize);
                 }
                 0x2 => {
                     self.peer_settings.enable_push = *value != 0;
@@ -435,6 +645,19 @@ impl Http2Session {
         }
     }
 
+    /// Clears the outstanding PING and records its round trip; returns
+    /// whether `opaque_data` acknowledged it.
+    fn record_ping_ack(&mut self, opaque_data: &[u8; 8]) -> bool {
+        match self.pending_ping {
+            Some((expected, sent_at)) if &expected == opaque_data => {
+                self.pending_ping = None;
+                self.ping_rtt = Some(sent_at.elapsed());
+                true
+            }
+            _ => false,
+        }
+    }
+
     fn apply_send_window_update(&mut self, stream_id: u32, increment: u32) {
         if stream_id == 0 {
             self.send_conn_window += increment as i32;
@@ -489,6 +712,12 @@ enum NegotiatedProtocol {
     Http2,
 }
 
+#[derive(Debug, Clone, Copy)]
+struct NegotiatedAlpn {
+    client: NegotiatedProtocol,
+    upstream: NegotiatedProtocol,
+}
+
 #[derive(Debug, Clone)]
 struct Http2RequestMeta {
     method: String,
@@ -594,6 +823,7 @@ where
                     }
                 }
                 send_settings_frame(sender_write, &recv_session.local_settings, true).await?;
+                forward_extension_settings(peer_write, &settings.settings).await?;
                 flush_pending_after_settings(
                     state,
                     direction,
@@ -627,6 +857,10 @@ where
                     .flush()
                     .await
                     .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+            } else if recv_session.record_ping_ack(&ping.opaque_data)
+                && let Some(rtt) = recv_session.ping_rtt
+            {
+                send_http2_ping_rtt_event(state, recv_session.connection_id, rtt).await;
             }
         }
         crossfeed_net::FramePayload::GoAway(goaway) => {
@@ -735,6 +969,7 @@ where
                     }
 
                     if headers.end_stream {
+                        attach_http2_connection(stream, send_session, recv_session);
                         let should_remove = finalize_http2_response(
                             state,
                             stream_id,
@@ -761,7 +996,17 @@ where
             match direction {
                 Direction::ClientToUpstream => {
                     let stream = streams.entry(stream_id).or_insert_with(Http2StreamState::new);
-                    stream.request_body.extend_from_slice(&data.payload);
+                    stream.request_body_size += data.payload.len();
+                    if stream.request_intercept {
+                        // Intercepted requests are replayed from this buffer, so keep it whole.
+                        stream.request_body.extend_from_slice(&data.payload);
+                    } else if capture_body_chunk(
+                        &mut stream.request_body,
+                        &data.payload,
+                        state.config.body_limits.request_max_bytes,
+                    ) {
+                        stream.request_body_truncated = true;
+                    }
 
                     if !stream.request_intercept {
                         send_data_with_flow(
@@ -793,7 +1038,16 @@ where
                 }
                 Direction::UpstreamToClient => {
                     let stream = streams.entry(stream_id).or_insert_with(Http2StreamState::new);
-                    stream.response_body.extend_from_slice(&data.payload);
+                    stream.response_body_size += data.payload.len();
+                    if stream.response_intercept {
+                        stream.response_body.extend_from_slice(&data.payload);
+                    } else if capture_body_chunk(
+                        &mut stream.response_body,
+                        &data.payload,
+                        state.config.body_limits.response_max_bytes,
+                    ) {
+                        stream.response_body_truncated = true;
+                    }
 
                     if !stream.response_intercept {
                         send_data_with_flow(
@@ -810,6 +1064,7 @@ where
                     }
 
                     if data.end_stream {
+                        attach_http2_connection(stream, send_session, recv_session);
                         let should_remove = finalize_http2_response(
                             state,
                             stream_id,
@@ -1068,8 +1323,14 @@ async fn initialize_http2_request_state(
     stream.port = Some(meta.port);
     stream.path = Some(meta.path.clone());
     stream.scheme = Some(meta.scheme.clone());
-    stream.request_started_at = Some(chrono::Utc::now().to_rfc3339());
-    let in_scope = is_in_scope(&state.config.scope.rules, &meta.host, &meta.path);
+    stream.request_started_at = Some(state.config.timestamps.now());
+    let in_scope = is_in_scope(
+        &state.config.scope.rules,
+        &meta.scheme,
+        &meta.host,
+        meta.port,
+        &meta.path,
+    );
     stream.scope_status = Some(if in_scope { "in_scope" } else { "out_of_scope" }.to_string());
     let intercepts = state.intercepts.lock().await;
     stream.request_intercept = intercepts.is_request_intercept_enabled();
@@ -1117,9 +1378,15 @@ async fn finalize_http2_request<W: AsyncWrite + Unpin>(
     let started_at = stream
         .request_started_at
         .clone()
-        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+        .unwrap_or_else(|| state.config.timestamps.now());
     let scope_status = stream.scope_status.clone().unwrap_or_else(|| {
-        let in_scope = is_in_scope(&state.config.scope.rules, &meta.host, &meta.path);
+        let in_scope = is_in_scope(
+            &state.config.scope.rules,
+            &meta.scheme,
+            &meta.host,
+            meta.port,
+            &meta.path,
+        );
         if in_scope {
             "in_scope".to_string()
         } else {
@@ -1127,17 +1394,23 @@ async fn finalize_http2_request<W: AsyncWrite + Unpin>(
         }
     });
     let request_bytes = synthesize_http2_request_bytes(&meta, &stream.request_headers, &stream.request_body);
-    let timeline_request = build_http2_timeline_request(
+    let limit = state.config.body_limits.request_max_bytes;
+    let mut timeline_request = build_http2_timeline_request(
         &meta,
         request_bytes.clone(),
-        stream.request_body.clone(),
+        stream.request_body[..stream.request_body.len().min(limit)].to_vec(),
         started_at,
         &scope_status,
+        send_session.alpn,
     );
+    timeline_request.request_body_size = stream.request_body_size;
+    timeline_request.request_body_truncated =
+        stream.request_body_truncated || stream.request_body.len() > limit;
     let proxy_request = ProxyRequest {
         id: request_id,
         timeline: timeline_request,
         raw_request: request_bytes,
+        http2_connection: None,
     };
     stream.proxy_request = Some(proxy_request.clone());
 
@@ -1241,16 +1514,26 @@ async fn finalize_http2_response<W: AsyncWrite + Unpin>(
     let status_code = parse_http2_status(&stream.response_headers)?;
     let response_bytes =
         synthesize_http2_response_bytes(status_code, &stream.response_headers, &stream.response_body);
-    let timeline_response = build_http2_timeline_response(
+    let limit = state.config.body_limits.response_max_bytes;
+    let mut timeline_response = build_http2_timeline_response(
         status_code,
         response_bytes.clone(),
-        stream.response_body.clone(),
-        chrono::Utc::now().to_rfc3339(),
+        stream.response_body[..stream.response_body.len().min(limit)].to_vec(),
+        state.config.timestamps.now(),
     );
+    timeline_response.response_body_size = stream.response_body_size;
+    timeline_response.response_body_truncated =
+        stream.response_body_truncated || stream.response_body.len() > limit;
+    if let Some(request) = stream.proxy_request.as_mut() {
+        request
+            .timeline
+            .record_completion(&timeline_response.received_at);
+    }
     let proxy_response = ProxyResponse {
         id: Uuid::new_v4(),
         timeline: timeline_response,
         raw_response: response_bytes,
+        spill: None,
     };
     stream.proxy_response = Some(proxy_response.clone());
 
@@ -1375,6 +1658,34 @@ async fn send_settings_frame<W: AsyncWrite + Unpin>(
     Ok(())
 }
 
+/// Known settings (0x1-0x6) are negotiated per hop; anything else is an
+/// extension the proxy does not interpret, so it is relayed to the peer verbatim.
+async fn forward_extension_settings<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    settings: &[(u16, u32)],
+) -> Result<(), ProxyError> {
+    let mut payload = Vec::new();
+    for (id, value) in settings {
+        if !(0x1..=0x6).contains(id) {
+            payload.extend_from_slice(&id.to_be_bytes());
+            payload.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    if payload.is_empty() {
+        return Ok(());
+    }
+    let frame = encode_raw_frame(crossfeed_net::FrameType::Settings, 0x0, 0, &payload);
+    writer
+        .write_all(&frame)
+        .await
+        .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+    writer
+        .flush()
+        .await
+        .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+    Ok(())
+}
+
 fn build_settings_payload(settings: &Http2Settings) -> Vec<u8> {
     let mut payload = Vec::with_capacity(30);
     payload.extend_from_slice(&0x1u16.to_be_bytes());
@@ -1515,6 +1826,57 @@ async fn send_data_with_flow<W: AsyncWrite + Unpin>(
     Ok(())
 }
 
+/// Snapshots the SETTINGS both peers advertised, plus the connection-level send
+/// windows, onto the stream's request so it is stored with the exchange.
+fn attach_http2_connection(
+    stream: &mut Http2StreamState,
+    client_session: &Http2Session,
+    upstream_session: &Http2Session,
+) {
+    if let Some(request) = stream.proxy_request.as_mut() {
+        request.http2_connection = Some(Http2ConnectionRecord {
+            connection_id: client_session.connection_id.to_string(),
+            client_settings: client_session.peer_settings.to_record(),
+            upstream_settings: upstream_session.peer_settings.to_record(),
+            client_send_window: client_session.send_conn_window as i64,
+            upstream_send_window: upstream_session.send_conn_window as i64,
+        });
+    }
+}
+
+fn http2_buffered_bytes(streams: &HashMap<u32, Http2StreamState>) -> usize {
+    streams
+        .values()
+        .map(|stream| {
+            stream.request_body.len()
+                + stream.response_body.len()
+                + stream.pending_request_data.len()
+                + stream.pending_response_data.len()
+        })
+        .sum()
+}
+
+async fn send_goaway<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    streams: &HashMap<u32, Http2StreamState>,
+    error_code: u32,
+) -> Result<(), ProxyError> {
+    let last_stream_id = streams.keys().copied().max().unwrap_or(0);
+    let mut payload = Vec::with_capacity(8);
+    payload.extend_from_slice(&(last_stream_id & 0x7FFF_FFFF).to_be_bytes());
+    payload.extend_from_slice(&error_code.to_be_bytes());
+    let frame = encode_raw_frame(crossfeed_net::FrameType::GoAway, 0, 0, &payload);
+    writer
+        .write_all(&frame)
+        .await
+        .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+    writer
+        .flush()
+        .await
+        .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+    Ok(())
+}
+
 async fn send_rst_stream<W: AsyncWrite + Unpin>(
     writer: &mut W,
     stream_id: u32,
@@ -1687,13 +2049,19 @@ fn parse_http2_request_meta(
             b":scheme" => scheme = Some(String::from_utf8_lossy(&header.value).to_string()),
             b":authority" => authority = Some(String::from_utf8_lossy(&header.value).to_string()),
             b":path" => path = Some(String::from_utf8_lossy(&header.value).to_string()),
+            name if authority.is_none() && name.eq_ignore_ascii_case(b"host") => {
+                authority = Some(String::from_utf8_lossy(&header.value).to_string())
+            }
             _ => {}
         }
     }
 
     let method = method.ok_or_else(|| ProxyError::Runtime("missing :method".to_string()))?;
     let scheme = scheme.unwrap_or_else(|| default_scheme_for_port(default_port).to_string());
-    let authority = authority.unwrap_or_else(|| format_authority(default_host, default_port, &scheme));
+    let authority = match authority {
+        Some(authority) => strip_userinfo(&authority).to_string(),
+        None => format_authority(default_host, default_port, &scheme),
+    };
     let path = path.unwrap_or_else(|| "/".to_string());
     let (host, port) = split_host_port_with_scheme(&authority, &scheme, default_port);
 
@@ -1707,6 +2075,50 @@ fn parse_http2_request_meta(
     })
 }
 
+/// Converts HTTP/2 request headers into the HTTP/1.1 request used for replay,
+/// rebuilding `Host` from `:authority` (or an explicit `host` header).
+pub fn h2_headers_to_http1(
+    headers: &[crossfeed_net::HeaderField],
+    body: &[u8],
+) -> Result<crossfeed_net::Request, ProxyError> {
+    if !has_http2_authority(headers) {
+        return Err(ProxyError::Runtime("missing :authority".to_string()));
+    }
+    let meta = parse_http2_request_meta(headers, "", 443)?;
+    parse_http1_request(&synthesize_http2_request_bytes(&meta, headers, body))
+}
+
+fn has_http2_authority(headers: &[crossfeed_net::HeaderField]) -> bool {
+    headers.iter().any(|header| {
+        header.name.as_slice() == b":authority" || header.name.eq_ignore_ascii_case(b"host")
+    })
+}
+
+/// The raw `:authority` (or `host`) value without userinfo, picked the same
+/// way as [`parse_http2_request_meta`] but without a lossy UTF-8 round trip.
+fn http2_authority_bytes(headers: &[crossfeed_net::HeaderField]) -> Option<&[u8]> {
+    let header = headers
+        .iter()
+        .find(|header| header.name.as_slice() == b":authority")
+        .or_else(|| {
+            headers
+                .iter()
+                .find(|header| header.name.eq_ignore_ascii_case(b"host"))
+        })?;
+    let value = header.value.as_slice();
+    Some(match value.iter().rposition(|byte| *byte == b'@') {
+        Some(at) => &value[at + 1..],
+        None => value,
+    })
+}
+
+fn strip_userinfo(authority: &str) -> &str {
+    authority
+        .rsplit_once('@')
+        .map(|(_, host)| host)
+        .unwrap_or(authority)
+}
+
 fn parse_http2_status(headers: &[crossfeed_net::HeaderField]) -> Result<u16, ProxyError> {
     for header in headers {
         if header.name.as_slice() == b":status" {
@@ -1725,23 +2137,37 @@ fn synthesize_http2_request_bytes(
     body: &[u8],
 ) -> Vec<u8> {
     let mut bytes = Vec::new();
-    bytes.extend_from_slice(format!("{} {} HTTP/2\r\n", meta.method, meta.path).as_bytes());
-    let mut has_host = false;
+    bytes.extend_from_slice(format!("{} {} HTTP/1.1\r\n", meta.method, meta.path).as_bytes());
+    bytes.extend_from_slice(b"Host: ");
+    bytes.extend_from_slice(http2_authority_bytes(headers).unwrap_or(meta.authority.as_bytes()));
+    bytes.extend_from_slice(b"\r\n");
+    let mut cookies: Vec<&[u8]> = Vec::new();
+    let mut has_length = false;
     for header in headers {
-        if header.name.starts_with(b":") {
+        if header.name.starts_with(b":") || header.name.eq_ignore_ascii_case(b"host") {
             continue;
         }
-        let name = String::from_utf8_lossy(&header.name).to_string();
-        if name.eq_ignore_ascii_case("host") {
-            has_host = true;
+        if header.name.eq_ignore_ascii_case(b"cookie") {
+            cookies.push(&header.value);
+            continue;
         }
-        bytes.extend_from_slice(name.as_bytes());
+        if header.name.eq_ignore_ascii_case(b"content-length")
+            || header.name.eq_ignore_ascii_case(b"transfer-encoding")
+        {
+            has_length = true;
+        }
+        bytes.extend_from_slice(&header.name);
         bytes.extend_from_slice(b": ");
         bytes.extend_from_slice(&header.value);
         bytes.extend_from_slice(b"\r\n");
     }
-    if !has_host {
-        bytes.extend_from_slice(format!("Host: {}\r\n", meta.authority).as_bytes());
+    if !cookies.is_empty() {
+        bytes.extend_from_slice(b"cookie: ");
+        bytes.extend_from_slice(&cookies.join(&b"; "[..]));
+        bytes.extend_from_slice(b"\r\n");
+    }
+    if !has_length && !body.is_empty() {
+        bytes.extend_from_slice(format!("content-length: {}\r\n", body.len()).as_bytes());
     }
     bytes.extend_from_slice(b"\r\n");
     bytes.extend_from_slice(body);
@@ -1754,7 +2180,8 @@ fn synthesize_http2_response_bytes(
     body: &[u8],
 ) -> Vec<u8> {
     let mut bytes = Vec::new();
-    bytes.extend_from_slice(format!("HTTP/2 {}\r\n", status_code).as_bytes());
+    let reason = reason_phrase(status_code).unwrap_or(UNKNOWN_REASON);
+    bytes.extend_from_slice(format!("HTTP/1.1 {} {}\r\n", status_code, reason).as_bytes());
     for header in headers {
         if header.name.starts_with(b":") {
             continue;
@@ -1770,12 +2197,19 @@ fn synthesize_http2_response_bytes(
     bytes
 }
 
+fn capture_body_chunk(captured: &mut Vec<u8>, chunk: &[u8], limit: usize) -> bool {
+    let room = limit.saturating_sub(captured.len());
+    captured.extend_from_slice(&chunk[..chunk.len().min(room)]);
+    chunk.len() > room
+}
+
 fn build_http2_timeline_request(
     meta: &Http2RequestMeta,
     headers: Vec<u8>,
     body: Vec<u8>,
     started_at: String,
     scope_status: &str,
+    alpn: Option<&str>,
 ) -> TimelineRequest {
     TimelineRequest {
         source: "proxy".to_string(),
@@ -1799,6 +2233,9 @@ fn build_http2_timeline_request(
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        // TLS HTTP/2 is only relayed once both legs negotiated h2; h2c has no ALPN.
+        client_alpn: alpn.map(str::to_string),
+        upstream_alpn: alpn.map(str::to_string),
     }
 }
 
@@ -1811,17 +2248,18 @@ fn build_http2_timeline_response(
     TimelineResponse {
         timeline_request_id: 0,
         status_code,
-        reason: None,
+        reason: reason_phrase(status_code).map(str::to_string),
         response_headers: headers,
         response_body: body.clone(),
         response_body_size: body.len(),
         response_body_truncated: false,
         http_version: "HTTP/2".to_string(),
         received_at,
+        ttfb_ms: None,
     }
 }
 
-fn parse_http1_request(raw: &[u8]) -> Result<crossfeed_net::Request, ProxyError> {
+pub(crate) fn parse_http1_request(raw: &[u8]) -> Result<crossfeed_net::Request, ProxyError> {
     let mut parser = RequestParser::new();
     match parser.push(raw) {
         crossfeed_net::ParseStatus::Complete { message, .. } => Ok(message),
@@ -2036,6 +2474,8 @@ async fn send_proxy_event(
             kind,
             request,
             response,
+            websocket_frame: None,
+            http2_ping_rtt_ms: None,
         })
         .await;
 }
@@ -2070,7 +2510,11 @@ async fn handle_http1_tcp(
     loop {
         if buffer.is_empty() {
             let mut temp = vec![0u8; 8192];
-            let n = client.read(&mut temp).await?;
+            let n = if request_bytes.is_empty() {
+                read_idle_client(&mut client, &mut temp, &state.config).await?
+            } else {
+                client.read(&mut temp).await?
+            };
             if n == 0 {
                 return Ok(());
             }
@@ -2093,6 +2537,14 @@ async fn handle_http1_tcp(
                         .find(|header| header.name.eq_ignore_ascii_case("host"))
                         .map(|header| header.value.as_str())
                         .unwrap_or("");
+                    if !is_proxy_authorized(&state.config.auth, &info.headers) {
+                        client
+                            .write_all(PROXY_AUTH_REQUIRED)
+                            .await
+                            .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+                        close_http1_connection(&mut client, None::<&mut TcpStream>).await;
+                        return Ok(());
+                    }
                     if method == "CONNECT" {
                         handle_connect(Arc::clone(&state), &mut client, info.target.clone())
                             .await?;
@@ -2115,7 +2567,8 @@ async fn handle_http1_tcp(
                             let Some(header_end) = expect_header_end else {
                                 continue;
                             };
-                            let header_bytes = request_bytes[..header_end].to_vec();
+                            let header_bytes =
+                                strip_proxy_authorization(&request_bytes[..header_end]);
                             let request_stub = crossfeed_net::Request {
                                 line: crossfeed_net::RequestLine {
                                     method: info.method.clone(),
@@ -2152,6 +2605,7 @@ async fn handle_http1_tcp(
                                     http1_response_limits(&state.config),
                                     &request_stub,
                                     Some(peek_bytes),
+                                    None,
                                 )
                                 .await?;
                                 if response.should_close {
@@ -2182,6 +2636,7 @@ async fn handle_http1_tcp(
                                 message,
                                 UpstreamWriteMode::BodyOnly,
                                 false,
+                                None,
                             )
                             .await?;
                         } else {
@@ -2192,6 +2647,7 @@ async fn handle_http1_tcp(
                                 message,
                                 UpstreamWriteMode::FullRequest,
                                 true,
+                                None,
                             )
                             .await?;
                         }
@@ -2203,6 +2659,7 @@ async fn handle_http1_tcp(
                             message,
                             UpstreamWriteMode::FullRequest,
                             false,
+                            None,
                         )
                         .await?;
                     }
@@ -2219,11 +2676,32 @@ async fn handle_http1_tcp(
     }
 }
 
+/// Reads the next request from a keep-alive client, treating an idle timeout
+/// as end of stream.
+async fn read_idle_client<C>(
+    client: &mut C,
+    buf: &mut [u8],
+    config: &ProxyConfig,
+) -> Result<usize, ProxyError>
+where
+    C: AsyncRead + Unpin,
+{
+    let secs = config.client_idle_timeout_secs;
+    if secs == 0 {
+        return Ok(client.read(buf).await?);
+    }
+    match tokio::time::timeout(std::time::Duration::from_secs(secs), client.read(buf)).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Ok(0),
+    }
+}
+
 async fn handle_http1_tls<C, U>(
     state: Arc<ProxyState>,
     mut client: C,
     mut buffer: Vec<u8>,
     mut upstream: U,
+    alpn: NegotiatedAlpn,
 ) -> Result<(), ProxyError>
 where
     C: AsyncRead + AsyncWrite + Unpin,
@@ -2239,7 +2717,11 @@ where
     loop {
         if buffer.is_empty() {
             let mut temp = vec![0u8; 8192];
-            let n = client.read(&mut temp).await?;
+            let n = if request_bytes.is_empty() {
+                read_idle_client(&mut client, &mut temp, &state.config).await?
+            } else {
+                client.read(&mut temp).await?
+            };
             if n == 0 {
                 return Ok(());
             }
@@ -2308,6 +2790,7 @@ where
                                 http1_response_limits(&state.config),
                                 &request_stub,
                                 Some(peek_bytes),
+                                None,
                             )
                             .await?;
                             if response.should_close {
@@ -2338,6 +2821,7 @@ where
                             UpstreamWriteMode::FullRequest
                         },
                         expect_continue,
+                        Some(alpn),
                     )
                     .await?;
                     request_bytes.clear();
@@ -2359,6 +2843,7 @@ async fn handle_http1_request<C, U>(
     message: crossfeed_net::Request,
     write_mode: UpstreamWriteMode,
     expect_continue: bool,
+    alpn: Option<NegotiatedAlpn>,
 ) -> Result<(), ProxyError>
 where
     C: AsyncRead + AsyncWrite + Unpin,
@@ -2373,23 +2858,31 @@ where
         .ok_or_else(|| ProxyError::Runtime("missing host".to_string()))?;
 
 
-    let in_scope = is_in_scope(&state.config.scope.rules, &host, &path);
+    // ALPN is only negotiated on the MITM'd TLS path.
+    let scheme = if alpn.is_some() { "https" } else { "http" };
+    let in_scope = is_in_scope(&state.config.scope.rules, scheme, &host, port, &path);
 
     let request_id = Uuid::new_v4();
-    let started_at = chrono::Utc::now().to_rfc3339();
+    let started_at = state.config.timestamps.now();
     let scope_status = if in_scope { "in_scope" } else { "out_of_scope" };
-    let (timeline_request, request_bytes) = build_request_record(
+    let (mut timeline_request, request_bytes) = build_request_record(
         &message,
+        scheme,
         &path,
         &host,
         port,
         scope_status,
         started_at.clone(),
     );
+    if let Some(alpn) = alpn {
+        timeline_request.client_alpn = Some(protocol_name(alpn.client).to_string());
+        timeline_request.upstream_alpn = Some(protocol_name(alpn.upstream).to_string());
+    }
     let proxy_request = ProxyRequest {
         id: request_id,
         timeline: timeline_request.clone(),
         raw_request: request_bytes,
+        http2_connection: None,
     };
 
     let mut intercepts = state.intercepts.lock().await;
@@ -2412,11 +2905,14 @@ where
                     kind: ProxyEventKind::RequestForwarded,
                     request: Some(proxy_request.clone()),
                     response: None,
+                    websocket_frame: None,
+                    http2_ping_rtt_ms: None,
                 })
                 .await;
 
             if !response_intercept_enabled {
                 let limits = http1_response_limits(&state.config);
+                let mut connected_upstream = None;
                 let streamed = match upstream.as_mut() {
                     Some(upstream) => {
                         log_http1_upstream_send(request_id, &host, port, true);
@@ -2444,6 +2940,7 @@ where
                                         limits,
                                         &message,
                                         Some(peek_bytes),
+                                        None,
                                     )
                                     .await?;
                                     if response.should_close {
@@ -2471,7 +2968,14 @@ where
                             .flush()
                             .await
                             .map_err(|err| ProxyError::Runtime(err.to_string()))?;
-                        read_response_streaming(upstream, client, limits, &message, None)
+                        read_response_streaming(
+                            upstream,
+                            client,
+                            limits,
+                            &message,
+                            None,
+                            state.config.capture_spill_threshold,
+                        )
                         .await?
                     }
                     None => {
@@ -2488,19 +2992,47 @@ where
                             .flush()
                             .await
                             .map_err(|err| ProxyError::Runtime(err.to_string()))?;
-                        read_response_streaming(&mut upstream, client, limits, &message, None)
-                        .await?
+                        let streamed = read_response_streaming(
+                            &mut upstream,
+                            client,
+                            limits,
+                            &message,
+                            None,
+                            state.config.capture_spill_threshold,
+                        )
+                        .await?;
+                        connected_upstream = Some(upstream);
+                        streamed
                     }
                 };
 
-                let proxy_response =
-                    parse_response(&streamed.bytes, &started_at, limits).map(|timeline_response| {
-                        ProxyResponse {
-                            id: Uuid::new_v4(),
-                            timeline: timeline_response,
-                            raw_response: streamed.bytes,
-                        }
-                    });
+                let received_at = state.config.timestamps.now();
+                let proxy_response = streamed_proxy_response(
+                    streamed.capture,
+                    &received_at,
+                    limits,
+                    streamed.incomplete,
+                    streamed.ttfb_ms,
+                );
+
+                if let Some(initial_frames) = streamed.upgrade {
+                    send_proxy_event(
+                        &state,
+                        request_id,
+                        ProxyEventKind::WebSocketEstablished,
+                        Some(proxy_request.clone()),
+                        proxy_response,
+                    )
+                    .await;
+                    if let Some(upstream) = upstream.as_deref_mut() {
+                        relay_websocket(&state, request_id, client, upstream, initial_frames)
+                            .await?;
+                    } else if let Some(upstream) = connected_upstream.as_mut() {
+                        relay_websocket(&state, request_id, client, upstream, initial_frames)
+                            .await?;
+                    }
+                    return Ok(());
+                }
 
                 if let Some(proxy_response) = proxy_response {
                     let _ = state
@@ -2511,6 +3043,8 @@ where
                             kind: ProxyEventKind::ResponseForwarded,
                             request: Some(proxy_request.clone()),
                             response: Some(proxy_response),
+                            websocket_frame: None,
+                            http2_ping_rtt_ms: None,
                         })
                         .await;
                 }
@@ -2593,13 +3127,15 @@ where
                 }
             };
 
+            let received_at = state.config.timestamps.now();
             (
                 Some(proxy_request),
-                parse_response(&response_bytes, &started_at, http1_response_limits(&state.config))
+                parse_response(&response_bytes, &received_at, http1_response_limits(&state.config))
                     .map(|timeline_response| ProxyResponse {
                         id: Uuid::new_v4(),
                         timeline: timeline_response,
                         raw_response: response_bytes,
+                        spill: None,
                     }),
             )
         }
@@ -2612,6 +3148,8 @@ where
                     kind: ProxyEventKind::RequestIntercepted,
                     request: Some(proxy_request.clone()),
                     response: None,
+                    websocket_frame: None,
+                    http2_ping_rtt_ms: None,
                 })
                 .await;
 
@@ -2631,11 +3169,14 @@ where
                     kind: ProxyEventKind::RequestForwarded,
                     request: Some(proxy_request.clone()),
                     response: None,
+                    websocket_frame: None,
+                    http2_ping_rtt_ms: None,
                 })
                 .await;
 
             if !response_intercept_enabled {
                 let limits = http1_response_limits(&state.config);
+                let mut connected_upstream = None;
                 let streamed = match upstream.as_mut() {
                     Some(upstream) => {
                         log_http1_upstream_send(request_id, &host, port, true);
@@ -2663,6 +3204,7 @@ where
                                         limits,
                                         &message,
                                         Some(peek_bytes),
+                                        None,
                                     )
                                     .await?;
                                     if response.should_close {
@@ -2690,7 +3232,14 @@ where
                             .flush()
                             .await
                             .map_err(|err| ProxyError::Runtime(err.to_string()))?;
-                        read_response_streaming(upstream, client, limits, &message, None)
+                        read_response_streaming(
+                            upstream,
+                            client,
+                            limits,
+                            &message,
+                            None,
+                            state.config.capture_spill_threshold,
+                        )
                         .await?
                     }
                     None => {
@@ -2707,19 +3256,47 @@ where
                             .flush()
                             .await
                             .map_err(|err| ProxyError::Runtime(err.to_string()))?;
-                        read_response_streaming(&mut upstream, client, limits, &message, None)
-                        .await?
+                        let streamed = read_response_streaming(
+                            &mut upstream,
+                            client,
+                            limits,
+                            &message,
+                            None,
+                            state.config.capture_spill_threshold,
+                        )
+                        .await?;
+                        connected_upstream = Some(upstream);
+                        streamed
                     }
                 };
 
-                let proxy_response =
-                    parse_response(&streamed.bytes, &started_at, limits).map(|timeline_response| {
-                        ProxyResponse {
-                            id: Uuid::new_v4(),
-                            timeline: timeline_response,
-                            raw_response: streamed.bytes,
-                        }
-                    });
+                let received_at = state.config.timestamps.now();
+                let proxy_response = streamed_proxy_response(
+                    streamed.capture,
+                    &received_at,
+                    limits,
+                    streamed.incomplete,
+                    streamed.ttfb_ms,
+                );
+
+                if let Some(initial_frames) = streamed.upgrade {
+                    send_proxy_event(
+                        &state,
+                        request_id,
+                        ProxyEventKind::WebSocketEstablished,
+                        Some(proxy_request.clone()),
+                        proxy_response,
+                    )
+                    .await;
+                    if let Some(upstream) = upstream.as_deref_mut() {
+                        relay_websocket(&state, request_id, client, upstream, initial_frames)
+                            .await?;
+                    } else if let Some(upstream) = connected_upstream.as_mut() {
+                        relay_websocket(&state, request_id, client, upstream, initial_frames)
+                            .await?;
+                    }
+                    return Ok(());
+                }
 
                 if let Some(proxy_response) = proxy_response {
                     let _ = state
@@ -2730,6 +3307,8 @@ where
                             kind: ProxyEventKind::ResponseForwarded,
                             request: Some(proxy_request.clone()),
                             response: Some(proxy_response),
+                            websocket_frame: None,
+                            http2_ping_rtt_ms: None,
                         })
                         .await;
                 }
@@ -2812,13 +3391,15 @@ where
                 }
             };
 
+            let received_at = state.config.timestamps.now();
             (
                 Some(proxy_request),
-                parse_response(&response_bytes, &started_at, http1_response_limits(&state.config))
+                parse_response(&response_bytes, &received_at, http1_response_limits(&state.config))
                     .map(|timeline_response| ProxyResponse {
                         id: Uuid::new_v4(),
                         timeline: timeline_response,
                         raw_response: response_bytes,
+                        spill: None,
                     }),
             )
         }
@@ -2854,6 +3435,8 @@ where
                         kind: ProxyEventKind::ResponseForwarded,
                         request: Some(forwarded_request.clone()),
                         response: Some(proxy_response),
+                        websocket_frame: None,
+                        http2_ping_rtt_ms: None,
                     })
                     .await;
                 if should_close {
@@ -2870,6 +3453,8 @@ where
                         kind: ProxyEventKind::ResponseIntercepted,
                         request: Some(forwarded_request.clone()),
                         response: Some(proxy_response.clone()),
+                        websocket_frame: None,
+                        http2_ping_rtt_ms: None,
                     })
                     .await;
                 let decision = receiver
@@ -2898,6 +3483,8 @@ where
                                 kind: ProxyEventKind::ResponseForwarded,
                                 request: Some(forwarded_request.clone()),
                                 response: Some(proxy_response),
+                                websocket_frame: None,
+                                http2_ping_rtt_ms: None,
                             })
                             .await;
                         if should_close {
@@ -3050,24 +3637,155 @@ where
     }
 }
 
-async fn handle_connect<S>(
-    state: Arc<ProxyState>,
-    client: &mut S,
-    target: String,
+/// Relays an upgraded WebSocket connection until either side closes,
+/// reporting every frame that crosses the proxy without altering the bytes.
+async fn relay_websocket<C, U>(
+    state: &Arc<ProxyState>,
+    request_id: Uuid,
+    client: &mut C,
+    upstream: &mut U,
+    initial_frames: Vec<u8>,
 ) -> Result<(), ProxyError>
 where
-    S: AsyncRead + AsyncWrite + Unpin,
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: AsyncRead + AsyncWrite + Unpin,
 {
-    let (host, port) = split_host_port(&target);
+    let mut from_client = WebSocketFrameTap::new(WEBSOCKET_CLIENT_TO_SERVER);
+    let mut from_upstream = WebSocketFrameTap::new(WEBSOCKET_SERVER_TO_CLIENT);
+    for frame in from_upstream.push(&initial_frames) {
+        send_websocket_frame_event(state, request_id, frame).await;
+    }
+    let mut client_buf = vec![0u8; 8192];
+    let mut upstream_buf = vec![0u8; 8192];
+    loop {
+        let frames = tokio::select! {
+            read = client.read(&mut client_buf) => {
+                let n = read?;
+                if n == 0 {
+                    break;
+                }
+                upstream.write_all(&client_buf[..n]).await?;
+                upstream.flush().await?;
+                from_client.push(&client_buf[..n])
+            }
+            read = upstream.read(&mut upstream_buf) => {
+                let n = read?;
+                if n == 0 {
+                    break;
+                }
+                client.write_all(&upstream_buf[..n]).await?;
+                client.flush().await?;
+                from_upstream.push(&upstream_buf[..n])
+            }
+        };
+        for frame in frames {
+            send_websocket_frame_event(state, request_id, frame).await;
+        }
+    }
+    close_http1_connection(client, Some(upstream)).await;
+    Ok(())
+}
 
-    client
-        .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
-        .await
-        .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+struct WebSocketFrameTap {
+    direction: &'static str,
+    /// Dropped after the first malformed frame; the relay keeps copying bytes.
+    parser: Option<WsFrameParser>,
+}
 
-    if !state.config.tls.enabled {
-        let mut upstream = connect_upstream(&state.config, host.clone(), port).await?;
-        let (mut client_read, mut client_write) = tokio::io::split(client);
+impl WebSocketFrameTap {
+    fn new(direction: &'static str) -> Self {
+        Self {
+            direction,
+            parser: Some(WsFrameParser::new()),
+        }
+    }
+
+    /// Returns `(direction, opcode, payload length)` for each completed frame.
+    fn push(&mut self, bytes: &[u8]) -> Vec<(&'static str, u8, usize)> {
+        let mut frames = Vec::new();
+        let Some(parser) = self.parser.as_mut() else {
+            return frames;
+        };
+        let mut input = bytes;
+        loop {
+            match parser.push(input) {
+                WsParseStatus::Complete { frame } => {
+                    frames.push((self.direction, frame.opcode.as_u8(), frame.payload.len()));
+                }
+                WsParseStatus::NeedMore => break,
+                WsParseStatus::Error { .. } => {
+                    self.parser = None;
+                    break;
+                }
+            }
+            input = &[];
+        }
+        frames
+    }
+}
+
+async fn send_websocket_frame_event(
+    state: &Arc<ProxyState>,
+    request_id: Uuid,
+    (direction, opcode, payload_len): (&'static str, u8, usize),
+) {
+    let _ = state
+        .sender
+        .send(ProxyEvent {
+            event_id: Uuid::new_v4(),
+            request_id,
+            kind: ProxyEventKind::WebSocketFrame,
+            request: None,
+            response: None,
+            websocket_frame: Some(TimelineWebSocketFrame {
+                connection_id: request_id.to_string(),
+                direction: direction.to_string(),
+                opcode,
+                payload_len,
+                captured_at: state.config.timestamps.now(),
+            }),
+            http2_ping_rtt_ms: None,
+        })
+        .await;
+}
+
+async fn send_http2_ping_rtt_event(
+    state: &Arc<ProxyState>,
+    connection_id: Uuid,
+    rtt: std::time::Duration,
+) {
+    let _ = state
+        .sender
+        .send(ProxyEvent {
+            event_id: Uuid::new_v4(),
+            request_id: connection_id,
+            kind: ProxyEventKind::Http2PingRtt,
+            request: None,
+            response: None,
+            websocket_frame: None,
+            http2_ping_rtt_ms: Some(rtt.as_millis() as u64),
+        })
+        .await;
+}
+
+async fn handle_connect<S>(
+    state: Arc<ProxyState>,
+    client: &mut S,
+    target: String,
+) -> Result<(), ProxyError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (host, port) = split_host_port(&target);
+
+    client
+        .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+        .await
+        .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+
+    if !state.config.tls.enabled {
+        let mut upstream = connect_upstream(&state.config, host.clone(), port).await?;
+        let (mut client_read, mut client_write) = tokio::io::split(client);
         let (mut upstream_read, mut upstream_write) = tokio::io::split(&mut upstream);
         tokio::try_join!(
             tokio::io::copy(&mut client_read, &mut upstream_write),
@@ -3120,22 +3838,7 @@ where
         ProxyProtocolMode::Auto => {}
     }
 
-    let client_preferred = match protocol_mode {
-        ProxyProtocolMode::Http1 => NegotiatedProtocol::Http1,
-        ProxyProtocolMode::Http2 => NegotiatedProtocol::Http2,
-        ProxyProtocolMode::Auto => upstream_protocol,
-    };
-    let client_alpn_list = match protocol_mode {
-        ProxyProtocolMode::Http2 => alpn_list(client_preferred, false),
-        ProxyProtocolMode::Http1 => alpn_list(client_preferred, false),
-        ProxyProtocolMode::Auto => {
-            if upstream_protocol == NegotiatedProtocol::Http2 {
-                alpn_list(client_preferred, true)
-            } else {
-                alpn_list(client_preferred, false)
-            }
-        }
-    };
+    let client_alpn_list = build_client_alpn_list(protocol_mode.clone(), upstream_protocol);
 
     let acceptor = build_acceptor(
         &TlsConfig {
@@ -3204,9 +3907,22 @@ where
         if !buffer.starts_with(HTTP2_PREFACE) {
             return Err(ProxyError::Runtime("missing http2 preface".to_string()));
         }
-        handle_http2_stream(state, tls_client, tls_upstream, buffer, host, port).await?;
+        handle_http2_stream(
+            state,
+            tls_client,
+            tls_upstream,
+            buffer,
+            host,
+            port,
+            Some("h2"),
+        )
+        .await?;
     } else {
-        handle_http1_tls(state, tls_client, buffer, tls_upstream).await?;
+        let alpn = NegotiatedAlpn {
+            client: client_protocol,
+            upstream: upstream_protocol,
+        };
+        handle_http1_tls(state, tls_client, buffer, tls_upstream, alpn).await?;
     }
 
     Ok(())
@@ -3216,11 +3932,23 @@ async fn connect_upstream(
     host: String,
     port: u16,
 ) -> Result<TcpStream, ProxyError> {
+    let connect_timeout = std::time::Duration::from_millis(config.upstream.connect_timeout_ms);
     match config.upstream.mode {
-        UpstreamMode::Direct => TcpStream::connect((host.as_str(), port))
-            .await
-            .map_err(|err| ProxyError::Runtime(err.to_string())),
-        UpstreamMode::Socks => connect_via_socks(config.upstream.socks.as_ref(), host, port).await,
+        UpstreamMode::Direct => connect_tcp(&host, port, connect_timeout).await,
+        UpstreamMode::Socks => {
+            connect_via_socks(config.upstream.socks.as_ref(), host, port, connect_timeout).await
+        }
+    }
+}
+
+async fn connect_tcp(
+    host: &str,
+    port: u16,
+    limit: std::time::Duration,
+) -> Result<TcpStream, ProxyError> {
+    match tokio::time::timeout(limit, TcpStream::connect((host, port))).await {
+        Ok(stream) => stream.map_err(|err| ProxyError::Runtime(err.to_string())),
+        Err(_) => Err(ProxyError::Runtime("upstream connect timeout".to_string())),
     }
 }
 
@@ -3239,6 +3967,20 @@ fn build_upstream_alpn_list(
     }
 }
 
+fn build_client_alpn_list(
+    mode: ProxyProtocolMode,
+    upstream_protocol: NegotiatedProtocol,
+) -> Vec<String> {
+    match mode {
+        ProxyProtocolMode::Http1 => alpn_list(NegotiatedProtocol::Http1, false),
+        ProxyProtocolMode::Http2 => alpn_list(NegotiatedProtocol::Http2, false),
+        ProxyProtocolMode::Auto => alpn_list(
+            upstream_protocol,
+            upstream_protocol == NegotiatedProtocol::Http2,
+        ),
+    }
+}
+
 async fn connect_tls_upstream(
     config: &ProxyConfig,
     host: String,
@@ -3286,14 +4028,24 @@ async fn connect_via_socks(
     socks: Option<&SocksConfig>,
     host: String,
     port: u16,
+    connect_timeout: std::time::Duration,
 ) -> Result<TcpStream, ProxyError> {
     let Some(socks) = socks else {
         return Err(ProxyError::Config("missing socks config".to_string()));
     };
+    let userpass_request = match &socks.auth {
+        SocksAuthConfig::UserPass { username, password }
+            if matches!(socks.version, ProxySocksVersion::V5) =>
+        {
+            Some(
+                crossfeed_net::build_userpass_auth(username, password)
+                    .map_err(|err| ProxyError::Config(format!("socks auth {err:?}")))?,
+            )
+        }
+        _ => None,
+    };
 
-    let mut stream = TcpStream::connect((socks.host.as_str(), socks.port))
-        .await
-        .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+    let mut stream = connect_tcp(&socks.host, socks.port, connect_timeout).await?;
 
     match socks.version {
         ProxySocksVersion::V5 => {
@@ -3317,10 +4069,29 @@ async fn connect_via_socks(
                 .map_err(|err| ProxyError::Runtime(err.to_string()))?;
             let method = crossfeed_net::parse_handshake_response(&response)
                 .map_err(|err| ProxyError::Runtime(format!("socks handshake {err:?}")))?;
-            if method == 0x02 {
-                return Err(ProxyError::Runtime(
-                    "socks auth not implemented".to_string(),
-                ));
+            match (method, &userpass_request) {
+                (0x00, _) => {}
+                (0x02, Some(request)) => {
+                    stream
+                        .write_all(request)
+                        .await
+                        .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+                    let mut reply = [0u8; 2];
+                    stream
+                        .read_exact(&mut reply)
+                        .await
+                        .map_err(|err| ProxyError::Runtime(err.to_string()))?;
+                    let accepted = crossfeed_net::parse_userpass_auth_response(&reply)
+                        .map_err(|err| ProxyError::Runtime(format!("socks auth {err:?}")))?;
+                    if !accepted {
+                        return Err(ProxyError::Runtime("socks auth rejected".to_string()));
+                    }
+                }
+                _ => {
+                    return Err(ProxyError::Runtime(
+                        "socks server selected no acceptable auth method".to_string(),
+                    ));
+                }
             }
 
             let address = SocksAddress::Domain(host);
@@ -3385,8 +4156,15 @@ async fn connect_via_socks(
 }
 
 struct StreamedHttp1Response {
-    bytes: Vec<u8>,
+    capture: CapturedResponse,
     should_close: bool,
+    incomplete: bool,
+    /// Set when the upstream switched to WebSocket; holds any frame bytes
+    /// that arrived in the same read as the `101` head.
+    upgrade: Option<Vec<u8>>,
+    /// Time to first byte, measured from when streaming started; unknown
+    /// when the head was already peeked before the call.
+    ttfb_ms: Option<i64>,
 }
 
 
@@ -3396,6 +4174,7 @@ enum StreamEndReason {
     ChunkedComplete,
     UpstreamEofComplete,
     UpstreamEofIncomplete,
+    UpstreamReset,
     CloseDelimited,
     ParseError,
 }
@@ -3407,31 +4186,35 @@ async fn read_response_streaming<S, C>(
     limits: crossfeed_net::Limits,
     request: &crossfeed_net::Request,
     initial: Option<Vec<u8>>,
+    spill_threshold: Option<usize>,
 ) -> Result<StreamedHttp1Response, ProxyError>
 where
     S: AsyncRead + AsyncWrite + Unpin,
     C: AsyncRead + AsyncWrite + Unpin,
 {
     let mut buffer = vec![0u8; 8192];
-    let mut response = Vec::new();
     let capture_limit = limits.max_header_bytes.saturating_add(limits.max_body_bytes);
+    let mut capture = CaptureBuffer::new(capture_limit, spill_threshold);
     let mut parser = ResponseStreamParser::with_limits(limits);
     let mut frame_info: Option<crossfeed_net::ResponseFrameInfo> = None;
     let mut should_close = false;
     let mut end_reason = StreamEndReason::ParseError;
     let mut upstream_incomplete = false;
+    let mut head = Vec::new();
+    let mut upgrade = None;
+    let sent_at = std::time::Instant::now();
+    let mut first_byte_at = None;
+    // Peeked bytes arrived before `sent_at`, so no honest TTFB exists for them.
+    let mut ttfb_known = true;
 
     if let Some(initial_bytes) = initial {
         if !initial_bytes.is_empty() {
+            ttfb_known = false;
             client
                 .write_all(&initial_bytes)
                 .await
                 .map_err(|err| ProxyError::Runtime(err.to_string()))?;
-            if response.len() < capture_limit {
-                let remaining = capture_limit - response.len();
-                let to_copy = remaining.min(initial_bytes.len());
-                response.extend_from_slice(&initial_bytes[..to_copy]);
-            }
+            capture.extend(&initial_bytes).await?;
             let events = parser
                 .push(&initial_bytes)
                 .map_err(|error| ProxyError::Runtime(format!("response parse error {error:?}")))?;
@@ -3443,8 +4226,11 @@ where
                     }
                 ResponseStreamEvent::EndOfMessage => {
                     return Ok(StreamedHttp1Response {
-                        bytes: response,
+                        capture: capture.finish().await?,
                         should_close,
+                        incomplete: false,
+                        upgrade: None,
+                        ttfb_ms: None,
                         });
                     }
                     ResponseStreamEvent::BodyBytes { .. } => {}
@@ -3454,7 +4240,16 @@ where
     }
 
     loop {
-        let n = upstream.read(&mut buffer).await?;
+        let n = match upstream.read(&mut buffer).await {
+            Ok(n) => n,
+            // Without a head there is no response to forward as truncated.
+            Err(err) if frame_info.is_none() => return Err(err.into()),
+            Err(_) => {
+                upstream_incomplete = true;
+                end_reason = StreamEndReason::UpstreamReset;
+                break;
+            }
+        };
         if n == 0 {
             match parser.push_eof() {
                 Ok(events) => {
@@ -3475,17 +4270,16 @@ where
             }
             break;
         }
+        first_byte_at.get_or_insert_with(std::time::Instant::now);
 
         if let Err(err) = client.write_all(&buffer[..n]).await {
             return Err(ProxyError::Runtime(err.to_string()));
         }
 
-        if response.len() < capture_limit {
-            let remaining = capture_limit - response.len();
-            let to_copy = remaining.min(n);
-            response.extend_from_slice(&buffer[..to_copy]);
+        if frame_info.is_none() {
+            head.extend_from_slice(&buffer[..n]);
         }
-
+        let mut captured = n;
         let events = parser
             .push(&buffer[..n])
             .map_err(|error| ProxyError::Runtime(format!("response parse error {error:?}")))?;
@@ -3493,6 +4287,12 @@ where
             match event {
                 ResponseStreamEvent::Headers(info) => {
                     should_close = should_close_from_frame(request, &info);
+                    if is_websocket_upgrade(request, &info) {
+                        let head_len = find_header_end(&head).map_or(head.len(), |end| end + 4);
+                        let frames = head[head_len..].to_vec();
+                        captured = n - frames.len();
+                        upgrade = Some(frames);
+                    }
                     frame_info = Some(info);
                 }
                 ResponseStreamEvent::EndOfMessage => {
@@ -3511,6 +4311,8 @@ where
             }
         }
 
+        capture.extend(&buffer[..captured]).await?;
+
         if matches!(end_reason, StreamEndReason::ChunkedComplete | StreamEndReason::ContentLength | StreamEndReason::CloseDelimited) {
             break;
         }
@@ -3527,11 +4329,67 @@ where
 
     let _ = end_reason;
     Ok(StreamedHttp1Response {
-        bytes: response,
+        capture: capture.finish().await?,
         should_close,
+        incomplete: upstream_incomplete,
+        upgrade,
+        ttfb_ms: first_byte_at
+            .filter(|_| ttfb_known)
+            .map(|at| at.duration_since(sent_at).as_millis() as i64),
     })
 }
 
+fn streamed_proxy_response(
+    capture: CapturedResponse,
+    received_at: &str,
+    limits: crossfeed_net::Limits,
+    incomplete: bool,
+    ttfb_ms: Option<i64>,
+) -> Option<ProxyResponse> {
+    let mut response = match capture {
+        CapturedResponse::Memory(bytes) => {
+            let timeline_response = if incomplete {
+                parse_incomplete_response(&bytes, received_at)
+            } else {
+                parse_response(&bytes, received_at, limits)
+            };
+            timeline_response.map(|timeline_response| ProxyResponse {
+                id: Uuid::new_v4(),
+                timeline: timeline_response,
+                raw_response: bytes,
+                spill: None,
+            })
+        }
+        CapturedResponse::Spilled(path) => {
+            // Parse the head now so consumers that never load the body still
+            // see the real status line and headers.
+            let head = read_spilled_head(&path, limits.max_header_bytes).ok()?;
+            let header_end = find_header_end(&head)? + 4;
+            let mut timeline_response =
+                parse_incomplete_response(&head[..header_end], received_at)?;
+            timeline_response.response_body_truncated = incomplete;
+            Some(ProxyResponse {
+                id: Uuid::new_v4(),
+                timeline: timeline_response,
+                raw_response: Vec::new(),
+                spill: Some(SpillFile::new(path)),
+            })
+        }
+    }?;
+    response.timeline.ttfb_ms = ttfb_ms;
+    Some(response)
+}
+
+fn read_spilled_head(path: &std::path::Path, max_header_bytes: usize) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut head = Vec::new();
+    std::fs::File::open(path)?
+        .take(max_header_bytes as u64 + 4)
+        .read_to_end(&mut head)?;
+    Ok(head)
+}
+
 async fn read_response_stream<S>(
     stream: &mut S,
     limits: crossfeed_net::Limits,
@@ -3715,6 +4573,14 @@ fn header_has_token(headers: &[crossfeed_net::Header], name: &str, token: &str)
     })
 }
 
+fn is_websocket_upgrade(
+    request: &crossfeed_net::Request,
+    response: &crossfeed_net::ResponseFrameInfo,
+) -> bool {
+    response.status_code == 101
+        && header_has_token(&request.headers, "upgrade", "websocket")
+        && header_has_token(&response.headers, "upgrade", "websocket")
+}
 
 fn has_content_length(headers: &[crossfeed_net::Header]) -> bool {
     headers
@@ -3736,7 +4602,7 @@ fn status_has_no_body(status_code: u16) -> bool {
     status_code / 100 == 1 || status_code == 204 || status_code == 304
 }
 
-fn resolve_target(
+pub(crate) fn resolve_target(
     target: &str,
     headers: &[crossfeed_net::Header],
 ) -> Option<(String, u16, String)> {
@@ -3771,20 +4637,54 @@ fn split_host_port(host: &str) -> (String, u16) {
     (host.to_string(), 443)
 }
 
+fn is_proxy_authorized(auth: &ProxyAuthConfig, headers: &[crossfeed_net::Header]) -> bool {
+    if !auth.enabled {
+        return true;
+    }
+    let expected = format!("{}:{}", auth.username, auth.password);
+    headers
+        .iter()
+        .filter(|header| header.name.eq_ignore_ascii_case("proxy-authorization"))
+        .any(|header| {
+            let Some((scheme, credentials)) = header.value.trim().split_once(' ') else {
+                return false;
+            };
+            scheme.eq_ignore_ascii_case("basic")
+                && crossfeed_codec::base64_decode_str(credentials.trim())
+                    .is_ok_and(|decoded| decoded == expected.as_bytes())
+        })
+}
+
+fn strip_proxy_authorization(header_bytes: &[u8]) -> Vec<u8> {
+    let mut stripped = Vec::with_capacity(header_bytes.len());
+    for line in header_bytes.split_inclusive(|byte| *byte == b'\n') {
+        let is_proxy_authorization =
+            line.iter()
+                .position(|byte| *byte == b':')
+                .is_some_and(|colon| {
+                    line[..colon]
+                        .trim_ascii()
+                        .eq_ignore_ascii_case(b"proxy-authorization")
+                });
+        if !is_proxy_authorization {
+            stripped.extend_from_slice(line);
+        }
+    }
+    stripped
+}
+
 fn serialize_request(request: &crossfeed_net::Request, path: &str, host: &str) -> Vec<u8> {
     let mut bytes = Vec::new();
-    let version = match request.line.version {
-        crossfeed_net::HttpVersion::Http10 => "HTTP/1.0",
-        crossfeed_net::HttpVersion::Http11 => "HTTP/1.1",
-        crossfeed_net::HttpVersion::Other(ref other) => other.as_str(),
-    };
+    let version = request.line.version.as_str();
     bytes.extend_from_slice(format!("{} {} {}\r\n", request.line.method, path, version).as_bytes());
     let mut has_host = false;
     for header in &request.headers {
         if header.name.eq_ignore_ascii_case("host") {
             has_host = true;
         }
-        if header.name.eq_ignore_ascii_case("proxy-connection") {
+        if header.name.eq_ignore_ascii_case("proxy-connection")
+            || header.name.eq_ignore_ascii_case("proxy-authorization")
+        {
             continue;
         }
         bytes.extend_from_slice(header.raw_name.as_bytes());
@@ -3800,8 +4700,9 @@ fn serialize_request(request: &crossfeed_net::Request, path: &str, host: &str) -
     bytes
 }
 
-fn build_request_record(
+pub(crate) fn build_request_record(
     request: &crossfeed_net::Request,
+    scheme: &str,
     path: &str,
     host: &str,
     port: u16,
@@ -3812,17 +4713,13 @@ fn build_request_record(
     let timeline_request = TimelineRequest {
         source: "proxy".to_string(),
         method: request.line.method.clone(),
-        scheme: "http".to_string(),
+        scheme: scheme.to_string(),
         host: host.to_string(),
         port,
         path: path.to_string(),
         query: None,
-        url: format!("http://{}{}", request.line.target, path),
-        http_version: match request.line.version {
-            crossfeed_net::HttpVersion::Http10 => "HTTP/1.0".to_string(),
-            crossfeed_net::HttpVersion::Http11 => "HTTP/1.1".to_string(),
-            crossfeed_net::HttpVersion::Other(ref other) => other.to_string(),
-        },
+        url: format!("{}://{}{}", scheme, request.line.target, path),
+        http_version: request.line.version.to_string(),
         request_headers: request_headers.clone(),
         request_body: request.body.clone(),
         request_body_size: request.body.len(),
@@ -3835,12 +4732,14 @@ fn build_request_record(
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
     };
 
     (timeline_request, request_headers)
 }
 
-fn parse_response(
+pub(crate) fn parse_response(
     response_bytes: &[u8],
     received_at: &str,
     limits: crossfeed_net::Limits,
@@ -3862,15 +4761,79 @@ fn parse_response(
         response_body: body,
         response_body_size: body_size,
         response_body_truncated: false,
-        http_version: match message.line.version {
-            crossfeed_net::HttpVersion::Http10 => "HTTP/1.0".to_string(),
-            crossfeed_net::HttpVersion::Http11 => "HTTP/1.1".to_string(),
-            crossfeed_net::HttpVersion::Other(ref other) => other.to_string(),
-        },
+        http_version: message.line.version.to_string(),
+        received_at: received_at.to_string(),
+        ttfb_ms: None,
+    })
+}
+
+/// Builds a timeline response from the bytes received before the upstream
+/// went away mid-body; whatever body arrived is kept and marked truncated.
+pub(crate) fn parse_incomplete_response(
+    response_bytes: &[u8],
+    received_at: &str,
+) -> Option<TimelineResponse> {
+    let header_end = find_header_end(response_bytes)?;
+    let head = &response_bytes[..header_end];
+    let status_line = head.split(|byte| *byte == b'\n').next()?;
+    let status_line = String::from_utf8_lossy(status_line);
+    let mut parts = status_line.trim_end().splitn(3, ' ');
+    let version = crossfeed_net::HttpVersion::parse(parts.next()?);
+    let status_code = parts.next()?.parse().ok()?;
+    let reason = parts.next().unwrap_or_default().to_string();
+    let body = &response_bytes[header_end + 4..];
+    let chunked = String::from_utf8_lossy(head).lines().skip(1).any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value
+                    .split(',')
+                    .any(|encoding| encoding.trim().eq_ignore_ascii_case("chunked"))
+        })
+    });
+    let body = if chunked {
+        dechunk_truncated_body(body)
+    } else {
+        body.to_vec()
+    };
+
+    Some(TimelineResponse {
+        timeline_request_id: 0,
+        status_code,
+        reason: Some(reason),
+        response_headers: response_bytes.to_vec(),
+        response_body_size: body.len(),
+        response_body: body,
+        response_body_truncated: true,
+        http_version: version.to_string(),
         received_at: received_at.to_string(),
+        ttfb_ms: None,
     })
 }
 
+/// Decodes the chunks of a chunked body that was cut off mid-stream, keeping
+/// the data of a final partial chunk and dropping the chunk framing.
+fn dechunk_truncated_body(mut body: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    while let Some(line_end) = body.windows(2).position(|window| window == b"\r\n") {
+        let size = std::str::from_utf8(&body[..line_end])
+            .ok()
+            .and_then(|line| {
+                usize::from_str_radix(line.split(';').next().unwrap_or_default().trim(), 16).ok()
+            })
+            .unwrap_or(0);
+        if size == 0 {
+            break;
+        }
+        let data = &body[line_end + 2..];
+        decoded.extend_from_slice(&data[..size.min(data.len())]);
+        let Some(rest) = data.get(size + 2..) else {
+            break;
+        };
+        body = rest;
+    }
+    decoded
+}
+
 async fn control_loop(state: Arc<ProxyState>) {
     loop {
         let command = {
@@ -3900,3 +4863,1866 @@ async fn control_loop(state: Arc<ProxyState>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc;
+    use tokio_stream::StreamExt;
+
+    use super::{
+        Direction, HTTP2_PREFACE, Http2RequestMeta, Http2Session, NegotiatedAlpn,
+        NegotiatedProtocol, Proxy, build_client_alpn_list, build_http2_timeline_request,
+        build_http2_timeline_response, build_upstream_alpn_list, connect_upstream,
+        h2_headers_to_http1,
+        handle_http1_tls, handle_http2_frame, handle_http2_stream, http1_request_to_h2,
+        parse_http1_request, parse_http2_request_meta, parse_http2_status,
+        parse_incomplete_response, read_response_streaming,
+        serialize_request, synthesize_http2_request_bytes, synthesize_http2_response_bytes,
+    };
+    use crate::config::{
+        ProxyConfig, ProxyProtocolMode, ScopePatternType, ScopeRule, ScopeRuleType, ScopeTarget,
+        SocksAuthConfig, SocksConfig, SocksVersion, UpstreamMode,
+    };
+    use crate::error::ProxyError;
+    use crate::events::ProxyEvents;
+    use crate::timeline_event::ProxyEventKind;
+    use crossfeed_storage::{WEBSOCKET_CLIENT_TO_SERVER, WEBSOCKET_SERVER_TO_CLIENT};
+
+    const REQUEST_HEAD: &str = "X-Custom-Header: Value\r\nACCEPT: */*\r\nConnection: close\r\n\r\n";
+
+    async fn start_proxy(
+        configure: impl FnOnce(&mut ProxyConfig),
+    ) -> (u16, ProxyEvents, tempfile::TempDir) {
+        let certs = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.listen.port = 0;
+        config.tls.ca_cert_dir = certs.path().display().to_string();
+        config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+        configure(&mut config);
+        let (proxy, events, _control) = Proxy::new(config).unwrap();
+        let proxy_port = proxy.local_addr().port();
+        tokio::spawn(async move {
+            let _ = proxy.run().await;
+        });
+        (proxy_port, events, certs)
+    }
+
+    #[tokio::test]
+    async fn proxy_reports_ephemeral_listen_port() {
+        let (proxy_port, _events, _certs) = start_proxy(|_| {}).await;
+        assert_ne!(proxy_port, 0);
+        TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+
+        let certs = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.listen.port = proxy_port;
+        config.tls.ca_cert_dir = certs.path().display().to_string();
+        config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+        assert!(Proxy::new(config).is_err());
+    }
+
+    #[test]
+    fn serialize_request_preserves_header_casing() {
+        let raw = format!("GET http://example.com/ HTTP/1.1\r\nHost: example.com\r\n{REQUEST_HEAD}");
+        let request = parse_http1_request(raw.as_bytes()).unwrap();
+        let bytes = serialize_request(&request, "/", "example.com");
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.contains("\r\nX-Custom-Header: Value\r\n"));
+        assert!(text.contains("\r\nACCEPT: */*\r\n"));
+    }
+
+    #[tokio::test]
+    async fn http1_forwarding_preserves_header_casing() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        let received = tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let (proxy_port, _events, _certs) = start_proxy(|_| {}).await;
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        let request = format!(
+            "GET http://{upstream_addr}/ HTTP/1.1\r\nHost: {upstream_addr}\r\n{REQUEST_HEAD}"
+        );
+        client.write_all(request.as_bytes()).await.unwrap();
+
+        let forwarded = tokio::time::timeout(Duration::from_secs(10), received)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(forwarded.contains("\r\nX-Custom-Header: Value\r\n"));
+        assert!(forwarded.contains("\r\nACCEPT: */*\r\n"));
+    }
+
+    async fn send_with_proxy_auth(credentials: Option<&str>) -> (String, Option<String>) {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        let received = tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let (proxy_port, _events, _certs) = start_proxy(|config| {
+            config.auth.enabled = true;
+            config.auth.username = "user".to_string();
+            config.auth.password = "secret".to_string();
+        })
+        .await;
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        let auth_header = credentials
+            .map(|value| {
+                format!(
+                    "Proxy-Authorization: Basic {}\r\n",
+                    crossfeed_codec::base64_encode_str(value)
+                )
+            })
+            .unwrap_or_default();
+        let request = format!(
+            "GET http://{upstream_addr}/ HTTP/1.1\r\nHost: {upstream_addr}\r\n{auth_header}{REQUEST_HEAD}"
+        );
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut response = Vec::new();
+        tokio::time::timeout(Duration::from_secs(10), client.read_to_end(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let forwarded = tokio::time::timeout(Duration::from_millis(200), received)
+            .await
+            .ok()
+            .map(|result| result.unwrap());
+        (String::from_utf8(response).unwrap(), forwarded)
+    }
+
+    #[tokio::test]
+    async fn proxy_auth_rejects_missing_credentials() {
+        let (response, forwarded) = send_with_proxy_auth(None).await;
+        assert!(response.starts_with("HTTP/1.1 407 Proxy Authentication Required\r\n"));
+        assert!(response.contains("Proxy-Authenticate: Basic"));
+        assert!(forwarded.is_none());
+    }
+
+    #[tokio::test]
+    async fn proxy_auth_rejects_wrong_credentials() {
+        let (response, forwarded) = send_with_proxy_auth(Some("user:wrong")).await;
+        assert!(response.starts_with("HTTP/1.1 407 "));
+        assert!(forwarded.is_none());
+    }
+
+    #[tokio::test]
+    async fn proxy_auth_forwards_valid_credentials_without_header() {
+        let (response, forwarded) = send_with_proxy_auth(Some("user:secret")).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let forwarded = forwarded.unwrap();
+        assert!(forwarded.contains("\r\nX-Custom-Header: Value\r\n"));
+        assert!(
+            !forwarded
+                .to_ascii_lowercase()
+                .contains("proxy-authorization")
+        );
+    }
+
+    async fn start_keep_alive_upstream() -> std::net::SocketAddr {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = upstream.accept().await {
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                        match stream.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let _ = stream
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK")
+                        .await;
+                });
+            }
+        });
+        upstream_addr
+    }
+
+    async fn keep_alive_exchange(client: &mut TcpStream, upstream_addr: std::net::SocketAddr) {
+        let request =
+            format!("GET http://{upstream_addr}/ HTTP/1.1\r\nHost: {upstream_addr}\r\n\r\n");
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut response = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !response.ends_with(b"\r\n\r\nOK") {
+            let n = tokio::time::timeout(Duration::from_secs(5), client.read(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(n > 0, "connection closed mid-exchange");
+            response.extend_from_slice(&buf[..n]);
+        }
+        assert!(response.starts_with(b"HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[tokio::test]
+    async fn idle_keep_alive_client_is_closed_after_timeout() {
+        let upstream_addr = start_keep_alive_upstream().await;
+        let (proxy_port, _events, _certs) = start_proxy(|config| {
+            config.client_idle_timeout_secs = 1;
+        })
+        .await;
+
+        let mut idle = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        keep_alive_exchange(&mut idle, upstream_addr).await;
+        let idle_since = std::time::Instant::now();
+
+        let mut active = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        for _ in 0..4 {
+            keep_alive_exchange(&mut active, upstream_addr).await;
+            tokio::time::sleep(Duration::from_millis(400)).await;
+        }
+        keep_alive_exchange(&mut active, upstream_addr).await;
+
+        let mut buf = [0u8; 16];
+        let n = tokio::time::timeout(Duration::from_secs(5), idle.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(n, 0);
+        assert!(idle_since.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn large_response_capture_spills_to_disk() {
+        let body: Vec<u8> = (0..256 * 1024).map(|index| (index % 251) as u8).collect();
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        let upstream_body = body.clone();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                upstream_body.len()
+            );
+            stream.write_all(head.as_bytes()).await.unwrap();
+            stream.write_all(&upstream_body).await.unwrap();
+        });
+
+        let (proxy_port, mut events, _certs) = start_proxy(|config| {
+            config.capture_spill_threshold = Some(16 * 1024);
+        })
+        .await;
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        let request = format!(
+            "GET http://{upstream_addr}/large HTTP/1.1\r\nHost: {upstream_addr}\r\nConnection: close\r\n\r\n"
+        );
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut received = Vec::new();
+        tokio::time::timeout(Duration::from_secs(10), client.read_to_end(&mut received))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(received.ends_with(&body));
+
+        let mut response = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let event = events.next().await.unwrap();
+                if event.kind == ProxyEventKind::ResponseForwarded {
+                    break event.response.unwrap();
+                }
+            }
+        })
+        .await
+        .unwrap();
+        let spill_path = response.spill.as_ref().unwrap().path().to_path_buf();
+        assert!(response.raw_response.is_empty());
+        assert!(spill_path.exists());
+        assert_eq!(response.timeline.status_code, 200);
+        assert!(response.timeline.response_body.is_empty());
+
+        let mut other = response.clone();
+        response.load_spilled().unwrap();
+        assert_eq!(response.timeline.status_code, 200);
+        assert_eq!(response.timeline.response_body, body);
+        assert!(response.raw_response.ends_with(&body));
+        assert!(spill_path.exists());
+
+        other.load_spilled().unwrap();
+        assert_eq!(other.timeline.response_body, body);
+        drop(other);
+        assert!(!spill_path.exists());
+    }
+
+    #[tokio::test]
+    async fn streamed_response_records_ttfb_separately_from_body() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            stream.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(400)).await;
+            stream.write_all(b"slow").await.unwrap();
+        });
+
+        let (proxy_port, mut events, _certs) = start_proxy(|_| {}).await;
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        let request = format!(
+            "GET http://{upstream_addr}/slow HTTP/1.1\r\nHost: {upstream_addr}\r\nConnection: close\r\n\r\n"
+        );
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut received = Vec::new();
+        tokio::time::timeout(Duration::from_secs(10), client.read_to_end(&mut received))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(received.ends_with(b"\r\n\r\nslow"));
+
+        let (mut request, response) = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let event = events.next().await.unwrap();
+                if event.kind == ProxyEventKind::ResponseForwarded {
+                    break (event.request.unwrap(), event.response.unwrap());
+                }
+            }
+        })
+        .await
+        .unwrap();
+        let ttfb_ms = response.timeline.ttfb_ms.unwrap();
+        assert!((200..600).contains(&ttfb_ms), "ttfb was {ttfb_ms}ms");
+
+        request
+            .timeline
+            .record_completion(&response.timeline.received_at);
+        let duration_ms = request.timeline.duration_ms.unwrap();
+        assert!(duration_ms >= 600, "duration was {duration_ms}ms");
+    }
+
+    #[tokio::test]
+    async fn peeked_response_head_reports_no_ttfb() {
+        let request =
+            parse_http1_request(b"GET /peek HTTP/1.1\r\nHost: example.com\r\n\r\n").unwrap();
+        let (mut client, mut client_peer) = tokio::io::duplex(1 << 16);
+        let (mut upstream, mut upstream_peer) = tokio::io::duplex(1 << 16);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            upstream_peer.write_all(b"body").await.unwrap();
+        });
+        tokio::spawn(async move {
+            let mut sink = Vec::new();
+            let _ = client_peer.read_to_end(&mut sink).await;
+        });
+
+        let streamed = read_response_streaming(
+            &mut upstream,
+            &mut client,
+            crossfeed_net::Limits::default(),
+            &request,
+            Some(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\n".to_vec()),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(!streamed.incomplete);
+        assert_eq!(streamed.ttfb_ms, None);
+    }
+
+    #[tokio::test]
+    async fn websocket_upgrade_relays_and_reports_frames() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        let server_frame = crossfeed_net::encode_ws_frame(&crossfeed_net::WsFrame::text("hello"), None);
+        let client_frame = crossfeed_net::encode_ws_frame(
+            &crossfeed_net::WsFrame::text("hi upstream"),
+            Some([1, 2, 3, 4]),
+        );
+        let upstream_frame = server_frame.clone();
+        let expected_client_frame = client_frame.clone();
+        let upstream_task = tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            let mut reply = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n".to_vec();
+            reply.extend_from_slice(&upstream_frame);
+            stream.write_all(&reply).await.unwrap();
+            let mut received = vec![0u8; expected_client_frame.len()];
+            stream.read_exact(&mut received).await.unwrap();
+            assert_eq!(received, expected_client_frame);
+        });
+
+        let (proxy_port, mut events, _certs) = start_proxy(|_| {}).await;
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        let request = format!(
+            "GET http://{upstream_addr}/ws HTTP/1.1\r\nHost: {upstream_addr}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n"
+        );
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut received = Vec::new();
+        tokio::time::timeout(Duration::from_secs(10), async {
+            let mut buf = [0u8; 4096];
+            while !received.ends_with(&server_frame) {
+                let n = client.read(&mut buf).await.unwrap();
+                assert!(n > 0, "proxy closed before relaying the upgrade");
+                received.extend_from_slice(&buf[..n]);
+            }
+        })
+        .await
+        .unwrap();
+        assert!(received.starts_with(b"HTTP/1.1 101 Switching Protocols\r\n"));
+        client.write_all(&client_frame).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(10), upstream_task)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let (established, frames) = tokio::time::timeout(Duration::from_secs(10), async {
+            let mut established = None;
+            let mut frames = Vec::new();
+            while frames.len() < 2 {
+                let event = events.next().await.unwrap();
+                match event.kind {
+                    ProxyEventKind::WebSocketEstablished => established = Some(event),
+                    ProxyEventKind::WebSocketFrame => frames.push(event.websocket_frame.unwrap()),
+                    _ => {}
+                }
+            }
+            (established.unwrap(), frames)
+        })
+        .await
+        .unwrap();
+        assert_eq!(established.response.unwrap().timeline.status_code, 101);
+        let connection_id = established.request_id.to_string();
+        assert_eq!(frames[0].connection_id, connection_id);
+        assert_eq!(frames[0].direction, WEBSOCKET_SERVER_TO_CLIENT);
+        assert_eq!(frames[0].opcode, 1);
+        assert_eq!(frames[0].payload_len, 5);
+        assert_eq!(frames[1].connection_id, connection_id);
+        assert_eq!(frames[1].direction, WEBSOCKET_CLIENT_TO_SERVER);
+        assert_eq!(frames[1].opcode, 1);
+        assert_eq!(frames[1].payload_len, 11);
+    }
+
+    #[tokio::test]
+    async fn upstream_connect_times_out_on_unresponsive_host() {
+        // A listener whose accept queue is already full silently drops SYNs,
+        // which behaves like an unroutable host without leaving loopback.
+        let socket = tokio::net::TcpSocket::new_v4().unwrap();
+        socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let listener = socket.listen(0).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let _queued = TcpStream::connect(addr).await.unwrap();
+
+        let mut config = ProxyConfig::default();
+        config.upstream.connect_timeout_ms = 100;
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            connect_upstream(&config, addr.ip().to_string(), addr.port()),
+        )
+        .await
+        .unwrap();
+        let Err(ProxyError::Runtime(message)) = result else {
+            panic!("expected upstream connect timeout");
+        };
+        assert_eq!(message, "upstream connect timeout");
+    }
+
+    async fn spawn_socks5_userpass_server(username: &'static str, password: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut greeting = [0u8; 4];
+            stream.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [0x05, 0x02, 0x00, 0x02]);
+            stream.write_all(&[0x05, 0x02]).await.unwrap();
+
+            let mut header = [0u8; 2];
+            stream.read_exact(&mut header).await.unwrap();
+            let mut user = vec![0u8; header[1] as usize];
+            stream.read_exact(&mut user).await.unwrap();
+            let mut plen = [0u8; 1];
+            stream.read_exact(&mut plen).await.unwrap();
+            let mut pass = vec![0u8; plen[0] as usize];
+            stream.read_exact(&mut pass).await.unwrap();
+            if user != username.as_bytes() || pass != password.as_bytes() {
+                stream.write_all(&[0x01, 0x01]).await.unwrap();
+                return;
+            }
+            stream.write_all(&[0x01, 0x00]).await.unwrap();
+
+            let mut connect = [0u8; 5];
+            stream.read_exact(&mut connect).await.unwrap();
+            let mut rest = vec![0u8; connect[4] as usize + 2];
+            stream.read_exact(&mut rest).await.unwrap();
+            stream
+                .write_all(&[0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0x1f, 0x90])
+                .await
+                .unwrap();
+            let mut ping = [0u8; 4];
+            stream.read_exact(&mut ping).await.unwrap();
+            stream.write_all(&ping).await.unwrap();
+        });
+        port
+    }
+
+    fn socks5_userpass_config(port: u16, password: &str) -> ProxyConfig {
+        let mut config = ProxyConfig::default();
+        config.upstream.mode = UpstreamMode::Socks;
+        config.upstream.socks = Some(SocksConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            version: SocksVersion::V5,
+            auth: SocksAuthConfig::UserPass {
+                username: "alice".to_string(),
+                password: password.to_string(),
+            },
+        });
+        config
+    }
+
+    #[tokio::test]
+    async fn socks5_upstream_authenticates_with_username_password() {
+        let port = spawn_socks5_userpass_server("alice", "secret").await;
+        let config = socks5_userpass_config(port, "secret");
+
+        let mut stream = connect_upstream(&config, "example.com".to_string(), 80)
+            .await
+            .unwrap();
+        stream.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    }
+
+    #[tokio::test]
+    async fn socks5_upstream_reports_rejected_credentials() {
+        let port = spawn_socks5_userpass_server("alice", "secret").await;
+        let config = socks5_userpass_config(port, "wrong");
+
+        let result = connect_upstream(&config, "example.com".to_string(), 80).await;
+        let Err(ProxyError::Runtime(message)) = result else {
+            panic!("expected socks auth failure");
+        };
+        assert_eq!(message, "socks auth rejected");
+    }
+
+    #[tokio::test]
+    async fn socks5_upstream_rejects_empty_password_before_connecting() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = socks5_userpass_config(listener.local_addr().unwrap().port(), "");
+
+        let result = connect_upstream(&config, "example.com".to_string(), 80).await;
+        assert!(matches!(result, Err(ProxyError::Config(_))));
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), listener.accept())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn upstream_reset_before_headers_is_an_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            stream.set_zero_linger().unwrap();
+        });
+        let request =
+            parse_http1_request(b"GET /reset HTTP/1.1\r\nHost: example.com\r\n\r\n").unwrap();
+        let mut upstream = TcpStream::connect(addr).await.unwrap();
+        let (mut client, _client_peer) = tokio::io::duplex(1 << 16);
+
+        let result = read_response_streaming(
+            &mut upstream,
+            &mut client,
+            crossfeed_net::Limits::default(),
+            &request,
+            None,
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn truncated_chunked_response_body_is_dechunked() {
+        let response = parse_incomplete_response(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6;ext=1\r\n wor",
+            "now",
+        )
+        .unwrap();
+        assert_eq!(response.response_body, b"hello wor");
+        assert_eq!(response.response_body_size, 9);
+        assert!(response.response_body_truncated);
+    }
+
+    #[tokio::test]
+    async fn upstream_reset_mid_body_forwards_truncated_response() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\npartial body")
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            stream.set_zero_linger().unwrap();
+        });
+
+        let (proxy_port, mut events, _certs) = start_proxy(|_| {}).await;
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        let request =
+            format!("GET http://{upstream_addr}/ HTTP/1.1\r\nHost: {upstream_addr}\r\n\r\n");
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut received = Vec::new();
+        tokio::time::timeout(Duration::from_secs(10), client.read_to_end(&mut received))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(received.ends_with(b"\r\n\r\npartial body"));
+
+        let response = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let event = events.next().await.unwrap();
+                if event.kind == ProxyEventKind::ResponseForwarded {
+                    break event.response.unwrap();
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(response.timeline.status_code, 200);
+        assert_eq!(response.timeline.reason.as_deref(), Some("OK"));
+        assert_eq!(response.timeline.response_body, b"partial body");
+        assert_eq!(response.timeline.response_body_size, 12);
+        assert!(response.timeline.response_body_truncated);
+    }
+
+    #[test]
+    fn http1_mode_never_offers_h2() {
+        for cached in [
+            None,
+            Some(NegotiatedProtocol::Http1),
+            Some(NegotiatedProtocol::Http2),
+        ] {
+            assert_eq!(
+                build_upstream_alpn_list(ProxyProtocolMode::Http1, cached),
+                vec!["http/1.1".to_string()]
+            );
+        }
+        for upstream in [NegotiatedProtocol::Http1, NegotiatedProtocol::Http2] {
+            assert_eq!(
+                build_client_alpn_list(ProxyProtocolMode::Http1, upstream),
+                vec!["http/1.1".to_string()]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn http1_mode_offers_only_http11_upstream() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        let client_hello = tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            buf.truncate(n);
+            buf
+        });
+
+        let (proxy_port, _events, _certs) = start_proxy(|config| {
+            config.tls.enabled = true;
+            config.protocol_mode = ProxyProtocolMode::Http1;
+        })
+        .await;
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        let request = format!("CONNECT {upstream_addr} HTTP/1.1\r\nHost: {upstream_addr}\r\n\r\n");
+        client.write_all(request.as_bytes()).await.unwrap();
+
+        let client_hello = tokio::time::timeout(Duration::from_secs(10), client_hello)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(contains(&client_hello, b"\x08http/1.1"));
+        assert!(!contains(&client_hello, b"\x02h2"));
+    }
+
+    #[tokio::test]
+    async fn http1_mode_client_handshake_selects_http11() {
+        let certs = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.listen.port = 0;
+        config.tls.ca_cert_dir = certs.path().display().to_string();
+        config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+        let (proxy, _events, _control) = Proxy::new(config).unwrap();
+        let leaf = crossfeed_net::generate_leaf_cert("example.com", &proxy.state.ca).unwrap();
+        let acceptor = crossfeed_net::build_acceptor(
+            &crossfeed_net::TlsConfig {
+                allow_legacy: false,
+                alpn_protocols: build_client_alpn_list(
+                    ProxyProtocolMode::Http1,
+                    NegotiatedProtocol::Http2,
+                ),
+            },
+            &leaf,
+        )
+        .unwrap();
+        let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(async move {
+            let ssl = openssl::ssl::Ssl::new(acceptor.context()).unwrap();
+            let mut stream = tokio_openssl::SslStream::new(ssl, server_io).unwrap();
+            std::pin::Pin::new(&mut stream).accept().await.unwrap();
+            stream
+        });
+
+        let mut connector =
+            openssl::ssl::SslConnector::builder(openssl::ssl::SslMethod::tls()).unwrap();
+        connector.set_verify(openssl::ssl::SslVerifyMode::NONE);
+        connector.set_alpn_protos(b"\x02h2\x08http/1.1").unwrap();
+        let ssl = connector
+            .build()
+            .configure()
+            .unwrap()
+            .into_ssl("example.com")
+            .unwrap();
+        let mut stream = tokio_openssl::SslStream::new(ssl, client_io).unwrap();
+        std::pin::Pin::new(&mut stream).connect().await.unwrap();
+        let _server = server.await.unwrap();
+
+        assert_eq!(
+            stream.ssl().selected_alpn_protocol(),
+            Some(&b"http/1.1"[..])
+        );
+    }
+
+    fn field(name: &str, value: &str) -> crossfeed_net::HeaderField {
+        crossfeed_net::HeaderField {
+            name: name.as_bytes().to_vec(),
+            value: value.as_bytes().to_vec(),
+        }
+    }
+
+    fn http2_meta() -> Http2RequestMeta {
+        Http2RequestMeta {
+            method: "POST".to_string(),
+            scheme: "https".to_string(),
+            authority: "example.com:8443".to_string(),
+            host: "example.com".to_string(),
+            port: 8443,
+            path: "/submit?x=1".to_string(),
+        }
+    }
+
+    fn http2_headers() -> Vec<crossfeed_net::HeaderField> {
+        vec![
+            field(":method", "POST"),
+            field(":scheme", "https"),
+            field(":authority", "example.com:8443"),
+            field(":path", "/submit?x=1"),
+            field("content-type", "application/json"),
+            field("cookie", "a=1"),
+            field("cookie", "b=2"),
+        ]
+    }
+
+    fn headers_frame(
+        stream_id: u32,
+        headers: Vec<crossfeed_net::HeaderField>,
+        end_stream: bool,
+    ) -> crossfeed_net::Frame {
+        crossfeed_net::Frame {
+            header: crossfeed_net::FrameHeader {
+                length: 0,
+                frame_type: crossfeed_net::FrameType::Headers,
+                flags: 0x4 | if end_stream { 0x1 } else { 0 },
+                stream_id,
+            },
+            payload: crossfeed_net::FramePayload::Headers(crossfeed_net::HeadersFrame {
+                end_stream,
+                end_headers: true,
+                header_block: Vec::new(),
+                headers,
+            }),
+        }
+    }
+
+    fn data_frame(stream_id: u32, payload: &[u8]) -> crossfeed_net::Frame {
+        crossfeed_net::Frame {
+            header: crossfeed_net::FrameHeader {
+                length: payload.len(),
+                frame_type: crossfeed_net::FrameType::Data,
+                flags: 0x1,
+                stream_id,
+            },
+            payload: crossfeed_net::FramePayload::Data(crossfeed_net::DataFrame {
+                end_stream: true,
+                payload: payload.to_vec(),
+            }),
+        }
+    }
+
+    async fn run_http2_exchange(
+        request_body: &[u8],
+        response_body: &[u8],
+        configure: impl FnOnce(&mut ProxyConfig),
+    ) -> (Vec<u8>, Vec<u8>, super::ProxyRequest, super::ProxyResponse) {
+        let frames = vec![
+            (
+                Direction::ClientToUpstream,
+                headers_frame(1, http2_headers(), false),
+            ),
+            (Direction::ClientToUpstream, data_frame(1, request_body)),
+            (
+                Direction::UpstreamToClient,
+                headers_frame(1, vec![field(":status", "200")], false),
+            ),
+            (Direction::UpstreamToClient, data_frame(1, response_body)),
+        ];
+        run_http2_frames(frames, configure).await
+    }
+
+    async fn run_http2_frames(
+        frames: Vec<(Direction, crossfeed_net::Frame)>,
+        configure: impl FnOnce(&mut ProxyConfig),
+    ) -> (Vec<u8>, Vec<u8>, super::ProxyRequest, super::ProxyResponse) {
+        let certs = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.listen.port = 0;
+        config.tls.ca_cert_dir = certs.path().display().to_string();
+        config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+        configure(&mut config);
+        let (proxy, mut events, _control) = Proxy::new(config).unwrap();
+        let mut client_session = Http2Session::new();
+        let mut upstream_session = Http2Session::new();
+        client_session.alpn = Some("h2");
+        upstream_session.alpn = Some("h2");
+        let mut client_bytes = Vec::new();
+        let mut upstream_bytes = Vec::new();
+        let mut streams = HashMap::new();
+        let (decision_tx, _decision_rx) = mpsc::channel(4);
+
+        for (direction, frame) in frames {
+            let result = match direction {
+                Direction::ClientToUpstream => {
+                    handle_http2_frame(
+                        &proxy.state,
+                        direction,
+                        &mut client_session,
+                        &mut upstream_session,
+                        &mut client_bytes,
+                        &mut upstream_bytes,
+                        &mut streams,
+                        &decision_tx,
+                        frame,
+                        "example.com",
+                        8443,
+                    )
+                    .await
+                }
+                Direction::UpstreamToClient => {
+                    handle_http2_frame(
+                        &proxy.state,
+                        direction,
+                        &mut upstream_session,
+                        &mut client_session,
+                        &mut upstream_bytes,
+                        &mut client_bytes,
+                        &mut streams,
+                        &decision_tx,
+                        frame,
+                        "example.com",
+                        8443,
+                    )
+                    .await
+                }
+            };
+            result.unwrap();
+        }
+
+        let (request, response) = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let event = events.next().await.unwrap();
+                if event.kind == ProxyEventKind::ResponseForwarded {
+                    break (event.request.unwrap(), event.response.unwrap());
+                }
+            }
+        })
+        .await
+        .unwrap();
+        (upstream_bytes, client_bytes, request, response)
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[tokio::test]
+    async fn http2_settings_forward_unknown_ids_verbatim() {
+        let certs = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.listen.port = 0;
+        config.tls.ca_cert_dir = certs.path().display().to_string();
+        config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+        let (proxy, _events, _control) = Proxy::new(config).unwrap();
+        let mut client_session = Http2Session::new();
+        let mut upstream_session = Http2Session::new();
+        let mut client_bytes = Vec::new();
+        let mut upstream_bytes = Vec::new();
+        let (decision_tx, _decision_rx) = mpsc::channel(4);
+        let settings = vec![(0x4, 1_048_576), (0x8, 1), (0x4a4a, 7)];
+        let frame = crossfeed_net::Frame {
+            header: crossfeed_net::FrameHeader {
+                length: settings.len() * 6,
+                frame_type: crossfeed_net::FrameType::Settings,
+                flags: 0,
+                stream_id: 0,
+            },
+            payload: crossfeed_net::FramePayload::Settings(crossfeed_net::SettingsFrame {
+                settings,
+                ack: false,
+            }),
+        };
+
+        handle_http2_frame(
+            &proxy.state,
+            Direction::ClientToUpstream,
+            &mut client_session,
+            &mut upstream_session,
+            &mut client_bytes,
+            &mut upstream_bytes,
+            &mut HashMap::new(),
+            &decision_tx,
+            frame,
+            "example.com",
+            443,
+        )
+        .await
+        .unwrap();
+
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&[0x00, 0x08, 0x00, 0x00, 0x00, 0x01]);
+        payload.extend_from_slice(&[0x4a, 0x4a, 0x00, 0x00, 0x00, 0x07]);
+        let forwarded =
+            crossfeed_net::encode_raw_frame(crossfeed_net::FrameType::Settings, 0, 0, &payload);
+        assert_eq!(upstream_bytes, forwarded);
+        assert_eq!(
+            client_bytes,
+            crossfeed_net::encode_raw_frame(crossfeed_net::FrameType::Settings, 0x1, 0, &[])
+        );
+        assert_eq!(client_session.peer_settings.initial_window_size, 1_048_576);
+    }
+
+    fn settings_frame(settings: Vec<(u16, u32)>) -> crossfeed_net::Frame {
+        crossfeed_net::Frame {
+            header: crossfeed_net::FrameHeader {
+                length: settings.len() * 6,
+                frame_type: crossfeed_net::FrameType::Settings,
+                flags: 0,
+                stream_id: 0,
+            },
+            payload: crossfeed_net::FramePayload::Settings(crossfeed_net::SettingsFrame {
+                settings,
+                ack: false,
+            }),
+        }
+    }
+
+    #[test]
+    fn peer_header_table_size_resizes_encoder() {
+        let mut session = Http2Session::new();
+        session.hpack_encoder.encode(&[field("x-trace-id", "abc")]);
+        assert!(session.hpack_encoder.dynamic_table_size() > 0);
+
+        session.apply_peer_settings(&crossfeed_net::SettingsFrame {
+            settings: vec![(0x1, 0)],
+            ack: false,
+        });
+
+        assert_eq!(session.peer_settings.header_table_size, 0);
+        assert_eq!(session.hpack_encoder.dynamic_table_size(), 0);
+        let block = session.hpack_encoder.encode(&[field("x-trace-id", "abc")]);
+        assert_eq!(block[0], 0x20);
+        let mut decoder = crossfeed_net::HpackDecoder::new();
+        assert_eq!(
+            decoder.decode(&block).unwrap(),
+            vec![field("x-trace-id", "abc")]
+        );
+    }
+
+    #[tokio::test]
+    async fn http2_connection_settings_are_recorded_after_handshake() {
+        let frames = vec![
+            (
+                Direction::ClientToUpstream,
+                settings_frame(vec![(0x4, 1 << 20), (0x5, 32_768)]),
+            ),
+            (
+                Direction::UpstreamToClient,
+                settings_frame(vec![(0x4, 131_072), (0x5, 65_536)]),
+            ),
+            (
+                Direction::ClientToUpstream,
+                headers_frame(1, http2_headers(), false),
+            ),
+            (Direction::ClientToUpstream, data_frame(1, b"{}")),
+            (
+                Direction::UpstreamToClient,
+                headers_frame(1, vec![field(":status", "200")], false),
+            ),
+            (Direction::UpstreamToClient, data_frame(1, b"ok")),
+        ];
+        let (_, _, request, _) = run_http2_frames(frames, |_| {}).await;
+
+        let connection = request.http2_connection.unwrap();
+        assert_eq!(connection.client_settings.initial_window_size, 1 << 20);
+        assert_eq!(connection.client_settings.max_frame_size, 32_768);
+        assert_eq!(connection.upstream_settings.initial_window_size, 131_072);
+        assert_eq!(connection.upstream_settings.max_frame_size, 65_536);
+        assert_eq!(connection.client_send_window, 65_535 - 2);
+        assert_eq!(connection.upstream_send_window, 65_535 - 2);
+    }
+
+    #[tokio::test]
+    async fn http2_request_body_capture_truncates_but_forwards_whole() {
+        let body: Vec<u8> = (0..64u8).collect();
+        let (upstream_bytes, _, request, _) = run_http2_exchange(&body, b"ok", |config| {
+            config.body_limits.request_max_bytes = 16;
+        })
+        .await;
+
+        assert!(contains(&upstream_bytes, &body));
+        assert_eq!(request.timeline.request_body, body[..16].to_vec());
+        assert_eq!(request.timeline.request_body_size, 64);
+        assert!(request.timeline.request_body_truncated);
+    }
+
+    #[tokio::test]
+    async fn http2_response_body_capture_truncates_but_forwards_whole() {
+        let body: Vec<u8> = (0..64u8).rev().collect();
+        let (_, client_bytes, request, response) = run_http2_exchange(b"{}", &body, |config| {
+            config.body_limits.response_max_bytes = 16;
+        })
+        .await;
+
+        assert!(contains(&client_bytes, &body));
+        assert_eq!(response.timeline.response_body, body[..16].to_vec());
+        assert_eq!(response.timeline.response_body_size, 64);
+        assert!(response.timeline.response_body_truncated);
+        assert!(!request.timeline.request_body_truncated);
+    }
+
+    #[tokio::test]
+    async fn http2_capture_records_h2_alpn_on_both_legs() {
+        let (_, _, request, _) = run_http2_exchange(b"{}", b"ok", |_| {}).await;
+
+        assert_eq!(request.timeline.client_alpn.as_deref(), Some("h2"));
+        assert_eq!(request.timeline.upstream_alpn.as_deref(), Some("h2"));
+    }
+
+    #[tokio::test]
+    async fn tls_http1_capture_records_http11_alpn() {
+        let certs = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.listen.port = 0;
+        config.tls.ca_cert_dir = certs.path().display().to_string();
+        config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+        let (proxy, mut events, _control) = Proxy::new(config).unwrap();
+        let (client, mut client_peer) = tokio::io::duplex(64 * 1024);
+        let (upstream, mut upstream_peer) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            let mut buf = [0u8; 4096];
+            let _ = upstream_peer.read(&mut buf).await.unwrap();
+            upstream_peer
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK")
+                .await
+                .unwrap();
+        });
+
+        let request = format!("GET /alpn HTTP/1.1\r\nHost: example.com\r\n{REQUEST_HEAD}");
+        let alpn = NegotiatedAlpn {
+            client: NegotiatedProtocol::Http1,
+            upstream: NegotiatedProtocol::Http1,
+        };
+        let proxied = tokio::spawn(handle_http1_tls(
+            proxy.state.clone(),
+            client,
+            request.into_bytes(),
+            upstream,
+            alpn,
+        ));
+        let mut response = Vec::new();
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            client_peer.read_to_end(&mut response),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(response.starts_with(b"HTTP/1.1 200 OK\r\n"));
+        proxied.abort();
+
+        let request = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let event = events.next().await.unwrap();
+                if event.kind == ProxyEventKind::ResponseForwarded {
+                    break event.request.unwrap();
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(request.timeline.client_alpn.as_deref(), Some("http/1.1"));
+        assert_eq!(request.timeline.upstream_alpn.as_deref(), Some("http/1.1"));
+    }
+
+    #[tokio::test]
+    async fn tls_http1_capture_uses_https_scheme_for_scope() {
+        let certs = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.listen.port = 0;
+        config.tls.ca_cert_dir = certs.path().display().to_string();
+        config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+        config.scope.rules = vec![ScopeRule {
+            rule_type: ScopeRuleType::Include,
+            pattern_type: ScopePatternType::Wildcard,
+            target: ScopeTarget::Host,
+            pattern: "example.com".to_string(),
+            scheme: Some("https".to_string()),
+            port: None,
+            enabled: true,
+        }];
+        let (proxy, mut events, _control) = Proxy::new(config).unwrap();
+        let (client, mut client_peer) = tokio::io::duplex(64 * 1024);
+        let (upstream, mut upstream_peer) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            let mut buf = [0u8; 4096];
+            let _ = upstream_peer.read(&mut buf).await.unwrap();
+            upstream_peer
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK")
+                .await
+                .unwrap();
+        });
+
+        let request = format!("GET /scoped HTTP/1.1\r\nHost: example.com\r\n{REQUEST_HEAD}");
+        let alpn = NegotiatedAlpn {
+            client: NegotiatedProtocol::Http1,
+            upstream: NegotiatedProtocol::Http1,
+        };
+        let proxied = tokio::spawn(handle_http1_tls(
+            proxy.state.clone(),
+            client,
+            request.into_bytes(),
+            upstream,
+            alpn,
+        ));
+        let mut response = Vec::new();
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            client_peer.read_to_end(&mut response),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        proxied.abort();
+
+        let request = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let event = events.next().await.unwrap();
+                if event.kind == ProxyEventKind::ResponseForwarded {
+                    break event.request.unwrap();
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(request.timeline.scheme, "https");
+        assert_eq!(request.timeline.scope_status_at_capture, "in_scope");
+    }
+
+    #[tokio::test]
+    async fn http2_buffered_bytes_cap_sends_goaway() {
+        let certs = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.listen.port = 0;
+        config.tls.ca_cert_dir = certs.path().display().to_string();
+        config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+        config.http2_max_buffered_bytes = 32 * 1024;
+        let (proxy, _events, _control) = Proxy::new(config).unwrap();
+        let (client, mut client_peer) = tokio::io::duplex(1 << 20);
+        let (upstream, _upstream_peer) = tokio::io::duplex(1 << 20);
+
+        let mut encoder = crossfeed_net::HpackEncoder::new();
+        let mut bytes = HTTP2_PREFACE.to_vec();
+        bytes.extend(crossfeed_net::encode_raw_frame(
+            crossfeed_net::FrameType::Settings,
+            0,
+            0,
+            &[],
+        ));
+        for stream_id in (1..=15).step_by(2) {
+            let headers = crossfeed_net::encode_headers_from_fields(
+                stream_id,
+                false,
+                &http2_headers(),
+                &mut encoder,
+                16 * 1024,
+            );
+            let data =
+                crossfeed_net::encode_data_frames(stream_id, false, &[b'a'; 8192], 16 * 1024);
+            bytes.extend(headers.into_iter().chain(data).flatten());
+        }
+        client_peer.write_all(&bytes).await.unwrap();
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            handle_http2_stream(
+                proxy.state.clone(),
+                client,
+                upstream,
+                Vec::new(),
+                "example.com".to_string(),
+                443,
+                Some("h2"),
+            ),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let mut received = Vec::new();
+        client_peer.read_to_end(&mut received).await.unwrap();
+        let mut parser = crossfeed_net::Http2Parser::new_without_preface();
+        let mut status = parser.push(&received);
+        let mut goaway = None;
+        while let crossfeed_net::Http2ParseStatus::Complete { frame, .. } = status {
+            if let crossfeed_net::FramePayload::GoAway(frame) = frame.payload {
+                goaway = Some(frame);
+            }
+            status = parser.push(&[]);
+        }
+        let goaway = goaway.unwrap();
+        assert_eq!(goaway.error_code, 0xb);
+        assert!(goaway.last_stream_id < 15);
+    }
+
+    async fn start_h2c_upstream() -> (
+        std::net::SocketAddr,
+        tokio::sync::oneshot::Receiver<Vec<crossfeed_net::HeaderField>>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (headers_tx, headers_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let settings =
+                crossfeed_net::encode_raw_frame(crossfeed_net::FrameType::Settings, 0, 0, &[]);
+            stream.write_all(&settings).await.unwrap();
+            let mut parser = crossfeed_net::Http2Parser::new();
+            let mut buf = vec![0u8; 8192];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                assert_ne!(n, 0);
+                for payload in parse_http2_frames(&mut parser, &buf[..n]) {
+                    match payload {
+                        crossfeed_net::FramePayload::Settings(settings) if !settings.ack => {
+                            parser.set_settings_received(true);
+                            let ack = crossfeed_net::encode_raw_frame(
+                                crossfeed_net::FrameType::Settings,
+                                0x1,
+                                0,
+                                &[],
+                            );
+                            stream.write_all(&ack).await.unwrap();
+                        }
+                        crossfeed_net::FramePayload::Headers(headers) => {
+                            let mut encoder = crossfeed_net::HpackEncoder::new();
+                            let response = crossfeed_net::encode_headers_from_fields(
+                                1,
+                                false,
+                                &[field(":status", "200"), field("content-type", "text/plain")],
+                                &mut encoder,
+                                16 * 1024,
+                            );
+                            let data =
+                                crossfeed_net::encode_data_frames(1, true, b"h2c ok", 16 * 1024);
+                            for frame in response.into_iter().chain(data) {
+                                stream.write_all(&frame).await.unwrap();
+                            }
+                            let _ = headers_tx.send(headers.headers);
+                            let _ = stream.read(&mut buf).await;
+                            return;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        });
+        (addr, headers_rx)
+    }
+
+    #[tokio::test]
+    async fn h2c_prior_knowledge_request_is_bridged_to_cleartext_upstream() {
+        let (upstream_addr, upstream_headers) = start_h2c_upstream().await;
+        let (proxy_port, mut events, _certs) = start_proxy(|_| {}).await;
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+
+        let mut encoder = crossfeed_net::HpackEncoder::new();
+        let mut bytes = HTTP2_PREFACE.to_vec();
+        bytes.extend(crossfeed_net::encode_raw_frame(
+            crossfeed_net::FrameType::Settings,
+            0,
+            0,
+            &[],
+        ));
+        let authority = upstream_addr.to_string();
+        let request = crossfeed_net::encode_headers_from_fields(
+            1,
+            true,
+            &[
+                field(":method", "GET"),
+                field(":scheme", "http"),
+                field(":authority", &authority),
+                field(":path", "/grpc.health.v1.Health/Check"),
+            ],
+            &mut encoder,
+            16 * 1024,
+        );
+        bytes.extend(request.into_iter().flatten());
+        client.write_all(&bytes).await.unwrap();
+
+        let mut parser = crossfeed_net::Http2Parser::new_without_preface();
+        let mut status = None;
+        let mut body = Vec::new();
+        let mut buf = vec![0u8; 8192];
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let n = client.read(&mut buf).await.unwrap();
+                assert_ne!(n, 0);
+                for payload in parse_http2_frames(&mut parser, &buf[..n]) {
+                    match payload {
+                        crossfeed_net::FramePayload::Settings(settings) if !settings.ack => {
+                            parser.set_settings_received(true);
+                        }
+                        crossfeed_net::FramePayload::Headers(headers) => {
+                            status = parse_http2_status(&headers.headers).ok();
+                        }
+                        crossfeed_net::FramePayload::Data(data) => {
+                            body.extend_from_slice(&data.payload);
+                            if data.end_stream {
+                                return;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(status, Some(200));
+        assert_eq!(body, b"h2c ok");
+        let forwarded = upstream_headers.await.unwrap();
+        assert!(forwarded.contains(&field(":authority", &authority)));
+        assert!(forwarded.contains(&field(":path", "/grpc.health.v1.Health/Check")));
+
+        let request = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let event = events.next().await.unwrap();
+                if let Some(request) = event.request {
+                    break request;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(request.timeline.client_alpn, None);
+        assert_eq!(request.timeline.upstream_alpn, None);
+    }
+
+    async fn send_h2c_request(
+        proxy_port: u16,
+        headers: &[crossfeed_net::HeaderField],
+    ) -> (Option<u16>, Vec<crossfeed_net::HeaderField>) {
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        let mut encoder = crossfeed_net::HpackEncoder::new();
+        let mut bytes = HTTP2_PREFACE.to_vec();
+        bytes.extend(crossfeed_net::encode_raw_frame(
+            crossfeed_net::FrameType::Settings,
+            0,
+            0,
+            &[],
+        ));
+        let request =
+            crossfeed_net::encode_headers_from_fields(1, true, headers, &mut encoder, 16 * 1024);
+        bytes.extend(request.into_iter().flatten());
+        client.write_all(&bytes).await.unwrap();
+
+        let mut parser = crossfeed_net::Http2Parser::new_without_preface();
+        let mut buf = vec![0u8; 8192];
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let n = client.read(&mut buf).await.unwrap();
+                assert_ne!(n, 0);
+                let mut status = parser.push(&buf[..n]);
+                while let crossfeed_net::Http2ParseStatus::Complete { frame, .. } = status {
+                    match frame.payload {
+                        crossfeed_net::FramePayload::Settings(settings) if !settings.ack => {
+                            parser.set_settings_received(true);
+                        }
+                        crossfeed_net::FramePayload::Headers(headers) => {
+                            return (parse_http2_status(&headers.headers).ok(), headers.headers);
+                        }
+                        _ => {}
+                    }
+                    status = parser.push(&[]);
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn h2c_prior_knowledge_requires_proxy_auth_when_enabled() {
+        let (upstream_addr, mut upstream_headers) = start_h2c_upstream().await;
+        let (proxy_port, _events, _certs) = start_proxy(|config| {
+            config.auth.enabled = true;
+            config.auth.username = "user".to_string();
+            config.auth.password = "pass".to_string();
+        })
+        .await;
+        let authority = upstream_addr.to_string();
+        let request = [
+            field(":method", "GET"),
+            field(":scheme", "http"),
+            field(":authority", &authority),
+            field(":path", "/"),
+        ];
+
+        let (status, headers) = send_h2c_request(proxy_port, &request).await;
+        assert_eq!(status, Some(407));
+        assert!(headers.contains(&field("proxy-authenticate", "Basic realm=\"crossfeed\"")));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(upstream_headers.try_recv().is_err());
+
+        let credentials = format!("Basic {}", crossfeed_codec::base64_encode_str("user:pass"));
+        let mut authorized = request.to_vec();
+        authorized.push(field("proxy-authorization", &credentials));
+        let (status, _) = send_h2c_request(proxy_port, &authorized).await;
+        assert_eq!(status, Some(200));
+        assert!(upstream_headers.await.is_ok());
+    }
+
+    fn http2_keepalive_proxy(
+        interval_ms: u64,
+        timeout_ms: u64,
+    ) -> (Proxy, ProxyEvents, tempfile::TempDir) {
+        let certs = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.listen.port = 0;
+        config.tls.ca_cert_dir = certs.path().display().to_string();
+        config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+        config.http2_keepalive.enabled = true;
+        config.http2_keepalive.interval_ms = interval_ms;
+        config.http2_keepalive.timeout_ms = timeout_ms;
+        let (proxy, events, _control) = Proxy::new(config).unwrap();
+        (proxy, events, certs)
+    }
+
+    fn parse_http2_frames(
+        parser: &mut crossfeed_net::Http2Parser,
+        bytes: &[u8],
+    ) -> Vec<crossfeed_net::FramePayload> {
+        let mut frames = Vec::new();
+        let mut status = parser.push(bytes);
+        while let crossfeed_net::Http2ParseStatus::Complete { frame, .. } = status {
+            frames.push(frame.payload);
+            status = parser.push(&[]);
+        }
+        frames
+    }
+
+    #[tokio::test]
+    async fn http2_keepalive_pings_upstream_on_interval() {
+        let (proxy, _events, _certs) = http2_keepalive_proxy(50, 1_000);
+        let (client, client_peer) = tokio::io::duplex(1 << 16);
+        let (upstream, mut upstream_peer) = tokio::io::duplex(1 << 16);
+
+        let upstream_task = async move {
+            let settings =
+                crossfeed_net::encode_raw_frame(crossfeed_net::FrameType::Settings, 0, 0, &[]);
+            upstream_peer.write_all(&settings).await.unwrap();
+            let mut parser = crossfeed_net::Http2Parser::new();
+            let mut pings = Vec::new();
+            let mut buf = vec![0u8; 4096];
+            while pings.len() < 2 {
+                let n = upstream_peer.read(&mut buf).await.unwrap();
+                assert_ne!(n, 0);
+                for payload in parse_http2_frames(&mut parser, &buf[..n]) {
+                    match payload {
+                        crossfeed_net::FramePayload::Ping(ping) if !ping.ack => {
+                            let ack = crossfeed_net::encode_raw_frame(
+                                crossfeed_net::FrameType::Ping,
+                                0x1,
+                                0,
+                                &ping.opaque_data,
+                            );
+                            upstream_peer.write_all(&ack).await.unwrap();
+                            pings.push(ping.opaque_data);
+                        }
+                        crossfeed_net::FramePayload::GoAway(_) => panic!("unexpected goaway"),
+                        _ => {}
+                    }
+                }
+            }
+            drop(client_peer);
+            pings
+        };
+
+        let (result, pings) = tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::join!(
+                handle_http2_stream(
+                    proxy.state.clone(),
+                    client,
+                    upstream,
+                    Vec::new(),
+                    "example.com".to_string(),
+                    443,
+                    Some("h2"),
+                ),
+                upstream_task
+            )
+        })
+        .await
+        .unwrap();
+
+        result.unwrap();
+        assert_eq!(pings.len(), 2);
+        assert_ne!(pings[0], pings[1]);
+    }
+
+    #[tokio::test]
+    async fn http2_keepalive_reports_ping_round_trip() {
+        let (proxy, mut events, _certs) = http2_keepalive_proxy(50, 1_000);
+        let (client, client_peer) = tokio::io::duplex(1 << 16);
+        let (upstream, mut upstream_peer) = tokio::io::duplex(1 << 16);
+
+        let upstream_task = async move {
+            let settings =
+                crossfeed_net::encode_raw_frame(crossfeed_net::FrameType::Settings, 0, 0, &[]);
+            upstream_peer.write_all(&settings).await.unwrap();
+            let mut parser = crossfeed_net::Http2Parser::new();
+            let mut buf = vec![0u8; 4096];
+            let opaque_data = loop {
+                let n = upstream_peer.read(&mut buf).await.unwrap();
+                assert_ne!(n, 0);
+                let ping = parse_http2_frames(&mut parser, &buf[..n])
+                    .into_iter()
+                    .find_map(|payload| match payload {
+                        crossfeed_net::FramePayload::Ping(ping) if !ping.ack => {
+                            Some(ping.opaque_data)
+                        }
+                        _ => None,
+                    });
+                if let Some(opaque_data) = ping {
+                    break opaque_data;
+                }
+            };
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            let ack = crossfeed_net::encode_raw_frame(
+                crossfeed_net::FrameType::Ping,
+                0x1,
+                0,
+                &opaque_data,
+            );
+            upstream_peer.write_all(&ack).await.unwrap();
+            let event = loop {
+                let event = events.next().await.unwrap();
+                if event.kind == ProxyEventKind::Http2PingRtt {
+                    break event;
+                }
+            };
+            drop(client_peer);
+            event
+        };
+
+        let (result, event) = tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::join!(
+                handle_http2_stream(
+                    proxy.state.clone(),
+                    client,
+                    upstream,
+                    Vec::new(),
+                    "example.com".to_string(),
+                    443,
+                    Some("h2"),
+                ),
+                upstream_task
+            )
+        })
+        .await
+        .unwrap();
+
+        result.unwrap();
+        let rtt_ms = event.http2_ping_rtt_ms.unwrap();
+        assert!((40..1_000).contains(&rtt_ms), "rtt {rtt_ms}ms");
+        assert!(event.request.is_none());
+    }
+
+    #[tokio::test]
+    async fn http2_keepalive_unanswered_ping_tears_down_connection() {
+        let (proxy, _events, _certs) = http2_keepalive_proxy(50, 100);
+        let (client, mut client_peer) = tokio::io::duplex(1 << 16);
+        let (upstream, mut upstream_peer) = tokio::io::duplex(1 << 16);
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            handle_http2_stream(
+                proxy.state.clone(),
+                client,
+                upstream,
+                Vec::new(),
+                "example.com".to_string(),
+                443,
+                Some("h2"),
+            ),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let mut received = Vec::new();
+        upstream_peer.read_to_end(&mut received).await.unwrap();
+        let frames = parse_http2_frames(&mut crossfeed_net::Http2Parser::new(), &received);
+        let pings = frames
+            .iter()
+            .filter(|payload| matches!(payload, crossfeed_net::FramePayload::Ping(_)))
+            .count();
+        assert_eq!(pings, 1);
+        assert!(matches!(
+            frames.last(),
+            Some(crossfeed_net::FramePayload::GoAway(goaway)) if goaway.error_code == 0
+        ));
+
+        let mut received = Vec::new();
+        client_peer.read_to_end(&mut received).await.unwrap();
+        let frames = parse_http2_frames(
+            &mut crossfeed_net::Http2Parser::new_without_preface(),
+            &received,
+        );
+        assert!(matches!(
+            frames.last(),
+            Some(crossfeed_net::FramePayload::GoAway(goaway)) if goaway.error_code == 0
+        ));
+    }
+
+    #[test]
+    fn synthesized_http2_request_parses_as_http11() {
+        let bytes = synthesize_http2_request_bytes(&http2_meta(), &http2_headers(), b"{}");
+
+        let mut parser = crossfeed_net::RequestParser::new();
+        let crossfeed_net::ParseStatus::Complete { message, warnings } = parser.push(&bytes) else {
+            panic!("synthesized request did not parse");
+        };
+        assert!(warnings.is_empty());
+        assert_eq!(message.line.method, "POST");
+        assert_eq!(message.line.target, "/submit?x=1");
+        assert_eq!(message.line.version, crossfeed_net::HttpVersion::Http11);
+        let header = |name: &str| {
+            message
+                .headers
+                .iter()
+                .find(|header| header.name.eq_ignore_ascii_case(name))
+                .map(|header| header.value.clone())
+        };
+        assert_eq!(header("host").as_deref(), Some("example.com:8443"));
+        assert_eq!(header("cookie").as_deref(), Some("a=1; b=2"));
+        assert_eq!(header("content-length").as_deref(), Some("2"));
+        assert_eq!(message.body, b"{}");
+    }
+
+    #[test]
+    fn synthesized_http2_request_keeps_raw_authority_bytes() {
+        let mut headers = http2_headers();
+        headers[2] = crossfeed_net::HeaderField {
+            name: b":authority".to_vec(),
+            value: b"user@caf\xe9.example:8443".to_vec(),
+        };
+        let meta = parse_http2_request_meta(&headers, "", 443).unwrap();
+        let bytes = synthesize_http2_request_bytes(&meta, &headers, b"{}");
+
+        assert!(
+            bytes
+                .split(|byte| *byte == b'\n')
+                .any(|line| line == b"Host: caf\xe9.example:8443\r")
+        );
+    }
+
+    #[test]
+    fn synthesized_http2_request_round_trips_to_http2_fields() {
+        let bytes = synthesize_http2_request_bytes(&http2_meta(), &http2_headers(), b"{}");
+        let request = parse_http1_request(&bytes).unwrap();
+
+        let (meta, fields) = http1_request_to_h2(&request, "https", "fallback").unwrap();
+        assert_eq!(meta.authority, "example.com:8443");
+        assert_eq!(meta.path, "/submit?x=1");
+        assert!(fields.contains(&field(":method", "POST")));
+        assert!(fields.contains(&field(":authority", "example.com:8443")));
+        assert!(fields.contains(&field("content-type", "application/json")));
+        assert!(!fields.iter().any(|field| field.name == b"host"));
+    }
+
+    fn request_header(request: &crossfeed_net::Request, name: &str) -> Vec<String> {
+        request
+            .headers
+            .iter()
+            .filter(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.clone())
+            .collect()
+    }
+
+    #[test]
+    fn h2_headers_downgrade_keeps_authority_port_and_query() {
+        let request = h2_headers_to_http1(&http2_headers(), b"{}").unwrap();
+
+        assert_eq!(request.line.method, "POST");
+        assert_eq!(request.line.target, "/submit?x=1");
+        assert_eq!(request_header(&request, "host"), vec!["example.com:8443"]);
+        assert!(request.headers.iter().all(|header| !header.name.starts_with(':')));
+        assert_eq!(request.body, b"{}");
+
+        let (meta, fields) = http1_request_to_h2(&request, "https", "fallback").unwrap();
+        assert_eq!((meta.host.as_str(), meta.port), ("example.com", 8443));
+        assert!(fields.contains(&field(":authority", "example.com:8443")));
+        assert!(fields.contains(&field(":path", "/submit?x=1")));
+    }
+
+    #[test]
+    fn h2_headers_downgrade_without_authority_port() {
+        let headers = vec![
+            field(":method", "GET"),
+            field(":scheme", "https"),
+            field(":authority", "user@example.com"),
+            field(":path", "/search?q=a%20b&page=2"),
+            field("accept", "*/*"),
+        ];
+        let request = h2_headers_to_http1(&headers, b"").unwrap();
+
+        assert_eq!(request.line.target, "/search?q=a%20b&page=2");
+        assert_eq!(request_header(&request, "host"), vec!["example.com"]);
+        assert_eq!(request_header(&request, "accept"), vec!["*/*"]);
+
+        let (meta, _) = http1_request_to_h2(&request, "https", "fallback").unwrap();
+        assert_eq!(meta.authority, "example.com");
+        assert_eq!(meta.port, 443);
+    }
+
+    #[test]
+    fn h2_headers_downgrade_falls_back_to_host_header() {
+        let headers = vec![
+            field(":method", "GET"),
+            field(":scheme", "http"),
+            field(":path", "/"),
+            field("host", "example.com:8080"),
+        ];
+        let request = h2_headers_to_http1(&headers, b"").unwrap();
+        assert_eq!(request_header(&request, "host"), vec!["example.com:8080"]);
+
+        let missing = vec![field(":method", "GET"), field(":path", "/")];
+        assert!(h2_headers_to_http1(&missing, b"").is_err());
+    }
+
+    #[test]
+    fn synthesized_http2_response_uses_reason_phrases() {
+        let headers = vec![field(":status", "404"), field("content-length", "0")];
+        for (status, line) in [
+            (200, "HTTP/1.1 200 OK\r\n"),
+            (404, "HTTP/1.1 404 Not Found\r\n"),
+            (503, "HTTP/1.1 503 Service Unavailable\r\n"),
+            (599, "HTTP/1.1 599 Unknown\r\n"),
+        ] {
+            let bytes = synthesize_http2_response_bytes(status, &headers, b"");
+            assert!(bytes.starts_with(line.as_bytes()));
+            assert!(!bytes.windows(7).any(|window| window == b":status"));
+        }
+    }
+
+    #[test]
+    fn http2_response_completes_request_duration() {
+        let mut request = build_http2_timeline_request(
+            &http2_meta(),
+            Vec::new(),
+            Vec::new(),
+            "2024-01-01T00:00:00.100+00:00".to_string(),
+            "in_scope",
+            Some("h2"),
+        );
+        let response = build_http2_timeline_response(
+            200,
+            Vec::new(),
+            Vec::new(),
+            "2024-01-01T00:00:01.350+00:00".to_string(),
+        );
+
+        request.record_completion(&response.received_at);
+        assert_eq!(
+            request.completed_at.as_deref(),
+            Some("2024-01-01T00:00:01.350+00:00")
+        );
+        assert_eq!(request.duration_ms, Some(1250));
+    }
+
+    #[test]
+    fn http2_timeline_response_keeps_protocol_and_reason() {
+        let response = build_http2_timeline_response(201, Vec::new(), Vec::new(), "now".to_string());
+        assert_eq!(response.http_version, "HTTP/2");
+        assert_eq!(response.reason.as_deref(), Some("Created"));
+
+        let unknown = build_http2_timeline_response(599, Vec::new(), Vec::new(), "now".to_string());
+        assert_eq!(unknown.reason, None);
+    }
+}
@@ -1,27 +1,27 @@
 use crate::config::{ScopePatternType, ScopeRule, ScopeRuleType, ScopeTarget};
 
-pub fn is_in_scope(rules: &[ScopeRule], host: &str, path: &str) -> bool {
-    let mut include_match = false;
-    let mut exclude_match = false;
-
-    for rule in rules.iter().filter(|rule| rule.enabled) {
-        if matches_rule(rule, host, path) {
-            match rule.rule_type {
-                ScopeRuleType::Include => include_match = true,
-                ScopeRuleType::Exclude => exclude_match = true,
-            }
-        }
-    }
-
-    if exclude_match {
-        return false;
-    }
-
-    if include_match {
-        return true;
-    }
+pub fn is_in_scope(rules: &[ScopeRule], scheme: &str, host: &str, port: u16, path: &str) -> bool {
+    decisive_scope_rule(rules, scheme, host, port, path)
+        .is_some_and(|index| rules[index].rule_type == ScopeRuleType::Include)
+}
 
-    false
+/// Rules are evaluated in order and the last enabled rule that matches decides.
+pub fn decisive_scope_rule(
+    rules: &[ScopeRule],
+    scheme: &str,
+    host: &str,
+    port: u16,
+    path: &str,
+) -> Option<usize> {
+    rules.iter().rposition(|rule| {
+        rule.enabled
+            && rule.port.is_none_or(|rule_port| rule_port == port)
+            && rule
+                .scheme
+                .as_deref()
+                .is_none_or(|rule_scheme| rule_scheme.eq_ignore_ascii_case(scheme))
+            && matches_rule(rule, host, path)
+    })
 }
 
 fn matches_rule(rule: &ScopeRule, host: &str, path: &str) -> bool {
@@ -74,46 +74,122 @@ fn wildcard_match(pattern: &str, value: &str) -> bool {
 mod tests {
     use crate::config::{ScopePatternType, ScopeRule, ScopeRuleType, ScopeTarget};
 
-    use super::is_in_scope;
+    use super::{decisive_scope_rule, is_in_scope};
 
     #[test]
-    fn exclude_overrides_include() {
+    fn last_matching_rule_decides_in_either_order() {
+        let rules = vec![
+            host_rule(ScopeRuleType::Include, "*.example.com"),
+            host_rule(ScopeRuleType::Exclude, "api.example.com"),
+        ];
+        assert!(!is_in_scope(&rules, "https", "api.example.com", 443, "/"));
+        assert!(is_in_scope(&rules, "https", "www.example.com", 443, "/"));
+
+        // The reverse order lets a narrow include carve out of a broad exclude.
+        let rules = vec![
+            host_rule(ScopeRuleType::Exclude, "*.example.com"),
+            host_rule(ScopeRuleType::Include, "api.example.com"),
+        ];
+        assert!(is_in_scope(&rules, "https", "api.example.com", 443, "/"));
+        assert!(!is_in_scope(&rules, "https", "www.example.com", 443, "/"));
+    }
+
+    #[test]
+    fn include_matches_when_present() {
+        let rules = vec![ScopeRule {
+            rule_type: ScopeRuleType::Include,
+            pattern_type: ScopePatternType::Wildcard,
+            target: ScopeTarget::Host,
+            pattern: "*.example.com".to_string(),
+            scheme: None,
+            port: None,
+            enabled: true,
+        }];
+
+        assert!(is_in_scope(&rules, "https", "api.example.com", 443, "/"));
+    }
+
+    #[test]
+    fn no_rules_means_out_of_scope() {
+        let rules = Vec::new();
+        assert!(!is_in_scope(&rules, "https", "example.com", 443, "/"));
+    }
+
+    fn host_rule(rule_type: ScopeRuleType, pattern: &str) -> ScopeRule {
+        ScopeRule {
+            rule_type,
+            pattern_type: ScopePatternType::Wildcard,
+            target: ScopeTarget::Host,
+            pattern: pattern.to_string(),
+            scheme: None,
+            port: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn disabled_rules_are_skipped() {
+        let mut exclude = host_rule(ScopeRuleType::Exclude, "api.example.com");
+        exclude.enabled = false;
+        let rules = vec![host_rule(ScopeRuleType::Include, "*.example.com"), exclude];
+
+        assert!(is_in_scope(&rules, "https", "api.example.com", 443, "/"));
+    }
+
+    #[test]
+    fn decisive_rule_is_last_match() {
         let rules = vec![
             ScopeRule {
                 rule_type: ScopeRuleType::Include,
                 pattern_type: ScopePatternType::Wildcard,
                 target: ScopeTarget::Host,
                 pattern: "*.example.com".to_string(),
+                scheme: None,
+                port: None,
                 enabled: true,
             },
             ScopeRule {
                 rule_type: ScopeRuleType::Exclude,
                 pattern_type: ScopePatternType::Wildcard,
-                target: ScopeTarget::Host,
-                pattern: "api.example.com".to_string(),
+                target: ScopeTarget::Path,
+                pattern: "/logout*".to_string(),
+                scheme: None,
+                port: None,
                 enabled: true,
             },
         ];
 
-        assert!(!is_in_scope(&rules, "api.example.com", "/"));
+        assert_eq!(
+            decisive_scope_rule(&rules, "https", "api.example.com", 443, "/"),
+            Some(0)
+        );
+        assert_eq!(
+            decisive_scope_rule(&rules, "https", "api.example.com", 443, "/logout"),
+            Some(1)
+        );
+        assert_eq!(
+            decisive_scope_rule(&rules, "https", "example.org", 443, "/"),
+            None
+        );
     }
 
     #[test]
-    fn include_matches_when_present() {
-        let rules = vec![ScopeRule {
-            rule_type: ScopeRuleType::Include,
-            pattern_type: ScopePatternType::Wildcard,
-            target: ScopeTarget::Host,
-            pattern: "*.example.com".to_string(),
-            enabled: true,
-        }];
+    fn port_restricted_rule_skips_other_ports() {
+        let mut rule = host_rule(ScopeRuleType::Include, "example.com");
+        rule.port = Some(8443);
+        let rules = vec![rule];
 
-        assert!(is_in_scope(&rules, "api.example.com", "/"));
+        assert!(is_in_scope(&rules, "https", "example.com", 8443, "/"));
+        assert!(!is_in_scope(&rules, "https", "example.com", 443, "/"));
     }
 
     #[test]
-    fn no_rules_means_out_of_scope() {
-        let rules = Vec::new();
-        assert!(!is_in_scope(&rules, "example.com", "/"));
+    fn scheme_restricted_rule_skips_other_schemes() {
+        let mut rule = host_rule(ScopeRuleType::Include, "example.com");
+        rule.scheme = Some("https".to_string());
+        let rules = vec![rule];
+
+        assert!(is_in_scope(&rules, "HTTPS", "example.com", 443, "/"));
+        assert!(!is_in_scope(&rules, "http", "example.com", 80, "/"));
     }
 }
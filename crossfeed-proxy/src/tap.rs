@@ -0,0 +1,148 @@
+use crossfeed_storage::{TimelineRequest, TimelineResponse};
+
+use crate::config::ProxyConfig;
+use crate::error::ProxyError;
+use crate::proxy::{build_request_record, parse_http1_request, parse_response, resolve_target};
+use crate::scope::is_in_scope;
+
+/// Parses a request/response pair captured outside the proxy. Timestamps
+/// and the scope label follow `config` the same way proxied traffic does.
+pub fn parse_tap_pair(
+    request_bytes: &[u8],
+    response_bytes: &[u8],
+    config: &ProxyConfig,
+) -> Result<(TimelineRequest, TimelineResponse), ProxyError> {
+    let request = parse_http1_request(request_bytes)?;
+    let (host, mut port, path) = resolve_target(&request.line.target, &request.headers)
+        .ok_or_else(|| ProxyError::Runtime("missing host".to_string()))?;
+    // A tapped pair has no TLS context, so the scheme comes from an
+    // absolute-form target or an explicit HTTPS port in `Host`.
+    let target = &request.line.target;
+    let scheme = if target.starts_with("https://") {
+        "https"
+    } else if target.starts_with("http://") {
+        "http"
+    } else if host_has_port(&request.headers) {
+        if port == 443 { "https" } else { "http" }
+    } else {
+        port = 80;
+        "http"
+    };
+    let in_scope = is_in_scope(&config.scope.rules, scheme, &host, port, &path);
+    let scope_status = if in_scope { "in_scope" } else { "out_of_scope" };
+    let captured_at = config.timestamps.now();
+    let (mut timeline_request, _) = build_request_record(
+        &request,
+        scheme,
+        &path,
+        &host,
+        port,
+        scope_status,
+        captured_at.clone(),
+    );
+    timeline_request.source = "tap".to_string();
+    timeline_request.url = format!("{scheme}://{host}:{port}{path}");
+    timeline_request.completed_at = Some(captured_at.clone());
+
+    let limits = crossfeed_net::Limits {
+        max_header_bytes: response_bytes.len(),
+        max_body_bytes: response_bytes.len(),
+    };
+    let timeline_response = parse_response(response_bytes, &captured_at, limits)
+        .ok_or_else(|| ProxyError::Runtime("incomplete response".to_string()))?;
+    Ok((timeline_request, timeline_response))
+}
+
+fn host_has_port(headers: &[crossfeed_net::Header]) -> bool {
+    headers
+        .iter()
+        .find(|header| header.name.eq_ignore_ascii_case("host"))
+        .and_then(|header| header.value.rsplit_once(':'))
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use crossfeed_core::{TimestampFormat, TimestampPrecision};
+
+    use super::parse_tap_pair;
+    use crate::config::{ProxyConfig, ScopePatternType, ScopeRule, ScopeRuleType, ScopeTarget};
+
+    fn host_rule(pattern: &str) -> ScopeRule {
+        ScopeRule {
+            rule_type: ScopeRuleType::Include,
+            pattern_type: ScopePatternType::Wildcard,
+            target: ScopeTarget::Host,
+            pattern: pattern.to_string(),
+            scheme: None,
+            port: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn parses_origin_form_pair() {
+        let (request, response) = parse_tap_pair(
+            b"POST /submit HTTP/1.1\r\nHost: example.com:8080\r\nContent-Length: 5\r\n\r\nhello",
+            b"HTTP/1.1 201 Created\r\nContent-Length: 2\r\n\r\nok",
+            &ProxyConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(request.source, "tap");
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.host, "example.com");
+        assert_eq!(request.port, 8080);
+        assert_eq!(request.path, "/submit");
+        assert_eq!(request.url, "http://example.com:8080/submit");
+        assert_eq!(request.request_body, b"hello");
+        assert_eq!(response.status_code, 201);
+        assert_eq!(response.response_body, b"ok");
+    }
+
+    #[test]
+    fn rejects_truncated_response() {
+        let result = parse_tap_pair(
+            b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort",
+            &ProxyConfig::default(),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn applies_scope_rules_and_timestamp_format() {
+        let mut config = ProxyConfig::default();
+        config.scope.rules = vec![host_rule("*.example.com")];
+        config.timestamps = TimestampFormat {
+            precision: TimestampPrecision::Seconds,
+            ..TimestampFormat::default()
+        };
+
+        let (request, response) = parse_tap_pair(
+            b"GET https://api.example.com/items HTTP/1.1\r\nHost: api.example.com\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n",
+            &config,
+        )
+        .unwrap();
+
+        assert_eq!(request.scheme, "https");
+        assert_eq!(request.port, 443);
+        assert_eq!(request.url, "https://api.example.com:443/items");
+        assert_eq!(request.scope_status_at_capture, "in_scope");
+        assert!(!request.started_at.contains('.'));
+        assert_eq!(response.received_at, request.started_at);
+
+        let (request, _) = parse_tap_pair(
+            b"GET / HTTP/1.1\r\nHost: other.test\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n",
+            &config,
+        )
+        .unwrap();
+
+        assert_eq!(request.scheme, "http");
+        assert_eq!(request.port, 80);
+        assert_eq!(request.scope_status_at_capture, "out_of_scope");
+    }
+}
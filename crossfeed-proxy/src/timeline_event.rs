@@ -1,12 +1,23 @@
-use crossfeed_storage::{TimelineRequest, TimelineResponse};
+use std::path::Path;
+use std::sync::Arc;
+
+use crossfeed_storage::{
+    Http2ConnectionRecord, TimelineRequest, TimelineResponse, TimelineWebSocketFrame,
+};
 use serde::{Deserialize, Serialize};
+use tempfile::TempPath;
 use uuid::Uuid;
 
+use crate::error::ProxyError;
+use crate::proxy::{parse_incomplete_response, parse_response};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ProxyRequest {
     pub id: Uuid,
     pub timeline: TimelineRequest,
     pub raw_request: Vec<u8>,
+    #[serde(default)]
+    pub http2_connection: Option<Http2ConnectionRecord>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -14,6 +25,59 @@ pub struct ProxyResponse {
     pub id: Uuid,
     pub timeline: TimelineResponse,
     pub raw_response: Vec<u8>,
+    /// Set when the captured response was spilled to disk; `timeline` then
+    /// carries only the parsed head until [`ProxyResponse::load_spilled`].
+    #[serde(skip)]
+    pub spill: Option<SpillFile>,
+}
+
+/// A response capture spilled to disk. The file is removed once the last
+/// clone is dropped, so every receiver of the event can load it.
+#[derive(Debug, Clone)]
+pub struct SpillFile(Arc<TempPath>);
+
+impl SpillFile {
+    pub(crate) fn new(path: TempPath) -> Self {
+        Self(Arc::new(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl PartialEq for SpillFile {
+    fn eq(&self, other: &Self) -> bool {
+        self.path() == other.path()
+    }
+}
+
+impl Eq for SpillFile {}
+
+impl ProxyResponse {
+    pub fn load_spilled(&mut self) -> Result<(), ProxyError> {
+        let Some(spill) = self.spill.as_ref() else {
+            return Ok(());
+        };
+        let bytes = std::fs::read(spill.path())?;
+        let limits = crossfeed_net::Limits {
+            max_header_bytes: bytes.len(),
+            max_body_bytes: bytes.len(),
+        };
+        let received_at = &self.timeline.received_at;
+        let ttfb_ms = self.timeline.ttfb_ms;
+        let timeline = if self.timeline.response_body_truncated {
+            parse_incomplete_response(&bytes, received_at)
+        } else {
+            parse_response(&bytes, received_at, limits)
+        };
+        self.timeline = timeline
+            .ok_or_else(|| ProxyError::Runtime("spilled response is incomplete".to_string()))?;
+        self.timeline.ttfb_ms = ttfb_ms;
+        self.raw_response = bytes;
+        self.spill = None;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -24,6 +88,13 @@ pub enum ProxyEventKind {
     ResponseObserved,
     ResponseIntercepted,
     ResponseForwarded,
+    /// The upstream answered an HTTP/1.1 `Upgrade: websocket` request with
+    /// `101 Switching Protocols`; carries the handshake request and response.
+    WebSocketEstablished,
+    WebSocketFrame,
+    /// The upstream acknowledged an HTTP/2 keepalive PING; carries the
+    /// measured round trip in `http2_ping_rtt_ms`.
+    Http2PingRtt,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -33,4 +104,8 @@ pub struct ProxyEvent {
     pub kind: ProxyEventKind,
     pub request: Option<ProxyRequest>,
     pub response: Option<ProxyResponse>,
+    #[serde(default)]
+    pub websocket_frame: Option<TimelineWebSocketFrame>,
+    #[serde(default)]
+    pub http2_ping_rtt_ms: Option<u64>,
 }
@@ -0,0 +1,207 @@
+use crossfeed_storage::ReplayVersion;
+use http::Uri;
+
+use crate::service::build_url;
+use crate::{ReplayEdit, ReplayError};
+
+/// Renders a replay version as a `curl` command. Every captured header is
+/// passed with `-H`, including `Host` and `Content-Length`, so curl sends
+/// them as-is. A body that is not UTF-8 is written lossily.
+pub fn replay_version_to_curl(version: &ReplayVersion) -> String {
+    let url = build_url(
+        &version.scheme,
+        &version.host,
+        version.port,
+        &version.path,
+        version.query.as_deref(),
+    );
+    let mut command = format!(
+        "curl --http1.1 -X {} {}",
+        shell_quote(&version.method),
+        shell_quote(&url)
+    );
+    let headers = String::from_utf8_lossy(&version.request_headers);
+    for line in headers.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Skips a request line left at the top of a captured head.
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            continue;
+        }
+        command.push_str(" -H ");
+        command.push_str(&shell_quote(&format!("{}: {}", name, value.trim())));
+    }
+    if !version.request_body.is_empty() {
+        command.push_str(" --data-binary ");
+        command.push_str(&shell_quote(&String::from_utf8_lossy(
+            &version.request_body,
+        )));
+    }
+    command
+}
+
+/// Parses a `curl` command, such as one from [`replay_version_to_curl`],
+/// into an edit. Only the options that describe the request are accepted.
+pub fn curl_to_replay_edit(command: &str) -> Result<ReplayEdit, ReplayError> {
+    let words = shell_words(command)?;
+    let mut words = words.into_iter();
+    if words.next().as_deref() != Some("curl") {
+        return Err(ReplayError::InvalidRequest(
+            "command does not start with curl".to_string(),
+        ));
+    }
+
+    let mut method = None;
+    let mut url = None;
+    let mut headers = Vec::new();
+    let mut body: Option<Vec<u8>> = None;
+    while let Some(word) = words.next() {
+        let mut value = || {
+            words
+                .next()
+                .ok_or_else(|| ReplayError::InvalidRequest(format!("{word} needs a value")))
+        };
+        match word.as_str() {
+            "-X" | "--request" => method = Some(value()?),
+            "-H" | "--header" => headers.push(value()?),
+            "-d" | "--data" | "--data-raw" | "--data-binary" => {
+                let data = value()?;
+                let body = body.get_or_insert_with(Vec::new);
+                if !body.is_empty() {
+                    body.push(b'&');
+                }
+                body.extend_from_slice(data.as_bytes());
+            }
+            "--url" => url = Some(value()?),
+            "--http1.1" | "--compressed" | "-k" | "--insecure" | "-s" | "--silent" | "-i"
+            | "--include" | "-L" | "--location" => {}
+            flag if flag.starts_with('-') => {
+                return Err(ReplayError::InvalidRequest(format!(
+                    "unsupported curl option {flag}"
+                )));
+            }
+            _ if url.is_none() => url = Some(word),
+            _ => {
+                return Err(ReplayError::InvalidRequest(format!(
+                    "unexpected curl argument {word}"
+                )));
+            }
+        }
+    }
+
+    let url = url.ok_or_else(|| ReplayError::InvalidRequest("missing curl URL".to_string()))?;
+    let uri: Uri = url
+        .parse()
+        .map_err(|err: http::uri::InvalidUri| ReplayError::InvalidRequest(err.to_string()))?;
+    let scheme = uri.scheme_str().unwrap_or("http").to_string();
+    let host = uri
+        .host()
+        .ok_or_else(|| ReplayError::InvalidRequest("missing host in curl URL".to_string()))?
+        .to_string();
+    let port = uri
+        .port_u16()
+        .unwrap_or(if scheme == "https" { 443 } else { 80 });
+    let path = match uri.path() {
+        "" => "/".to_string(),
+        path => path.to_string(),
+    };
+    let query = uri.query().map(str::to_string);
+    let body = body.unwrap_or_default();
+    let method = method.unwrap_or_else(|| if body.is_empty() { "GET" } else { "POST" }.to_string());
+
+    let mut header_block = Vec::new();
+    for header in &headers {
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| ReplayError::InvalidRequest(format!("invalid curl header {header}")))?;
+        header_block.extend_from_slice(format!("{}: {}\r\n", name.trim(), value.trim()).as_bytes());
+    }
+
+    Ok(ReplayEdit {
+        method: Some(method),
+        url: Some(build_url(&scheme, &host, port, &path, query.as_deref())),
+        scheme: Some(scheme),
+        host: Some(host),
+        port: Some(port),
+        path: Some(path),
+        query,
+        http_version: Some("HTTP/1.1".to_string()),
+        request_headers: Some(header_block),
+        request_body_size: Some(body.len()),
+        request_body: Some(body),
+        label: None,
+    })
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Splits a command line the way a POSIX shell would for single quotes,
+/// double quotes, backslash escapes and line continuations.
+fn shell_words(command: &str) -> Result<Vec<String>, ReplayError> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => current.push(ch),
+                        None => return Err(unterminated_quote()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(next @ ('"' | '\\' | '$' | '`')) => current.push(next),
+                            Some('\n') => {}
+                            Some(next) => {
+                                current.push('\\');
+                                current.push(next);
+                            }
+                            None => return Err(unterminated_quote()),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err(unterminated_quote()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(next) => {
+                    in_word = true;
+                    current.push(next);
+                }
+                None => {}
+            },
+            ch if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            ch => {
+                in_word = true;
+                current.push(ch);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+fn unterminated_quote() -> ReplayError {
+    ReplayError::InvalidRequest("unterminated quote in curl command".to_string())
+}
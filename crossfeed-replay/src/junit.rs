@@ -0,0 +1,81 @@
+use crate::model::{ReplayAssertionResult, ReplayStepResult};
+
+/// Renders replay step results as JUnit XML, one `<testsuite>` per step and
+/// one `<testcase>` per assertion, matching Newman's reporter layout.
+pub fn replay_results_to_junit(results: &[ReplayStepResult]) -> String {
+    let tests: usize = results.iter().map(|step| step_cases(step).len()).sum();
+    let failures: usize = results.iter().map(step_failures).sum();
+    let total_ms: u64 = results.iter().map(|step| step.duration_ms).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"crossfeed-replay\" tests=\"{tests}\" failures=\"{failures}\" time=\"{}\">\n",
+        seconds(total_ms)
+    ));
+    for step in results {
+        let cases = step_cases(step);
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{}\">\n",
+            xml_escape(&step.name),
+            cases.len(),
+            step_failures(step),
+            seconds(step.duration_ms)
+        ));
+        for case in cases {
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+                xml_escape(&case.name),
+                xml_escape(&step.name),
+                seconds(step.duration_ms)
+            );
+            if case.passed {
+                xml.push_str(&open);
+                xml.push_str("/>\n");
+                continue;
+            }
+            let message = case.message.as_deref().unwrap_or("assertion failed");
+            xml.push_str(&open);
+            xml.push_str(">\n");
+            xml.push_str(&format!(
+                "      <failure type=\"AssertionFailure\" message=\"{}\">{}</failure>\n",
+                xml_escape(message),
+                xml_escape(message)
+            ));
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn step_cases(step: &ReplayStepResult) -> Vec<ReplayAssertionResult> {
+    if step.assertions.is_empty() {
+        return vec![ReplayAssertionResult {
+            name: step.name.clone(),
+            passed: true,
+            message: None,
+        }];
+    }
+    step.assertions.clone()
+}
+
+fn step_failures(step: &ReplayStepResult) -> usize {
+    step.assertions
+        .iter()
+        .filter(|assertion| !assertion.passed)
+        .count()
+}
+
+fn seconds(duration_ms: u64) -> String {
+    format!("{:.3}", duration_ms as f64 / 1000.0)
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -1,7 +1,16 @@
+mod curl;
 mod error;
+mod junit;
 mod model;
 mod service;
+mod websocket;
 
+pub use curl::{curl_to_replay_edit, replay_version_to_curl};
 pub use error::ReplayError;
-pub use model::{ReplayDiff, ReplayEdit, ReplaySendResult, ReplaySendScope};
+pub use junit::replay_results_to_junit;
+pub use model::{
+    ReplayAssertionResult, ReplayDiff, ReplayEdit, ReplayProxyTarget, ReplaySendResult,
+    ReplaySendScope, ReplayStepResult, ReplayTarget,
+};
 pub use service::{ReplayService, send_replay_request};
+pub use websocket::{WsDirection, WsReplay, WsReplayMessage, WsReplayTranscript};
@@ -16,6 +16,13 @@ pub struct ReplayEdit {
     pub label: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ReplayTarget {
+    pub scheme: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ReplayDiff {
     pub json: serde_json::Value,
@@ -28,9 +35,30 @@ pub struct ReplaySendScope {
     pub scope_rules_version: i64,
     pub capture_filtered: bool,
     pub timeline_filtered: bool,
+    pub via_proxy: Option<ReplayProxyTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReplayProxyTarget {
+    pub host: String,
+    pub port: u16,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ReplaySendResult {
     pub timeline_request_id: i64,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReplayStepResult {
+    pub name: String,
+    pub duration_ms: u64,
+    pub assertions: Vec<ReplayAssertionResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReplayAssertionResult {
+    pub name: String,
+    pub passed: bool,
+    pub message: Option<String>,
+}
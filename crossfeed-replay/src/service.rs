@@ -1,16 +1,23 @@
 use chrono::Utc;
 use similar::{ChangeTag, TextDiff};
 
+use crossfeed_codec::canonical_json;
 use crossfeed_storage::{
     ReplayExecution, ReplayRequest, ReplayVersion, SqliteStore, TimelineRequest, TimelineResponse,
 };
 use crossfeed_storage::TimelineStore;
-use crossfeed_web::{CancelToken, Client, ClientConfig, Request as WebRequest, RequestError};
+use crossfeed_web::{
+    CancelToken, Client, ClientConfig, ProxyConfig, ProxyKind, Request as WebRequest, RequestError,
+    TlsClientConfig,
+};
 use http::{HeaderMap, HeaderName, HeaderValue, Method, Uri};
 use std::path::Path;
 use std::time::Instant;
 
-use crate::{ReplayDiff, ReplayEdit, ReplayError, ReplaySendResult, ReplaySendScope};
+use crate::{
+    ReplayDiff, ReplayEdit, ReplayError, ReplayProxyTarget, ReplaySendResult, ReplaySendScope,
+    ReplayTarget,
+};
 
 pub struct ReplayService {
     store: SqliteStore,
@@ -95,6 +102,22 @@ impl ReplayService {
     }
 
     pub fn apply_edit(
+        &self,
+        active_request: &ReplayRequest,
+        mut edit: ReplayEdit,
+    ) -> Result<ReplayVersion, ReplayError> {
+        if let Some(body) = edit.request_body.as_ref() {
+            let headers = edit
+                .request_headers
+                .as_deref()
+                .unwrap_or(&active_request.request_headers);
+            edit.request_headers = Some(sync_content_length(headers, body.len()));
+            edit.request_body_size = Some(body.len());
+        }
+        self.store_edit(active_request, edit)
+    }
+
+    fn store_edit(
         &self,
         active_request: &ReplayRequest,
         edit: ReplayEdit,
@@ -154,7 +177,51 @@ impl ReplayService {
             .map_err(ReplayError::Storage)?
             .ok_or_else(|| ReplayError::InvalidRequest("Replay request not found".to_string()))?;
         let edit = parse_raw_request(raw_request, &request)?;
-        self.apply_edit(&request, edit)
+        self.store_edit(&request, edit)
+    }
+
+    pub fn retarget(
+        &self,
+        active_request: &ReplayRequest,
+        target: ReplayTarget,
+    ) -> Result<ReplayVersion, ReplayError> {
+        let scheme = target
+            .scheme
+            .unwrap_or_else(|| active_request.scheme.clone());
+        let host = target.host.unwrap_or_else(|| active_request.host.clone());
+        let port = target.port.unwrap_or_else(|| {
+            if scheme != active_request.scheme
+                && active_request.port == default_port(&active_request.scheme)
+            {
+                default_port(&scheme)
+            } else {
+                active_request.port
+            }
+        });
+        let host_value = if port == default_port(&scheme) {
+            host.clone()
+        } else {
+            format!("{host}:{port}")
+        };
+        let edit = ReplayEdit {
+            url: Some(build_url(
+                &scheme,
+                &host,
+                port,
+                &active_request.path,
+                active_request.query.as_deref(),
+            )),
+            request_headers: Some(rewrite_host_header(
+                &active_request.request_headers,
+                &host_value,
+            )),
+            label: Some(format!("Retarget to {host_value}")),
+            scheme: Some(scheme),
+            host: Some(host),
+            port: Some(port),
+            ..ReplayEdit::default()
+        };
+        self.store_edit(active_request, edit)
     }
 
     pub fn set_active_version(
@@ -212,9 +279,21 @@ impl ReplayService {
         Ok(execution)
     }
 
+    /// Diffs two stored versions of the same replay request.
+    pub fn diff_versions(
+        &self,
+        request_id: i64,
+        from_version: i64,
+        to_version: i64,
+    ) -> Result<ReplayDiff, ReplayError> {
+        let from = self.load_request_version(request_id, from_version)?;
+        let to = self.load_request_version(request_id, to_version)?;
+        Ok(self.diff_version_pair(&from, &to))
+    }
 
-    pub fn diff_versions(&self, left: &ReplayVersion, right: &ReplayVersion) -> ReplayDiff {
+    pub fn diff_version_pair(&self, left: &ReplayVersion, right: &ReplayVersion) -> ReplayDiff {
         let json = serde_json::json!({
+            "request_line": diff_value(&request_line(left), &request_line(right)),
             "method": diff_value(&left.method, &right.method),
             "scheme": diff_value(&left.scheme, &right.scheme),
             "host": diff_value(&left.host, &right.host),
@@ -223,14 +302,26 @@ impl ReplayService {
             "query": diff_value(&left.query, &right.query),
             "url": diff_value(&left.url, &right.url),
             "http_version": diff_value(&left.http_version, &right.http_version),
-            "headers": diff_bytes(&left.request_headers, &right.request_headers),
-            "body": diff_bytes(&left.request_body, &right.request_body),
+            "headers": diff_headers(&left.request_headers, &right.request_headers),
+            "body": diff_body(&left.request_body, &right.request_body),
         });
         let raw_left = format_request_bytes(left);
         let raw_right = format_request_bytes(right);
         let raw = build_raw_diff(&raw_left, &raw_right);
         ReplayDiff { json, raw }
     }
+
+    fn load_request_version(
+        &self,
+        request_id: i64,
+        version_id: i64,
+    ) -> Result<ReplayVersion, ReplayError> {
+        self.store
+            .get_replay_version(version_id)
+            .map_err(ReplayError::Storage)?
+            .filter(|version| version.replay_request_id == request_id)
+            .ok_or_else(|| ReplayError::InvalidRequest("Replay version not found".to_string()))
+    }
 }
 
 pub async fn send_replay_request(
@@ -250,7 +341,7 @@ pub async fn send_replay_request(
         (version, web_request, started_at)
     };
 
-    let client = Client::new(ClientConfig::default());
+    let client = Client::new(client_config(scope.via_proxy.as_ref()));
     let started = Instant::now();
     let response = client
         .request_with_cancel(web_request, cancel)
@@ -280,6 +371,8 @@ pub async fn send_replay_request(
         scope_rules_version: scope.scope_rules_version,
         capture_filtered: scope.capture_filtered,
         timeline_filtered: scope.timeline_filtered,
+        client_alpn: None,
+        upstream_alpn: None,
     };
     let timeline_response = TimelineResponse {
         timeline_request_id: 0,
@@ -291,6 +384,7 @@ pub async fn send_replay_request(
         response_body_truncated: false,
         http_version: version.http_version.clone(),
         received_at: Utc::now().to_rfc3339(),
+        ttfb_ms: None,
     };
 
     let store = SqliteStore::open(store_path).map_err(ReplayError::Storage)?;
@@ -311,6 +405,123 @@ pub async fn send_replay_request(
     })
 }
 
+fn client_config(via_proxy: Option<&ReplayProxyTarget>) -> ClientConfig {
+    let Some(target) = via_proxy else {
+        return ClientConfig::default();
+    };
+    ClientConfig {
+        proxy: Some(ProxyConfig {
+            host: target.host.clone(),
+            port: target.port,
+            kind: ProxyKind::Http,
+        }),
+        // The live proxy re-signs TLS traffic with the project CA.
+        tls: TlsClientConfig {
+            verify: false,
+            ..TlsClientConfig::default()
+        },
+        ..ClientConfig::default()
+    }
+}
+
+/// Rewrites only the `Content-Length` value; every other byte of the head,
+/// including non-UTF-8 values and the blank terminator line, is kept as-is.
+fn sync_content_length(headers: &[u8], body_len: usize) -> Vec<u8> {
+    fn header_name(line: &[u8]) -> Option<&[u8]> {
+        line.iter()
+            .position(|byte| *byte == b':')
+            .map(|colon| line[..colon].trim_ascii())
+    }
+
+    let lines: Vec<&[u8]> = headers.split_inclusive(|byte| *byte == b'\n').collect();
+    if lines.iter().any(|line| {
+        header_name(line).is_some_and(|name| name.eq_ignore_ascii_case(b"transfer-encoding"))
+    }) {
+        return headers.to_vec();
+    }
+
+    let mut synced = Vec::with_capacity(headers.len() + 24);
+    let mut found = false;
+    for line in &lines {
+        match header_name(line) {
+            Some(name) if name.eq_ignore_ascii_case(b"content-length") => {
+                let colon = line.iter().position(|byte| *byte == b':').unwrap_or(0);
+                let ending = if line.ends_with(b"\r\n") {
+                    &b"\r\n"[..]
+                } else if line.ends_with(b"\n") {
+                    &b"\n"[..]
+                } else {
+                    &[][..]
+                };
+                synced.extend_from_slice(&line[..colon]);
+                synced.extend_from_slice(format!(": {body_len}").as_bytes());
+                synced.extend_from_slice(ending);
+                found = true;
+            }
+            _ => synced.extend_from_slice(line),
+        }
+    }
+    if found || body_len == 0 {
+        return synced;
+    }
+
+    // A missing header goes ahead of the blank line that ends the head.
+    let terminator = lines
+        .iter()
+        .position(|line| line.trim_ascii().is_empty())
+        .map(|index| lines[..index].iter().map(|line| line.len()).sum::<usize>())
+        .unwrap_or(synced.len());
+    let mut header = Vec::new();
+    if terminator > 0 && !synced[..terminator].ends_with(b"\n") {
+        header.extend_from_slice(b"\r\n");
+    }
+    header.extend_from_slice(format!("Content-Length: {body_len}\r\n").as_bytes());
+    synced.splice(terminator..terminator, header);
+    synced
+}
+
+fn rewrite_host_header(headers: &[u8], host: &str) -> Vec<u8> {
+    let text = String::from_utf8_lossy(headers);
+    // Captured HTTP/1 requests keep their request line ahead of the headers.
+    let request_line_len = text
+        .split_inclusive('\n')
+        .next()
+        .filter(|line| {
+            !line.trim().is_empty()
+                && line
+                    .split_once(':')
+                    .is_none_or(|(name, _)| name.contains(char::is_whitespace))
+        })
+        .map_or(0, str::len);
+    let mut found = false;
+    let mut in_head = true;
+    let mut rewritten = String::new();
+    for line in text.split_inclusive('\n') {
+        if in_head && line.trim().is_empty() {
+            in_head = false;
+        }
+        match line.split_once(':') {
+            Some((name, _)) if in_head && name.trim().eq_ignore_ascii_case("host") => {
+                rewritten.push_str(&format!("{}: {host}\r\n", name.trim()));
+                found = true;
+            }
+            _ => rewritten.push_str(line),
+        }
+    }
+    if !found {
+        rewritten.insert_str(request_line_len, &format!("Host: {host}\r\n"));
+    }
+    rewritten.into_bytes()
+}
+
+fn default_port(scheme: &str) -> u16 {
+    if scheme.eq_ignore_ascii_case("https") {
+        443
+    } else {
+        80
+    }
+}
+
 fn diff_value<T: PartialEq + serde::Serialize>(left: &T, right: &T) -> serde_json::Value {
     if left == right {
         serde_json::json!({ "status": "unchanged", "value": left })
@@ -335,6 +546,89 @@ fn diff_bytes(left: &[u8], right: &[u8]) -> serde_json::Value {
     }
 }
 
+fn request_line(version: &ReplayVersion) -> String {
+    let target = match version.query.as_deref() {
+        Some(query) => format!("{}?{}", version.path, query),
+        None => version.path.clone(),
+    };
+    format!("{} {} {}", version.method, target, version.http_version)
+}
+
+/// Compares header blocks by name (case-insensitively), reporting headers
+/// only on one side as added/removed and differing values as changed.
+fn diff_headers(left: &[u8], right: &[u8]) -> serde_json::Value {
+    let left = header_values(left);
+    let right = header_values(right);
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for (name, from) in &left {
+        match right
+            .iter()
+            .find(|(other, _)| other.eq_ignore_ascii_case(name))
+        {
+            None => removed.push(serde_json::json!({ "name": name, "value": from.join(", ") })),
+            Some((_, to)) if to != from => changed.push(serde_json::json!({
+                "name": name,
+                "from": from.join(", "),
+                "to": to.join(", "),
+            })),
+            Some(_) => {}
+        }
+    }
+    for (name, to) in &right {
+        if !left
+            .iter()
+            .any(|(other, _)| other.eq_ignore_ascii_case(name))
+        {
+            added.push(serde_json::json!({ "name": name, "value": to.join(", ") }));
+        }
+    }
+    let status = if added.is_empty() && removed.is_empty() && changed.is_empty() {
+        "unchanged"
+    } else {
+        "changed"
+    };
+    serde_json::json!({
+        "status": status,
+        "added": added,
+        "removed": removed,
+        "changed": changed,
+    })
+}
+
+fn header_values(raw: &[u8]) -> Vec<(String, Vec<String>)> {
+    let mut headers: Vec<(String, Vec<String>)> = Vec::new();
+    for line in String::from_utf8_lossy(raw).lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim();
+        let value = value.trim().to_string();
+        match headers
+            .iter_mut()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+        {
+            Some((_, values)) => values.push(value),
+            None => headers.push((name.to_string(), vec![value])),
+        }
+    }
+    headers
+}
+
+/// Like [`diff_bytes`], but two JSON bodies that only differ in whitespace,
+/// key order or number formatting are reported as `equivalent`.
+fn diff_body(left: &[u8], right: &[u8]) -> serde_json::Value {
+    let mut diff = diff_bytes(left, right);
+    if left != right
+        && let (Ok(left), Ok(right)) = (canonical_json(left), canonical_json(right))
+        && left == right
+    {
+        diff["status"] = serde_json::json!("equivalent");
+    }
+    diff
+}
+
 fn format_request_bytes(version: &ReplayVersion) -> String {
     let mut lines = Vec::new();
     lines.push(format!(
@@ -419,9 +713,10 @@ fn serialize_response_headers(headers: &HeaderMap) -> Vec<u8> {
     bytes
 }
 
-fn map_request_error(error: RequestError) -> ReplayError {
+pub(crate) fn map_request_error(error: RequestError) -> ReplayError {
     match error {
         RequestError::Cancelled => ReplayError::Cancelled,
+        RequestError::DeadlineExceeded => ReplayError::Network("deadline exceeded".to_string()),
         RequestError::Transport(message) => ReplayError::Network(message),
     }
 }
@@ -573,7 +868,13 @@ fn parse_host_port(value: &str, default_port: u16) -> (String, u16) {
     (trimmed.to_string(), default_port)
 }
 
-fn build_url(scheme: &str, host: &str, port: u16, path: &str, query: Option<&str>) -> String {
+pub(crate) fn build_url(
+    scheme: &str,
+    host: &str,
+    port: u16,
+    path: &str,
+    query: Option<&str>,
+) -> String {
     let mut url = format!("{scheme}://{host}:{port}{path}");
     if let Some(query) = query {
         if !query.is_empty() {
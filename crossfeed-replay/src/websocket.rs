@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use crossfeed_web::{CancelToken, Client, Request as WebRequest, Response, WsFrame, WsOpcode};
+
+use crate::ReplayError;
+use crate::service::map_request_error;
+
+const DEFAULT_RESPONSE_WAIT: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsDirection {
+    Sent,
+    Received,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WsReplayMessage {
+    pub direction: WsDirection,
+    pub frame: WsFrame,
+}
+
+#[derive(Debug, Clone)]
+pub struct WsReplayTranscript {
+    pub handshake: Response,
+    pub messages: Vec<WsReplayMessage>,
+}
+
+impl WsReplayTranscript {
+    pub fn received(&self) -> impl Iterator<Item = &WsFrame> {
+        self.messages
+            .iter()
+            .filter(|message| message.direction == WsDirection::Received)
+            .map(|message| &message.frame)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WsReplay {
+    client: Client,
+    response_wait: Duration,
+}
+
+impl WsReplay {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            response_wait: DEFAULT_RESPONSE_WAIT,
+        }
+    }
+
+    pub fn with_response_wait(mut self, response_wait: Duration) -> Self {
+        self.response_wait = response_wait;
+        self
+    }
+
+    pub async fn run(
+        &self,
+        request: WebRequest,
+        frames: &[WsFrame],
+        cancel: CancelToken,
+    ) -> Result<WsReplayTranscript, ReplayError> {
+        let mut socket = self
+            .client
+            .websocket(request, cancel)
+            .await
+            .map_err(map_request_error)?;
+        let handshake = socket.response().clone();
+        let mut messages = Vec::new();
+        let mut open = true;
+        for frame in frames {
+            if !open {
+                break;
+            }
+            socket.send(frame).await.map_err(map_request_error)?;
+            messages.push(WsReplayMessage {
+                direction: WsDirection::Sent,
+                frame: frame.clone(),
+            });
+            while let Ok(received) = tokio::time::timeout(self.response_wait, socket.recv()).await {
+                let Some(received) = received.map_err(map_request_error)? else {
+                    open = false;
+                    break;
+                };
+                let closed = received.opcode == WsOpcode::Close;
+                messages.push(WsReplayMessage {
+                    direction: WsDirection::Received,
+                    frame: received,
+                });
+                if closed {
+                    open = false;
+                    break;
+                }
+            }
+        }
+        if open {
+            socket.close().await.map_err(map_request_error)?;
+        }
+        Ok(WsReplayTranscript {
+            handshake,
+            messages,
+        })
+    }
+}
@@ -0,0 +1,110 @@
+use crossfeed_net::HeaderField;
+use crossfeed_replay::{ReplayError, curl_to_replay_edit, replay_version_to_curl};
+use crossfeed_storage::ReplayVersion;
+
+fn field(name: &str, value: &str) -> HeaderField {
+    HeaderField {
+        name: name.as_bytes().to_vec(),
+        value: value.as_bytes().to_vec(),
+    }
+}
+
+fn version_from_http1(request: &crossfeed_net::Request, scheme: &str) -> ReplayVersion {
+    let host_header = request
+        .headers
+        .iter()
+        .find(|header| header.name.eq_ignore_ascii_case("host"))
+        .map(|header| header.value.clone())
+        .unwrap();
+    let (host, port) = match host_header.rsplit_once(':') {
+        Some((host, port)) => (host.to_string(), port.parse().unwrap()),
+        None => (host_header.clone(), 443),
+    };
+    let (path, query) = match request.line.target.split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query.to_string())),
+        None => (request.line.target.clone(), None),
+    };
+    let mut request_headers = Vec::new();
+    for header in &request.headers {
+        request_headers
+            .extend_from_slice(format!("{}: {}\r\n", header.name, header.value).as_bytes());
+    }
+    ReplayVersion {
+        id: 1,
+        replay_request_id: 1,
+        parent_id: None,
+        label: "captured".to_string(),
+        created_at: "now".to_string(),
+        method: request.line.method.clone(),
+        scheme: scheme.to_string(),
+        url: format!("{scheme}://{host}:{port}{}", request.line.target),
+        host,
+        port,
+        path,
+        query,
+        http_version: "HTTP/1.1".to_string(),
+        request_headers,
+        request_body_size: request.body.len(),
+        request_body: request.body.clone(),
+    }
+}
+
+#[test]
+fn http2_capture_round_trips_through_curl() {
+    let headers = vec![
+        field(":method", "POST"),
+        field(":scheme", "https"),
+        field(":authority", "api.example.com:8443"),
+        field(":path", "/v1/items?tag=a&tag=b"),
+        field("content-type", "application/json"),
+        field("cookie", "a=1"),
+        field("cookie", "b=2"),
+    ];
+    let body = br#"{"name":"it's"}"#;
+    let request = crossfeed_proxy::h2_headers_to_http1(&headers, body).unwrap();
+    let version = version_from_http1(&request, "https");
+
+    let command = replay_version_to_curl(&version);
+    assert!(command.starts_with(
+        "curl --http1.1 -X 'POST' 'https://api.example.com:8443/v1/items?tag=a&tag=b'"
+    ));
+    assert!(command.contains("-H 'cookie: a=1; b=2'"));
+
+    let edit = curl_to_replay_edit(&command).unwrap();
+    assert_eq!(edit.method.as_deref(), Some("POST"));
+    assert_eq!(edit.scheme.as_deref(), Some("https"));
+    assert_eq!(edit.host.as_deref(), Some("api.example.com"));
+    assert_eq!(edit.port, Some(8443));
+    assert_eq!(edit.path.as_deref(), Some("/v1/items"));
+    assert_eq!(edit.query.as_deref(), Some("tag=a&tag=b"));
+    assert_eq!(edit.url.as_deref(), Some(version.url.as_str()));
+    assert_eq!(
+        edit.request_headers.as_deref(),
+        Some(version.request_headers.as_slice())
+    );
+    assert_eq!(edit.request_body.as_deref(), Some(&body[..]));
+    assert_eq!(edit.request_body_size, Some(body.len()));
+}
+
+#[test]
+fn curl_import_accepts_double_quotes_and_continuations() {
+    let command = "curl \"http://example.com/search?q=1\" \\\n  -H \"X-Note: say \\\"hi\\\"\" \\\n  -d 'a=1' -d 'b=2'";
+
+    let edit = curl_to_replay_edit(command).unwrap();
+    assert_eq!(edit.method.as_deref(), Some("POST"));
+    assert_eq!(edit.port, Some(80));
+    assert_eq!(
+        edit.request_headers.as_deref(),
+        Some(&b"X-Note: say \"hi\"\r\n"[..])
+    );
+    assert_eq!(edit.request_body.as_deref(), Some(&b"a=1&b=2"[..]));
+}
+
+#[test]
+fn curl_import_rejects_unsupported_options() {
+    let result = curl_to_replay_edit("curl -F 'file=@x' http://example.com/");
+    assert!(matches!(
+        result,
+        Err(ReplayError::InvalidRequest(message)) if message.contains("-F")
+    ));
+}
@@ -0,0 +1,59 @@
+use crossfeed_replay::{ReplayAssertionResult, ReplayStepResult, replay_results_to_junit};
+
+fn step(name: &str, assertions: Vec<ReplayAssertionResult>) -> ReplayStepResult {
+    ReplayStepResult {
+        name: name.to_string(),
+        duration_ms: 125,
+        assertions,
+    }
+}
+
+fn assertion(name: &str, passed: bool, message: Option<&str>) -> ReplayAssertionResult {
+    ReplayAssertionResult {
+        name: name.to_string(),
+        passed,
+        message: message.map(str::to_string),
+    }
+}
+
+#[test]
+fn junit_renders_passing_assertion_as_empty_testcase() {
+    let xml =
+        replay_results_to_junit(&[step("login", vec![assertion("status is 200", true, None)])]);
+
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(xml.contains(
+        "<testsuites name=\"crossfeed-replay\" tests=\"1\" failures=\"0\" time=\"0.125\">"
+    ));
+    assert!(xml.contains("<testsuite name=\"login\" tests=\"1\" failures=\"0\" time=\"0.125\">"));
+    assert!(xml.contains("<testcase name=\"status is 200\" classname=\"login\" time=\"0.125\"/>"));
+    assert!(!xml.contains("<failure"));
+    assert!(xml.ends_with("</testsuites>\n"));
+}
+
+#[test]
+fn junit_renders_failing_assertion_with_escaped_failure() {
+    let xml = replay_results_to_junit(&[
+        step("login", vec![assertion("status is 200", true, None)]),
+        step(
+            "fetch <profile>",
+            vec![
+                assertion("status is 200", true, None),
+                assertion(
+                    "body has \"id\"",
+                    false,
+                    Some("expected \"id\" & got <none>"),
+                ),
+            ],
+        ),
+    ]);
+
+    assert!(xml.contains("tests=\"3\" failures=\"1\" time=\"0.250\""));
+    assert!(xml.contains(
+        "<testsuite name=\"fetch &lt;profile&gt;\" tests=\"2\" failures=\"1\" time=\"0.125\">"
+    ));
+    assert!(xml.contains(
+        "    <testcase name=\"body has &quot;id&quot;\" classname=\"fetch &lt;profile&gt;\" time=\"0.125\">\n      <failure type=\"AssertionFailure\" message=\"expected &quot;id&quot; &amp; got &lt;none&gt;\">expected &quot;id&quot; &amp; got &lt;none&gt;</failure>\n    </testcase>\n"
+    ));
+    assert_eq!(xml.matches("<failure").count(), 1);
+}
@@ -0,0 +1,169 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crossfeed_proxy::{InterceptDecision, Proxy, ProxyCommand, ProxyConfig, ProxyEventKind};
+use crossfeed_replay::{ReplayProxyTarget, ReplaySendScope, ReplayService, send_replay_request};
+use crossfeed_storage::{SqliteStore, TimelineRequest};
+use crossfeed_web::CancelToken;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+
+async fn start_echo_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let mut response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    request.len()
+                )
+                .into_bytes();
+                response.extend_from_slice(&request);
+                let _ = stream.write_all(&response).await;
+            });
+        }
+    });
+
+    addr
+}
+
+fn timeline_request(addr: SocketAddr) -> TimelineRequest {
+    TimelineRequest {
+        source: "proxy".to_string(),
+        method: "GET".to_string(),
+        scheme: "http".to_string(),
+        host: addr.ip().to_string(),
+        port: addr.port(),
+        path: "/replayed".to_string(),
+        query: None,
+        url: format!("http://{addr}/replayed"),
+        http_version: "HTTP/1.1".to_string(),
+        request_headers: format!("Host: {addr}\r\nX-Stage: original\r\n").into_bytes(),
+        request_body: Vec::new(),
+        request_body_size: 0,
+        request_body_truncated: false,
+        started_at: "now".to_string(),
+        completed_at: None,
+        duration_ms: None,
+        scope_status_at_capture: "in_scope".to_string(),
+        scope_status_current: None,
+        scope_rules_version: 1,
+        capture_filtered: false,
+        timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
+    }
+}
+
+#[tokio::test]
+async fn replay_routed_through_proxy_is_captured_and_rewritten() {
+    let upstream = start_echo_server().await;
+
+    let certs = tempfile::tempdir().unwrap();
+    let mut config = ProxyConfig::default();
+    config.listen.port = 0;
+    config.tls.ca_cert_dir = certs.path().display().to_string();
+    config.tls.leaf_cert_dir = certs.path().join("leaf").display().to_string();
+    let (proxy, mut events, control) = Proxy::new(config).unwrap();
+    let proxy_port = proxy.local_addr().port();
+    tokio::spawn(async move {
+        let _ = proxy.run().await;
+    });
+    control
+        .sender
+        .send(ProxyCommand::SetRequestIntercept(true))
+        .await
+        .unwrap();
+
+    let observed = tokio::spawn(async move {
+        let mut kinds = Vec::new();
+        let mut forwarded = None;
+        while let Some(event) = events.next().await {
+            kinds.push(event.kind.clone());
+            match event.kind {
+                ProxyEventKind::RequestIntercepted => {
+                    let mut request = event.request.unwrap();
+                    let raw = String::from_utf8(request.raw_request).unwrap();
+                    assert_eq!(raw.matches("x-stage: original").count(), 1);
+                    request.raw_request = raw
+                        .replace("x-stage: original", "x-stage: rewritten")
+                        .into_bytes();
+                    control
+                        .sender
+                        .send(ProxyCommand::DecideRequest {
+                            id: event.request_id,
+                            decision: InterceptDecision::Allow(request),
+                        })
+                        .await
+                        .unwrap();
+                }
+                ProxyEventKind::RequestForwarded => {
+                    forwarded = event.request.map(|request| request.raw_request);
+                }
+                ProxyEventKind::ResponseForwarded => break,
+                _ => {}
+            }
+        }
+        (kinds, forwarded)
+    });
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let service = ReplayService::new(SqliteStore::open(file.path()).unwrap());
+    let (request, _version) = service
+        .import_from_timeline(
+            &timeline_request(upstream),
+            "GET /replayed".to_string(),
+            None,
+        )
+        .unwrap();
+    let scope = ReplaySendScope {
+        scope_status_at_capture: "in_scope".to_string(),
+        scope_rules_version: 1,
+        capture_filtered: false,
+        timeline_filtered: false,
+        via_proxy: Some(ReplayProxyTarget {
+            host: "127.0.0.1".to_string(),
+            port: proxy_port,
+        }),
+    };
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        send_replay_request(file.path(), request.id, scope, CancelToken::new()),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+
+    let (kinds, forwarded) = tokio::time::timeout(Duration::from_secs(10), observed)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(kinds.contains(&ProxyEventKind::RequestIntercepted));
+    assert!(kinds.contains(&ProxyEventKind::RequestForwarded));
+    let forwarded = String::from_utf8(forwarded.unwrap()).unwrap();
+    assert!(forwarded.contains("x-stage: rewritten"));
+    assert!(!forwarded.contains("x-stage: original"));
+
+    let response = service
+        .store()
+        .get_response_by_request_id(result.timeline_request_id)
+        .unwrap()
+        .unwrap();
+    let echoed = String::from_utf8_lossy(&response.response_body).to_ascii_lowercase();
+    assert!(echoed.starts_with("get /replayed http/1.1\r\n"));
+    assert!(echoed.contains("x-stage: rewritten"));
+    assert!(!echoed.contains("x-stage: original"));
+}
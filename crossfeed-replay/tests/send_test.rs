@@ -24,6 +24,8 @@ fn sample_timeline_request() -> TimelineRequest {
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
     }
 }
 
@@ -1,4 +1,4 @@
-use crossfeed_replay::{ReplayEdit, ReplayService};
+use crossfeed_replay::{ReplayEdit, ReplayError, ReplayService, ReplayTarget};
 use crossfeed_storage::{ReplayRequest, ReplayVersion, SqliteStore, TimelineRequest};
 
 fn sample_timeline_request() -> TimelineRequest {
@@ -24,6 +24,8 @@ fn sample_timeline_request() -> TimelineRequest {
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
     }
 }
 
@@ -115,6 +117,112 @@ fn apply_edit_creates_new_version() {
     assert_eq!(version.label, "Edit 1");
 }
 
+#[test]
+fn apply_edit_recomputes_content_length_for_body_changes() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+
+    let mut timeline = sample_timeline_request();
+    timeline.method = "POST".to_string();
+    timeline.request_headers = b"Host: example.com\r\ncontent-length: 3\r\n".to_vec();
+    timeline.request_body = b"a=1".to_vec();
+    timeline.request_body_size = 3;
+    let (request, _version) = service
+        .import_from_timeline(&timeline, "POST /".to_string(), None)
+        .unwrap();
+
+    let edit = ReplayEdit {
+        request_body: Some(b"a=12345".to_vec()),
+        ..Default::default()
+    };
+    let version = service.apply_edit(&request, edit).unwrap();
+
+    assert_eq!(
+        version.request_headers,
+        b"Host: example.com\r\ncontent-length: 7\r\n".to_vec()
+    );
+    assert_eq!(version.request_body_size, 7);
+
+    let request = service
+        .store()
+        .get_replay_request(request.id)
+        .unwrap()
+        .unwrap();
+    let edit = ReplayEdit {
+        request_headers: Some(b"Host: example.com\r\n".to_vec()),
+        request_body: Some(b"{}".to_vec()),
+        ..Default::default()
+    };
+    let version = service.apply_edit(&request, edit).unwrap();
+    assert_eq!(
+        version.request_headers,
+        b"Host: example.com\r\nContent-Length: 2\r\n".to_vec()
+    );
+}
+
+#[test]
+fn apply_edit_keeps_non_utf8_header_bytes_and_terminator() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+
+    let mut timeline = sample_timeline_request();
+    timeline.method = "POST".to_string();
+    timeline.request_headers =
+        b"Host: example.com\r\nX-Name: caf\xe9\r\nContent-Length: 3\r\n\r\n".to_vec();
+    timeline.request_body = b"a=1".to_vec();
+    timeline.request_body_size = 3;
+    let (request, _version) = service
+        .import_from_timeline(&timeline, "POST /".to_string(), None)
+        .unwrap();
+
+    let edit = ReplayEdit {
+        request_body: Some(b"a=12345".to_vec()),
+        ..Default::default()
+    };
+    let version = service.apply_edit(&request, edit).unwrap();
+    assert_eq!(
+        version.request_headers,
+        b"Host: example.com\r\nX-Name: caf\xe9\r\nContent-Length: 7\r\n\r\n".to_vec()
+    );
+
+    let request = service
+        .store()
+        .get_replay_request(request.id)
+        .unwrap()
+        .unwrap();
+    let edit = ReplayEdit {
+        request_headers: Some(b"Host: example.com\r\nX-Name: caf\xe9\r\n\r\n".to_vec()),
+        request_body: Some(b"{}".to_vec()),
+        ..Default::default()
+    };
+    let version = service.apply_edit(&request, edit).unwrap();
+    assert_eq!(
+        version.request_headers,
+        b"Host: example.com\r\nX-Name: caf\xe9\r\nContent-Length: 2\r\n\r\n".to_vec()
+    );
+}
+
+#[test]
+fn apply_raw_edit_preserves_typed_content_length() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+
+    let (request, _version) = service
+        .import_from_timeline(&sample_timeline_request(), "GET /".to_string(), None)
+        .unwrap();
+    let raw = "POST /submit HTTP/1.1\r\nHost: example.com\r\nContent-Length: 99\r\n\r\nshort";
+    let version = service.apply_raw_edit(request.id, raw).unwrap();
+
+    assert_eq!(
+        version.request_headers,
+        b"Host: example.com\r\nContent-Length: 99\r\n".to_vec()
+    );
+    assert_eq!(version.request_body, b"short".to_vec());
+}
+
 #[test]
 fn diff_versions_includes_raw_output() {
     let file = tempfile::NamedTempFile::new().unwrap();
@@ -124,8 +232,210 @@ fn diff_versions_includes_raw_output() {
     let left = sample_version();
     let mut right = sample_version();
     right.path = "/other".to_string();
-    let diff = service.diff_versions(&left, &right);
+    let diff = service.diff_version_pair(&left, &right);
 
     assert!(diff.raw.contains("-GET /"));
     assert!(diff.raw.contains("+GET /other"));
 }
+
+#[test]
+fn diff_versions_reports_header_only_changes() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+    let mut timeline = sample_timeline_request();
+    timeline.request_headers = b"Host: example.com\r\nAccept: */*\r\nX-Trace: 1\r\n".to_vec();
+    let (request, original) = service
+        .import_from_timeline(&timeline, "GET /".to_string(), None)
+        .unwrap();
+    let edit = ReplayEdit {
+        request_headers: Some(b"host: example.com\r\nAccept: text/html\r\nX-New: yes\r\n".to_vec()),
+        ..Default::default()
+    };
+    let edited = service.apply_edit(&request, edit).unwrap();
+
+    let diff = service
+        .diff_versions(request.id, original.id, edited.id)
+        .unwrap();
+
+    assert_eq!(diff.json["request_line"]["status"], "unchanged");
+    assert_eq!(diff.json["body"]["status"], "unchanged");
+    let headers = &diff.json["headers"];
+    assert_eq!(headers["status"], "changed");
+    assert_eq!(
+        headers["added"],
+        serde_json::json!([{ "name": "X-New", "value": "yes" }])
+    );
+    assert_eq!(
+        headers["removed"],
+        serde_json::json!([{ "name": "X-Trace", "value": "1" }])
+    );
+    assert_eq!(
+        headers["changed"],
+        serde_json::json!([{ "name": "Accept", "from": "*/*", "to": "text/html" }])
+    );
+}
+
+#[test]
+fn diff_versions_reports_body_only_changes() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+    let mut timeline = sample_timeline_request();
+    timeline.method = "POST".to_string();
+    timeline.request_headers = b"Host: example.com\r\nContent-Length: 3\r\n".to_vec();
+    timeline.request_body = b"a=1".to_vec();
+    timeline.request_body_size = 3;
+    let (request, original) = service
+        .import_from_timeline(&timeline, "POST /".to_string(), None)
+        .unwrap();
+    let edit = ReplayEdit {
+        request_body: Some(b"a=2".to_vec()),
+        ..Default::default()
+    };
+    let edited = service.apply_edit(&request, edit).unwrap();
+
+    let diff = service
+        .diff_versions(request.id, original.id, edited.id)
+        .unwrap();
+
+    assert_eq!(diff.json["request_line"]["value"], "POST / HTTP/1.1");
+    assert_eq!(diff.json["headers"]["status"], "unchanged");
+    assert_eq!(diff.json["body"]["status"], "changed");
+    assert_eq!(diff.json["body"]["from_text"], "a=1");
+    assert_eq!(diff.json["body"]["to_text"], "a=2");
+    assert!(diff.raw.contains("-a=1"));
+    assert!(diff.raw.contains("+a=2"));
+}
+
+#[test]
+fn diff_version_pair_treats_reformatted_json_body_as_equivalent() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+
+    let mut left = sample_version();
+    left.request_body = br#"{"a":1,"b":[true,2.0]}"#.to_vec();
+    let mut right = sample_version();
+    right.request_body = b"{\n  \"b\": [true, 2],\n  \"a\": 1\n}".to_vec();
+    let diff = service.diff_version_pair(&left, &right);
+    assert_eq!(diff.json["body"]["status"], "equivalent");
+
+    right.request_body = br#"{"a":1,"b":[true,3]}"#.to_vec();
+    let diff = service.diff_version_pair(&left, &right);
+    assert_eq!(diff.json["body"]["status"], "changed");
+}
+
+#[test]
+fn diff_versions_rejects_versions_of_another_request() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+    let (first, first_version) = service
+        .import_from_timeline(&sample_timeline_request(), "GET /".to_string(), None)
+        .unwrap();
+    let (_, other_version) = service
+        .import_from_timeline(&sample_timeline_request(), "GET /".to_string(), None)
+        .unwrap();
+
+    assert!(matches!(
+        service.diff_versions(first.id, first_version.id, other_version.id),
+        Err(ReplayError::InvalidRequest(_))
+    ));
+}
+
+#[test]
+fn retarget_rewrites_url_and_host_header() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+    let mut timeline = sample_timeline_request();
+    timeline.path = "/api/items".to_string();
+    timeline.query = Some("page=2".to_string());
+    timeline.request_headers =
+        b"host: example.com\r\nX-Api-Key: secret\r\nAccept: */*\r\n".to_vec();
+    let (request, _) = service
+        .import_from_timeline(&timeline, "GET /api/items".to_string(), None)
+        .unwrap();
+
+    let version = service
+        .retarget(
+            &request,
+            ReplayTarget {
+                scheme: Some("https".to_string()),
+                host: Some("staging.example.com".to_string()),
+                port: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(version.parent_id, request.active_version_id);
+    assert_eq!(version.scheme, "https");
+    assert_eq!(version.host, "staging.example.com");
+    assert_eq!(version.port, 443);
+    assert_eq!(
+        version.url,
+        "https://staging.example.com:443/api/items?page=2"
+    );
+    assert_eq!(
+        version.request_headers,
+        b"host: staging.example.com\r\nX-Api-Key: secret\r\nAccept: */*\r\n".to_vec()
+    );
+}
+
+#[test]
+fn retarget_adds_host_header_after_request_line() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+    let mut timeline = sample_timeline_request();
+    timeline.request_headers = b"GET / HTTP/1.1\r\nAccept: */*\r\n\r\n".to_vec();
+    let (request, _) = service
+        .import_from_timeline(&timeline, "GET /".to_string(), None)
+        .unwrap();
+
+    let version = service
+        .retarget(
+            &request,
+            ReplayTarget {
+                host: Some("staging.example.com".to_string()),
+                ..ReplayTarget::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        version.request_headers,
+        b"GET / HTTP/1.1\r\nHost: staging.example.com\r\nAccept: */*\r\n\r\n".to_vec()
+    );
+}
+
+#[test]
+fn retarget_adds_host_header_with_custom_port() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let service = ReplayService::new(store);
+    let mut timeline = sample_timeline_request();
+    timeline.request_headers = b"Accept: */*\r\n".to_vec();
+    let (request, _) = service
+        .import_from_timeline(&timeline, "GET /".to_string(), None)
+        .unwrap();
+
+    let version = service
+        .retarget(
+            &request,
+            ReplayTarget {
+                port: Some(8080),
+                ..ReplayTarget::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(version.scheme, "http");
+    assert_eq!(version.host, "example.com");
+    assert_eq!(version.url, "http://example.com:8080/");
+    assert_eq!(
+        version.request_headers,
+        b"Host: example.com:8080\r\nAccept: */*\r\n".to_vec()
+    );
+}
@@ -0,0 +1,89 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crossfeed_net::{
+    WsFrameParser, WsOpcode, WsParseStatus, encode_ws_frame, websocket_accept_key,
+};
+use crossfeed_replay::{WsDirection, WsReplay};
+use crossfeed_web::{CancelToken, Client, ClientConfig, Request, WsFrame};
+use http::Uri;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+async fn start_websocket_echo_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut head = Vec::new();
+        let mut buf = [0u8; 4096];
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).await.unwrap();
+            head.extend_from_slice(&buf[..n]);
+        }
+        let head = String::from_utf8(head).unwrap();
+        let key = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("sec-websocket-key")
+                    .then(|| value.trim().to_string())
+            })
+            .unwrap();
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            websocket_accept_key(&key)
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+
+        let mut parser = WsFrameParser::new();
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            let mut status = parser.push(&buf[..n]);
+            while let WsParseStatus::Complete { frame } = status {
+                stream
+                    .write_all(&encode_ws_frame(&frame, None))
+                    .await
+                    .unwrap();
+                if frame.opcode == WsOpcode::Close {
+                    return;
+                }
+                status = parser.push(&[]);
+            }
+        }
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn ws_replay_round_trips_text_frame() {
+    let addr = start_websocket_echo_server().await;
+    let uri: Uri = format!("ws://{}/echo", addr).parse().unwrap();
+    let request = Request::builder(uri).build();
+    let replay = WsReplay::new(Client::new(ClientConfig::default()))
+        .with_response_wait(Duration::from_millis(200));
+
+    let transcript = tokio::time::timeout(
+        Duration::from_secs(10),
+        replay.run(
+            request,
+            &[WsFrame::text("hello replay")],
+            CancelToken::new(),
+        ),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(transcript.handshake.status, 101);
+    assert_eq!(transcript.messages.len(), 2);
+    assert_eq!(transcript.messages[0].direction, WsDirection::Sent);
+    assert_eq!(transcript.messages[1].direction, WsDirection::Received);
+    let received: Vec<_> = transcript.received().collect();
+    assert_eq!(received, vec![&WsFrame::text("hello replay")]);
+}
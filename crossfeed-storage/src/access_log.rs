@@ -0,0 +1,56 @@
+use crossfeed_core::ParsedHeaders;
+
+use crate::sqlite::{ResponseSummary, TimelineRequestSummary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Common,
+    Combined,
+}
+
+pub(crate) fn access_log_line(
+    request: &TimelineRequestSummary,
+    response: Option<&ResponseSummary>,
+    format: LogFormat,
+) -> String {
+    let target = match request.query.as_deref().filter(|query| !query.is_empty()) {
+        Some(query) => format!("{}?{}", request.path, query),
+        None => request.path.clone(),
+    };
+    let request_line = format!("{} {} {}", request.method, target, request.http_version);
+    let status = response
+        .map(|response| response.status_code.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    let bytes = response
+        .map(|response| response.body_size)
+        .filter(|size| *size > 0)
+        .map(|size| size.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    let mut line = format!(
+        "{} - - [{}] \"{}\" {} {}",
+        request.host,
+        log_timestamp(&request.started_at),
+        escape(&request_line),
+        status,
+        bytes
+    );
+    if format == LogFormat::Combined {
+        let headers = ParsedHeaders::parse(&request.request_headers);
+        line.push_str(&format!(
+            " \"{}\" \"{}\"",
+            escape(headers.get("referer").unwrap_or("-")),
+            escape(headers.get("user-agent").unwrap_or("-"))
+        ));
+    }
+    line
+}
+
+fn log_timestamp(started_at: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(started_at)
+        .map(|timestamp| timestamp.format("%d/%b/%Y:%H:%M:%S %z").to_string())
+        .unwrap_or_else(|_| started_at.to_string())
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
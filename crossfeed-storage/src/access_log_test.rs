@@ -0,0 +1,85 @@
+use crate::{
+    LogFormat, SqliteStore, TimelineQuery, TimelineRequest, TimelineResponse, TimelineStore,
+};
+
+fn captured_request() -> TimelineRequest {
+    TimelineRequest {
+        source: "proxy".to_string(),
+        method: "GET".to_string(),
+        scheme: "https".to_string(),
+        host: "example.com".to_string(),
+        port: 443,
+        path: "/search".to_string(),
+        query: Some("q=rust".to_string()),
+        url: "https://example.com/search?q=rust".to_string(),
+        http_version: "HTTP/1.1".to_string(),
+        request_headers: b"Host: example.com\r\nReferer: https://example.com/\r\nUser-Agent: Mozilla/5.0 (X11; \"quoted\")\r\n\r\n".to_vec(),
+        request_body: Vec::new(),
+        request_body_size: 0,
+        request_body_truncated: false,
+        started_at: "2024-03-05T14:07:09+01:00".to_string(),
+        completed_at: None,
+        duration_ms: None,
+        scope_status_at_capture: "in_scope".to_string(),
+        scope_status_current: None,
+        scope_rules_version: 1,
+        capture_filtered: false,
+        timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
+    }
+}
+
+fn captured_response(request_id: i64) -> TimelineResponse {
+    TimelineResponse {
+        timeline_request_id: request_id,
+        status_code: 200,
+        reason: Some("OK".to_string()),
+        response_headers: b"Content-Length: 5\r\n\r\n".to_vec(),
+        response_body: b"hello".to_vec(),
+        response_body_size: 5,
+        response_body_truncated: false,
+        http_version: "HTTP/1.1".to_string(),
+        received_at: "2024-03-05T14:07:09+01:00".to_string(),
+        ttfb_ms: None,
+    }
+}
+
+#[test]
+fn access_log_renders_combined_line_with_quoted_referer_and_user_agent() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let request_id = store.insert_request(captured_request()).unwrap().request_id;
+    store
+        .insert_response(captured_response(request_id))
+        .unwrap();
+
+    let log = store
+        .export_access_log(&TimelineQuery::default(), LogFormat::Combined)
+        .unwrap();
+
+    assert_eq!(
+        log,
+        "example.com - - [05/Mar/2024:14:07:09 +0100] \"GET /search?q=rust HTTP/1.1\" 200 5 \"https://example.com/\" \"Mozilla/5.0 (X11; \\\"quoted\\\")\"\n"
+    );
+}
+
+#[test]
+fn access_log_common_format_omits_headers_and_dashes_missing_response() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let mut request = captured_request();
+    request.request_headers = b"Host: example.com\r\n\r\n".to_vec();
+    store.insert_request(request).unwrap();
+
+    let common = store
+        .export_access_log(&TimelineQuery::default(), LogFormat::Common)
+        .unwrap();
+    assert_eq!(
+        common,
+        "example.com - - [05/Mar/2024:14:07:09 +0100] \"GET /search?q=rust HTTP/1.1\" - -\n"
+    );
+
+    let combined = store
+        .export_access_log(&TimelineQuery::default(), LogFormat::Combined)
+        .unwrap();
+    assert!(combined.ends_with(" - - \"-\" \"-\"\n"));
+}
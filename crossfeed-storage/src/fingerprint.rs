@@ -0,0 +1,64 @@
+use crossfeed_core::ParsedHeaders;
+use sha2::{Digest, Sha256};
+
+use crate::timeline::TimelineRequest;
+
+const IGNORED_HEADERS: &[&str] = &[
+    "connection",
+    "content-length",
+    "date",
+    "keep-alive",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+pub fn request_fingerprint(request: &TimelineRequest) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(request.method.to_ascii_uppercase().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(normalized_url(request).as_bytes());
+    hasher.update(b"\n");
+    for (name, value) in significant_headers(&request.request_headers) {
+        hasher.update(name.as_bytes());
+        hasher.update(b":");
+        hasher.update(value.as_bytes());
+        hasher.update(b"\n");
+    }
+    hasher.update(b"\n");
+    hasher.update(&request.request_body);
+    format!("{:x}", hasher.finalize())
+}
+
+fn normalized_url(request: &TimelineRequest) -> String {
+    let scheme = request.scheme.to_ascii_lowercase();
+    let host = request.host.to_ascii_lowercase();
+    let default_port = match scheme.as_str() {
+        "https" => 443,
+        _ => 80,
+    };
+    let mut url = if request.port == default_port {
+        format!("{scheme}://{host}{}", request.path)
+    } else {
+        format!("{scheme}://{host}:{}{}", request.port, request.path)
+    };
+    if let Some(query) = request.query.as_deref().filter(|query| !query.is_empty()) {
+        let mut pairs: Vec<&str> = query.split('&').filter(|pair| !pair.is_empty()).collect();
+        pairs.sort_unstable();
+        url.push('?');
+        url.push_str(&pairs.join("&"));
+    }
+    url
+}
+
+fn significant_headers(raw: &[u8]) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = ParsedHeaders::parse(raw)
+        .iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.to_string()))
+        .filter(|(name, _)| !IGNORED_HEADERS.contains(&name.as_str()))
+        .collect();
+    headers.sort();
+    headers
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FuzzRun {
+    pub id: i64,
+    pub name: String,
+    pub template: Vec<u8>,
+    pub total_requests: usize,
+    pub completed_index: usize,
+    pub created_at: String,
+    pub updated_at: String,
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// SETTINGS values one side of an HTTP/2 connection advertised to the proxy.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Http2SettingsRecord {
+    pub header_table_size: u32,
+    pub enable_push: bool,
+    pub initial_window_size: u32,
+    pub max_frame_size: u32,
+    pub max_header_list_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Http2ConnectionRecord {
+    pub connection_id: String,
+    pub client_settings: Http2SettingsRecord,
+    pub upstream_settings: Http2SettingsRecord,
+    /// Connection-level send windows at the time of this exchange; stored
+    /// per request, unlike the SETTINGS which are shared by the connection.
+    pub client_send_window: i64,
+    pub upstream_send_window: i64,
+}
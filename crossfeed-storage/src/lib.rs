@@ -1,3 +1,9 @@
+mod access_log;
+#[cfg(test)]
+mod access_log_test;
+mod fingerprint;
+mod fuzz;
+mod http2;
 mod project;
 mod query;
 #[cfg(test)]
@@ -13,8 +19,16 @@ mod sqlite_test;
 mod timeline;
 #[cfg(test)]
 mod timeline_test;
+mod view;
+#[cfg(test)]
+mod view_test;
+mod websocket;
 mod worker;
 
+pub use access_log::LogFormat;
+pub use fingerprint::request_fingerprint;
+pub use fuzz::FuzzRun;
+pub use http2::{Http2ConnectionRecord, Http2SettingsRecord};
 pub use project::{
     BodyLimitsConfig, ProjectConfig, ProjectLayout, ProjectPaths, ProxyProjectConfig,
     ProxyProtocolMode, TimelineConfig,
@@ -28,6 +42,11 @@ pub use timeline::{
     BodyLimits, TimelineInsertResult, TimelineRecorder, TimelineRequest, TimelineResponse,
     TimelineStore,
 };
+pub use view::SavedView;
+pub use websocket::{
+    TimelineWebSocketFrame, WEBSOCKET_CLIENT_TO_SERVER, WEBSOCKET_SERVER_TO_CLIENT,
+};
 pub use worker::{
-    TimelineEvent, TimelineWorkerConfig, TimelineWorkerHandle, spawn_timeline_worker,
+    TimelineEvent, TimelineWorkerConfig, TimelineWorkerHandle, TimelineWorkerHealth,
+    spawn_timeline_worker,
 };
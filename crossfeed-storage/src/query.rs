@@ -4,14 +4,26 @@ use serde::{Deserialize, Serialize};
 pub struct TimelineQuery {
     pub host: Option<String>,
     pub method: Option<String>,
+    #[serde(default)]
+    pub methods: Vec<String>,
     pub status: Option<u16>,
+    #[serde(default)]
+    pub status_classes: Vec<u16>,
+    #[serde(default)]
+    pub status_range: Option<(u16, u16)>,
     pub scope_status: Option<String>,
+    #[serde(default)]
+    pub scope_status_current: Option<String>,
     pub source: Option<String>,
     pub search: Option<String>,
     pub path_exact: Option<String>,
     pub path_prefix: Option<String>,
     pub path_contains: Option<String>,
     pub path_case_sensitive: bool,
+    #[serde(default)]
+    pub path_regex: Option<String>,
+    #[serde(default)]
+    pub url_regex: Option<String>,
     pub tags_any: Vec<String>,
     pub since: Option<String>,
     pub until: Option<String>,
@@ -26,14 +38,20 @@ impl Default for TimelineQuery {
         Self {
             host: None,
             method: None,
+            methods: Vec::new(),
             status: None,
+            status_classes: Vec::new(),
+            status_range: None,
             scope_status: None,
+            scope_status_current: None,
             source: None,
             search: None,
             path_exact: None,
             path_prefix: None,
             path_contains: None,
             path_case_sensitive: false,
+            path_regex: None,
+            url_regex: None,
             tags_any: Vec::new(),
             since: None,
             until: None,
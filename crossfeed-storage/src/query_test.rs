@@ -28,6 +28,8 @@ fn sample_request(url: &str, path: &str, method: &str, source: &str) -> Timeline
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
     }
 }
 
@@ -42,6 +44,7 @@ fn sample_response(request_id: i64, status_code: u16) -> TimelineResponse {
         response_body_truncated: false,
         http_version: "HTTP/1.1".to_string(),
         received_at: "2024-01-01T00:00:01Z".to_string(),
+        ttfb_ms: None,
     }
 }
 
@@ -79,6 +82,182 @@ fn fts_search_finds_request() {
     assert_eq!(results.len(), 1);
 }
 
+#[test]
+fn search_bodies_matches_request_and_response_bodies() {
+    let file = NamedTempFile::new().unwrap();
+    let config = SqliteConfig {
+        fts: crate::sqlite::FtsConfig {
+            enabled: true,
+            index_headers: true,
+            index_request_body: true,
+            index_response_body: true,
+        },
+    };
+    let store = SqliteStore::open_with_config(file.path(), config).unwrap();
+
+    let bodies = [
+        (
+            "/login",
+            "2024-01-01T00:00:01Z",
+            "user=alice&token=swordfish",
+            "welcome",
+        ),
+        (
+            "/profile",
+            "2024-01-01T00:00:02Z",
+            "",
+            "{\"session\":\"swordfish\"}",
+        ),
+        ("/logout", "2024-01-01T00:00:03Z", "bye", "goodbye"),
+    ];
+    for (path, started_at, request_body, response_body) in bodies {
+        let url = format!("http://example.com{path}");
+        let mut request = sample_request(&url, path, "POST", "proxy");
+        request.started_at = started_at.to_string();
+        request.request_body = request_body.as_bytes().to_vec();
+        let id = store.insert_request(request).unwrap().request_id;
+        let mut response = sample_response(id, 200);
+        response.response_body = response_body.as_bytes().to_vec();
+        store.insert_response(response).unwrap();
+    }
+
+    let paths = |term: &str, limit: usize| {
+        store
+            .search_bodies(term, limit)
+            .unwrap()
+            .into_iter()
+            .map(|summary| summary.path)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(paths("swordfish", 10), vec!["/profile", "/login"]);
+    assert_eq!(paths("swordfish", 1), vec!["/profile"]);
+    assert_eq!(paths("goodbye", 10), vec!["/logout"]);
+    assert!(paths("example", 10).is_empty());
+    assert!(paths("\"unbalanced", 10).is_empty());
+}
+
+fn fts_config() -> SqliteConfig {
+    SqliteConfig {
+        fts: crate::sqlite::FtsConfig {
+            enabled: true,
+            index_headers: true,
+            index_request_body: true,
+            index_response_body: true,
+        },
+    }
+}
+
+fn insert_with_response_body(store: &SqliteStore, path: &str, response_body: &str) -> i64 {
+    let url = format!("http://example.com{path}");
+    let id = store
+        .insert_request(sample_request(&url, path, "GET", "proxy"))
+        .unwrap()
+        .request_id;
+    let mut response = sample_response(id, 200);
+    response.response_body = response_body.as_bytes().to_vec();
+    store.insert_response(response).unwrap();
+    id
+}
+
+#[test]
+fn search_bodies_matches_substrings_literally() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open_with_config(file.path(), fts_config()).unwrap();
+    insert_with_response_body(&store, "/fish", "token=swordfish");
+    insert_with_response_body(&store, "/discount", "50% off");
+    insert_with_response_body(&store, "/plain", "500 off");
+
+    let paths = |term: &str| {
+        store
+            .search_bodies_substring(term, 10)
+            .unwrap()
+            .into_iter()
+            .map(|summary| summary.path)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(paths("ordfi"), vec!["/fish"]);
+    assert_eq!(paths("50%"), vec!["/discount"]);
+    assert_eq!(paths("0_ off"), Vec::<String>::new());
+}
+
+#[test]
+fn search_bodies_matches_whole_tokens() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open_with_config(file.path(), fts_config()).unwrap();
+    insert_with_response_body(&store, "/fish", "token=swordfish");
+    insert_with_response_body(&store, "/greeting", "hello there world");
+
+    let paths = |term: &str| {
+        store
+            .search_bodies(term, 10)
+            .unwrap()
+            .into_iter()
+            .map(|summary| summary.path)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(paths("swordfish"), vec!["/fish"]);
+    assert_eq!(paths("ordfi"), Vec::<String>::new());
+    assert_eq!(paths("hello there"), vec!["/greeting"]);
+    assert_eq!(paths("hello world"), Vec::<String>::new());
+    assert_eq!(paths("say \"hi\""), Vec::<String>::new());
+}
+
+#[test]
+fn search_bodies_survives_request_updates() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open_with_config(file.path(), fts_config()).unwrap();
+    let id = insert_with_response_body(&store, "/secret", "swordfish");
+
+    store
+        .recompute_scope_status(2, |_, _, _, _| "out_of_scope".to_string())
+        .unwrap();
+    let conn = rusqlite::Connection::open(file.path()).unwrap();
+    conn.execute(
+        "UPDATE timeline_requests SET url = 'http://example.com/renamed' WHERE id = ?1",
+        [id],
+    )
+    .unwrap();
+
+    let results = store.search_bodies("swordfish", 10).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].url, "http://example.com/renamed");
+    assert_eq!(store.search_bodies("hello body", 10).unwrap().len(), 1);
+}
+
+#[test]
+fn reopening_store_replaces_legacy_fts_triggers() {
+    let file = NamedTempFile::new().unwrap();
+    {
+        let store = SqliteStore::open_with_config(file.path(), fts_config()).unwrap();
+        insert_with_response_body(&store, "/legacy", "swordfish");
+    }
+    let conn = rusqlite::Connection::open(file.path()).unwrap();
+    conn.execute_batch(
+        "UPDATE timeline_requests SET started_at_ms = NULL;
+         DROP TRIGGER timeline_requests_fts_update;
+         CREATE TRIGGER timeline_requests_fts_update AFTER UPDATE ON timeline_requests BEGIN
+             INSERT INTO timeline_requests_fts(timeline_requests_fts, rowid, url, host, path, query, request_headers, request_body, response_headers, response_body)
+             VALUES('delete', old.id, old.url, old.host, old.path, old.query, '', '', '', '');
+         END;",
+    )
+    .unwrap();
+    drop(conn);
+
+    let store = SqliteStore::open_with_config(file.path(), fts_config()).unwrap();
+    assert_eq!(store.search_bodies("swordfish", 10).unwrap().len(), 1);
+}
+
+#[test]
+fn search_bodies_requires_fts() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+
+    let err = store.search_bodies("anything", 10).unwrap_err();
+    assert!(err.contains("full-text search is disabled"));
+    let err = store.search_bodies_substring("anything", 10).unwrap_err();
+    assert!(err.contains("full-text search is disabled"));
+}
+
 #[test]
 fn query_filters_by_path_variants() {
     let file = NamedTempFile::new().unwrap();
@@ -294,3 +473,248 @@ fn high_volume_inserts_support_filtering() {
         .unwrap();
     assert_eq!(status_results.len(), 2500);
 }
+
+#[test]
+fn bulk_tagging_applies_only_to_matching_rows() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+
+    let mut ids = Vec::new();
+    for (path, method) in [
+        ("/api/users", "GET"),
+        ("/api/orders", "POST"),
+        ("/static/app.js", "GET"),
+    ] {
+        let url = format!("http://example.com{path}");
+        ids.push(
+            store
+                .insert_request(sample_request(&url, path, method, "proxy"))
+                .unwrap()
+                .request_id,
+        );
+    }
+
+    let query = TimelineQuery {
+        path_prefix: Some("/api".to_string()),
+        ..TimelineQuery::default()
+    };
+    assert_eq!(store.tag_matching(&query, "api").unwrap(), 2);
+    assert_eq!(store.tag_matching(&query, "api").unwrap(), 0);
+
+    let tags = store.get_request_tags(&ids).unwrap();
+    assert_eq!(tags.get(&ids[0]), Some(&vec!["api".to_string()]));
+    assert_eq!(tags.get(&ids[1]), Some(&vec!["api".to_string()]));
+    assert_eq!(tags.get(&ids[2]), None);
+
+    assert_eq!(store.add_tag_to(&[ids[2], 9999], "review").unwrap(), 1);
+    assert_eq!(store.remove_tag_from(&[ids[0], ids[2]], "api").unwrap(), 1);
+
+    let tags = store.get_request_tags(&ids).unwrap();
+    assert_eq!(tags.get(&ids[0]), None);
+    assert_eq!(tags.get(&ids[1]), Some(&vec!["api".to_string()]));
+    assert_eq!(tags.get(&ids[2]), Some(&vec!["review".to_string()]));
+}
+
+#[test]
+fn query_filters_by_path_and_url_regex() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+
+    let mut ids = Vec::new();
+    for path in [
+        "/api/v1/users/42",
+        "/api/v2/users/7",
+        "/api/v2/users/me",
+        "/api/users/1",
+    ] {
+        let url = format!("http://example.com{path}");
+        ids.push(
+            store
+                .insert_request(sample_request(&url, path, "GET", "proxy"))
+                .unwrap()
+                .request_id,
+        );
+    }
+
+    let query = TimelineQuery {
+        path_regex: Some(r"^/api/v\d+/users/\d+$".to_string()),
+        ..TimelineQuery::default()
+    };
+    let mut matched: Vec<i64> = store
+        .query_request_summaries(&query, TimelineSort::StartedAtAsc)
+        .unwrap()
+        .into_iter()
+        .map(|summary| summary.id)
+        .collect();
+    matched.sort_unstable();
+    assert_eq!(matched, vec![ids[0], ids[1]]);
+
+    let query = TimelineQuery {
+        url_regex: Some(r"://example\.com/api/v2/".to_string()),
+        ..TimelineQuery::default()
+    };
+    let results = store
+        .query_request_summaries(&query, TimelineSort::StartedAtAsc)
+        .unwrap();
+    assert_eq!(results.len(), 2);
+}
+
+#[test]
+fn query_rejects_invalid_regex() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let query = TimelineQuery {
+        path_regex: Some("/api/(v1".to_string()),
+        ..TimelineQuery::default()
+    };
+
+    let err = store
+        .query_request_summaries(&query, TimelineSort::StartedAtDesc)
+        .unwrap_err();
+    assert!(err.starts_with("invalid regex \"/api/(v1\""), "{err}");
+}
+
+#[test]
+fn query_filters_by_method_set_and_status_class() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+
+    let cases = [
+        ("/list", "GET", Some(200)),
+        ("/create", "POST", Some(201)),
+        ("/missing", "GET", Some(404)),
+        ("/denied", "POST", Some(403)),
+        ("/broken", "DELETE", Some(500)),
+        ("/pending", "POST", None),
+    ];
+    for (path, method, status) in cases {
+        let url = format!("http://example.com{path}");
+        let id = store
+            .insert_request(sample_request(&url, path, method, "proxy"))
+            .unwrap()
+            .request_id;
+        if let Some(status) = status {
+            store.insert_response(sample_response(id, status)).unwrap();
+        }
+    }
+
+    let paths = |query: &TimelineQuery| {
+        let mut paths: Vec<String> = store
+            .query_request_summaries(query, TimelineSort::StartedAtAsc)
+            .unwrap()
+            .into_iter()
+            .map(|summary| summary.path)
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    let post_only = TimelineQuery {
+        methods: vec!["post".to_string()],
+        ..TimelineQuery::default()
+    };
+    assert_eq!(paths(&post_only), vec!["/create", "/denied", "/pending"]);
+
+    let client_errors = TimelineQuery {
+        status_classes: vec![4],
+        ..TimelineQuery::default()
+    };
+    assert_eq!(paths(&client_errors), vec!["/denied", "/missing"]);
+
+    let writes_failing = TimelineQuery {
+        methods: vec!["POST".to_string(), "DELETE".to_string()],
+        status_classes: vec![4, 5],
+        ..TimelineQuery::default()
+    };
+    assert_eq!(paths(&writes_failing), vec!["/broken", "/denied"]);
+}
+
+#[test]
+fn query_filters_by_inclusive_status_range() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+
+    for (path, status) in [
+        ("/ok", Some(200)),
+        ("/teapot", Some(499)),
+        ("/error", Some(500)),
+        ("/gateway", Some(599)),
+        ("/custom", Some(600)),
+        ("/pending", None),
+    ] {
+        let url = format!("http://example.com{path}");
+        let id = store
+            .insert_request(sample_request(&url, path, "GET", "proxy"))
+            .unwrap()
+            .request_id;
+        if let Some(status) = status {
+            store.insert_response(sample_response(id, status)).unwrap();
+        }
+    }
+
+    let paths = |query: &TimelineQuery| {
+        let mut paths: Vec<String> = store
+            .query_request_summaries(query, TimelineSort::StartedAtAsc)
+            .unwrap()
+            .into_iter()
+            .map(|summary| summary.path)
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    let server_errors = TimelineQuery {
+        status_range: Some((500, 599)),
+        ..TimelineQuery::default()
+    };
+    assert_eq!(paths(&server_errors), vec!["/error", "/gateway"]);
+
+    let failures = TimelineQuery {
+        status_range: Some((400, 599)),
+        ..TimelineQuery::default()
+    };
+    assert_eq!(paths(&failures), vec!["/error", "/gateway", "/teapot"]);
+}
+
+#[test]
+fn query_filters_in_scope_only() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+
+    for (path, at_capture, current) in [
+        ("/app", "in_scope", Some("in_scope")),
+        ("/cdn", "out_of_scope", Some("out_of_scope")),
+        ("/moved", "in_scope", Some("out_of_scope")),
+        ("/legacy", "in_scope", None),
+    ] {
+        let url = format!("http://example.com{path}");
+        let mut request = sample_request(&url, path, "GET", "proxy");
+        request.scope_status_at_capture = at_capture.to_string();
+        request.scope_status_current = current.map(str::to_string);
+        store.insert_request(request).unwrap();
+    }
+
+    let paths = |query: &TimelineQuery| {
+        let mut paths: Vec<String> = store
+            .query_request_summaries(query, TimelineSort::StartedAtAsc)
+            .unwrap()
+            .into_iter()
+            .map(|summary| summary.path)
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    assert_eq!(paths(&TimelineQuery::default()).len(), 4);
+
+    let in_scope_only = TimelineQuery {
+        scope_status: Some("in_scope".to_string()),
+        ..TimelineQuery::default()
+    };
+    assert_eq!(paths(&in_scope_only), vec!["/app", "/legacy", "/moved"]);
+
+    let in_scope_now = TimelineQuery {
+        scope_status_current: Some("in_scope".to_string()),
+        ..TimelineQuery::default()
+    };
+    assert_eq!(paths(&in_scope_now), vec!["/app"]);
+}
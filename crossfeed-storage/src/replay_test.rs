@@ -27,6 +27,8 @@ fn sample_timeline_request() -> TimelineRequest {
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
     }
 }
 
@@ -130,3 +132,50 @@ fn replay_storage_inserts_execution() {
     let execution_id = store.insert_replay_execution(&execution).unwrap();
     assert!(execution_id > 0);
 }
+
+#[test]
+fn prune_replay_versions_keeps_active_and_newest() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+
+    let timeline_request_id = store
+        .insert_request(sample_timeline_request())
+        .unwrap()
+        .request_id;
+    let request_id = store
+        .create_replay_request(&sample_replay_request(timeline_request_id))
+        .unwrap();
+    let mut version_ids = Vec::new();
+    let mut parent_id = None;
+    for index in 0..5 {
+        let label = format!("Edit {index}");
+        let id = store
+            .insert_replay_version(&sample_replay_version(request_id, parent_id, &label))
+            .unwrap();
+        version_ids.push(id);
+        parent_id = Some(id);
+    }
+    store
+        .update_replay_active_version(request_id, version_ids[1], "now")
+        .unwrap();
+
+    let deleted = store.prune_replay_versions(request_id, 2).unwrap();
+
+    assert_eq!(deleted, 2);
+    let remaining = store.list_replay_versions(request_id).unwrap();
+    let remaining_ids: Vec<i64> = remaining.iter().map(|version| version.id).collect();
+    assert_eq!(
+        remaining_ids,
+        vec![version_ids[1], version_ids[3], version_ids[4]]
+    );
+    assert_eq!(remaining[0].parent_id, None);
+    assert_eq!(remaining[2].parent_id, Some(version_ids[3]));
+    assert_eq!(
+        store
+            .get_replay_active_version(request_id)
+            .unwrap()
+            .unwrap()
+            .id,
+        version_ids[1]
+    );
+}
@@ -66,7 +66,15 @@ impl SchemaCatalog {
     scope_status_current TEXT,\
     scope_rules_version INTEGER NOT NULL DEFAULT 1,\
     capture_filtered INTEGER NOT NULL DEFAULT 0,\
-    timeline_filtered INTEGER NOT NULL DEFAULT 0\
+    timeline_filtered INTEGER NOT NULL DEFAULT 0,\
+    request_fingerprint TEXT,\
+    started_at_ms INTEGER,\
+    client_alpn TEXT,\
+    upstream_alpn TEXT,\
+    http2_connection_id TEXT,\
+    websocket_connection_id TEXT,\
+    http2_client_send_window INTEGER,\
+    http2_upstream_send_window INTEGER\
 )"
                     .to_string(),
                     indices: vec![
@@ -97,7 +105,8 @@ impl SchemaCatalog {
     response_body_size INTEGER NOT NULL DEFAULT 0,\
     response_body_truncated INTEGER NOT NULL DEFAULT 0,\
     http_version TEXT NOT NULL,\
-    received_at TEXT NOT NULL\
+    received_at TEXT NOT NULL,\
+    ttfb_ms INTEGER\
 )"
                     .to_string(),
                     indices: vec![
@@ -107,6 +116,35 @@ impl SchemaCatalog {
                             .to_string(),
                     ],
                 },
+                TableSpec {
+                    name: "http2_connections".to_string(),
+                    create_sql: "CREATE TABLE IF NOT EXISTS http2_connections (\
+    id INTEGER PRIMARY KEY,\
+    connection_id TEXT NOT NULL UNIQUE,\
+    client_settings_json TEXT NOT NULL,\
+    upstream_settings_json TEXT NOT NULL,\
+    client_send_window INTEGER NOT NULL,\
+    upstream_send_window INTEGER NOT NULL\
+)"
+                    .to_string(),
+                    indices: vec![],
+                },
+                TableSpec {
+                    name: "timeline_websocket_frames".to_string(),
+                    create_sql: "CREATE TABLE IF NOT EXISTS timeline_websocket_frames (\
+    id INTEGER PRIMARY KEY,\
+    connection_id TEXT NOT NULL,\
+    direction TEXT NOT NULL,\
+    opcode INTEGER NOT NULL,\
+    payload_len INTEGER NOT NULL,\
+    captured_at TEXT NOT NULL\
+)"
+                    .to_string(),
+                    indices: vec![
+                        "CREATE INDEX idx_timeline_websocket_frames_connection ON timeline_websocket_frames(connection_id)"
+                            .to_string(),
+                    ],
+                },
                 TableSpec {
                     name: "replay_collections".to_string(),
                     create_sql: "CREATE TABLE IF NOT EXISTS replay_collections (\
@@ -201,6 +239,20 @@ impl SchemaCatalog {
                             .to_string(),
                     ],
                 },
+                TableSpec {
+                    name: "fuzz_runs".to_string(),
+                    create_sql: "CREATE TABLE IF NOT EXISTS fuzz_runs (\
+    id INTEGER PRIMARY KEY,\
+    name TEXT NOT NULL,\
+    template BLOB NOT NULL,\
+    total_requests INTEGER NOT NULL DEFAULT 0,\
+    completed_index INTEGER NOT NULL DEFAULT 0,\
+    created_at TEXT NOT NULL,\
+    updated_at TEXT NOT NULL\
+)"
+                    .to_string(),
+                    indices: vec![],
+                },
                 TableSpec {
                     name: "tags".to_string(),
                     create_sql: "CREATE TABLE IF NOT EXISTS tags (\
@@ -221,6 +273,18 @@ impl SchemaCatalog {
                     indices: vec!["CREATE INDEX idx_timeline_request_tags_tag_id ON timeline_request_tags(tag_id)"
                         .to_string()],
                 },
+                TableSpec {
+                    name: "saved_views".to_string(),
+                    create_sql: "CREATE TABLE IF NOT EXISTS saved_views (\
+    id INTEGER PRIMARY KEY,\
+    name TEXT NOT NULL UNIQUE,\
+    query_json TEXT NOT NULL,\
+    sort_json TEXT NOT NULL,\
+    created_at TEXT NOT NULL\
+)"
+                    .to_string(),
+                    indices: vec![],
+                },
                 TableSpec {
                     name: "scope_rules".to_string(),
                     create_sql: "CREATE TABLE IF NOT EXISTS scope_rules (\
@@ -230,7 +294,9 @@ impl SchemaCatalog {
     target TEXT NOT NULL,\
     pattern TEXT NOT NULL,\
     enabled INTEGER NOT NULL DEFAULT 1,\
-    created_at TEXT NOT NULL\
+    created_at TEXT NOT NULL,\
+    scheme TEXT,\
+    port INTEGER\
 )"
                     .to_string(),
                     indices: vec![
@@ -284,12 +350,16 @@ mod tests {
             "timeline_sources",
             "timeline_requests",
             "timeline_responses",
+            "http2_connections",
+            "timeline_websocket_frames",
             "replay_collections",
             "replay_requests",
             "replay_versions",
             "replay_executions",
+            "fuzz_runs",
             "tags",
             "timeline_request_tags",
+            "saved_views",
             "scope_rules",
         ] {
             assert!(names.contains(&required), "missing table {required}");
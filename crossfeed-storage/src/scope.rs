@@ -5,6 +5,8 @@ pub struct ScopeRuleRow {
     pub pattern_type: String,
     pub target: String,
     pub pattern: String,
+    pub scheme: Option<String>,
+    pub port: Option<u16>,
     pub enabled: bool,
     pub created_at: String,
 }
@@ -1,13 +1,20 @@
 use std::collections::HashMap;
 use std::path::Path;
 
+use rusqlite::functions::FunctionFlags;
 use rusqlite::{Connection, OptionalExtension, Row, params};
 
+use crate::access_log::{LogFormat, access_log_line};
+use crate::fingerprint::request_fingerprint;
+use crate::fuzz::FuzzRun;
+use crate::http2::Http2ConnectionRecord;
 use crate::query::{TimelineQuery, TimelineSort};
 use crate::replay::{ReplayCollection, ReplayExecution, ReplayRequest, ReplayVersion};
 use crate::scope::ScopeRuleRow;
 use crate::schema::SchemaCatalog;
 use crate::timeline::{TimelineInsertResult, TimelineRequest, TimelineResponse, TimelineStore};
+use crate::view::SavedView;
+use crate::websocket::TimelineWebSocketFrame;
 
 #[derive(Debug, Clone)]
 pub struct FtsConfig {
@@ -63,6 +70,8 @@ pub struct TimelineRequestSummary {
     pub scope_rules_version: i64,
     pub capture_filtered: bool,
     pub timeline_filtered: bool,
+    pub client_alpn: Option<String>,
+    pub upstream_alpn: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -72,6 +81,7 @@ pub struct ResponseSummary {
     pub header_count: usize,
     pub body_size: usize,
     pub body_truncated: bool,
+    pub ttfb_ms: Option<i64>,
 }
 
 impl SqliteStore {
@@ -104,6 +114,21 @@ impl SqliteStore {
         self.conn
             .pragma_update(None, "synchronous", "NORMAL")
             .map_err(|err| err.to_string())?;
+        self.conn
+            .create_scalar_function(
+                "regexp",
+                2,
+                FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+                |ctx| {
+                    let pattern = ctx.get_or_create_aux(0, |value| {
+                        regex::Regex::new(value.as_str()?)
+                            .map_err(|err| rusqlite::Error::UserFunctionError(Box::new(err)))
+                    })?;
+                    let value = ctx.get_raw(1).as_str().unwrap_or_default();
+                    Ok(pattern.is_match(value))
+                },
+            )
+            .map_err(|err| err.to_string())?;
 
         let schema = SchemaCatalog::v1();
         for table in schema.tables {
@@ -129,10 +154,42 @@ impl SqliteStore {
             "sort_index",
             "INTEGER NOT NULL DEFAULT 0",
         )?;
-
+        self.ensure_column("scope_rules", "scheme", "TEXT")?;
+        self.ensure_column("scope_rules", "port", "INTEGER")?;
+        self.ensure_column("timeline_requests", "request_fingerprint", "TEXT")?;
+        self.conn
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_timeline_requests_fingerprint ON timeline_requests(request_fingerprint)",
+                [],
+            )
+            .map_err(|err| err.to_string())?;
+        self.ensure_column("timeline_requests", "started_at_ms", "INTEGER")?;
+        self.ensure_column("timeline_requests", "client_alpn", "TEXT")?;
+        self.ensure_column("timeline_requests", "upstream_alpn", "TEXT")?;
+        self.ensure_column("timeline_requests", "http2_connection_id", "TEXT")?;
+        self.ensure_column("timeline_requests", "websocket_connection_id", "TEXT")?;
+        self.ensure_column("timeline_responses", "ttfb_ms", "INTEGER")?;
+        self.ensure_column("timeline_requests", "http2_client_send_window", "INTEGER")?;
+        self.ensure_column("timeline_requests", "http2_upstream_send_window", "INTEGER")?;
+        // The FTS triggers must be current before any migration UPDATE fires them.
         if self.config.fts.enabled {
             self.create_fts_tables()?;
         }
+        self.conn
+            .execute(
+                &format!(
+                    "UPDATE timeline_requests SET started_at_ms = {} WHERE started_at_ms IS NULL",
+                    epoch_ms_sql("started_at")
+                ),
+                [],
+            )
+            .map_err(|err| err.to_string())?;
+        self.conn
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_timeline_requests_started_at_ms ON timeline_requests(started_at_ms)",
+                [],
+            )
+            .map_err(|err| err.to_string())?;
 
         Ok(())
     }
@@ -154,16 +211,25 @@ impl SqliteStore {
             )
             .map_err(|err| err.to_string())?;
 
+        // Older stores created these two triggers with the external-content
+        // 'delete' command, which a regular FTS5 table rejects, so they are
+        // always replaced rather than created if missing.
+        self.conn
+            .execute_batch(
+                "DROP TRIGGER IF EXISTS timeline_requests_fts_delete;\n                DROP TRIGGER IF EXISTS timeline_requests_fts_update;",
+            )
+            .map_err(|err| err.to_string())?;
+
         self.conn
             .execute(
-                "CREATE TRIGGER IF NOT EXISTS timeline_requests_fts_delete AFTER DELETE ON timeline_requests BEGIN\n                    INSERT INTO timeline_requests_fts(timeline_requests_fts, rowid, url, host, path, query, request_headers, request_body, response_headers, response_body)\n                    VALUES('delete', old.id, old.url, old.host, old.path, old.query, '', '', '', '');\n                END;",
+                "CREATE TRIGGER timeline_requests_fts_delete AFTER DELETE ON timeline_requests BEGIN\n                    DELETE FROM timeline_requests_fts WHERE rowid = old.id;\n                END;",
                 [],
             )
             .map_err(|err| err.to_string())?;
 
         self.conn
             .execute(
-                "CREATE TRIGGER IF NOT EXISTS timeline_requests_fts_update AFTER UPDATE ON timeline_requests BEGIN\n                    INSERT INTO timeline_requests_fts(timeline_requests_fts, rowid, url, host, path, query, request_headers, request_body, response_headers, response_body)\n                    VALUES('delete', old.id, old.url, old.host, old.path, old.query, '', '', '', '');\n                    INSERT INTO timeline_requests_fts (rowid, url, host, path, query, request_headers, request_body, response_headers, response_body)\n                    VALUES (new.id, new.url, new.host, new.path, new.query,\n                            CASE WHEN NEW.request_headers IS NOT NULL THEN CAST(NEW.request_headers AS TEXT) ELSE '' END,\n                            CASE WHEN NEW.request_body IS NOT NULL THEN CAST(NEW.request_body AS TEXT) ELSE '' END,\n                            '', '');\n                END;",
+                "CREATE TRIGGER timeline_requests_fts_update AFTER UPDATE OF url, host, path, query, request_headers, request_body ON timeline_requests BEGIN\n                    UPDATE timeline_requests_fts\n                    SET url = new.url, host = new.host, path = new.path, query = new.query,\n                        request_headers = CASE WHEN NEW.request_headers IS NOT NULL THEN CAST(NEW.request_headers AS TEXT) ELSE '' END,\n                        request_body = CASE WHEN NEW.request_body IS NOT NULL THEN CAST(NEW.request_body AS TEXT) ELSE '' END\n                    WHERE rowid = new.id;\n                END;",
                 [],
             )
             .map_err(|err| err.to_string())?;
@@ -220,13 +286,17 @@ impl SqliteStore {
         let source_id = self.ensure_source_id(&request.source)?;
         self.conn
             .execute(
-                "INSERT INTO timeline_requests (
+                &format!(
+                    "INSERT INTO timeline_requests (
                     source_id, method, scheme, host, port, path, query, url,
                     http_version, request_headers, request_body, request_body_size,
                     request_body_truncated, started_at, completed_at, duration_ms,
                     scope_status_at_capture, scope_status_current, scope_rules_version,
-                    capture_filtered, timeline_filtered
-                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
+                    capture_filtered, timeline_filtered, request_fingerprint, client_alpn,
+                    upstream_alpn, started_at_ms
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, {})",
+                    epoch_ms_sql("?14")
+                ),
                 params![
                     source_id,
                     request.method,
@@ -249,6 +319,9 @@ impl SqliteStore {
                     request.scope_rules_version,
                     request.capture_filtered as i32,
                     request.timeline_filtered as i32,
+                    request_fingerprint(request),
+                    request.client_alpn,
+                    request.upstream_alpn,
                 ],
             )
             .map_err(|err| err.to_string())?;
@@ -261,8 +334,8 @@ impl SqliteStore {
                 "INSERT INTO timeline_responses (
                     timeline_request_id, status_code, reason, response_headers,
                     response_body, response_body_size, response_body_truncated,
-                    http_version, received_at
-                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    http_version, received_at, ttfb_ms
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
                 params![
                     response.timeline_request_id,
                     response.status_code,
@@ -273,6 +346,7 @@ impl SqliteStore {
                     response.response_body_truncated as i32,
                     response.http_version,
                     response.received_at,
+                    response.ttfb_ms,
                 ],
             )
             .map_err(|err| err.to_string())?;
@@ -441,6 +515,102 @@ impl TimelineStore for SqliteStore {
     fn insert_response(&self, response: TimelineResponse) -> Result<(), String> {
         self.insert_response_inner(&response)
     }
+
+    fn insert_http2_connection(
+        &self,
+        request_id: i64,
+        connection: Http2ConnectionRecord,
+    ) -> Result<(), String> {
+        let client_settings =
+            serde_json::to_string(&connection.client_settings).map_err(|err| err.to_string())?;
+        let upstream_settings =
+            serde_json::to_string(&connection.upstream_settings).map_err(|err| err.to_string())?;
+        self.conn
+            .execute(
+                "INSERT INTO http2_connections (
+                    connection_id, client_settings_json, upstream_settings_json,
+                    client_send_window, upstream_send_window
+                 ) VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(connection_id) DO UPDATE SET
+                    client_settings_json = excluded.client_settings_json,
+                    upstream_settings_json = excluded.upstream_settings_json,
+                    client_send_window = excluded.client_send_window,
+                    upstream_send_window = excluded.upstream_send_window",
+                params![
+                    connection.connection_id,
+                    client_settings,
+                    upstream_settings,
+                    connection.client_send_window,
+                    connection.upstream_send_window,
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        self.conn
+            .execute(
+                "UPDATE timeline_requests
+                 SET http2_connection_id = ?1, http2_client_send_window = ?2,
+                     http2_upstream_send_window = ?3
+                 WHERE id = ?4",
+                params![
+                    connection.connection_id,
+                    connection.client_send_window,
+                    connection.upstream_send_window,
+                    request_id,
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        Ok(())
+    }
+
+    fn link_websocket_connection(
+        &self,
+        request_id: i64,
+        connection_id: &str,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE timeline_requests SET websocket_connection_id = ?1 WHERE id = ?2",
+                params![connection_id, request_id],
+            )
+            .map_err(|err| err.to_string())?;
+        Ok(())
+    }
+
+    fn insert_websocket_frame(&self, frame: TimelineWebSocketFrame) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO timeline_websocket_frames (
+                    connection_id, direction, opcode, payload_len, captured_at
+                 ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    frame.connection_id,
+                    frame.direction,
+                    frame.opcode,
+                    frame.payload_len as i64,
+                    frame.captured_at,
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        Ok(())
+    }
+
+    fn begin_batch(&self) -> Result<(), String> {
+        self.conn
+            .execute_batch("BEGIN")
+            .map_err(|err| err.to_string())
+    }
+
+    fn commit_batch(&self) -> Result<(), String> {
+        self.conn
+            .execute_batch("COMMIT")
+            .map_err(|err| err.to_string())
+    }
+
+    fn rollback_batch(&self) -> Result<(), String> {
+        self.conn
+            .execute_batch("ROLLBACK")
+            .map_err(|err| err.to_string())
+    }
 }
 
 impl SqliteStore {
@@ -581,6 +751,50 @@ impl SqliteStore {
             .map(|value| value + 1)
     }
 
+    pub fn create_fuzz_run(
+        &self,
+        name: &str,
+        template: &[u8],
+        total_requests: usize,
+        created_at: &str,
+    ) -> Result<i64, String> {
+        self.conn
+            .execute(
+                "INSERT INTO fuzz_runs (name, template, total_requests, completed_index, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, 0, ?4, ?4)",
+                params![name, template, total_requests as i64, created_at],
+            )
+            .map_err(|err| err.to_string())?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_fuzz_run(&self, run_id: i64) -> Result<Option<FuzzRun>, String> {
+        self.conn
+            .query_row(
+                "SELECT id, name, template, total_requests, completed_index, created_at, updated_at
+                 FROM fuzz_runs WHERE id = ?1",
+                [run_id],
+                parse_fuzz_run_row,
+            )
+            .optional()
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn update_fuzz_run_checkpoint(
+        &self,
+        run_id: i64,
+        completed_index: usize,
+        updated_at: &str,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE fuzz_runs SET completed_index = ?1, updated_at = ?2 WHERE id = ?3",
+                params![completed_index as i64, updated_at, run_id],
+            )
+            .map_err(|err| err.to_string())?;
+        Ok(())
+    }
+
     pub fn add_tags(&self, request_id: i64, tags: &[&str]) -> Result<(), String> {
         for tag in tags {
             let tag_id = self.ensure_tag_id(tag)?;
@@ -594,6 +808,105 @@ impl SqliteStore {
         Ok(())
     }
 
+    pub fn add_tag_to(&self, request_ids: &[i64], tag: &str) -> Result<usize, String> {
+        if request_ids.is_empty() {
+            return Ok(0);
+        }
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|err| err.to_string())?;
+        let tag_id = self.ensure_tag_id(tag)?;
+        let mut tagged = 0;
+        for request_id in request_ids {
+            tagged += tx
+                .execute(
+                    "INSERT OR IGNORE INTO timeline_request_tags (timeline_request_id, tag_id)
+                     SELECT id, ?2 FROM timeline_requests WHERE id = ?1",
+                    params![request_id, tag_id],
+                )
+                .map_err(|err| err.to_string())?;
+        }
+        tx.commit().map_err(|err| err.to_string())?;
+        Ok(tagged)
+    }
+
+    pub fn remove_tag_from(&self, request_ids: &[i64], tag: &str) -> Result<usize, String> {
+        if request_ids.is_empty() {
+            return Ok(0);
+        }
+        let placeholders = vec!["?"; request_ids.len()].join(", ");
+        let sql = format!(
+            "DELETE FROM timeline_request_tags \
+             WHERE tag_id IN (SELECT id FROM tags WHERE name = ?) \
+             AND timeline_request_id IN ({placeholders})"
+        );
+        let mut params: Vec<rusqlite::types::Value> = vec![tag.to_string().into()];
+        params.extend(request_ids.iter().map(|id| (*id).into()));
+        self.conn
+            .execute(&sql, rusqlite::params_from_iter(params.iter()))
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn tag_matching(&self, query: &TimelineQuery, tag: &str) -> Result<usize, String> {
+        let request_ids: Vec<i64> = self
+            .query_request_summaries(query, TimelineSort::StartedAtDesc)?
+            .into_iter()
+            .map(|summary| summary.id)
+            .collect();
+        self.add_tag_to(&request_ids, tag)
+    }
+
+    pub fn save_view(
+        &self,
+        name: &str,
+        query: &TimelineQuery,
+        sort: TimelineSort,
+    ) -> Result<i64, String> {
+        let query_json = serde_json::to_string(query).map_err(|err| err.to_string())?;
+        let sort_json = serde_json::to_string(&sort).map_err(|err| err.to_string())?;
+        self.conn
+            .query_row(
+                "INSERT INTO saved_views (name, query_json, sort_json, created_at)
+                 VALUES (?1, ?2, ?3, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+                 ON CONFLICT(name) DO UPDATE SET query_json = excluded.query_json, sort_json = excluded.sort_json
+                 RETURNING id",
+                params![name, query_json, sort_json],
+                |row| row.get(0),
+            )
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn list_views(&self) -> Result<Vec<SavedView>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, name, query_json, sort_json, created_at FROM saved_views ORDER BY name",
+            )
+            .map_err(|err| err.to_string())?;
+        let mut rows = stmt.query([]).map_err(|err| err.to_string())?;
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().map_err(|err| err.to_string())? {
+            let query_json: String = row.get(2).map_err(|err| err.to_string())?;
+            let sort_json: String = row.get(3).map_err(|err| err.to_string())?;
+            results.push(SavedView {
+                id: row.get(0).map_err(|err| err.to_string())?,
+                name: row.get(1).map_err(|err| err.to_string())?,
+                query: serde_json::from_str(&query_json).map_err(|err| err.to_string())?,
+                sort: serde_json::from_str(&sort_json).map_err(|err| err.to_string())?,
+                created_at: row.get(4).map_err(|err| err.to_string())?,
+            });
+        }
+        Ok(results)
+    }
+
+    pub fn delete_view(&self, view_id: i64) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM saved_views WHERE id = ?1", [view_id])
+            .map_err(|err| err.to_string())?;
+        Ok(())
+    }
+
     pub fn get_request_tags(
         &self,
         request_ids: &[i64],
@@ -628,7 +941,7 @@ impl SqliteStore {
         }
         let placeholders = vec!["?"; request_ids.len()].join(", ");
         let sql = format!(
-            "SELECT timeline_request_id, status_code, reason, response_headers, response_body_size, response_body_truncated \
+            "SELECT timeline_request_id, status_code, reason, response_headers, response_body_size, response_body_truncated, ttfb_ms \
              FROM timeline_responses WHERE timeline_request_id IN ({placeholders})"
         );
         let mut statement = self.conn.prepare(&sql).map_err(|err| err.to_string())?;
@@ -644,12 +957,30 @@ impl SqliteStore {
                 header_count: count_headers(&headers),
                 body_size: row.get::<_, i64>(4).map_err(|err| err.to_string())? as usize,
                 body_truncated: row.get::<_, i64>(5).map_err(|err| err.to_string())? != 0,
+                ttfb_ms: row.get(6).map_err(|err| err.to_string())?,
             };
             results.insert(request_id, summary);
         }
         Ok(results)
     }
 
+    pub fn export_access_log(
+        &self,
+        query: &TimelineQuery,
+        format: LogFormat,
+    ) -> Result<String, String> {
+        let requests = self.query_request_summaries(query, TimelineSort::StartedAtAsc)?;
+        let request_ids: Vec<i64> = requests.iter().map(|request| request.id).collect();
+        let responses = self.get_response_summaries(&request_ids)?;
+        let mut log = String::new();
+        for request in &requests {
+            let line = access_log_line(request, responses.get(&request.id), format);
+            log.push_str(&line);
+            log.push('\n');
+        }
+        Ok(log)
+    }
+
     pub fn create_replay_request(&self, request: &ReplayRequest) -> Result<i64, String> {
         self.insert_replay_request_inner(request)
     }
@@ -754,6 +1085,60 @@ impl SqliteStore {
         Ok(results)
     }
 
+    pub fn prune_replay_versions(
+        &self,
+        request_id: i64,
+        keep_last: usize,
+    ) -> Result<usize, String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|err| err.to_string())?;
+        let keep =
+            "SELECT id FROM replay_versions WHERE replay_request_id = ?1 ORDER BY id DESC LIMIT ?2";
+        let active = "SELECT active_version_id FROM replay_requests WHERE id = ?1 AND active_version_id IS NOT NULL";
+        tx.execute(
+            &format!(
+                "UPDATE replay_versions SET parent_id = NULL
+                 WHERE replay_request_id = ?1
+                   AND (id IN ({keep}) OR id IN ({active}))
+                   AND parent_id NOT IN ({keep})
+                   AND parent_id NOT IN ({active})"
+            ),
+            params![request_id, keep_last as i64],
+        )
+        .map_err(|err| err.to_string())?;
+        let deleted = tx
+            .execute(
+                &format!(
+                    "DELETE FROM replay_versions
+                     WHERE replay_request_id = ?1
+                       AND id NOT IN ({keep})
+                       AND id NOT IN ({active})"
+                ),
+                params![request_id, keep_last as i64],
+            )
+            .map_err(|err| err.to_string())?;
+        tx.commit().map_err(|err| err.to_string())?;
+        Ok(deleted)
+    }
+
+    pub fn list_replay_versions(&self, request_id: i64) -> Result<Vec<ReplayVersion>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, replay_request_id, parent_id, label, created_at, method, scheme, host, port, path, query, url, http_version, request_headers, request_body, request_body_size
+                 FROM replay_versions WHERE replay_request_id = ?1 ORDER BY id",
+            )
+            .map_err(|err| err.to_string())?;
+        let mut rows = stmt.query([request_id]).map_err(|err| err.to_string())?;
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().map_err(|err| err.to_string())? {
+            results.push(parse_replay_version_row(row).map_err(|err| err.to_string())?);
+        }
+        Ok(results)
+    }
+
     pub fn get_latest_replay_execution(
         &self,
         request_id: i64,
@@ -776,11 +1161,78 @@ impl SqliteStore {
         .map_err(|err| err.to_string())
     }
 
+    pub fn insert_scope_rule(&self, rule: &ScopeRuleRow) -> Result<i64, String> {
+        self.conn
+            .execute(
+                "INSERT INTO scope_rules (rule_type, pattern_type, target, pattern, scheme, port, enabled, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    rule.rule_type,
+                    rule.pattern_type,
+                    rule.target,
+                    rule.pattern,
+                    rule.scheme,
+                    rule.port,
+                    rule.enabled as i64,
+                    rule.created_at,
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn recompute_scope_status<F>(
+        &self,
+        rules_version: i64,
+        mut evaluator: F,
+    ) -> Result<usize, String>
+    where
+        F: FnMut(&str, &str, u16, &str) -> String,
+    {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|err| err.to_string())?;
+        let targets = {
+            let mut stmt = tx
+                .prepare("SELECT id, scheme, host, port, path FROM timeline_requests")
+                .map_err(|err| err.to_string())?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, u16>(3)?,
+                        row.get::<_, String>(4)?,
+                    ))
+                })
+                .map_err(|err| err.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|err| err.to_string())?
+        };
+        {
+            let mut update = tx
+                .prepare(
+                    "UPDATE timeline_requests SET scope_status_current = ?1, scope_rules_version = ?2 WHERE id = ?3",
+                )
+                .map_err(|err| err.to_string())?;
+            for (id, scheme, host, port, path) in &targets {
+                let status = evaluator(scheme, host, *port, path);
+                update
+                    .execute(params![status, rules_version, id])
+                    .map_err(|err| err.to_string())?;
+            }
+        }
+        tx.commit().map_err(|err| err.to_string())?;
+        Ok(targets.len())
+    }
+
     pub fn list_scope_rules(&self) -> Result<Vec<ScopeRuleRow>, String> {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, rule_type, pattern_type, target, pattern, enabled, created_at FROM scope_rules",
+                "SELECT id, rule_type, pattern_type, target, pattern, scheme, port, enabled, created_at FROM scope_rules ORDER BY id",
             )
             .map_err(|err| err.to_string())?;
         let mut rows = stmt.query([]).map_err(|err| err.to_string())?;
@@ -792,8 +1244,10 @@ impl SqliteStore {
                 pattern_type: row.get(2).map_err(|err| err.to_string())?,
                 target: row.get(3).map_err(|err| err.to_string())?,
                 pattern: row.get(4).map_err(|err| err.to_string())?,
-                enabled: row.get::<_, i64>(5).map_err(|err| err.to_string())? != 0,
-                created_at: row.get(6).map_err(|err| err.to_string())?,
+                scheme: row.get(5).map_err(|err| err.to_string())?,
+                port: row.get(6).map_err(|err| err.to_string())?,
+                enabled: row.get::<_, i64>(7).map_err(|err| err.to_string())? != 0,
+                created_at: row.get(8).map_err(|err| err.to_string())?,
             });
         }
         Ok(results)
@@ -805,7 +1259,7 @@ impl SqliteStore {
         sort: TimelineSort,
     ) -> Result<Vec<TimelineRequestSummary>, String> {
         let mut sql = String::from(
-            "SELECT DISTINCT req.id, source.name, req.method, req.scheme, req.host, req.port, req.path, req.query, req.url, req.http_version, req.request_headers, req.request_body, req.request_body_size, req.request_body_truncated, req.started_at, req.completed_at, req.duration_ms, req.scope_status_at_capture, req.scope_status_current, req.scope_rules_version, req.capture_filtered, req.timeline_filtered FROM timeline_requests req JOIN timeline_sources source ON req.source_id = source.id",
+            "SELECT DISTINCT req.id, source.name, req.method, req.scheme, req.host, req.port, req.path, req.query, req.url, req.http_version, req.request_headers, req.request_body, req.request_body_size, req.request_body_truncated, req.started_at, req.completed_at, req.duration_ms, req.scope_status_at_capture, req.scope_status_current, req.scope_rules_version, req.capture_filtered, req.timeline_filtered, req.client_alpn, req.upstream_alpn FROM timeline_requests req JOIN timeline_sources source ON req.source_id = source.id",
         );
         let mut where_clauses = Vec::new();
         let mut params: Vec<rusqlite::types::Value> = Vec::new();
@@ -825,10 +1279,35 @@ impl SqliteStore {
             params.push((*status as i64).into());
             join_responses = true;
         }
+        if !query.methods.is_empty() {
+            let placeholders = vec!["?"; query.methods.len()].join(", ");
+            where_clauses.push(format!("UPPER(req.method) IN ({placeholders})"));
+            for method in &query.methods {
+                params.push(method.to_ascii_uppercase().into());
+            }
+        }
+        if !query.status_classes.is_empty() {
+            let placeholders = vec!["?"; query.status_classes.len()].join(", ");
+            where_clauses.push(format!("resp.status_code / 100 IN ({placeholders})"));
+            for class in &query.status_classes {
+                params.push((*class as i64).into());
+            }
+            join_responses = true;
+        }
+        if let Some((low, high)) = query.status_range {
+            where_clauses.push("resp.status_code BETWEEN ? AND ?".to_string());
+            params.push((low as i64).into());
+            params.push((high as i64).into());
+            join_responses = true;
+        }
         if let Some(scope_status) = &query.scope_status {
             where_clauses.push("req.scope_status_at_capture = ?".to_string());
             params.push(scope_status.clone().into());
         }
+        if let Some(scope_status) = &query.scope_status_current {
+            where_clauses.push("req.scope_status_current = ?".to_string());
+            params.push(scope_status.clone().into());
+        }
         if let Some(source) = &query.source {
             where_clauses.push("source.name = ?".to_string());
             params.push(source.clone().into());
@@ -855,6 +1334,18 @@ impl SqliteStore {
                 params.push(format!("%{path_contains}%").into());
             }
         }
+        for (column, pattern) in [
+            ("req.path", &query.path_regex),
+            ("req.url", &query.url_regex),
+        ] {
+            let Some(pattern) = pattern else {
+                continue;
+            };
+            regex::Regex::new(pattern)
+                .map_err(|err| format!("invalid regex {pattern:?}: {err}"))?;
+            where_clauses.push(format!("{column} REGEXP ?"));
+            params.push(pattern.clone().into());
+        }
         if !query.tags_any.is_empty() {
             join_tags = true;
             let placeholders = vec!["?"; query.tags_any.len()].join(", ");
@@ -936,6 +1427,71 @@ impl SqliteStore {
         Ok(summaries.into_iter().map(TimelineRequest::from).collect())
     }
 
+    /// Full-text search over the indexed request and response bodies, newest
+    /// first. `term` is matched as an FTS phrase, so it finds whole tokens;
+    /// use [`Self::search_bodies_substring`] to match inside tokens.
+    pub fn search_bodies(
+        &self,
+        term: &str,
+        limit: usize,
+    ) -> Result<Vec<TimelineRequestSummary>, String> {
+        if !self.config.fts.enabled {
+            return Err("full-text search is disabled for this store".to_string());
+        }
+        let phrase = format!(
+            "{{request_body response_body}} : \"{}\"",
+            term.replace('"', "\"\"")
+        );
+        self.query_body_search("timeline_requests_fts MATCH ?1", phrase, limit)
+    }
+
+    /// Substring fallback for [`Self::search_bodies`]. `term` is matched
+    /// literally (`%` and `_` are not wildcards), which scans every indexed
+    /// row instead of using the token index.
+    pub fn search_bodies_substring(
+        &self,
+        term: &str,
+        limit: usize,
+    ) -> Result<Vec<TimelineRequestSummary>, String> {
+        if !self.config.fts.enabled {
+            return Err("full-text search is disabled for this store".to_string());
+        }
+        let pattern = format!(
+            "%{}%",
+            term.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        self.query_body_search(
+            "fts.request_body LIKE ?1 ESCAPE '\\' OR fts.response_body LIKE ?1 ESCAPE '\\'",
+            pattern,
+            limit,
+        )
+    }
+
+    fn query_body_search(
+        &self,
+        condition: &str,
+        term: String,
+        limit: usize,
+    ) -> Result<Vec<TimelineRequestSummary>, String> {
+        let sql = format!(
+            "SELECT req.id, source.name, req.method, req.scheme, req.host, req.port, req.path, req.query, req.url, req.http_version, req.request_headers, req.request_body, req.request_body_size, req.request_body_truncated, req.started_at, req.completed_at, req.duration_ms, req.scope_status_at_capture, req.scope_status_current, req.scope_rules_version, req.capture_filtered, req.timeline_filtered, req.client_alpn, req.upstream_alpn FROM timeline_requests req JOIN timeline_sources source ON req.source_id = source.id JOIN timeline_requests_fts fts ON fts.rowid = req.id WHERE {condition} ORDER BY req.started_at_ms DESC, req.id DESC LIMIT ?2"
+        );
+        let mut statement = self.conn.prepare(&sql).map_err(|err| err.to_string())?;
+        let rows = statement
+            .query_map(
+                rusqlite::params![term, limit as i64],
+                parse_request_summary_row,
+            )
+            .map_err(|err| err.to_string())?;
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row.map_err(|err| err.to_string())?);
+        }
+        Ok(results)
+    }
+
     pub fn get_request_summary(
         &self,
         request_id: i64,
@@ -943,7 +1499,7 @@ impl SqliteStore {
         let mut statement = self
             .conn
             .prepare(
-                "SELECT req.id, source.name, req.method, req.scheme, req.host, req.port, req.path, req.query, req.url, req.http_version, req.request_headers, req.request_body, req.request_body_size, req.request_body_truncated, req.started_at, req.completed_at, req.duration_ms, req.scope_status_at_capture, req.scope_status_current, req.scope_rules_version, req.capture_filtered, req.timeline_filtered FROM timeline_requests req JOIN timeline_sources source ON req.source_id = source.id WHERE req.id = ?1",
+                "SELECT req.id, source.name, req.method, req.scheme, req.host, req.port, req.path, req.query, req.url, req.http_version, req.request_headers, req.request_body, req.request_body_size, req.request_body_truncated, req.started_at, req.completed_at, req.duration_ms, req.scope_status_at_capture, req.scope_status_current, req.scope_rules_version, req.capture_filtered, req.timeline_filtered, req.client_alpn, req.upstream_alpn FROM timeline_requests req JOIN timeline_sources source ON req.source_id = source.id WHERE req.id = ?1",
             )
             .map_err(|err| err.to_string())?;
         statement
@@ -952,6 +1508,130 @@ impl SqliteStore {
             .map_err(|err| err.to_string())
     }
 
+    pub fn get_request_fingerprint(&self, request_id: i64) -> Result<Option<String>, String> {
+        self.conn
+            .query_row(
+                "SELECT request_fingerprint FROM timeline_requests WHERE id = ?1",
+                [request_id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()
+            .map(Option::flatten)
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn list_request_ids_by_fingerprint(&self, fingerprint: &str) -> Result<Vec<i64>, String> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT id FROM timeline_requests WHERE request_fingerprint = ?1 ORDER BY id")
+            .map_err(|err| err.to_string())?;
+        let rows = statement
+            .query_map([fingerprint], |row| row.get::<_, i64>(0))
+            .map_err(|err| err.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn get_request_started_at_ms(&self, request_id: i64) -> Result<Option<i64>, String> {
+        self.conn
+            .query_row(
+                "SELECT started_at_ms FROM timeline_requests WHERE id = ?1",
+                [request_id],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .optional()
+            .map(Option::flatten)
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn list_request_ids_by_started_at(
+        &self,
+        since_ms: Option<i64>,
+        until_ms: Option<i64>,
+    ) -> Result<Vec<i64>, String> {
+        let mut statement = self
+            .conn
+            .prepare(
+                "SELECT id FROM timeline_requests
+                 WHERE started_at_ms IS NOT NULL
+                   AND (?1 IS NULL OR started_at_ms >= ?1)
+                   AND (?2 IS NULL OR started_at_ms < ?2)
+                 ORDER BY started_at_ms, id",
+            )
+            .map_err(|err| err.to_string())?;
+        let rows = statement
+            .query_map(params![since_ms, until_ms], |row| row.get::<_, i64>(0))
+            .map_err(|err| err.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn get_http2_connection_for_request(
+        &self,
+        request_id: i64,
+    ) -> Result<Option<Http2ConnectionRecord>, String> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT conn.connection_id, conn.client_settings_json, conn.upstream_settings_json,
+                        COALESCE(req.http2_client_send_window, conn.client_send_window),
+                        COALESCE(req.http2_upstream_send_window, conn.upstream_send_window)
+                 FROM timeline_requests req JOIN http2_connections conn ON req.http2_connection_id = conn.connection_id
+                 WHERE req.id = ?1",
+                [request_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, i64>(4)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(|err| err.to_string())?;
+        row.map(|(connection_id, client, upstream, client_window, upstream_window)| {
+            Ok(Http2ConnectionRecord {
+                connection_id,
+                client_settings: serde_json::from_str(&client).map_err(|err| err.to_string())?,
+                upstream_settings: serde_json::from_str(&upstream)
+                    .map_err(|err| err.to_string())?,
+                client_send_window: client_window,
+                upstream_send_window: upstream_window,
+            })
+        })
+        .transpose()
+    }
+
+    pub fn get_websocket_frames_for_request(
+        &self,
+        request_id: i64,
+    ) -> Result<Vec<TimelineWebSocketFrame>, String> {
+        let mut statement = self
+            .conn
+            .prepare(
+                "SELECT frame.connection_id, frame.direction, frame.opcode, frame.payload_len, frame.captured_at
+                 FROM timeline_requests req JOIN timeline_websocket_frames frame ON req.websocket_connection_id = frame.connection_id
+                 WHERE req.id = ?1
+                 ORDER BY frame.id",
+            )
+            .map_err(|err| err.to_string())?;
+        let rows = statement
+            .query_map([request_id], |row| {
+                Ok(TimelineWebSocketFrame {
+                    connection_id: row.get(0)?,
+                    direction: row.get(1)?,
+                    opcode: row.get(2)?,
+                    payload_len: row.get::<_, i64>(3)? as usize,
+                    captured_at: row.get(4)?,
+                })
+            })
+            .map_err(|err| err.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())
+    }
+
     pub fn get_response_by_request_id(
         &self,
         request_id: i64,
@@ -959,7 +1639,7 @@ impl SqliteStore {
         let mut statement = self
             .conn
             .prepare(
-                "SELECT timeline_request_id, status_code, reason, response_headers, response_body, response_body_size, response_body_truncated, http_version, received_at FROM timeline_responses WHERE timeline_request_id = ?1",
+                "SELECT timeline_request_id, status_code, reason, response_headers, response_body, response_body_size, response_body_truncated, http_version, received_at, ttfb_ms FROM timeline_responses WHERE timeline_request_id = ?1",
             )
             .map_err(|err| err.to_string())?;
         statement
@@ -1014,6 +1694,8 @@ impl From<TimelineRequestSummary> for TimelineRequest {
             scope_rules_version: summary.scope_rules_version,
             capture_filtered: summary.capture_filtered,
             timeline_filtered: summary.timeline_filtered,
+            client_alpn: summary.client_alpn,
+            upstream_alpn: summary.upstream_alpn,
         }
     }
 }
@@ -1042,6 +1724,8 @@ pub fn parse_request_summary_row(row: &Row<'_>) -> Result<TimelineRequestSummary
         scope_rules_version: row.get(19)?,
         capture_filtered: row.get::<_, i64>(20)? != 0,
         timeline_filtered: row.get::<_, i64>(21)? != 0,
+        client_alpn: row.get(22)?,
+        upstream_alpn: row.get(23)?,
     })
 }
 
@@ -1056,6 +1740,7 @@ pub fn parse_response_row(row: &Row<'_>) -> Result<TimelineResponse, rusqlite::E
         response_body_truncated: row.get::<_, i64>(6)? != 0,
         http_version: row.get(7)?,
         received_at: row.get(8)?,
+        ttfb_ms: row.get(9)?,
     })
 }
 
@@ -1104,6 +1789,18 @@ fn parse_replay_version_row(row: &Row<'_>) -> Result<ReplayVersion, rusqlite::Er
     })
 }
 
+fn parse_fuzz_run_row(row: &Row<'_>) -> Result<FuzzRun, rusqlite::Error> {
+    Ok(FuzzRun {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        template: row.get(2)?,
+        total_requests: row.get::<_, i64>(3)? as usize,
+        completed_index: row.get::<_, i64>(4)? as usize,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
 fn count_headers(headers: &[u8]) -> usize {
     if headers.is_empty() {
         return 0;
@@ -1111,3 +1808,10 @@ fn count_headers(headers: &[u8]) -> usize {
     let text = String::from_utf8_lossy(headers);
     text.lines().filter(|line| !line.trim().is_empty()).count()
 }
+
+fn epoch_ms_sql(column: &str) -> String {
+    format!(
+        "CASE WHEN {column} GLOB '[0-9][0-9][0-9][0-9]-*' \
+         THEN CAST(ROUND((julianday({column}) - 2440587.5) * 86400000.0) AS INTEGER) END"
+    )
+}
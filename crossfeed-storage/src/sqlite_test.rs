@@ -1,6 +1,10 @@
 use tempfile::NamedTempFile;
 
-use crate::{SqliteStore, TimelineRequest, TimelineResponse, TimelineStore};
+use crate::{
+    Http2ConnectionRecord, Http2SettingsRecord, SqliteStore, TimelineRequest, TimelineResponse,
+    TimelineStore, TimelineWebSocketFrame, WEBSOCKET_CLIENT_TO_SERVER, WEBSOCKET_SERVER_TO_CLIENT,
+    request_fingerprint,
+};
 
 fn sample_request() -> TimelineRequest {
     TimelineRequest {
@@ -25,6 +29,8 @@ fn sample_request() -> TimelineRequest {
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
     }
 }
 
@@ -39,6 +45,7 @@ fn sample_response(request_id: i64) -> TimelineResponse {
         response_body_truncated: false,
         http_version: "HTTP/1.1".to_string(),
         received_at: "now".to_string(),
+        ttfb_ms: None,
     }
 }
 
@@ -50,3 +57,217 @@ fn sqlite_inserts_request_and_response() {
     let request_id = store.insert_request(sample_request()).unwrap().request_id;
     store.insert_response(sample_response(request_id)).unwrap();
 }
+
+#[test]
+fn sqlite_records_http2_connection_settings_per_request() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let settings = Http2SettingsRecord {
+        header_table_size: 4096,
+        enable_push: false,
+        initial_window_size: 65_535,
+        max_frame_size: 16_384,
+        max_header_list_size: 262_144,
+    };
+    let mut connection = Http2ConnectionRecord {
+        connection_id: "conn-1".to_string(),
+        client_settings: Http2SettingsRecord {
+            initial_window_size: 1 << 20,
+            ..settings
+        },
+        upstream_settings: Http2SettingsRecord {
+            max_frame_size: 32_768,
+            ..settings
+        },
+        client_send_window: 65_535,
+        upstream_send_window: 65_535,
+    };
+
+    let first = store.insert_request(sample_request()).unwrap().request_id;
+    store
+        .insert_http2_connection(first, connection.clone())
+        .unwrap();
+    let first_connection = connection.clone();
+    connection.upstream_send_window = 1_024;
+    let second = store.insert_request(sample_request()).unwrap().request_id;
+    store
+        .insert_http2_connection(second, connection.clone())
+        .unwrap();
+    let plain = store.insert_request(sample_request()).unwrap().request_id;
+
+    assert_eq!(
+        store.get_http2_connection_for_request(first).unwrap(),
+        Some(first_connection)
+    );
+    assert_eq!(
+        store.get_http2_connection_for_request(second).unwrap(),
+        Some(connection)
+    );
+    assert_eq!(store.get_http2_connection_for_request(plain).unwrap(), None);
+}
+
+#[test]
+fn sqlite_records_websocket_frames_for_upgrade_request() {
+    let file = NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let upgrade = store.insert_request(sample_request()).unwrap().request_id;
+    let plain = store.insert_request(sample_request()).unwrap().request_id;
+
+    let frame = |direction: &str, opcode: u8, payload_len: usize| TimelineWebSocketFrame {
+        connection_id: "ws-1".to_string(),
+        direction: direction.to_string(),
+        opcode,
+        payload_len,
+        captured_at: "2024-01-01T00:00:02Z".to_string(),
+    };
+    store.link_websocket_connection(upgrade, "ws-1").unwrap();
+    store
+        .insert_websocket_frame(frame(WEBSOCKET_SERVER_TO_CLIENT, 1, 5))
+        .unwrap();
+    store
+        .insert_websocket_frame(frame(WEBSOCKET_CLIENT_TO_SERVER, 8, 2))
+        .unwrap();
+
+    assert_eq!(
+        store.get_websocket_frames_for_request(upgrade).unwrap(),
+        vec![
+            frame(WEBSOCKET_SERVER_TO_CLIENT, 1, 5),
+            frame(WEBSOCKET_CLIENT_TO_SERVER, 8, 2),
+        ]
+    );
+    assert!(store.get_websocket_frames_for_request(plain).unwrap().is_empty());
+}
+
+#[test]
+fn sqlite_persists_fuzz_run_checkpoint() {
+    let store = SqliteStore::open_in_memory().unwrap();
+
+    let run_id = store
+        .create_fuzz_run("login", b"POST /login", 10, "2024-01-01T00:00:00Z")
+        .unwrap();
+    store
+        .update_fuzz_run_checkpoint(run_id, 4, "2024-01-01T00:01:00Z")
+        .unwrap();
+
+    let run = store.get_fuzz_run(run_id).unwrap().unwrap();
+    assert_eq!(run.total_requests, 10);
+    assert_eq!(run.completed_index, 4);
+    assert_eq!(run.updated_at, "2024-01-01T00:01:00Z");
+    assert!(store.get_fuzz_run(run_id + 1).unwrap().is_none());
+}
+
+#[test]
+fn sqlite_stores_matching_fingerprints_for_identical_requests() {
+    let store = SqliteStore::open_in_memory().unwrap();
+
+    let first = store.insert_request(sample_request()).unwrap().request_id;
+    let second = store.insert_request(sample_request()).unwrap().request_id;
+
+    let fingerprint = store.get_request_fingerprint(first).unwrap().unwrap();
+    assert_eq!(fingerprint, request_fingerprint(&sample_request()));
+    assert_eq!(
+        store.get_request_fingerprint(second).unwrap(),
+        Some(fingerprint.clone())
+    );
+    assert_eq!(
+        store.list_request_ids_by_fingerprint(&fingerprint).unwrap(),
+        vec![first, second]
+    );
+}
+
+#[test]
+fn request_fingerprint_differs_for_different_bodies() {
+    let mut first = sample_request();
+    first.request_body = b"a=1".to_vec();
+    let mut second = sample_request();
+    second.request_body = b"a=2".to_vec();
+
+    assert_ne!(request_fingerprint(&first), request_fingerprint(&second));
+}
+
+#[test]
+fn request_fingerprint_ignores_header_order_and_volatile_headers() {
+    let mut first = sample_request();
+    first.request_headers = b"GET / HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\n".to_vec();
+    let mut second = sample_request();
+    second.request_headers =
+        b"GET / HTTP/1.1\r\naccept: */*\r\nDate: today\r\nHost: example.com\r\n\r\n".to_vec();
+
+    assert_eq!(request_fingerprint(&first), request_fingerprint(&second));
+}
+
+#[test]
+fn sqlite_populates_started_at_ms_from_rfc3339() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let mut request = sample_request();
+    request.started_at = "2024-01-01T00:00:01.250+00:00".to_string();
+    let id = store.insert_request(request).unwrap().request_id;
+    let unparsed = store.insert_request(sample_request()).unwrap().request_id;
+
+    assert_eq!(
+        store.get_request_started_at_ms(id).unwrap(),
+        Some(1_704_067_201_250)
+    );
+    assert_eq!(store.get_request_started_at_ms(unparsed).unwrap(), None);
+}
+
+#[test]
+fn sqlite_orders_requests_by_started_at_ms_across_offsets() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let mut ids = Vec::new();
+    for started_at in [
+        "2024-01-01T09:30:00Z",
+        "2024-01-01T10:00:00+02:00",
+        "2024-01-01T09:00:00.123456789+00:00",
+    ] {
+        let mut request = sample_request();
+        request.started_at = started_at.to_string();
+        ids.push(store.insert_request(request).unwrap().request_id);
+    }
+
+    assert_eq!(
+        store.list_request_ids_by_started_at(None, None).unwrap(),
+        vec![ids[1], ids[2], ids[0]]
+    );
+    let nine = store.get_request_started_at_ms(ids[2]).unwrap().unwrap() - 123;
+    assert_eq!(
+        store
+            .list_request_ids_by_started_at(Some(nine), Some(nine + 60 * 60 * 1000))
+            .unwrap(),
+        vec![ids[2], ids[0]]
+    );
+}
+
+#[test]
+fn sqlite_round_trips_negotiated_alpn() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let mut request = sample_request();
+    request.client_alpn = Some("h2".to_string());
+    request.upstream_alpn = Some("http/1.1".to_string());
+
+    let request_id = store.insert_request(request).unwrap().request_id;
+    let plain_id = store.insert_request(sample_request()).unwrap().request_id;
+
+    let summary = store.get_request_summary(request_id).unwrap().unwrap();
+    assert_eq!(summary.client_alpn.as_deref(), Some("h2"));
+    assert_eq!(summary.upstream_alpn.as_deref(), Some("http/1.1"));
+    let plain = store.get_request_summary(plain_id).unwrap().unwrap();
+    assert_eq!(plain.client_alpn, None);
+    assert_eq!(plain.upstream_alpn, None);
+}
+
+#[test]
+fn sqlite_round_trips_response_ttfb() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let request_id = store.insert_request(sample_request()).unwrap().request_id;
+    let plain_id = store.insert_request(sample_request()).unwrap().request_id;
+    let mut response = sample_response(request_id);
+    response.ttfb_ms = Some(42);
+    store.insert_response(response).unwrap();
+    store.insert_response(sample_response(plain_id)).unwrap();
+
+    let stored = store.get_response_by_request_id(request_id).unwrap().unwrap();
+    assert_eq!(stored.ttfb_ms, Some(42));
+    let summaries = store.get_response_summaries(&[request_id, plain_id]).unwrap();
+    assert_eq!(summaries[&request_id].ttfb_ms, Some(42));
+    assert_eq!(summaries[&plain_id].ttfb_ms, None);
+}
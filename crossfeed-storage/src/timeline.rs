@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::http2::Http2ConnectionRecord;
+use crate::websocket::TimelineWebSocketFrame;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TimelineRequest {
     pub source: String,
@@ -23,6 +26,21 @@ pub struct TimelineRequest {
     pub scope_rules_version: i64,
     pub capture_filtered: bool,
     pub timeline_filtered: bool,
+    #[serde(default)]
+    pub client_alpn: Option<String>,
+    #[serde(default)]
+    pub upstream_alpn: Option<String>,
+}
+
+impl TimelineRequest {
+    pub fn record_completion(&mut self, completed_at: &str) {
+        let started = chrono::DateTime::parse_from_rfc3339(&self.started_at);
+        let completed = chrono::DateTime::parse_from_rfc3339(completed_at);
+        if let (Ok(started), Ok(completed)) = (started, completed) {
+            self.duration_ms = Some((completed - started).num_milliseconds().max(0));
+        }
+        self.completed_at = Some(completed_at.to_string());
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -36,6 +54,10 @@ pub struct TimelineResponse {
     pub response_body_truncated: bool,
     pub http_version: String,
     pub received_at: String,
+    /// Milliseconds between the request reaching the upstream and the first
+    /// response byte arriving back.
+    #[serde(default)]
+    pub ttfb_ms: Option<i64>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -61,6 +83,41 @@ pub struct TimelineInsertResult {
 pub trait TimelineStore: Send {
     fn insert_request(&self, request: TimelineRequest) -> Result<TimelineInsertResult, String>;
     fn insert_response(&self, response: TimelineResponse) -> Result<(), String>;
+
+    fn insert_http2_connection(
+        &self,
+        request_id: i64,
+        connection: Http2ConnectionRecord,
+    ) -> Result<(), String> {
+        let _ = (request_id, connection);
+        Ok(())
+    }
+
+    fn link_websocket_connection(
+        &self,
+        request_id: i64,
+        connection_id: &str,
+    ) -> Result<(), String> {
+        let _ = (request_id, connection_id);
+        Ok(())
+    }
+
+    fn insert_websocket_frame(&self, frame: TimelineWebSocketFrame) -> Result<(), String> {
+        let _ = frame;
+        Ok(())
+    }
+
+    fn begin_batch(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn commit_batch(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn rollback_batch(&self) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 pub struct TimelineRecorder {
@@ -73,6 +130,18 @@ impl TimelineRecorder {
         Self { store, limits }
     }
 
+    pub fn begin_batch(&self) -> Result<(), String> {
+        self.store.begin_batch()
+    }
+
+    pub fn commit_batch(&self) -> Result<(), String> {
+        self.store.commit_batch()
+    }
+
+    pub fn rollback_batch(&self) -> Result<(), String> {
+        self.store.rollback_batch()
+    }
+
     pub fn record_request(
         &self,
         mut request: TimelineRequest,
@@ -90,6 +159,26 @@ impl TimelineRecorder {
         response.response_body_truncated = truncated;
         self.store.insert_response(response)
     }
+
+    pub fn record_http2_connection(
+        &self,
+        request_id: i64,
+        connection: Http2ConnectionRecord,
+    ) -> Result<(), String> {
+        self.store.insert_http2_connection(request_id, connection)
+    }
+
+    pub fn record_websocket_connection(
+        &self,
+        request_id: i64,
+        connection_id: &str,
+    ) -> Result<(), String> {
+        self.store.link_websocket_connection(request_id, connection_id)
+    }
+
+    pub fn record_websocket_frame(&self, frame: TimelineWebSocketFrame) -> Result<(), String> {
+        self.store.insert_websocket_frame(frame)
+    }
 }
 
 fn truncate_body(body: Vec<u8>, limit: usize) -> (Vec<u8>, bool) {
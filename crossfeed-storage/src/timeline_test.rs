@@ -2,7 +2,12 @@ use crate::timeline::{
     BodyLimits, TimelineRecorder, TimelineRequest, TimelineResponse, TimelineStore,
 };
 
+use crate::SqliteStore;
+use crate::websocket::TimelineWebSocketFrame;
+use crate::worker::{TimelineEvent, TimelineWorkerConfig, spawn_timeline_worker};
+
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 struct MockStore {
     last_request: Mutex<Option<TimelineRequest>>,
@@ -65,6 +70,8 @@ fn mock_store_captures_requests() {
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
     };
 
     store.insert_request(request).unwrap();
@@ -101,6 +108,8 @@ fn recorder_truncates_request_body() {
         scope_rules_version: 1,
         capture_filtered: false,
         timeline_filtered: false,
+        client_alpn: None,
+        upstream_alpn: None,
     };
 
     recorder.record_request(request).unwrap();
@@ -123,6 +132,7 @@ fn mock_store_captures_response() {
         response_body_truncated: false,
         http_version: "HTTP/1.1".to_string(),
         received_at: "now".to_string(),
+        ttfb_ms: None,
     };
 
     store.insert_response(response).unwrap();
@@ -147,6 +157,7 @@ fn recorder_truncates_response_body() {
         response_body_truncated: false,
         http_version: "HTTP/1.1".to_string(),
         received_at: "now".to_string(),
+        ttfb_ms: None,
     };
 
     recorder.record_response(response).unwrap();
@@ -155,3 +166,308 @@ fn recorder_truncates_response_body() {
     assert_eq!(stored.response_body, b"abcde".to_vec());
     assert!(stored.response_body_truncated);
 }
+
+fn completed_pair(started_at: &str, received_at: &str) -> TimelineEvent {
+    TimelineEvent {
+        request: TimelineRequest {
+            source: "proxy".to_string(),
+            method: "GET".to_string(),
+            scheme: "http".to_string(),
+            host: "example.com".to_string(),
+            port: 80,
+            path: "/".to_string(),
+            query: None,
+            url: "http://example.com/".to_string(),
+            http_version: "HTTP/1.1".to_string(),
+            request_headers: b"Host: example.com\r\n".to_vec(),
+            request_body: Vec::new(),
+            request_body_size: 0,
+            request_body_truncated: false,
+            started_at: started_at.to_string(),
+            completed_at: None,
+            duration_ms: None,
+            scope_status_at_capture: "in_scope".to_string(),
+            scope_status_current: None,
+            scope_rules_version: 1,
+            capture_filtered: false,
+            timeline_filtered: false,
+            client_alpn: None,
+            upstream_alpn: None,
+        },
+        response: Some(TimelineResponse {
+            timeline_request_id: 0,
+            status_code: 200,
+            reason: Some("OK".to_string()),
+            response_headers: b"Content-Length: 0\r\n".to_vec(),
+            response_body: Vec::new(),
+            response_body_size: 0,
+            response_body_truncated: false,
+            http_version: "HTTP/1.1".to_string(),
+            received_at: received_at.to_string(),
+            ttfb_ms: None,
+        }),
+        http2_connection: None,
+        websocket_connection_id: None,
+    }
+}
+
+#[test]
+fn record_completion_computes_duration() {
+    let mut request = completed_pair("2024-01-01T00:00:00Z", "now").request;
+    request.record_completion("2024-01-01T00:00:00.420Z");
+    assert_eq!(
+        request.completed_at.as_deref(),
+        Some("2024-01-01T00:00:00.420Z")
+    );
+    assert_eq!(request.duration_ms, Some(420));
+
+    let mut unparsed = completed_pair("now", "now").request;
+    unparsed.record_completion("later");
+    assert_eq!(unparsed.completed_at.as_deref(), Some("later"));
+    assert_eq!(unparsed.duration_ms, None);
+}
+
+#[test]
+fn worker_records_duration_for_completed_pairs() {
+    let store = MockStore::new();
+    let config = TimelineWorkerConfig {
+        batch_size: 1,
+        ..TimelineWorkerConfig::default()
+    };
+    let worker = spawn_timeline_worker(Box::new(store.clone()), BodyLimits::default(), config);
+    worker
+        .send(completed_pair(
+            "2024-01-01T00:00:00.000+00:00",
+            "2024-01-01T00:00:01.500+00:00",
+        ))
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while store.last_response.lock().unwrap().is_none() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let stored = store.last_request.lock().unwrap();
+    let stored = stored.as_ref().unwrap();
+    assert_eq!(
+        stored.completed_at.as_deref(),
+        Some("2024-01-01T00:00:01.500+00:00")
+    );
+    assert_eq!(stored.duration_ms, Some(1500));
+    assert_eq!(
+        store
+            .last_response
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .timeline_request_id,
+        42
+    );
+}
+
+#[test]
+fn worker_batches_many_events_into_store() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let store = SqliteStore::open(file.path()).unwrap();
+    let config = TimelineWorkerConfig {
+        batch_size: 128,
+        flush_interval_ms: 20,
+        ..TimelineWorkerConfig::default()
+    };
+    let worker = spawn_timeline_worker(Box::new(store), BodyLimits::default(), config);
+    for index in 0..1000 {
+        let started_at = format!("2024-01-01T00:00:{:02}.{:03}Z", index / 1000, index % 1000);
+        worker
+            .send(completed_pair(&started_at, "2024-01-01T00:01:00Z"))
+            .unwrap();
+    }
+    drop(worker);
+
+    let reader = SqliteStore::open(file.path()).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let ids = loop {
+        let ids = reader.list_request_ids_by_started_at(None, None).unwrap();
+        if ids.len() == 1000 || Instant::now() >= deadline {
+            break ids;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    assert_eq!(ids.len(), 1000);
+    for id in [ids[0], ids[499], ids[999]] {
+        let response = reader.get_response_by_request_id(id).unwrap().unwrap();
+        assert_eq!(response.timeline_request_id, id);
+    }
+}
+
+#[test]
+fn worker_flush_interval_bounds_single_event_latency() {
+    let store = MockStore::new();
+    let config = TimelineWorkerConfig {
+        batch_size: 500,
+        flush_interval_ms: 50,
+        ..TimelineWorkerConfig::default()
+    };
+    let worker = spawn_timeline_worker(Box::new(store.clone()), BodyLimits::default(), config);
+    let sent_at = Instant::now();
+    worker
+        .send(completed_pair(
+            "2024-01-01T00:00:00Z",
+            "2024-01-01T00:00:01Z",
+        ))
+        .unwrap();
+
+    while store.last_response.lock().unwrap().is_none() {
+        assert!(sent_at.elapsed() < Duration::from_secs(1));
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(sent_at.elapsed() >= Duration::from_millis(40));
+    drop(worker);
+}
+
+struct SlowStore {
+    delay: Duration,
+}
+
+impl TimelineStore for SlowStore {
+    fn insert_request(
+        &self,
+        _request: TimelineRequest,
+    ) -> Result<crate::timeline::TimelineInsertResult, String> {
+        std::thread::sleep(self.delay);
+        Ok(crate::timeline::TimelineInsertResult { request_id: 1 })
+    }
+
+    fn insert_response(&self, _response: TimelineResponse) -> Result<(), String> {
+        Err("disk I/O error".to_string())
+    }
+}
+
+fn websocket_frame() -> TimelineWebSocketFrame {
+    TimelineWebSocketFrame {
+        connection_id: "ws-1".to_string(),
+        direction: "client_to_server".to_string(),
+        opcode: 1,
+        payload_len: 5,
+        captured_at: "now".to_string(),
+    }
+}
+
+#[test]
+fn worker_handle_counts_dropped_websocket_frames_when_queue_is_full() {
+    let config = TimelineWorkerConfig {
+        batch_size: 1,
+        flush_interval_ms: 10,
+        max_queue_size: 1,
+    };
+    let store = SlowStore {
+        delay: Duration::from_millis(300),
+    };
+    let worker = spawn_timeline_worker(Box::new(store), BodyLimits::default(), config);
+    worker.send(completed_pair("now", "now")).unwrap();
+
+    let rejected = (0..5)
+        .map(|_| worker.send_websocket_frame(websocket_frame()))
+        .filter(Result::is_err)
+        .count();
+
+    let health = worker.health();
+    assert!(rejected >= 3);
+    assert_eq!(health.dropped, rejected as u64);
+    assert_eq!(
+        health.last_error.as_deref(),
+        Some("timeline worker queue is full")
+    );
+}
+
+#[test]
+fn worker_send_waits_for_room_instead_of_dropping_events() {
+    let config = TimelineWorkerConfig {
+        batch_size: 1,
+        flush_interval_ms: 10,
+        max_queue_size: 1,
+    };
+    let store = SlowStore {
+        delay: Duration::from_millis(50),
+    };
+    let worker = spawn_timeline_worker(Box::new(store), BodyLimits::default(), config);
+
+    let started = Instant::now();
+    for _ in 0..5 {
+        worker.send(completed_pair("now", "now")).unwrap();
+    }
+
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    assert_eq!(worker.health().dropped, 0);
+}
+
+#[test]
+fn worker_handle_reports_store_failures() {
+    let store = SlowStore {
+        delay: Duration::ZERO,
+    };
+    let config = TimelineWorkerConfig {
+        batch_size: 1,
+        ..TimelineWorkerConfig::default()
+    };
+    let worker = spawn_timeline_worker(Box::new(store), BodyLimits::default(), config);
+    worker.send(completed_pair("now", "now")).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while worker.health().failed == 0 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let health = worker.health();
+    assert_eq!(health.dropped, 0);
+    assert_eq!(health.failed, 1);
+    assert_eq!(health.last_error.as_deref(), Some("disk I/O error"));
+}
+
+#[derive(Default)]
+struct FailingCommitStore {
+    rollbacks: Arc<Mutex<usize>>,
+}
+
+impl TimelineStore for FailingCommitStore {
+    fn insert_request(
+        &self,
+        _request: TimelineRequest,
+    ) -> Result<crate::timeline::TimelineInsertResult, String> {
+        Ok(crate::timeline::TimelineInsertResult { request_id: 1 })
+    }
+
+    fn insert_response(&self, _response: TimelineResponse) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn commit_batch(&self) -> Result<(), String> {
+        Err("database is locked".to_string())
+    }
+
+    fn rollback_batch(&self) -> Result<(), String> {
+        *self.rollbacks.lock().unwrap() += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn worker_rolls_back_when_commit_fails() {
+    let store = FailingCommitStore::default();
+    let rollbacks = store.rollbacks.clone();
+    let config = TimelineWorkerConfig {
+        batch_size: 1,
+        ..TimelineWorkerConfig::default()
+    };
+    let worker = spawn_timeline_worker(Box::new(store), BodyLimits::default(), config);
+    let mut inserts = worker.subscribe_inserts();
+    worker.send(completed_pair("now", "now")).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while *rollbacks.lock().unwrap() == 0 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(*rollbacks.lock().unwrap(), 1);
+    let health = worker.health();
+    assert_eq!(health.failed, 1);
+    assert_eq!(health.last_error.as_deref(), Some("database is locked"));
+    assert!(inserts.try_recv().is_err());
+}
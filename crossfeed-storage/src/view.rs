@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+use crate::query::{TimelineQuery, TimelineSort};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SavedView {
+    pub id: i64,
+    pub name: String,
+    pub query: TimelineQuery,
+    pub sort: TimelineSort,
+    pub created_at: String,
+}
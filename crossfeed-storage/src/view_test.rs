@@ -0,0 +1,60 @@
+use crate::{SqliteStore, TimelineQuery, TimelineSort};
+
+#[test]
+fn saved_views_round_trip_query_and_sort() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let query = TimelineQuery {
+        host: Some("api.example.com".to_string()),
+        status: Some(401),
+        path_prefix: Some("/v2".to_string()),
+        tags_any: vec!["auth".to_string(), "critical".to_string()],
+        limit: 250,
+        ..TimelineQuery::default()
+    };
+
+    let id = store
+        .save_view("auth failures", &query, TimelineSort::StartedAtAsc)
+        .unwrap();
+    store
+        .save_view(
+            "everything",
+            &TimelineQuery::default(),
+            TimelineSort::StartedAtDesc,
+        )
+        .unwrap();
+
+    let views = store.list_views().unwrap();
+    assert_eq!(views.len(), 2);
+    assert_eq!(views[0].id, id);
+    assert_eq!(views[0].name, "auth failures");
+    assert_eq!(views[0].query, query);
+    assert_eq!(views[0].sort, TimelineSort::StartedAtAsc);
+    assert!(!views[0].created_at.is_empty());
+}
+
+#[test]
+fn saving_existing_view_name_replaces_it() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let first = store
+        .save_view(
+            "errors",
+            &TimelineQuery::default(),
+            TimelineSort::StartedAtDesc,
+        )
+        .unwrap();
+    let query = TimelineQuery {
+        status: Some(500),
+        ..TimelineQuery::default()
+    };
+    let second = store
+        .save_view("errors", &query, TimelineSort::StartedAtDesc)
+        .unwrap();
+
+    assert_eq!(first, second);
+    let views = store.list_views().unwrap();
+    assert_eq!(views.len(), 1);
+    assert_eq!(views[0].query.status, Some(500));
+
+    store.delete_view(first).unwrap();
+    assert!(store.list_views().unwrap().is_empty());
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+pub const WEBSOCKET_CLIENT_TO_SERVER: &str = "client_to_server";
+pub const WEBSOCKET_SERVER_TO_CLIENT: &str = "server_to_client";
+
+/// One WebSocket frame relayed after an upgrade. Frames share the
+/// `connection_id` recorded on the upgrade request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimelineWebSocketFrame {
+    pub connection_id: String,
+    pub direction: String,
+    pub opcode: u8,
+    pub payload_len: usize,
+    pub captured_at: String,
+}
@@ -1,7 +1,12 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use crossbeam_channel::{Receiver, Sender, bounded};
+use crossbeam_channel::{Receiver, Sender, TrySendError, bounded};
+use tokio::sync::broadcast;
 
+use crate::http2::Http2ConnectionRecord;
+use crate::websocket::TimelineWebSocketFrame;
 use crate::timeline::{
     BodyLimits, TimelineInsertResult, TimelineRecorder, TimelineRequest, TimelineResponse,
     TimelineStore,
@@ -26,18 +31,86 @@ impl Default for TimelineWorkerConfig {
 
 #[derive(Debug, Clone)]
 pub struct TimelineWorkerHandle {
-    sender: Sender<TimelineEvent>,
+    sender: Sender<WorkerMessage>,
+    health: Arc<WorkerHealth>,
+    inserts: broadcast::Sender<TimelineInsertResult>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimelineWorkerHealth {
+    pub dropped: u64,
+    pub failed: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct WorkerHealth {
+    dropped: AtomicU64,
+    failed: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl WorkerHealth {
+    fn record_drop(&self, error: String) {
+        self.dropped.fetch_add(1, Ordering::SeqCst);
+        *self.last_error.lock().unwrap() = Some(error);
+    }
+
+    fn record_failure(&self, error: String) {
+        self.failed.fetch_add(1, Ordering::SeqCst);
+        *self.last_error.lock().unwrap() = Some(error);
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct TimelineEvent {
     pub request: TimelineRequest,
     pub response: Option<TimelineResponse>,
+    pub http2_connection: Option<Http2ConnectionRecord>,
+    pub websocket_connection_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum WorkerMessage {
+    Timeline(Box<TimelineEvent>),
+    WebSocketFrame(TimelineWebSocketFrame),
 }
 
 impl TimelineWorkerHandle {
+    /// Queues a request/response event, waiting for room when the queue is
+    /// full. Only fails once the worker has stopped.
     pub fn send(&self, event: TimelineEvent) -> Result<(), String> {
-        self.sender.send(event).map_err(|err| err.to_string())
+        self.sender
+            .send(WorkerMessage::Timeline(Box::new(event)))
+            .map_err(|_| {
+                let error = "timeline worker has stopped".to_string();
+                self.health.record_drop(error.clone());
+                error
+            })
+    }
+
+    /// Queues a WebSocket frame without waiting. Frames are dropped, and
+    /// counted in [`TimelineWorkerHandle::health`], when the queue is full.
+    pub fn send_websocket_frame(&self, frame: TimelineWebSocketFrame) -> Result<(), String> {
+        let error = match self.sender.try_send(WorkerMessage::WebSocketFrame(frame)) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Full(_)) => "timeline worker queue is full".to_string(),
+            Err(TrySendError::Disconnected(_)) => "timeline worker has stopped".to_string(),
+        };
+        self.health.record_drop(error.clone());
+        Err(error)
+    }
+
+    pub fn subscribe_inserts(&self) -> broadcast::Receiver<TimelineInsertResult> {
+        self.inserts.subscribe()
+    }
+
+    pub fn health(&self) -> TimelineWorkerHealth {
+        TimelineWorkerHealth {
+            dropped: self.health.dropped.load(Ordering::SeqCst),
+            failed: self.health.failed.load(Ordering::SeqCst),
+            last_error: self.health.last_error.lock().unwrap().clone(),
+        }
     }
 }
 
@@ -48,48 +121,127 @@ pub fn spawn_timeline_worker(
 ) -> TimelineWorkerHandle {
     let (sender, receiver) = bounded(config.max_queue_size);
     let recorder = TimelineRecorder::new(store, limits);
+    let health = Arc::new(WorkerHealth::default());
+    let worker_health = health.clone();
+    let (inserts, _) = broadcast::channel(1024);
+    let worker_inserts = inserts.clone();
 
-    std::thread::spawn(move || worker_loop(receiver, recorder, config));
+    std::thread::spawn(move || {
+        worker_loop(receiver, recorder, config, &worker_health, &worker_inserts)
+    });
 
-    TimelineWorkerHandle { sender }
+    TimelineWorkerHandle {
+        sender,
+        health,
+        inserts,
+    }
 }
 
 fn worker_loop(
-    receiver: Receiver<TimelineEvent>,
+    receiver: Receiver<WorkerMessage>,
     recorder: TimelineRecorder,
     config: TimelineWorkerConfig,
+    health: &WorkerHealth,
+    inserts: &broadcast::Sender<TimelineInsertResult>,
 ) {
-    let mut batch = Vec::with_capacity(config.batch_size);
-    let mut last_flush = Instant::now();
+    let batch_size = config.batch_size.max(1);
+    let flush_interval = Duration::from_millis(config.flush_interval_ms);
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut deadline: Option<Instant> = None;
 
     loop {
-        let timeout = Duration::from_millis(config.flush_interval_ms);
-        match receiver.recv_timeout(timeout) {
+        let next = match deadline {
+            Some(deadline) => {
+                receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => receiver
+                .recv()
+                .map_err(|_| crossbeam_channel::RecvTimeoutError::Disconnected),
+        };
+        match next {
             Ok(event) => {
+                deadline.get_or_insert_with(|| Instant::now() + flush_interval);
                 batch.push(event);
-                if batch.len() >= config.batch_size {
-                    flush_batch(&recorder, &mut batch);
-                    last_flush = Instant::now();
+                if batch.len() >= batch_size {
+                    flush_batch(&recorder, &mut batch, health, inserts);
+                    deadline = None;
                 }
             }
             Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
-                if !batch.is_empty() && last_flush.elapsed() >= timeout {
-                    flush_batch(&recorder, &mut batch);
-                    last_flush = Instant::now();
-                }
+                flush_batch(&recorder, &mut batch, health, inserts);
+                deadline = None;
+            }
+            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
+                flush_batch(&recorder, &mut batch, health, inserts);
+                break;
             }
-            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => break,
         }
     }
 }
 
-fn flush_batch(recorder: &TimelineRecorder, batch: &mut Vec<TimelineEvent>) {
-    for event in batch.drain(..) {
-        if let Ok(TimelineInsertResult { request_id }) = recorder.record_request(event.request) {
-            if let Some(mut response) = event.response {
-                response.timeline_request_id = request_id;
-                let _ = recorder.record_response(response);
+fn flush_batch(
+    recorder: &TimelineRecorder,
+    batch: &mut Vec<WorkerMessage>,
+    health: &WorkerHealth,
+    inserts: &broadcast::Sender<TimelineInsertResult>,
+) {
+    if batch.is_empty() {
+        return;
+    }
+    let in_transaction = recorder.begin_batch().is_ok();
+    let mut inserted = Vec::with_capacity(batch.len());
+    for message in batch.drain(..) {
+        let mut event = match message {
+            WorkerMessage::Timeline(event) => *event,
+            WorkerMessage::WebSocketFrame(frame) => {
+                if let Err(err) = recorder.record_websocket_frame(frame) {
+                    health.record_failure(err);
+                }
+                continue;
             }
+        };
+        if let Some(response) = event
+            .response
+            .as_ref()
+            .filter(|_| event.request.completed_at.is_none())
+        {
+            event.request.record_completion(&response.received_at);
         }
+        let request_id = match recorder.record_request(event.request) {
+            Ok(TimelineInsertResult { request_id }) => request_id,
+            Err(err) => {
+                health.record_failure(err);
+                continue;
+            }
+        };
+        if let Some(mut response) = event.response {
+            response.timeline_request_id = request_id;
+            if let Err(err) = recorder.record_response(response) {
+                health.record_failure(err);
+            }
+        }
+        if let Some(connection) = event.http2_connection
+            && let Err(err) = recorder.record_http2_connection(request_id, connection)
+        {
+            health.record_failure(err);
+        }
+        if let Some(connection_id) = event.websocket_connection_id
+            && let Err(err) = recorder.record_websocket_connection(request_id, &connection_id)
+        {
+            health.record_failure(err);
+        }
+        inserted.push(TimelineInsertResult { request_id });
+    }
+    if let Some(Err(err)) = in_transaction.then(|| recorder.commit_batch()) {
+        health.record_failure(err);
+        // A failed COMMIT can leave the transaction open; end it so the next
+        // batch does not fail on BEGIN.
+        if let Err(err) = recorder.rollback_batch() {
+            health.record_failure(err);
+        }
+        return;
+    }
+    for result in inserted {
+        let _ = inserts.send(result);
     }
 }
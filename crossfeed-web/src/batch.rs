@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::pin::Pin;
+use std::sync::Arc;
 
-use tokio::sync::mpsc;
+use tokio::sync::{Semaphore, mpsc};
 use tokio_stream::{Stream, wrappers::ReceiverStream};
 
 use crate::{Client, Request, Response};
@@ -8,6 +10,9 @@ use crate::{Client, Request, Response};
 #[derive(Debug, Clone)]
 pub struct BatchRequest {
     pub request: Request,
+    /// Maximum in-flight requests to this request's authority. The first
+    /// request seen for an authority sets the limit for the whole batch.
+    pub per_host_limit: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -26,11 +31,25 @@ pub type BatchResultStream = Pin<Box<dyn Stream<Item = Result<BatchResponse, Str
 
 impl Client {
     pub async fn request_batch(&self, requests: Vec<BatchRequest>) -> BatchResultStream {
-        let (sender, receiver) = mpsc::channel(requests.len());
+        let (sender, receiver) = mpsc::channel(requests.len().max(1));
+        let global = Arc::new(Semaphore::new(self.config.concurrency.max(1)));
+        let mut hosts: HashMap<String, Arc<Semaphore>> = HashMap::new();
         for (index, item) in requests.into_iter().enumerate() {
+            let host = item.per_host_limit.map(|limit| {
+                hosts
+                    .entry(host_key(&item.request))
+                    .or_insert_with(|| Arc::new(Semaphore::new(limit.max(1))))
+                    .clone()
+            });
+            let global = global.clone();
             let client = self.clone();
             let sender = sender.clone();
             tokio::spawn(async move {
+                let _host_permit = match &host {
+                    Some(host) => Some(host.acquire().await.expect("batch semaphore closed")),
+                    None => None,
+                };
+                let _permit = global.acquire().await.expect("batch semaphore closed");
                 let result = client
                     .request(item.request)
                     .await
@@ -45,3 +64,11 @@ impl Client {
         Box::pin(ReceiverStream::new(receiver))
     }
 }
+
+fn host_key(request: &Request) -> String {
+    request
+        .uri
+        .authority()
+        .map(|authority| authority.as_str().to_ascii_lowercase())
+        .unwrap_or_default()
+}
@@ -1,32 +1,106 @@
+use std::fmt;
 use std::sync::Arc;
 use std::time::Duration;
 
-use http::{HeaderMap, HeaderValue};
+use http::{HeaderMap, HeaderName, HeaderValue, Method};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tokio_native_tls::TlsConnector;
 use tokio_util::sync::CancellationToken;
 
+use crossfeed_core::{UNKNOWN_REASON, reason_phrase, redact_header_value};
 use crossfeed_net::{
     DEFAULT_MAX_FRAME_SIZE, FramePayload, FrameType, HeaderField, HpackEncoder, Http2ParseStatus,
     Http2Parser, ParseStatus, ResponseParser, SettingsFrame, encode_data_frames,
     encode_headers_from_fields, encode_raw_frame,
 };
 
+use crate::pool::{BoxedStream, ConnectionPool, PoolKey, PoolStats};
 use crate::rate_limit::RateLimiter;
 use crate::request::Request;
 use crate::response::Response;
-use crate::retry::RetryPolicy;
+use crate::retry::{RetryPolicy, RetryableError, parse_retry_after};
 
 const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ClientConfig {
     pub concurrency: usize,
+    /// Limit on each attempt; `total_deadline` bounds all attempts together.
     pub timeout: Duration,
     pub retry: RetryPolicy,
     pub rate_limit: Option<RateLimiter>,
     pub proxy: Option<ProxyConfig>,
+    pub default_headers: HeaderMap,
+    pub remove_headers: Vec<HeaderName>,
+    pub pool_idle_timeout: Option<Duration>,
+    pub tls: TlsClientConfig,
+    /// Keeps a copy of each response's raw bytes in [`Response::raw`].
+    pub capture_raw: bool,
+    pub total_deadline: Option<Duration>,
+}
+
+// Hand-written so credentials in `default_headers` never end up in logs.
+impl fmt::Debug for ClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let default_headers: Vec<(&str, &str)> = self
+            .default_headers
+            .iter()
+            .map(|(name, value)| {
+                let value = value.to_str().unwrap_or("<binary>");
+                (name.as_str(), redact_header_value(name.as_str(), value))
+            })
+            .collect();
+        f.debug_struct("ClientConfig")
+            .field("concurrency", &self.concurrency)
+            .field("timeout", &self.timeout)
+            .field("retry", &self.retry)
+            .field("rate_limit", &self.rate_limit)
+            .field("proxy", &self.proxy)
+            .field("default_headers", &default_headers)
+            .field("remove_headers", &self.remove_headers)
+            .field("pool_idle_timeout", &self.pool_idle_timeout)
+            .field("tls", &self.tls)
+            .field("capture_raw", &self.capture_raw)
+            .field("total_deadline", &self.total_deadline)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsClientConfig {
+    pub min_version: Option<TlsVersion>,
+    pub verify: bool,
+    pub alpn: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsVersion {
+    Tls10,
+    Tls11,
+    Tls12,
+    Tls13,
+}
+
+impl Default for TlsClientConfig {
+    fn default() -> Self {
+        Self {
+            min_version: None,
+            verify: true,
+            alpn: Vec::new(),
+        }
+    }
+}
+
+impl TlsVersion {
+    fn protocol(self) -> native_tls::Protocol {
+        match self {
+            TlsVersion::Tls10 => native_tls::Protocol::Tlsv10,
+            TlsVersion::Tls11 => native_tls::Protocol::Tlsv11,
+            TlsVersion::Tls12 => native_tls::Protocol::Tlsv12,
+            TlsVersion::Tls13 => native_tls::Protocol::Tlsv13,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -50,13 +124,20 @@ impl Default for ClientConfig {
             retry: RetryPolicy::default(),
             rate_limit: None,
             proxy: None,
+            default_headers: HeaderMap::new(),
+            remove_headers: Vec::new(),
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            tls: TlsClientConfig::default(),
+            capture_raw: false,
+            total_deadline: None,
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Client {
-    config: Arc<ClientConfig>,
+    pub(crate) config: Arc<ClientConfig>,
+    pool: Arc<ConnectionPool>,
 }
 
 #[derive(Debug, Clone)]
@@ -67,6 +148,7 @@ pub struct CancelToken {
 #[derive(Debug, Clone)]
 pub enum RequestError {
     Cancelled,
+    DeadlineExceeded,
     Transport(String),
 }
 
@@ -85,7 +167,7 @@ impl CancelToken {
         self.inner.is_cancelled()
     }
 
-    fn token(&self) -> CancellationToken {
+    pub(crate) fn token(&self) -> CancellationToken {
         self.inner.clone()
     }
 }
@@ -93,16 +175,34 @@ impl CancelToken {
 impl Client {
     pub fn new(config: ClientConfig) -> Self {
         Self {
+            pool: Arc::new(ConnectionPool::new(config.pool_idle_timeout)),
             config: Arc::new(config),
         }
     }
 
+    pub fn pool_stats(&self) -> PoolStats {
+        self.pool.stats()
+    }
+
+    pub(crate) fn apply_header_overrides(&self, mut request: Request) -> Request {
+        for (name, value) in &self.config.default_headers {
+            if !request.headers.contains_key(name) {
+                request.headers.insert(name.clone(), value.clone());
+            }
+        }
+        for name in &self.config.remove_headers {
+            request.headers.remove(name);
+        }
+        request
+    }
+
     pub async fn request(&self, request: Request) -> Result<Response, String> {
         let cancel = CancelToken::new();
         self.request_with_cancel(request, cancel)
             .await
             .map_err(|err| match err {
                 RequestError::Cancelled => "cancelled".to_string(),
+                RequestError::DeadlineExceeded => "deadline exceeded".to_string(),
                 RequestError::Transport(message) => message,
             })
     }
@@ -111,6 +211,22 @@ impl Client {
         &self,
         request: Request,
         cancel: CancelToken,
+    ) -> Result<Response, RequestError> {
+        let request = self.apply_header_overrides(request);
+        match self.config.total_deadline {
+            Some(deadline) => {
+                tokio::time::timeout(deadline, self.send_with_retries(request, cancel))
+                    .await
+                    .unwrap_or(Err(RequestError::DeadlineExceeded))
+            }
+            None => self.send_with_retries(request, cancel).await,
+        }
+    }
+
+    async fn send_with_retries(
+        &self,
+        request: Request,
+        cancel: CancelToken,
     ) -> Result<Response, RequestError> {
         let mut attempt = 0;
         loop {
@@ -120,15 +236,19 @@ impl Client {
             if let Some(limiter) = &self.config.rate_limit {
                 limiter.acquire().await;
             }
-            let result = self.execute_with_cancel(request.clone(), cancel.token()).await;
+            let result = tokio::time::timeout(
+                self.config.timeout,
+                self.execute_with_cancel(request.clone(), cancel.token()),
+            )
+            .await
+            .unwrap_or_else(|_| Err(RequestError::Transport("request timed out".to_string())));
             match result {
                 Ok(response) => {
-                    if self.config.retry.retry_on_5xx
-                        && response.status >= 500
-                        && attempt < self.config.retry.max_retries
-                    {
-                        let delay = self.config.retry.next_delay(attempt);
-                        tokio::time::sleep(delay).await;
+                    let retryable = RetryableError::from_status(response.status)
+                        .is_some_and(|error| self.config.retry.should_retry(&error));
+                    if retryable && attempt < self.config.retry.max_retries {
+                        let delay = self.config.retry.delay_for(attempt, retry_after(&response));
+                        sleep_with_cancel(delay, &cancel).await?;
                         attempt += 1;
                         continue;
                     }
@@ -140,7 +260,7 @@ impl Client {
                 Err(err) => {
                     if attempt < self.config.retry.max_retries {
                         let delay = self.config.retry.next_delay(attempt);
-                        tokio::time::sleep(delay).await;
+                        sleep_with_cancel(delay, &cancel).await?;
                         attempt += 1;
                         continue;
                     }
@@ -168,41 +288,188 @@ impl Client {
         let is_http2 = is_http2_version(http_version);
         let port = uri.port_u16().unwrap_or_else(|| if is_https { 443 } else { 80 });
 
-        let mut stream = tokio::select! {
-            _ = cancel.cancelled() => return Err(RequestError::Cancelled),
-            result = TcpStream::connect((host.as_str(), port)) => {
-                result.map_err(|err| RequestError::Transport(err.to_string()))?
+        if is_http2 {
+            let mut stream = self.connect(&host, port, is_https, true, &cancel).await?;
+            let mut response = send_http2_request(&mut stream, &request, &host, &cancel).await?;
+            if self.config.capture_raw {
+                response.raw = Some(synthesize_raw_response(&response));
             }
+            return Ok(response);
+        }
+
+        let key = PoolKey {
+            https: is_https,
+            host: host.clone(),
+            port,
         };
-        if is_https {
-            let mut builder = native_tls::TlsConnector::builder();
-            if is_http2 {
-                builder.request_alpns(&["h2"]);
+        if let Some(stream) = self.pool.checkout(&key) {
+            let result = self
+                .send_pooled(stream, &key, &request, &host, &cancel)
+                .await;
+            // The server may have closed the idle connection after reading
+            // part of the request, so only replay methods that are safe to
+            // send twice; anything else goes through the retry policy.
+            let replayable = matches!(request.method, Method::GET | Method::HEAD | Method::OPTIONS);
+            match result {
+                Err(RequestError::Transport(_)) if replayable => {}
+                result => return result,
+            }
+        }
+        let stream = self.connect(&host, port, is_https, false, &cancel).await?;
+        self.send_pooled(stream, &key, &request, &host, &cancel)
+            .await
+    }
+
+    async fn send_pooled(
+        &self,
+        mut stream: BoxedStream,
+        key: &PoolKey,
+        request: &Request,
+        host: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Response, RequestError> {
+        let _active = self.pool.activate();
+        let absolute_form = !key.https
+            && self
+                .config
+                .proxy
+                .as_ref()
+                .is_some_and(|proxy| matches!(proxy.kind, ProxyKind::Http));
+        let capture_raw = self.config.capture_raw;
+        match send_http1_request(&mut stream, request, host, absolute_form, capture_raw, cancel)
+            .await
+        {
+            Ok((response, reusable)) => {
+                self.pool.release(key.clone(), reusable.then_some(stream));
+                Ok(response)
             }
-            let connector = builder
-                .build()
-                .map_err(|err| RequestError::Transport(err.to_string()))?;
-            let connector = TlsConnector::from(connector);
-            let mut tls_stream = tokio::select! {
-                _ = cancel.cancelled() => return Err(RequestError::Cancelled),
-                result = connector.connect(&host, stream) => {
-                    result.map_err(|err| RequestError::Transport(err.to_string()))?
+            Err(err) => {
+                self.pool.release(key.clone(), None);
+                Err(err)
+            }
+        }
+    }
+
+    pub(crate) async fn connect(
+        &self,
+        host: &str,
+        port: u16,
+        is_https: bool,
+        is_http2: bool,
+        cancel: &CancellationToken,
+    ) -> Result<BoxedStream, RequestError> {
+        let stream = match &self.config.proxy {
+            Some(proxy) => match proxy.kind {
+                ProxyKind::Http => {
+                    let mut stream = connect_tcp(&proxy.host, proxy.port, cancel).await?;
+                    if is_https || is_http2 {
+                        establish_tunnel(&mut stream, host, port, cancel).await?;
+                    }
+                    stream
                 }
-            };
-            if is_http2 {
-                return send_http2_request(&mut tls_stream, &request, &host, &cancel).await;
+                ProxyKind::Socks => {
+                    return Err(RequestError::Transport(
+                        "socks proxy not supported".to_string(),
+                    ));
+                }
+            },
+            None => connect_tcp(host, port, cancel).await?,
+        };
+        if !is_https {
+            return Ok(Box::new(stream));
+        }
+        let tls = &self.config.tls;
+        let mut builder = native_tls::TlsConnector::builder();
+        if let Some(version) = tls.min_version {
+            builder.min_protocol_version(Some(version.protocol()));
+        }
+        if !tls.verify {
+            builder.danger_accept_invalid_certs(true);
+            builder.danger_accept_invalid_hostnames(true);
+        }
+        let mut alpn: Vec<&str> = if is_http2 { vec!["h2"] } else { Vec::new() };
+        alpn.extend(
+            tls.alpn
+                .iter()
+                .map(String::as_str)
+                .filter(|protocol| !(is_http2 && *protocol == "h2")),
+        );
+        if !alpn.is_empty() {
+            builder.request_alpns(&alpn);
+        }
+        let connector = builder
+            .build()
+            .map_err(|err| RequestError::Transport(err.to_string()))?;
+        let connector = TlsConnector::from(connector);
+        let tls_stream = tokio::select! {
+            _ = cancel.cancelled() => return Err(RequestError::Cancelled),
+            result = connector.connect(host, stream) => {
+                result.map_err(|err| RequestError::Transport(err.to_string()))?
             }
-            return send_http1_request(&mut tls_stream, &request, &host, &cancel).await;
+        };
+        Ok(Box::new(tls_stream))
+    }
+}
+
+async fn connect_tcp(
+    host: &str,
+    port: u16,
+    cancel: &CancellationToken,
+) -> Result<TcpStream, RequestError> {
+    tokio::select! {
+        _ = cancel.cancelled() => Err(RequestError::Cancelled),
+        result = TcpStream::connect((host, port)) => {
+            result.map_err(|err| RequestError::Transport(err.to_string()))
         }
+    }
+}
 
-        if is_http2 {
-            return send_http2_request(&mut stream, &request, &host, &cancel).await;
+async fn establish_tunnel(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    cancel: &CancellationToken,
+) -> Result<(), RequestError> {
+    let request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n\r\n");
+    write_with_cancel(stream, request.as_bytes(), cancel).await?;
+    let mut response = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !response.windows(4).any(|window| window == b"\r\n\r\n") {
+        if response.len() > 16 * 1024 {
+            return Err(RequestError::Transport(
+                "proxy CONNECT response too large".to_string(),
+            ));
         }
-        send_http1_request(&mut stream, &request, &host, &cancel).await
+        let n = tokio::select! {
+            _ = cancel.cancelled() => return Err(RequestError::Cancelled),
+            result = stream.read(&mut buffer) => {
+                result.map_err(|err| RequestError::Transport(err.to_string()))?
+            }
+        };
+        if n == 0 {
+            return Err(RequestError::Transport(
+                "proxy closed connection during CONNECT".to_string(),
+            ));
+        }
+        response.extend_from_slice(&buffer[..n]);
+    }
+    let status_line = String::from_utf8_lossy(&response);
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok());
+    match status {
+        Some(code) if (200..300).contains(&code) => Ok(()),
+        Some(code) => Err(RequestError::Transport(format!(
+            "proxy CONNECT failed with status {code}"
+        ))),
+        None => Err(RequestError::Transport(
+            "invalid proxy CONNECT response".to_string(),
+        )),
     }
 }
 
-async fn write_with_cancel<S>(
+pub(crate) async fn write_with_cancel<S>(
     stream: &mut S,
     bytes: &[u8],
     cancel: &CancellationToken,
@@ -220,15 +487,28 @@ async fn send_http1_request<S>(
     stream: &mut S,
     request: &Request,
     host: &str,
+    absolute_form: bool,
+    capture_raw: bool,
     cancel: &CancellationToken,
-) -> Result<Response, RequestError>
+) -> Result<(Response, bool), RequestError>
 where
     S: AsyncRead + AsyncWrite + Unpin,
 {
-    let path = request.uri.path_and_query().map(|v| v.as_str()).unwrap_or("/");
+    let target = request.uri.to_string();
+    let path = if absolute_form {
+        target.as_str()
+    } else {
+        request.uri.path_and_query().map(|v| v.as_str()).unwrap_or("/")
+    };
     let request_bytes = serialize_request(request, host, path);
     write_with_cancel(stream, &request_bytes, cancel).await?;
-    read_http1_response(stream, cancel).await
+    let (response, keep_alive) = read_http1_response(stream, capture_raw, cancel).await?;
+    let request_close = request
+        .headers
+        .get_all(http::header::CONNECTION)
+        .iter()
+        .any(|value| has_close_token(&String::from_utf8_lossy(value.as_bytes())));
+    Ok((response, keep_alive && !request_close))
 }
 
 async fn send_http2_request<S>(
@@ -267,13 +547,15 @@ where
 
 async fn read_http1_response<S>(
     stream: &mut S,
+    capture_raw: bool,
     cancel: &CancellationToken,
-) -> Result<Response, RequestError>
+) -> Result<(Response, bool), RequestError>
 where
     S: AsyncRead + Unpin,
 {
     let mut parser = ResponseParser::new();
     let mut buffer = vec![0u8; 8192];
+    let mut raw = Vec::new();
     loop {
         let n = tokio::select! {
             _ = cancel.cancelled() => return Err(RequestError::Cancelled),
@@ -284,13 +566,21 @@ where
         if n == 0 {
             let status = parser.push(&[]);
             return match status {
-                ParseStatus::Complete { message, .. } => Ok(convert_http1_response(message)),
+                ParseStatus::Complete { message, .. } => {
+                    let raw = capture_raw.then_some(raw);
+                    Ok((convert_http1_response(message, raw), false))
+                }
                 _ => Err(RequestError::Transport("unexpected eof".to_string())),
             };
         }
+        if capture_raw {
+            raw.extend_from_slice(&buffer[..n]);
+        }
         match parser.push(&buffer[..n]) {
             ParseStatus::Complete { message, .. } => {
-                return Ok(convert_http1_response(message));
+                let keep_alive = response_keeps_alive(&message);
+                let raw = capture_raw.then_some(raw);
+                return Ok((convert_http1_response(message, raw), keep_alive));
             }
             ParseStatus::NeedMore { .. } => {}
             ParseStatus::Error { error, .. } => {
@@ -372,7 +662,7 @@ where
     }
 }
 
-fn serialize_request(request: &Request, host: &str, path: &str) -> Vec<u8> {
+pub(crate) fn serialize_request(request: &Request, host: &str, path: &str) -> Vec<u8> {
     let mut bytes = Vec::new();
     let method = request.method.as_str();
     let version = if request.http_version.trim().is_empty() {
@@ -398,7 +688,51 @@ fn is_http2_version(version: &str) -> bool {
     normalized.eq_ignore_ascii_case("HTTP/2") || normalized.eq_ignore_ascii_case("HTTP/2.0")
 }
 
-fn convert_http1_response(response: crossfeed_net::Response) -> Response {
+async fn sleep_with_cancel(delay: Duration, cancel: &CancelToken) -> Result<(), RequestError> {
+    let token = cancel.token();
+    tokio::select! {
+        _ = tokio::time::sleep(delay) => Ok(()),
+        _ = token.cancelled() => Err(RequestError::Cancelled),
+    }
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    if !matches!(response.status, 429 | 503) {
+        return None;
+    }
+    let value = response
+        .headers
+        .get(http::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?;
+    parse_retry_after(value, chrono::Utc::now())
+}
+
+fn response_keeps_alive(response: &crossfeed_net::Response) -> bool {
+    let connection = response
+        .headers
+        .iter()
+        .filter(|header| header.name.eq_ignore_ascii_case("connection"))
+        .map(|header| header.value.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    match response.line.version {
+        crossfeed_net::HttpVersion::Http11 => {
+            !connection.iter().any(|value| has_close_token(value))
+        }
+        crossfeed_net::HttpVersion::Http10 => connection
+            .iter()
+            .any(|value| value.split(',').any(|token| token.trim() == "keep-alive")),
+        crossfeed_net::HttpVersion::Other(_) => false,
+    }
+}
+
+fn has_close_token(value: &str) -> bool {
+    value
+        .split(',')
+        .any(|token| token.trim().eq_ignore_ascii_case("close"))
+}
+
+fn convert_http1_response(response: crossfeed_net::Response, raw: Option<Vec<u8>>) -> Response {
     let mut headers = HeaderMap::new();
     for header in response.headers {
         if let (Ok(name), Ok(value)) = (
@@ -412,6 +746,7 @@ fn convert_http1_response(response: crossfeed_net::Response) -> Response {
         status: response.line.status_code,
         headers,
         body: response.body,
+        raw,
     }
 }
 
@@ -497,5 +832,24 @@ fn finalize_http2_response(
     body: Vec<u8>,
 ) -> Result<Response, RequestError> {
     let status = status.ok_or_else(|| RequestError::Transport("missing :status".to_string()))?;
-    Ok(Response { status, headers, body })
+    Ok(Response {
+        status,
+        headers,
+        body,
+        raw: None,
+    })
+}
+
+fn synthesize_raw_response(response: &Response) -> Vec<u8> {
+    let reason = reason_phrase(response.status).unwrap_or(UNKNOWN_REASON);
+    let mut bytes = format!("HTTP/1.1 {} {}\r\n", response.status, reason).into_bytes();
+    for (name, value) in &response.headers {
+        bytes.extend_from_slice(name.as_str().as_bytes());
+        bytes.extend_from_slice(b": ");
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.extend_from_slice(b"\r\n");
+    }
+    bytes.extend_from_slice(b"\r\n");
+    bytes.extend_from_slice(&response.body);
+    bytes
 }
@@ -1,11 +1,18 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use http::Uri;
+use http::header::{AUTHORIZATION, HeaderValue, USER_AGENT};
+use http::{HeaderName, Uri};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
 use tokio_stream::StreamExt;
 
-use crate::{BatchRequest, Client, ClientConfig, Request};
+use crate::{
+    BatchRequest, CancelToken, Client, ClientConfig, ProxyConfig, ProxyKind, Request, RequestError,
+    RetryPolicy,
+};
 
 async fn start_test_server(expected: usize) -> SocketAddr {
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -25,6 +32,110 @@ async fn start_test_server(expected: usize) -> SocketAddr {
     addr
 }
 
+async fn start_echo_server(expected: usize) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        for _ in 0..expected {
+            if let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let mut response =
+                    format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", n).into_bytes();
+                response.extend_from_slice(&buf[..n]);
+                let _ = stream.write_all(&response).await;
+            }
+        }
+    });
+
+    addr
+}
+
+async fn start_forward_proxy(
+    connect_status: &'static str,
+) -> (SocketAddr, tokio::sync::mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, receiver) = tokio::sync::mpsc::channel(4);
+
+    tokio::spawn(async move {
+        while let Ok((mut client, _)) = listener.accept().await {
+            let mut head = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = client.read(&mut buf).await.unwrap_or(0);
+                if n == 0 {
+                    break;
+                }
+                head.extend_from_slice(&buf[..n]);
+            }
+            let text = String::from_utf8_lossy(&head).to_string();
+            let request_line = text.lines().next().unwrap_or_default().to_string();
+            let _ = sender.send(request_line.clone()).await;
+            if request_line.starts_with("CONNECT ") {
+                let response = format!("HTTP/1.1 {connect_status}\r\nContent-Length: 0\r\n\r\n");
+                let _ = client.write_all(response.as_bytes()).await;
+                continue;
+            }
+            let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+            let authority = target
+                .trim_start_matches("http://")
+                .split('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let mut upstream = tokio::net::TcpStream::connect(authority).await.unwrap();
+            upstream.write_all(&head).await.unwrap();
+            let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+        }
+    });
+
+    (addr, receiver)
+}
+
+async fn start_closing_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            drop(stream);
+        }
+    });
+
+    addr
+}
+
+async fn start_slow_server(delay: Duration) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf).await.unwrap();
+        tokio::time::sleep(delay).await;
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\nslow")
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+fn header_override_config() -> ClientConfig {
+    let mut config = ClientConfig::default();
+    config
+        .default_headers
+        .insert(USER_AGENT, HeaderValue::from_static("crossfeed-test"));
+    config
+        .remove_headers
+        .push(HeaderName::from_static("x-tracking-id"));
+    config
+}
+
 #[tokio::test]
 async fn request_returns_response() {
     let addr = start_test_server(1).await;
@@ -45,9 +156,11 @@ async fn batch_returns_out_of_order() {
     let requests = vec![
         BatchRequest {
             request: Request::builder(uri.clone()).build(),
+            per_host_limit: None,
         },
         BatchRequest {
             request: Request::builder(uri).build(),
+            per_host_limit: None,
         },
     ];
 
@@ -59,3 +172,373 @@ async fn batch_returns_out_of_order() {
     }
     assert_eq!(count, 2);
 }
+
+async fn start_overlap_tracking_server(
+    active_everywhere: Arc<AtomicUsize>,
+    peak_everywhere: Arc<AtomicUsize>,
+) -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let peak_here = peak.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let active = active.clone();
+            let peak = peak.clone();
+            let active_everywhere = active_everywhere.clone();
+            let peak_everywhere = peak_everywhere.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                let now = active_everywhere.fetch_add(1, Ordering::SeqCst) + 1;
+                peak_everywhere.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+                active_everywhere.fetch_sub(1, Ordering::SeqCst);
+                let response =
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK";
+                let _ = stream.write_all(response).await;
+            });
+        }
+    });
+
+    (addr, peak_here)
+}
+
+#[tokio::test]
+async fn batch_per_host_limit_serializes_requests_to_each_host() {
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let (first, first_peak) = start_overlap_tracking_server(active.clone(), peak.clone()).await;
+    let (second, second_peak) = start_overlap_tracking_server(active, peak.clone()).await;
+    let client = Client::new(ClientConfig::default());
+
+    let mut requests = Vec::new();
+    for index in 0..3 {
+        for addr in [first, second] {
+            let uri: Uri = format!("http://{addr}/{index}").parse().unwrap();
+            requests.push(BatchRequest {
+                request: Request::builder(uri).build(),
+                per_host_limit: Some(1),
+            });
+        }
+    }
+
+    let mut stream = client.request_batch(requests).await;
+    let mut count = 0;
+    while let Some(result) = stream.next().await {
+        assert_eq!(result.unwrap().response.status, 200);
+        count += 1;
+    }
+    assert_eq!(count, 6);
+    assert_eq!(first_peak.load(Ordering::SeqCst), 1);
+    assert_eq!(second_peak.load(Ordering::SeqCst), 1);
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn throttled_response_is_retried_after_retry_after_delay() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let responses: [&[u8]; 2] = [
+            b"HTTP/1.1 429 Too Many Requests\r\nRetry-After: 1\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK",
+        ];
+        for response in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            let _ = stream.write_all(response).await;
+        }
+    });
+
+    let client = Client::new(ClientConfig {
+        retry: RetryPolicy {
+            max_retries: 1,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            retry_on_5xx: false,
+            ..RetryPolicy::default()
+        },
+        ..ClientConfig::default()
+    });
+    let uri: Uri = format!("http://{addr}/").parse().unwrap();
+
+    let started = Instant::now();
+    let response = client.request(Request::builder(uri).build()).await.unwrap();
+    assert_eq!(response.status, 200);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn cancel_interrupts_retry_after_wait() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf).await;
+        let _ = stream
+            .write_all(b"HTTP/1.1 429 Too Many Requests\r\nRetry-After: 10\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .await;
+    });
+
+    let client = Client::new(ClientConfig {
+        retry: RetryPolicy {
+            max_retries: 1,
+            ..RetryPolicy::default()
+        },
+        ..ClientConfig::default()
+    });
+    let uri: Uri = format!("http://{addr}/").parse().unwrap();
+    let cancel = CancelToken::new();
+    let canceller = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        canceller.cancel();
+    });
+
+    let started = Instant::now();
+    let result = client
+        .request_with_cancel(Request::builder(uri).build(), cancel)
+        .await;
+    assert!(matches!(result, Err(RequestError::Cancelled)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn default_user_agent_is_added_when_absent() {
+    let addr = start_echo_server(1).await;
+    let client = Client::new(header_override_config());
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+    let request = Request::builder(uri).build();
+
+    let response = client.request(request).await.unwrap();
+    let echoed = String::from_utf8_lossy(&response.body).to_ascii_lowercase();
+    assert!(echoed.contains("user-agent: crossfeed-test\r\n"));
+}
+
+#[test]
+fn config_debug_redacts_sensitive_default_headers() {
+    let mut config = header_override_config();
+    config.default_headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_static("Bearer secret-token"),
+    );
+
+    let debug = format!("{config:?}");
+    assert!(!debug.contains("secret-token"));
+    assert!(debug.contains("[redacted]"));
+    assert!(debug.contains("crossfeed-test"));
+}
+
+#[tokio::test]
+async fn default_user_agent_does_not_override_existing() {
+    let addr = start_echo_server(1).await;
+    let client = Client::new(header_override_config());
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+    let request = Request::builder(uri)
+        .header(USER_AGENT, HeaderValue::from_static("custom-agent"))
+        .build();
+
+    let response = client.request(request).await.unwrap();
+    let echoed = String::from_utf8_lossy(&response.body).to_ascii_lowercase();
+    assert!(echoed.contains("user-agent: custom-agent\r\n"));
+    assert!(!echoed.contains("crossfeed-test"));
+}
+
+#[tokio::test]
+async fn removed_headers_are_stripped() {
+    let addr = start_echo_server(1).await;
+    let client = Client::new(header_override_config());
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+    let request = Request::builder(uri)
+        .header(
+            HeaderName::from_static("x-tracking-id"),
+            HeaderValue::from_static("abc"),
+        )
+        .build();
+
+    let response = client.request(request).await.unwrap();
+    let echoed = String::from_utf8_lossy(&response.body).to_ascii_lowercase();
+    assert!(!echoed.contains("x-tracking-id"));
+}
+
+#[tokio::test]
+async fn http_proxy_receives_absolute_form_request() {
+    let origin = start_test_server(1).await;
+    let (proxy, mut seen) = start_forward_proxy("200 Connection Established").await;
+    let client = Client::new(ClientConfig {
+        proxy: Some(ProxyConfig {
+            host: proxy.ip().to_string(),
+            port: proxy.port(),
+            kind: ProxyKind::Http,
+        }),
+        ..ClientConfig::default()
+    });
+    let uri: Uri = format!("http://{}/via-proxy?x=1", origin).parse().unwrap();
+
+    let response = client.request(Request::builder(uri).build()).await.unwrap();
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, b"OK".to_vec());
+    assert_eq!(
+        seen.recv().await.unwrap(),
+        format!("GET http://{}/via-proxy?x=1 HTTP/1.1", origin)
+    );
+}
+
+#[tokio::test]
+async fn http_proxy_connect_rejection_is_reported() {
+    let (proxy, mut seen) = start_forward_proxy("407 Proxy Authentication Required").await;
+    let client = Client::new(ClientConfig {
+        proxy: Some(ProxyConfig {
+            host: proxy.ip().to_string(),
+            port: proxy.port(),
+            kind: ProxyKind::Http,
+        }),
+        retry: RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        },
+        ..ClientConfig::default()
+    });
+    let uri: Uri = "https://example.invalid:8443/".parse().unwrap();
+
+    let err = client
+        .request(Request::builder(uri).build())
+        .await
+        .unwrap_err();
+    assert!(err.contains("407"));
+    assert_eq!(
+        seen.recv().await.unwrap(),
+        "CONNECT example.invalid:8443 HTTP/1.1"
+    );
+}
+
+#[tokio::test]
+async fn socks_proxy_config_is_rejected() {
+    let client = Client::new(ClientConfig {
+        proxy: Some(ProxyConfig {
+            host: "127.0.0.1".to_string(),
+            port: 9,
+            kind: ProxyKind::Socks,
+        }),
+        ..ClientConfig::default()
+    });
+    let uri: Uri = "http://127.0.0.1:9/".parse().unwrap();
+
+    let result = client
+        .request_with_cancel(Request::builder(uri).build(), CancelToken::new())
+        .await;
+    assert!(matches!(
+        result,
+        Err(RequestError::Transport(message)) if message == "socks proxy not supported"
+    ));
+}
+
+#[tokio::test]
+async fn captured_raw_bytes_reparse_to_same_response() {
+    let addr = start_test_server(2).await;
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+
+    let client = Client::new(ClientConfig::default());
+    let response = client
+        .request(Request::builder(uri.clone()).build())
+        .await
+        .unwrap();
+    assert!(response.raw_bytes().is_none());
+
+    let client = Client::new(ClientConfig {
+        capture_raw: true,
+        ..ClientConfig::default()
+    });
+    let response = client.request(Request::builder(uri).build()).await.unwrap();
+    let raw = response.raw_bytes().unwrap();
+    assert_eq!(raw, b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK");
+
+    let mut parser = crossfeed_net::ResponseParser::new();
+    let crossfeed_net::ParseStatus::Complete { message, .. } = parser.push(raw) else {
+        panic!("raw bytes did not parse");
+    };
+    assert_eq!(message.line.status_code, response.status);
+    assert_eq!(message.body, response.body);
+    assert_eq!(message.headers.len(), response.headers.len());
+    for header in &message.headers {
+        assert_eq!(
+            response.headers.get(header.name.as_str()).unwrap(),
+            header.value.as_str()
+        );
+    }
+}
+
+#[tokio::test]
+async fn total_deadline_bounds_retries() {
+    let addr = start_closing_server().await;
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+    let client = Client::new(ClientConfig {
+        retry: RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_millis(200),
+            retry_on_5xx: false,
+            ..RetryPolicy::default()
+        },
+        total_deadline: Some(Duration::from_millis(300)),
+        ..ClientConfig::default()
+    });
+
+    let started = Instant::now();
+    let result = client
+        .request_with_cancel(Request::builder(uri).build(), CancelToken::new())
+        .await;
+
+    assert!(matches!(result, Err(RequestError::DeadlineExceeded)));
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(300));
+    assert!(elapsed < Duration::from_millis(700));
+    assert_eq!(client.pool_stats().active, 0);
+}
+
+#[tokio::test]
+async fn timeout_cuts_off_each_slow_attempt() {
+    let addr = start_slow_server(Duration::from_millis(300)).await;
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+    let client = Client::new(ClientConfig {
+        timeout: Duration::from_millis(100),
+        retry: RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        },
+        ..ClientConfig::default()
+    });
+
+    let started = Instant::now();
+    let result = client
+        .request_with_cancel(Request::builder(uri).build(), CancelToken::new())
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(RequestError::Transport(message)) if message == "request timed out"
+    ));
+    assert!(started.elapsed() < Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn slow_responses_within_the_timeout_complete() {
+    let addr = start_slow_server(Duration::from_millis(100)).await;
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+    let client = Client::new(ClientConfig {
+        timeout: Duration::from_millis(500),
+        total_deadline: Some(Duration::from_secs(2)),
+        ..ClientConfig::default()
+    });
+
+    let response = client.request(Request::builder(uri).build()).await.unwrap();
+    assert_eq!(response.body, b"slow");
+}
@@ -5,6 +5,10 @@ mod client_test;
 mod download;
 #[cfg(test)]
 mod download_test;
+mod multipart;
+mod pool;
+#[cfg(test)]
+mod pool_test;
 mod rate_limit;
 #[cfg(test)]
 mod rate_limit_test;
@@ -15,11 +19,24 @@ mod response;
 mod retry;
 #[cfg(test)]
 mod retry_test;
+#[cfg(test)]
+mod tls_test;
+mod websocket;
+#[cfg(test)]
+mod websocket_test;
 
 pub use batch::{BatchItem, BatchRequest, BatchResponse, BatchResultStream};
-pub use client::{CancelToken, Client, ClientConfig, ProxyConfig, ProxyKind, RequestError};
+pub use client::{
+    CancelToken, Client, ClientConfig, ProxyConfig, ProxyKind, RequestError, TlsClientConfig,
+    TlsVersion,
+};
 pub use download::{DownloadResult, DownloadTarget};
+pub use multipart::MultipartPart;
+pub use pool::PoolStats;
 pub use rate_limit::RateLimiter;
 pub use request::{Request, RequestBuilder, RequestMethod};
 pub use response::Response;
-pub use retry::{RetryPolicy, RetryableError};
+pub use retry::{JitterSource, RetryPolicy, RetryableError, parse_retry_after};
+pub use websocket::WebSocket;
+
+pub use crossfeed_net::{WsFrame, WsOpcode};
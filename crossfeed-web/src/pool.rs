@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+pub(crate) trait PooledStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> PooledStream for T {}
+
+pub(crate) type BoxedStream = Box<dyn PooledStream>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PoolStats {
+    pub idle: usize,
+    pub active: usize,
+    pub reused: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PoolKey {
+    pub(crate) https: bool,
+    pub(crate) host: String,
+    pub(crate) port: u16,
+}
+
+struct IdleConnection {
+    stream: BoxedStream,
+    idle_since: Instant,
+}
+
+pub(crate) struct ConnectionPool {
+    idle_timeout: Option<Duration>,
+    idle: Mutex<HashMap<PoolKey, Vec<IdleConnection>>>,
+    active: AtomicUsize,
+    reused: AtomicU64,
+}
+
+impl ConnectionPool {
+    pub(crate) fn new(idle_timeout: Option<Duration>) -> Self {
+        Self {
+            idle_timeout,
+            idle: Mutex::new(HashMap::new()),
+            active: AtomicUsize::new(0),
+            reused: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn checkout(&self, key: &PoolKey) -> Option<BoxedStream> {
+        let timeout = self.idle_timeout?;
+        let mut idle = self.idle.lock().unwrap();
+        let connections = idle.get_mut(key)?;
+        connections.retain(|connection| connection.idle_since.elapsed() < timeout);
+        let connection = connections.pop();
+        if connections.is_empty() {
+            idle.remove(key);
+        }
+        let connection = connection?;
+        self.reused.fetch_add(1, Ordering::SeqCst);
+        Some(connection.stream)
+    }
+
+    pub(crate) fn activate(&self) -> ActiveConnection<'_> {
+        self.active.fetch_add(1, Ordering::SeqCst);
+        ActiveConnection { pool: self }
+    }
+
+    pub(crate) fn release(&self, key: PoolKey, stream: Option<BoxedStream>) {
+        let Some(stream) = stream.filter(|_| self.idle_timeout.is_some()) else {
+            return;
+        };
+        self.idle
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .push(IdleConnection {
+                stream,
+                idle_since: Instant::now(),
+            });
+    }
+
+    pub(crate) fn stats(&self) -> PoolStats {
+        let mut idle = self.idle.lock().unwrap();
+        if let Some(timeout) = self.idle_timeout {
+            idle.retain(|_, connections| {
+                connections.retain(|connection| connection.idle_since.elapsed() < timeout);
+                !connections.is_empty()
+            });
+        }
+        PoolStats {
+            idle: idle.values().map(Vec::len).sum(),
+            active: self.active.load(Ordering::SeqCst),
+            reused: self.reused.load(Ordering::SeqCst),
+        }
+    }
+}
+
+pub(crate) struct ActiveConnection<'a> {
+    pool: &'a ConnectionPool,
+}
+
+impl Drop for ActiveConnection<'_> {
+    fn drop(&mut self) {
+        self.pool.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl fmt::Debug for ConnectionPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionPool")
+            .field("idle_timeout", &self.idle_timeout)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
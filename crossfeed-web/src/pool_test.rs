@@ -0,0 +1,159 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use http::{Method, Uri};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::{Client, ClientConfig, Request, RetryPolicy};
+
+async fn start_keep_alive_server() -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let counter = accepted.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let mut pending = Vec::new();
+                let mut buf = [0u8; 1024];
+                loop {
+                    let n = match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => n,
+                    };
+                    pending.extend_from_slice(&buf[..n]);
+                    while let Some(end) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+                        pending.drain(..end + 4);
+                        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK";
+                        if stream.write_all(response).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    (addr, accepted)
+}
+
+#[tokio::test]
+async fn sequential_requests_reuse_connection() {
+    let (addr, accepted) = start_keep_alive_server().await;
+    let client = Client::new(ClientConfig::default());
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+
+    for _ in 0..3 {
+        let response = client
+            .request(Request::builder(uri.clone()).build())
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"OK".to_vec());
+    }
+
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    let stats = client.pool_stats();
+    assert_eq!(stats.reused, 2);
+    assert_eq!(stats.idle, 1);
+    assert_eq!(stats.active, 0);
+}
+
+#[tokio::test]
+async fn idle_connections_expire() {
+    let (addr, accepted) = start_keep_alive_server().await;
+    let client = Client::new(ClientConfig {
+        pool_idle_timeout: Some(Duration::from_millis(100)),
+        ..ClientConfig::default()
+    });
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+
+    client
+        .request(Request::builder(uri.clone()).build())
+        .await
+        .unwrap();
+    assert_eq!(client.pool_stats().idle, 1);
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(client.pool_stats().idle, 0);
+
+    client.request(Request::builder(uri).build()).await.unwrap();
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    assert_eq!(client.pool_stats().reused, 0);
+}
+
+#[tokio::test]
+async fn disabled_pool_opens_fresh_connections() {
+    let (addr, accepted) = start_keep_alive_server().await;
+    let client = Client::new(ClientConfig {
+        pool_idle_timeout: None,
+        ..ClientConfig::default()
+    });
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+
+    for _ in 0..2 {
+        client
+            .request(Request::builder(uri.clone()).build())
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    assert_eq!(client.pool_stats().idle, 0);
+}
+
+async fn start_closing_after_response_server() -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let counter = accepted.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            let _ = stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK")
+                .await;
+        }
+    });
+
+    (addr, accepted)
+}
+
+#[tokio::test]
+async fn stale_connection_is_replayed_only_for_safe_methods() {
+    let (addr, accepted) = start_closing_after_response_server().await;
+    let client = Client::new(ClientConfig {
+        retry: RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        },
+        ..ClientConfig::default()
+    });
+    let uri: Uri = format!("http://{}/", addr).parse().unwrap();
+
+    client
+        .request(Request::builder(uri.clone()).build())
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    client
+        .request(Request::builder(uri.clone()).build())
+        .await
+        .unwrap();
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let post = Request::builder(uri)
+        .method(Method::POST)
+        .body(b"once".to_vec())
+        .build();
+    assert!(client.request(post).await.is_err());
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+}
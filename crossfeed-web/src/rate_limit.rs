@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use rand::Rng;
 use tokio::sync::Mutex;
 
 #[derive(Debug, Clone)]
@@ -8,12 +9,15 @@ pub struct RateLimiter {
     state: Arc<Mutex<State>>,
     capacity: u32,
     refill_per_sec: u32,
+    delay: Duration,
+    jitter: Duration,
 }
 
 #[derive(Debug)]
 struct State {
     tokens: u32,
     last_refill: Instant,
+    next_allowed: Option<Instant>,
 }
 
 impl RateLimiter {
@@ -23,18 +27,40 @@ impl RateLimiter {
             state: Arc::new(Mutex::new(State {
                 tokens: capacity,
                 last_refill: now,
+                next_allowed: None,
             })),
             capacity,
             refill_per_sec,
+            delay: Duration::ZERO,
+            jitter: Duration::ZERO,
         }
     }
 
+    pub fn paced(delay: Duration, jitter: Duration) -> Self {
+        Self::new(u32::MAX, u32::MAX).with_delay(delay, jitter)
+    }
+
+    pub fn with_delay(mut self, delay: Duration, jitter: Duration) -> Self {
+        self.delay = delay;
+        self.jitter = jitter;
+        self
+    }
+
     pub async fn acquire(&self) {
         loop {
             let mut state = self.state.lock().await;
+            let now = Instant::now();
+            if let Some(next_allowed) = state.next_allowed.filter(|next| *next > now) {
+                drop(state);
+                tokio::time::sleep(next_allowed - now).await;
+                continue;
+            }
             self.refill(&mut state);
             if state.tokens > 0 {
                 state.tokens -= 1;
+                if !self.delay.is_zero() || !self.jitter.is_zero() {
+                    state.next_allowed = Some(now + self.delay + self.next_jitter());
+                }
                 return;
             }
             drop(state);
@@ -42,12 +68,20 @@ impl RateLimiter {
         }
     }
 
+    fn next_jitter(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return Duration::ZERO;
+        }
+        let jitter_ms = self.jitter.as_millis() as u64;
+        Duration::from_millis(rand::thread_rng().gen_range(0..=jitter_ms))
+    }
+
     fn refill(&self, state: &mut State) {
         let now = Instant::now();
         let elapsed = now.duration_since(state.last_refill);
         let refill = (elapsed.as_secs_f64() * self.refill_per_sec as f64) as u32;
         if refill > 0 {
-            state.tokens = (state.tokens + refill).min(self.capacity);
+            state.tokens = state.tokens.saturating_add(refill).min(self.capacity);
             state.last_refill = now;
         }
     }
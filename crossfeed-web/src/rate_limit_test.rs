@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use crate::RateLimiter;
 
 #[tokio::test]
@@ -6,3 +8,13 @@ async fn rate_limiter_acquires() {
     limiter.acquire().await;
     limiter.acquire().await;
 }
+
+#[tokio::test]
+async fn paced_limiter_spaces_acquisitions() {
+    let limiter = RateLimiter::paced(Duration::from_millis(50), Duration::ZERO);
+    let started = Instant::now();
+    for _ in 0..3 {
+        limiter.acquire().await;
+    }
+    assert!(started.elapsed() >= Duration::from_millis(100));
+}
@@ -1,4 +1,9 @@
-use http::{HeaderMap, Method, Uri};
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{HeaderMap, HeaderValue, Method, Uri};
+
+use crossfeed_codec::url_encode_str;
+
+use crate::multipart::{MultipartPart, encode_multipart, random_boundary};
 
 #[derive(Debug, Clone)]
 pub struct Request {
@@ -50,6 +55,49 @@ impl RequestBuilder {
         self
     }
 
+    pub fn multipart(mut self, parts: Vec<MultipartPart>) -> Self {
+        let boundary = random_boundary();
+        self.body = encode_multipart(&parts, &boundary);
+        let content_type = format!("multipart/form-data; boundary={boundary}");
+        if let Ok(value) = HeaderValue::from_str(&content_type) {
+            self.headers.insert(CONTENT_TYPE, value);
+        }
+        self.headers
+            .insert(CONTENT_LENGTH, HeaderValue::from(self.body.len()));
+        self
+    }
+
+    pub fn form<K: AsRef<str>, V: AsRef<str>>(mut self, pairs: &[(K, V)]) -> Self {
+        self.body = pairs
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    "{}={}",
+                    url_encode_str(name.as_ref()),
+                    url_encode_str(value.as_ref())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+            .into_bytes();
+        self.headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        self.headers
+            .insert(CONTENT_LENGTH, HeaderValue::from(self.body.len()));
+        self
+    }
+
+    pub fn json(mut self, value: &serde_json::Value) -> Self {
+        self.body = value.to_string().into_bytes();
+        self.headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        self.headers
+            .insert(CONTENT_LENGTH, HeaderValue::from(self.body.len()));
+        self
+    }
+
     pub fn http_version(mut self, http_version: impl Into<String>) -> Self {
         self.http_version = http_version.into();
         self
@@ -1,6 +1,6 @@
 use http::Uri;
 
-use crate::Request;
+use crate::{MultipartPart, Request};
 
 #[test]
 fn builds_request() {
@@ -14,3 +14,99 @@ fn builds_request() {
     assert_eq!(request.method, http::Method::POST);
     assert_eq!(request.body, b"hello".to_vec());
 }
+
+#[test]
+fn builds_multipart_body() {
+    let uri: Uri = "http://example.com/upload".parse().unwrap();
+    let request = Request::builder(uri)
+        .method(http::Method::POST)
+        .multipart(vec![
+            MultipartPart::text("title", "report"),
+            MultipartPart::file(
+                "upload",
+                "data.bin",
+                "application/octet-stream",
+                vec![0, 1, 2],
+            ),
+        ])
+        .build();
+
+    let content_type = request.headers["content-type"].to_str().unwrap();
+    let boundary = content_type
+        .strip_prefix("multipart/form-data; boundary=")
+        .unwrap();
+    assert!(!boundary.is_empty());
+    assert_eq!(
+        request.headers["content-length"].to_str().unwrap(),
+        request.body.len().to_string()
+    );
+
+    let mut expected = format!(
+        "--{boundary}\r\n\
+         Content-Disposition: form-data; name=\"title\"\r\n\
+         \r\n\
+         report\r\n\
+         --{boundary}\r\n\
+         Content-Disposition: form-data; name=\"upload\"; filename=\"data.bin\"\r\n\
+         Content-Type: application/octet-stream\r\n\
+         \r\n"
+    )
+    .into_bytes();
+    expected.extend_from_slice(&[0, 1, 2]);
+    expected.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    assert_eq!(request.body, expected);
+}
+
+#[test]
+fn multipart_boundaries_are_random() {
+    let uri: Uri = "http://example.com/".parse().unwrap();
+    let first = Request::builder(uri.clone())
+        .multipart(vec![MultipartPart::text("a", "1")])
+        .build();
+    let second = Request::builder(uri)
+        .multipart(vec![MultipartPart::text("a", "1")])
+        .build();
+
+    assert_ne!(
+        first.headers["content-type"],
+        second.headers["content-type"]
+    );
+}
+
+#[test]
+fn builds_urlencoded_form_body() {
+    let uri: Uri = "http://example.com/login".parse().unwrap();
+    let request = Request::builder(uri)
+        .method(http::Method::POST)
+        .form(&[("user", "alice smith"), ("next", "/home?a=1&b=2")])
+        .build();
+
+    assert_eq!(
+        request.body,
+        b"user=alice%20smith&next=%2Fhome%3Fa%3D1%26b%3D2".to_vec()
+    );
+    assert_eq!(
+        request.headers["content-type"],
+        "application/x-www-form-urlencoded"
+    );
+    assert_eq!(
+        request.headers["content-length"].to_str().unwrap(),
+        request.body.len().to_string()
+    );
+}
+
+#[test]
+fn builds_json_body() {
+    let uri: Uri = "http://example.com/api".parse().unwrap();
+    let request = Request::builder(uri)
+        .method(http::Method::POST)
+        .json(&serde_json::json!({ "id": 7, "tags": ["a", "b"] }))
+        .build();
+
+    assert_eq!(request.headers["content-type"], "application/json");
+    assert_eq!(request.body, br#"{"id":7,"tags":["a","b"]}"#.to_vec());
+    assert_eq!(
+        request.headers["content-length"].to_str().unwrap(),
+        request.body.len().to_string()
+    );
+}
@@ -5,4 +5,16 @@ pub struct Response {
     pub status: u16,
     pub headers: HeaderMap,
     pub body: Vec<u8>,
+    /// Status line, headers and body as received, set only when
+    /// `ClientConfig::capture_raw` is on. This is a second copy of the body.
+    /// HTTP/2 responses have no HTTP/1 wire form, so for them this holds an
+    /// HTTP/1.1 rendering of the decoded status, headers and body.
+    pub raw: Option<Vec<u8>>,
+}
+
+impl Response {
+    /// See [`Response::raw`].
+    pub fn raw_bytes(&self) -> Option<&[u8]> {
+        self.raw.as_deref()
+    }
 }
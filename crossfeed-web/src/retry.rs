@@ -1,11 +1,22 @@
+use std::fmt;
+use std::sync::Arc;
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+use rand::Rng;
+
 #[derive(Debug, Clone)]
 pub struct RetryPolicy {
     pub max_retries: usize,
     pub base_delay: Duration,
     pub max_delay: Duration,
+    /// Upper bound on a server-provided `Retry-After` wait.
+    pub max_retry_after: Duration,
     pub retry_on_5xx: bool,
+    /// Growth factor per attempt. `None` keeps the fixed doubling schedule.
+    pub factor: Option<f64>,
+    /// Adds a random delay in `[0, delay / 2]` on top of each computed delay.
+    pub jitter: Option<JitterSource>,
 }
 
 impl Default for RetryPolicy {
@@ -14,7 +25,10 @@ impl Default for RetryPolicy {
             max_retries: 2,
             base_delay: Duration::from_millis(100),
             max_delay: Duration::from_millis(300),
+            max_retry_after: Duration::from_secs(30),
             retry_on_5xx: true,
+            factor: None,
+            jitter: None,
         }
     }
 }
@@ -24,16 +38,117 @@ pub enum RetryableError {
     Network,
     Timeout,
     ServerError(u16),
+    TooManyRequests,
+}
+
+impl RetryableError {
+    /// Classifies a response status. `None` means the response is final.
+    pub fn from_status(status: u16) -> Option<Self> {
+        match status {
+            429 => Some(Self::TooManyRequests),
+            500..=599 => Some(Self::ServerError(status)),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a `Retry-After` value in either delta-seconds or HTTP-date form.
+/// Dates in the past yield a zero delay.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        at.with_timezone(&Utc)
+            .signed_duration_since(now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Picks a jitter in `[0, bound]` for the given bound.
+#[derive(Clone)]
+pub struct JitterSource(Arc<dyn Fn(Duration) -> Duration + Send + Sync>);
+
+impl JitterSource {
+    pub fn new(source: impl Fn(Duration) -> Duration + Send + Sync + 'static) -> Self {
+        Self(Arc::new(source))
+    }
+
+    pub fn random() -> Self {
+        Self::new(|bound| {
+            let bound_ms = bound.as_millis() as u64;
+            Duration::from_millis(rand::thread_rng().gen_range(0..=bound_ms))
+        })
+    }
+
+    fn sample(&self, bound: Duration) -> Duration {
+        (self.0)(bound).min(bound)
+    }
+}
+
+impl fmt::Debug for JitterSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("JitterSource")
+    }
 }
 
 impl RetryPolicy {
+    pub fn exponential_with_jitter(base: Duration, max: Duration, factor: f64) -> Self {
+        Self {
+            base_delay: base,
+            max_delay: max,
+            factor: Some(factor),
+            jitter: Some(JitterSource::random()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_jitter_source(mut self, jitter: JitterSource) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    pub fn should_retry(&self, error: &RetryableError) -> bool {
+        match error {
+            RetryableError::ServerError(_) => self.retry_on_5xx,
+            RetryableError::Network | RetryableError::Timeout | RetryableError::TooManyRequests => {
+                true
+            }
+        }
+    }
+
+    /// Like [`RetryPolicy::next_delay`], but waits at least as long as a
+    /// server-provided `Retry-After`, capped at `max_retry_after`.
+    pub fn delay_for(&self, attempt: usize, retry_after: Option<Duration>) -> Duration {
+        let delay = self.next_delay(attempt);
+        retry_after.map_or(delay, |retry_after| {
+            retry_after.min(self.max_retry_after).max(delay)
+        })
+    }
+
     pub fn next_delay(&self, attempt: usize) -> Duration {
-        let multiplier = 1u64 << attempt.min(3);
-        let delay = self.base_delay.saturating_mul(multiplier as u32);
-        if delay > self.max_delay {
-            self.max_delay
-        } else {
-            delay
+        let delay = match self.factor {
+            Some(factor) => {
+                let scaled = self.base_delay.as_secs_f64() * factor.powi(attempt.min(64) as i32);
+                if scaled.is_finite() && scaled < self.max_delay.as_secs_f64() {
+                    Duration::from_secs_f64(scaled.max(0.0))
+                } else {
+                    self.max_delay
+                }
+            }
+            None => {
+                let multiplier = 1u64 << attempt.min(3);
+                self.base_delay
+                    .saturating_mul(multiplier as u32)
+                    .min(self.max_delay)
+            }
+        };
+        match &self.jitter {
+            Some(jitter) => delay + jitter.sample(delay / 2),
+            None => delay,
         }
     }
 }
@@ -1,4 +1,8 @@
-use crate::RetryPolicy;
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+
+use crate::{JitterSource, RetryPolicy, RetryableError, parse_retry_after};
 
 #[test]
 fn retry_policy_backoff_caps() {
@@ -6,3 +10,116 @@ fn retry_policy_backoff_caps() {
     let delay = policy.next_delay(5);
     assert_eq!(delay, policy.max_delay);
 }
+
+#[test]
+fn exponential_backoff_grows_and_caps() {
+    let policy = RetryPolicy::exponential_with_jitter(
+        Duration::from_millis(100),
+        Duration::from_secs(2),
+        3.0,
+    )
+    .with_jitter_source(JitterSource::new(|_| Duration::ZERO));
+
+    let delays: Vec<_> = (0..5).map(|attempt| policy.next_delay(attempt)).collect();
+    assert_eq!(
+        delays,
+        vec![
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+            Duration::from_millis(900),
+            Duration::from_secs(2),
+            Duration::from_secs(2),
+        ]
+    );
+    assert_eq!(policy.next_delay(10_000), Duration::from_secs(2));
+}
+
+#[test]
+fn exponential_backoff_jitter_is_bounded_by_half_the_delay() {
+    let policy = RetryPolicy::exponential_with_jitter(
+        Duration::from_millis(100),
+        Duration::from_secs(1),
+        2.0,
+    )
+    .with_jitter_source(JitterSource::new(|bound| bound * 10));
+
+    assert_eq!(policy.next_delay(0), Duration::from_millis(150));
+    assert_eq!(policy.next_delay(1), Duration::from_millis(300));
+    assert_eq!(policy.next_delay(8), Duration::from_millis(1500));
+}
+
+#[test]
+fn random_jitter_stays_within_bounds() {
+    let base = Duration::from_millis(40);
+    let max = Duration::from_millis(500);
+    let policy = RetryPolicy::exponential_with_jitter(base, max, 2.0);
+    for attempt in 0..8 {
+        let floor = base.saturating_mul(1 << attempt).min(max);
+        let delay = policy.next_delay(attempt);
+        assert!(delay >= floor, "attempt {attempt}: {delay:?} < {floor:?}");
+        assert!(
+            delay <= floor + floor / 2,
+            "attempt {attempt}: {delay:?} too large"
+        );
+    }
+}
+
+#[test]
+fn parses_retry_after_delta_seconds() {
+    assert_eq!(
+        parse_retry_after("5", Utc::now()),
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(parse_retry_after("soon", Utc::now()), None);
+}
+
+#[test]
+fn parses_retry_after_http_date() {
+    let now = Utc.with_ymd_and_hms(2015, 10, 21, 7, 27, 30).unwrap();
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+        Some(Duration::ZERO)
+    );
+}
+
+#[test]
+fn retry_after_wins_only_when_longer_than_backoff() {
+    let policy = RetryPolicy::default();
+    assert_eq!(
+        policy.delay_for(0, Some(Duration::from_secs(5))),
+        Duration::from_secs(5)
+    );
+    assert_eq!(
+        policy.delay_for(1, Some(Duration::from_millis(1))),
+        policy.next_delay(1)
+    );
+    assert_eq!(policy.delay_for(1, None), policy.next_delay(1));
+}
+
+#[test]
+fn retry_after_is_capped_by_policy() {
+    let policy = RetryPolicy {
+        max_retry_after: Duration::from_secs(2),
+        ..RetryPolicy::default()
+    };
+    assert_eq!(
+        policy.delay_for(0, Some(Duration::from_secs(86_400))),
+        Duration::from_secs(2)
+    );
+}
+
+#[test]
+fn too_many_requests_is_retryable_by_default() {
+    let policy = RetryPolicy {
+        retry_on_5xx: false,
+        ..RetryPolicy::default()
+    };
+    let throttled = RetryableError::from_status(429).unwrap();
+    assert!(policy.should_retry(&throttled));
+    assert!(!policy.should_retry(&RetryableError::from_status(503).unwrap()));
+    assert!(RetryableError::from_status(404).is_none());
+}
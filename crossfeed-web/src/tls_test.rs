@@ -0,0 +1,105 @@
+use std::net::SocketAddr;
+
+use http::Uri;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_native_tls::TlsAcceptor;
+
+use crate::{Client, ClientConfig, Request, RetryPolicy, TlsClientConfig, TlsVersion};
+
+async fn start_tls_server(max_version: Option<native_tls::Protocol>) -> SocketAddr {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let identity = native_tls::Identity::from_pkcs8(
+        cert.serialize_pem().unwrap().as_bytes(),
+        cert.serialize_private_key_pem().as_bytes(),
+    )
+    .unwrap();
+    let acceptor = native_tls::TlsAcceptor::builder(identity)
+        .max_protocol_version(max_version)
+        .build()
+        .unwrap();
+    let acceptor = TlsAcceptor::from(acceptor);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(mut stream) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let response = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK";
+                let _ = stream.write_all(response).await;
+            });
+        }
+    });
+
+    addr
+}
+
+fn client_with_tls(tls: TlsClientConfig) -> Client {
+    Client::new(ClientConfig {
+        tls,
+        retry: RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        },
+        ..ClientConfig::default()
+    })
+}
+
+#[tokio::test]
+async fn verification_rejects_self_signed_server_by_default() {
+    let addr = start_tls_server(None).await;
+    let client = client_with_tls(TlsClientConfig::default());
+    let uri: Uri = format!("https://localhost:{}/", addr.port())
+        .parse()
+        .unwrap();
+
+    assert!(client.request(Request::builder(uri).build()).await.is_err());
+}
+
+#[tokio::test]
+async fn disabled_verification_connects_to_self_signed_server() {
+    let addr = start_tls_server(None).await;
+    let client = client_with_tls(TlsClientConfig {
+        verify: false,
+        alpn: vec!["http/1.1".to_string()],
+        ..TlsClientConfig::default()
+    });
+    let uri: Uri = format!("https://localhost:{}/", addr.port())
+        .parse()
+        .unwrap();
+
+    let response = client.request(Request::builder(uri).build()).await.unwrap();
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, b"OK".to_vec());
+}
+
+#[tokio::test]
+async fn min_version_rejects_older_server() {
+    let addr = start_tls_server(Some(native_tls::Protocol::Tlsv12)).await;
+    let client = client_with_tls(TlsClientConfig {
+        min_version: Some(TlsVersion::Tls13),
+        verify: false,
+        ..TlsClientConfig::default()
+    });
+    let uri: Uri = format!("https://localhost:{}/", addr.port())
+        .parse()
+        .unwrap();
+
+    assert!(client.request(Request::builder(uri).build()).await.is_err());
+
+    let client = client_with_tls(TlsClientConfig {
+        min_version: Some(TlsVersion::Tls12),
+        verify: false,
+        ..TlsClientConfig::default()
+    });
+    let uri: Uri = format!("https://localhost:{}/", addr.port())
+        .parse()
+        .unwrap();
+    assert!(client.request(Request::builder(uri).build()).await.is_ok());
+}
@@ -0,0 +1,239 @@
+use std::fmt;
+
+use http::header::{CONNECTION, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_VERSION, UPGRADE};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use rand::RngCore;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+
+use crossfeed_codec::base64_encode_bytes;
+use crossfeed_core::ParsedHeaders;
+use crossfeed_net::{
+    WsFrame, WsFrameParser, WsOpcode, WsParseStatus, encode_ws_frame, websocket_accept_key,
+};
+
+use crate::client::{ProxyKind, serialize_request, write_with_cancel};
+use crate::pool::BoxedStream;
+use crate::{CancelToken, Client, Request, RequestError, Response};
+
+const MAX_HANDSHAKE_SIZE: usize = 64 * 1024;
+
+pub struct WebSocket {
+    response: Response,
+    stream: BoxedStream,
+    parser: WsFrameParser,
+    pending: Vec<u8>,
+    cancel: CancellationToken,
+}
+
+impl Client {
+    pub async fn websocket(
+        &self,
+        request: Request,
+        cancel: CancelToken,
+    ) -> Result<WebSocket, RequestError> {
+        let cancel = cancel.token();
+        let mut request = self.apply_header_overrides(request);
+        let uri = request.uri.clone();
+        let host = uri
+            .host()
+            .ok_or_else(|| RequestError::Transport("missing host".to_string()))?
+            .to_string();
+        let is_https = uri.scheme_str().is_some_and(|scheme| {
+            scheme.eq_ignore_ascii_case("https") || scheme.eq_ignore_ascii_case("wss")
+        });
+        let port = uri.port_u16().unwrap_or(if is_https { 443 } else { 80 });
+
+        let mut nonce = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let key = base64_encode_bytes(&nonce);
+        request
+            .headers
+            .insert(UPGRADE, HeaderValue::from_static("websocket"));
+        request
+            .headers
+            .insert(CONNECTION, HeaderValue::from_static("Upgrade"));
+        request
+            .headers
+            .insert(SEC_WEBSOCKET_VERSION, HeaderValue::from_static("13"));
+        request.headers.insert(
+            SEC_WEBSOCKET_KEY,
+            HeaderValue::from_str(&key).map_err(|err| RequestError::Transport(err.to_string()))?,
+        );
+        request.http_version = "HTTP/1.1".to_string();
+
+        let mut stream = self.connect(&host, port, is_https, false, &cancel).await?;
+        let absolute_form = !is_https
+            && self
+                .config
+                .proxy
+                .as_ref()
+                .is_some_and(|proxy| matches!(proxy.kind, ProxyKind::Http));
+        let target = if absolute_form {
+            http_target(&request)
+        } else {
+            uri.path_and_query()
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "/".to_string())
+        };
+        let bytes = serialize_request(&request, &host, &target);
+        write_with_cancel(&mut stream, &bytes, &cancel).await?;
+
+        let (head, pending) = read_handshake(&mut stream, &cancel).await?;
+        let response = parse_handshake(&head, self.config.capture_raw)?;
+        if response.status != 101 {
+            return Err(RequestError::Transport(format!(
+                "websocket upgrade failed with status {}",
+                response.status
+            )));
+        }
+        let accept = response
+            .headers
+            .get("sec-websocket-accept")
+            .and_then(|value| value.to_str().ok());
+        if accept != Some(websocket_accept_key(&key).as_str()) {
+            return Err(RequestError::Transport(
+                "invalid Sec-WebSocket-Accept".to_string(),
+            ));
+        }
+        Ok(WebSocket {
+            response,
+            stream,
+            parser: WsFrameParser::new(),
+            pending,
+            cancel,
+        })
+    }
+}
+
+impl WebSocket {
+    pub fn response(&self) -> &Response {
+        &self.response
+    }
+
+    pub async fn send(&mut self, frame: &WsFrame) -> Result<(), RequestError> {
+        let mut mask = [0u8; 4];
+        rand::thread_rng().fill_bytes(&mut mask);
+        let bytes = encode_ws_frame(frame, Some(mask));
+        write_with_cancel(&mut self.stream, &bytes, &self.cancel).await?;
+        self.stream
+            .flush()
+            .await
+            .map_err(|err| RequestError::Transport(err.to_string()))
+    }
+
+    pub async fn recv(&mut self) -> Result<Option<WsFrame>, RequestError> {
+        let mut input = std::mem::take(&mut self.pending);
+        let mut buffer = vec![0u8; 8192];
+        loop {
+            match self.parser.push(&input) {
+                WsParseStatus::Complete { frame } => return Ok(Some(frame)),
+                WsParseStatus::Error { error } => {
+                    return Err(RequestError::Transport(format!(
+                        "websocket parse error: {:?}",
+                        error
+                    )));
+                }
+                WsParseStatus::NeedMore => {}
+            }
+            let n = tokio::select! {
+                _ = self.cancel.cancelled() => return Err(RequestError::Cancelled),
+                result = self.stream.read(&mut buffer) => {
+                    result.map_err(|err| RequestError::Transport(err.to_string()))?
+                }
+            };
+            if n == 0 {
+                return Ok(None);
+            }
+            input = buffer[..n].to_vec();
+        }
+    }
+
+    pub async fn close(mut self) -> Result<(), RequestError> {
+        self.send(&WsFrame::close()).await?;
+        loop {
+            match self.recv().await? {
+                Some(frame) if frame.opcode == WsOpcode::Close => break,
+                Some(_) => {}
+                None => break,
+            }
+        }
+        let _ = self.stream.shutdown().await;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for WebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocket")
+            .field("response", &self.response)
+            .finish()
+    }
+}
+
+fn http_target(request: &Request) -> String {
+    let uri = &request.uri;
+    let authority = uri
+        .authority()
+        .map(|authority| authority.as_str())
+        .unwrap_or_default();
+    let path = uri
+        .path_and_query()
+        .map(|value| value.as_str())
+        .unwrap_or("/");
+    format!("http://{authority}{path}")
+}
+
+async fn read_handshake(
+    stream: &mut BoxedStream,
+    cancel: &CancellationToken,
+) -> Result<(Vec<u8>, Vec<u8>), RequestError> {
+    let mut response = Vec::new();
+    let mut buffer = [0u8; 1024];
+    loop {
+        if let Some(end) = response.windows(4).position(|window| window == b"\r\n\r\n") {
+            let pending = response.split_off(end + 4);
+            return Ok((response, pending));
+        }
+        if response.len() > MAX_HANDSHAKE_SIZE {
+            return Err(RequestError::Transport(
+                "websocket handshake response too large".to_string(),
+            ));
+        }
+        let n = tokio::select! {
+            _ = cancel.cancelled() => return Err(RequestError::Cancelled),
+            result = stream.read(&mut buffer) => {
+                result.map_err(|err| RequestError::Transport(err.to_string()))?
+            }
+        };
+        if n == 0 {
+            return Err(RequestError::Transport(
+                "connection closed during websocket handshake".to_string(),
+            ));
+        }
+        response.extend_from_slice(&buffer[..n]);
+    }
+}
+
+fn parse_handshake(head: &[u8], capture_raw: bool) -> Result<Response, RequestError> {
+    let status = String::from_utf8_lossy(head)
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| RequestError::Transport("invalid websocket handshake".to_string()))?;
+    let mut headers = HeaderMap::new();
+    for (name, value) in ParsedHeaders::parse(head).iter() {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    Ok(Response {
+        status,
+        headers,
+        body: Vec::new(),
+        raw: capture_raw.then(|| head.to_vec()),
+    })
+}
@@ -0,0 +1,61 @@
+use std::net::SocketAddr;
+
+use assert_matches::assert_matches;
+use http::Uri;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::{CancelToken, Client, ClientConfig, Request, RequestError};
+
+async fn start_handshake_server(response: &'static [u8]) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        if let Ok((mut stream, _)) = listener.accept().await {
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await;
+            let _ = stream.write_all(response).await;
+        }
+    });
+
+    addr
+}
+
+fn websocket_request(addr: SocketAddr) -> Request {
+    let uri: Uri = format!("ws://{}/socket", addr).parse().unwrap();
+    Request::builder(uri).build()
+}
+
+#[tokio::test]
+async fn websocket_rejects_non_upgrade_response() {
+    let addr = start_handshake_server(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n").await;
+    let client = Client::new(ClientConfig::default());
+
+    let result = client
+        .websocket(websocket_request(addr), CancelToken::new())
+        .await;
+
+    assert_matches!(
+        result,
+        Err(RequestError::Transport(message)) if message.contains("403")
+    );
+}
+
+#[tokio::test]
+async fn websocket_rejects_invalid_accept_key() {
+    let addr = start_handshake_server(
+        b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: bogus\r\n\r\n",
+    )
+    .await;
+    let client = Client::new(ClientConfig::default());
+
+    let result = client
+        .websocket(websocket_request(addr), CancelToken::new())
+        .await;
+
+    assert_matches!(
+        result,
+        Err(RequestError::Transport(message)) if message.contains("Sec-WebSocket-Accept")
+    );
+}